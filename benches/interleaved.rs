@@ -0,0 +1,133 @@
+use constriction::stream::{
+    interleaved::InterleavedAnsCoder,
+    model::DefaultContiguousCategoricalEntropyModel,
+    stack::{AnsCoder, DefaultAnsCoder},
+    Decode,
+};
+use criterion::{black_box, criterion_group, BatchSize, Criterion};
+use rand::{RngCore, SeedableRng};
+use rand_xoshiro::Xoshiro256StarStar;
+
+criterion_group!(
+    benches,
+    scalar,
+    interleaved_2_lanes,
+    interleaved_4_lanes,
+    interleaved_8_lanes
+);
+
+#[cfg(not(miri))]
+criterion::criterion_main!(benches);
+#[cfg(miri)]
+fn main() {} // miri currently doesn't seem to be able to run criterion benchmarks as tests.
+
+fn scalar(c: &mut Criterion) {
+    let (model, data) = make_model_and_data(10_000);
+
+    c.bench_function("scalar_ans_encoding", |b| {
+        b.iter(|| {
+            let mut encoder = DefaultAnsCoder::new();
+            encoder
+                .encode_iid_symbols_reverse(black_box(&data), &model)
+                .unwrap();
+            black_box(encoder.num_words());
+        })
+    });
+
+    let mut encoder = DefaultAnsCoder::new();
+    encoder.encode_iid_symbols_reverse(&data, &model).unwrap();
+    let compressed = encoder.into_compressed().unwrap();
+
+    c.bench_function("scalar_ans_decoding", |b| {
+        b.iter_batched(
+            || AnsCoder::<u32, u64>::from_compressed(compressed.clone()).unwrap(),
+            |mut decoder| {
+                let mut checksum = 1234usize;
+                for symbol in decoder.decode_iid_symbols(data.len(), &model) {
+                    checksum ^= symbol.unwrap();
+                }
+                black_box(checksum);
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    let mut decoder = AnsCoder::<u32, u64>::from_compressed(compressed).unwrap();
+    let decoded = decoder
+        .decode_iid_symbols(data.len(), &model)
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    assert_eq!(decoded, data);
+}
+
+fn interleaved_2_lanes(c: &mut Criterion) {
+    interleaved::<2>(c);
+}
+
+fn interleaved_4_lanes(c: &mut Criterion) {
+    interleaved::<4>(c);
+}
+
+fn interleaved_8_lanes(c: &mut Criterion) {
+    interleaved::<8>(c);
+}
+
+fn interleaved<const LANES: usize>(c: &mut Criterion) {
+    let (model, data) = make_model_and_data(10_000);
+
+    c.bench_function(&format!("interleaved_ans_encoding_{}_lanes", LANES), |b| {
+        b.iter(|| {
+            let mut encoder = InterleavedAnsCoder::<u32, u64, LANES>::new();
+            encoder
+                .encode_iid_symbols(black_box(&data), &model)
+                .unwrap();
+            black_box(encoder.num_words());
+        })
+    });
+
+    let mut encoder = InterleavedAnsCoder::<u32, u64, LANES>::new();
+    encoder.encode_iid_symbols(&data, &model).unwrap();
+    let compressed = encoder.into_compressed();
+
+    c.bench_function(&format!("interleaved_ans_decoding_{}_lanes", LANES), |b| {
+        b.iter_batched(
+            || InterleavedAnsCoder::<u32, u64, LANES>::from_compressed(compressed.clone()).unwrap(),
+            |mut decoder| {
+                let mut checksum = 1234usize;
+                for symbol in decoder.decode_iid_symbols(data.len(), &model) {
+                    checksum ^= symbol;
+                }
+                black_box(checksum);
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    let mut decoder = InterleavedAnsCoder::<u32, u64, LANES>::from_compressed(compressed).unwrap();
+    let decoded = decoder
+        .decode_iid_symbols(data.len(), &model)
+        .collect::<Vec<_>>();
+    assert_eq!(decoded, data);
+    assert!(decoder.is_empty());
+}
+
+fn make_model_and_data(
+    amt: usize,
+) -> (
+    DefaultContiguousCategoricalEntropyModel<Vec<u32>>,
+    Vec<usize>,
+) {
+    let mut rng = Xoshiro256StarStar::seed_from_u64(2282);
+    let probabilities = (0..100)
+        .map(|_| (rng.next_u32() % 1000 + 1) as f64)
+        .collect::<Vec<_>>();
+    let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+        &probabilities,
+        None,
+    )
+    .unwrap();
+    let data = (0..amt)
+        .map(|_| rng.next_u32() as usize % probabilities.len())
+        .collect();
+    (model, data)
+}
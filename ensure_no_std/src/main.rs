@@ -27,6 +27,28 @@ pub extern "C" fn _start() -> ! {
     assert_eq!(decoder.decode_symbol(model), Ok(5));
     assert_eq!(decoder.decode_symbol(model), Ok(3));
 
+    // Encode and decode a longer message with a categorical entropy model.
+    let categorical =
+        constriction::stream::model::DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+            &[0.1f64, 0.2, 0.4, 0.2, 0.1],
+            None,
+        )
+        .unwrap();
+    let message = [2usize, 0, 4, 2, 1, 3, 2, 2, 3];
+
+    let mut encoder = constriction::stream::stack::DefaultAnsCoder::new();
+    encoder
+        .encode_iid_symbols_reverse(message, &categorical)
+        .unwrap();
+    let compressed = core::hint::black_box(encoder.into_compressed().unwrap());
+
+    let mut decoder =
+        constriction::stream::stack::DefaultAnsCoder::from_compressed(compressed).unwrap();
+    for &expected in &message {
+        assert_eq!(decoder.decode_symbol(&categorical), Ok(expected));
+    }
+    assert!(decoder.is_empty());
+
     #[allow(clippy::empty_loop)]
     loop {}
 }
@@ -0,0 +1,40 @@
+//! Prints the data points of the bit rate vs. `PRECISION` curve for a categorical entropy
+//! model.
+//!
+//! Run with `cargo run --example bitrate_vs_precision`. The output is a tab separated table
+//! that can be pasted into any plotting tool.
+
+use constriction::stream::{
+    model::{DecoderModel, DefaultContiguousCategoricalEntropyModel, IterableEntropyModel},
+    stack::bitrate_vs_precision,
+};
+use rand::{RngCore, SeedableRng};
+use rand_xoshiro::Xoshiro256StarStar;
+
+fn main() {
+    // A distribution with a long tail of improbable symbols, which is where low
+    // precisions hurt most.
+    let probabilities = (0..50).map(|i| (-0.3 * i as f64).exp()).collect::<Vec<_>>();
+    let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+        &probabilities,
+        None,
+    )
+    .unwrap();
+
+    let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+    let symbols = (0..100_000)
+        .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+        .collect::<Vec<_>>();
+
+    let precisions = (6..=32).collect::<Vec<_>>();
+    let curve = bitrate_vs_precision(&symbols, &probabilities, &precisions).unwrap();
+
+    println!(
+        "# entropy: {} bits per symbol",
+        model.entropy_base2::<f64>()
+    );
+    println!("# precision\tbits_per_symbol");
+    for (precision, bits_per_symbol) in curve {
+        println!("{precision}\t{bits_per_symbol}");
+    }
+}
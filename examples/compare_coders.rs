@@ -0,0 +1,141 @@
+//! Compares the ANS coder (a stack) with the range coder (a queue) on the same data.
+//!
+//! Run with `cargo run --release --example compare_coders`. For each data set, the
+//! example encodes the same symbols with the same entropy model using both coders, checks
+//! that decoding recovers the symbols, and prints a table with the compressed size and the
+//! encoder and decoder throughput. Throughput numbers are only meaningful in release
+//! builds.
+
+use std::time::{Duration, Instant};
+
+use constriction::stream::{
+    model::{
+        DecoderModel, DefaultContiguousCategoricalEntropyModel, DefaultLeakyQuantizer, EncoderModel,
+    },
+    queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+    stack::DefaultAnsCoder,
+    Decode, Encode,
+};
+use probability::distribution::Gaussian;
+use rand::{RngCore, SeedableRng};
+use rand_xoshiro::Xoshiro256StarStar;
+
+/// Number of times each measurement is repeated; we report the fastest run.
+const REPETITIONS: usize = 5;
+
+struct Measurement {
+    compressed_bits: usize,
+    encode_time: Duration,
+    decode_time: Duration,
+}
+
+/// Drives both coders through their respective APIs with the same `symbols` and `model`.
+fn compare<M>(name: &str, symbols: &[M::Symbol], model: M)
+where
+    M: EncoderModel<24, Probability = u32> + DecoderModel<24> + Copy,
+    M::Symbol: PartialEq + core::fmt::Debug,
+{
+    let ans = measure(
+        symbols,
+        |symbols| {
+            let mut encoder = DefaultAnsCoder::new();
+            encoder.encode_iid_symbols_reverse(symbols, model).unwrap();
+            encoder.into_compressed().unwrap()
+        },
+        |compressed| {
+            let mut decoder = DefaultAnsCoder::from_compressed(compressed.to_vec()).unwrap();
+            decoder
+                .decode_iid_symbols(symbols.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        },
+    );
+
+    let range = measure(
+        symbols,
+        |symbols| {
+            let mut encoder = DefaultRangeEncoder::new();
+            encoder.encode_iid_symbols(symbols, model).unwrap();
+            encoder.into_compressed().unwrap()
+        },
+        |compressed| {
+            let mut decoder = DefaultRangeDecoder::from_compressed(compressed.to_vec()).unwrap();
+            decoder
+                .decode_iid_symbols(symbols.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        },
+    );
+
+    for (coder, measurement) in [("ANS (stack)", ans), ("range (queue)", range)] {
+        let throughput = |duration: Duration| symbols.len() as f64 / duration.as_secs_f64() / 1e6;
+        println!(
+            "{:<24} {:<14} {:>12} {:>12.4} {:>16.1} {:>16.1}",
+            name,
+            coder,
+            measurement.compressed_bits,
+            measurement.compressed_bits as f64 / symbols.len() as f64,
+            throughput(measurement.encode_time),
+            throughput(measurement.decode_time),
+        );
+    }
+}
+
+fn measure<Symbol>(
+    symbols: &[Symbol],
+    encode: impl Fn(&[Symbol]) -> Vec<u32>,
+    decode: impl Fn(&[u32]) -> Vec<Symbol>,
+) -> Measurement
+where
+    Symbol: PartialEq + core::fmt::Debug,
+{
+    let mut encode_time = Duration::MAX;
+    let mut decode_time = Duration::MAX;
+    let mut compressed = Vec::new();
+    for _ in 0..REPETITIONS {
+        let start = Instant::now();
+        compressed = encode(symbols);
+        encode_time = encode_time.min(start.elapsed());
+
+        let start = Instant::now();
+        let decoded = decode(&compressed);
+        decode_time = decode_time.min(start.elapsed());
+        assert_eq!(decoded, symbols);
+    }
+
+    Measurement {
+        compressed_bits: compressed.len() * 32,
+        encode_time,
+        decode_time,
+    }
+}
+
+fn main() {
+    const AMT: usize = 1_000_000;
+    let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+
+    println!(
+        "{:<24} {:<14} {:>12} {:>12} {:>16} {:>16}",
+        "data", "coder", "bits", "bits/symbol", "enc [Msymb/s]", "dec [Msymb/s]"
+    );
+
+    let quantizer = DefaultLeakyQuantizer::new(-1000..=1000);
+    for std_dev in [1.0, 100.0] {
+        let model = quantizer.quantize(Gaussian::new(0.0, std_dev));
+        let symbols = (0..AMT)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+        compare(&format!("gaussian (std={std_dev})"), &symbols, &model);
+    }
+
+    let probabilities = (0..50).map(|i| (-0.3 * i as f64).exp()).collect::<Vec<_>>();
+    let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+        &probabilities,
+        None,
+    )
+    .unwrap();
+    let symbols = (0..AMT)
+        .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+        .collect::<Vec<_>>();
+    compare("categorical (50 symbols)", &symbols, &model);
+}
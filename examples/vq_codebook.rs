@@ -0,0 +1,72 @@
+//! Entropy codes the indices of a vector quantizer with learned codebook probabilities.
+//!
+//! Run with `cargo run --example vq_codebook`.
+
+use constriction::stream::{model::SmallCodebook, stack::SmallAnsCoder, Decode};
+
+fn main() {
+    // A toy codebook of 2D vectors and the (learned) probabilities of its entries.
+    let codebook_vectors = [
+        [0.0, 0.0],
+        [1.0, 0.0],
+        [0.0, 1.0],
+        [-1.0, 0.0],
+        [0.0, -1.0],
+        [2.0, 2.0],
+    ];
+    let probabilities = [0.4, 0.15, 0.15, 0.12, 0.12, 0.06];
+    let codebook = SmallCodebook::from_learned_probabilities(&probabilities).unwrap();
+    println!(
+        "codebook with {} entries, most probable index: {}",
+        codebook.num_indices(),
+        codebook.most_probable_index()
+    );
+
+    // Quantize some data by mapping each point to its nearest codebook vector.
+    let data = [
+        [0.1, -0.2],
+        [0.9, 0.2],
+        [-0.1, 0.1],
+        [1.8, 2.3],
+        [0.0, -0.8],
+        [-1.1, 0.3],
+        [0.2, 0.2],
+        [0.1, 1.2],
+    ];
+    let indices = data
+        .iter()
+        .map(|point: &[f64; 2]| {
+            let squared_distance =
+                |v: &[f64; 2]| (point[0] - v[0]).powi(2) + (point[1] - v[1]).powi(2);
+            (0..codebook_vectors.len())
+                .min_by(|&a, &b| {
+                    squared_distance(&codebook_vectors[a])
+                        .partial_cmp(&squared_distance(&codebook_vectors[b]))
+                        .unwrap()
+                })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    println!("VQ indices: {:?}", indices);
+
+    // Encode the indices.
+    let mut ans = SmallAnsCoder::new();
+    ans.encode_iid_symbols_reverse(&indices, &codebook).unwrap();
+    let compressed = ans.into_compressed().unwrap();
+    println!(
+        "compressed {} indices into {} bits",
+        indices.len(),
+        16 * compressed.len()
+    );
+
+    // Decode them with a lookup decoder model, which is faster for low `PRECISION`.
+    let lookup = codebook.to_lookup_decoder_model();
+    let mut ans = SmallAnsCoder::from_compressed(compressed).unwrap();
+    let decoded = ans
+        .decode_iid_symbols(indices.len(), &lookup)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(decoded, indices);
+    assert!(ans.is_empty());
+    println!("decoded successfully");
+}
@@ -181,7 +181,7 @@
 //! [`ChainCoder`]: crate::stream::chain::ChainCoder
 //! [`AnsCoder`]: crate::stream::stack::AnsCoder
 
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
 use core::{
     convert::Infallible,
     fmt::{Debug, Display},
@@ -189,7 +189,8 @@ use core::{
 };
 use smallvec::SmallVec;
 
-use crate::{Pos, PosSeek, Queue, Seek, Semantics, Stack};
+use crate::{BitArray, Endianness, Pos, PosSeek, Queue, Seek, Semantics, Stack};
+use num_traits::AsPrimitive;
 
 // MAIN TRAITS FOR CAPABILITIES OF BACKENDS ===================================
 
@@ -296,6 +297,26 @@ pub trait WriteWords<Word> {
     fn maybe_full(&self) -> bool {
         true
     }
+
+    /// Hints that at least `additional` more `Word`s will be written to the data sink.
+    ///
+    /// Data sinks that grow dynamically may use this hint to preallocate memory so that
+    /// subsequent writes don't have to reallocate. The argument is in units of `Word`s
+    /// (not bits or bytes). The default implementation does nothing, which is always
+    /// correct.
+    #[inline(always)]
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
+
+    /// Hints that the data sink may release any memory that it holds in reserve.
+    ///
+    /// Data sinks that grow dynamically may use this hint to free up unused capacity,
+    /// e.g., after a lot of data has been read back from them. This must not change the
+    /// data in the data sink. The default implementation does nothing, which is always
+    /// correct.
+    #[inline(always)]
+    fn shrink_to_fit(&mut self) {}
 }
 
 /// A trait for data sources that know how much data is left.
@@ -468,6 +489,18 @@ impl<Word> WriteWords<Word> for Vec<Word> {
     fn maybe_full(&self) -> bool {
         false
     }
+
+    /// Reserves capacity for at least `additional` more words, see [`Vec::reserve`].
+    #[inline(always)]
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+
+    /// Shrinks the capacity as much as possible, see [`Vec::shrink_to_fit`].
+    #[inline(always)]
+    fn shrink_to_fit(&mut self) {
+        Vec::shrink_to_fit(self);
+    }
 }
 
 impl<Word> ReadWords<Word, Stack> for Vec<Word> {
@@ -560,6 +593,12 @@ where
     fn maybe_full(&self) -> bool {
         false
     }
+
+    /// Shrinks the capacity as much as possible, see [`SmallVec::shrink_to_fit`].
+    #[inline(always)]
+    fn shrink_to_fit(&mut self) {
+        SmallVec::shrink_to_fit(self);
+    }
 }
 
 impl<Array> ReadWords<Array::Item, Stack> for SmallVec<Array>
@@ -639,6 +678,107 @@ where
     }
 }
 
+// IMPLEMENTATIONS FOR `VecDeque<Word>` =======================================
+
+impl<Word> WriteWords<Word> for VecDeque<Word> {
+    /// The only way how writing to a `VecDeque<Word>` can fail is if a memory allocation
+    /// fails, which is typically treated as a fatal error (i.e., aborts) in Rust.
+    type WriteError = Infallible;
+
+    /// Appends the word to the back of the `VecDeque` (= top of the stack), exactly like
+    /// writing to a `Vec` appends to its end.
+    #[inline(always)]
+    fn write(&mut self, word: Word) -> Result<(), Self::WriteError> {
+        self.push_back(word);
+        Ok(())
+    }
+
+    fn extend_from_iter(
+        &mut self,
+        iter: impl Iterator<Item = Word>,
+    ) -> Result<(), Self::WriteError> {
+        self.extend(iter);
+        Ok(())
+    }
+
+    fn maybe_full(&self) -> bool {
+        false
+    }
+
+    /// Reserves capacity for at least `additional` more words, see [`VecDeque::reserve`].
+    #[inline(always)]
+    fn reserve(&mut self, additional: usize) {
+        VecDeque::reserve(self, additional);
+    }
+
+    /// Shrinks the capacity as much as possible, see [`VecDeque::shrink_to_fit`].
+    #[inline(always)]
+    fn shrink_to_fit(&mut self) {
+        VecDeque::shrink_to_fit(self);
+    }
+}
+
+impl<Word> ReadWords<Word, Stack> for VecDeque<Word> {
+    /// The only way how reading from a `VecDeque` can fail is if it is empty, but that's
+    /// not considered an error (it returns `Ok(None)` instead).
+    type ReadError = Infallible;
+
+    /// Pops the word off the back of the `VecDeque` (= top of the stack), i.e., the most
+    /// recently written word.
+    ///
+    /// Note that there's deliberately no implementation of `ReadWords<Word, Queue>` for
+    /// `VecDeque<Word>` because reads and writes have to refer to the same end for a
+    /// backend that supports both (see [module-level documentation](self)). You can still
+    /// take words off the front (= bottom of the stack, i.e., the words that were written
+    /// first) manually with [`VecDeque::pop_front`], e.g., to stream out compressed data
+    /// from an [`AnsCoder`](crate::stream::stack::AnsCoder) that is still encoding. But
+    /// be aware that decoding then can't go beyond the removed words.
+    #[inline(always)]
+    fn read(&mut self) -> Result<Option<Word>, Self::ReadError> {
+        Ok(self.pop_back())
+    }
+
+    #[inline(always)]
+    fn maybe_exhausted(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<Word> BoundedReadWords<Word, Stack> for VecDeque<Word> {
+    #[inline(always)]
+    fn remaining(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<Word> PosSeek for VecDeque<Word> {
+    type Position = usize;
+}
+
+impl<Word> Pos for VecDeque<Word> {
+    /// Returns the length of the `VecDeque` since that's the current read and write
+    /// position (`VecDeque`s, like `Vec`s, have [`Stack`] semantics when used as a
+    /// backend). Positions count from the front of the `VecDeque`.
+    fn pos(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<Word> Seek for VecDeque<Word> {
+    /// Seeking in a `VecDeque` only succeeds if the provided position `pos` is smaller
+    /// than or equal to the `VecDeque`'s current length. In this case, seeking will
+    /// truncate the `VecDeque` to length `pos` by removing words from its back, analogous
+    /// to seeking in a `Vec`.
+    fn seek(&mut self, pos: usize) -> Result<(), ()> {
+        if pos <= self.len() {
+            self.truncate(pos);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+}
+
 // ADAPTER FOR (SEMANTIC) REVERSING OF READING DIRECTION ======================
 
 /// Wrapper that inverts the read/write directions of a data source and/or data sink.
@@ -1072,8 +1212,8 @@ pub struct Cursor<Word, Buf> {
 /// of bounds without bounds checks.
 pub unsafe trait SafeBuf<Word>: AsRef<[Word]> {}
 
-unsafe impl<'a, Word> SafeBuf<Word> for &'a [Word] {}
-unsafe impl<'a, Word> SafeBuf<Word> for &'a mut [Word] {}
+unsafe impl<Word> SafeBuf<Word> for &[Word] {}
+unsafe impl<Word> SafeBuf<Word> for &mut [Word] {}
 unsafe impl<Word> SafeBuf<Word> for Vec<Word> {}
 unsafe impl<Word> SafeBuf<Word> for Box<[Word]> {}
 
@@ -1599,6 +1739,201 @@ where
     }
 }
 
+// RING BUFFER WITH A FIXED CAPACITY ==========================================
+
+/// What a [`RingBuffer`] does when a client writes to it while it is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// Writing to a full `RingBuffer` fails with [`BoundedWriteError::OutOfSpace`].
+    Fail,
+
+    /// Writing to a full `RingBuffer` discards the oldest word (i.e., the word that was
+    /// written first among all words in the buffer) to make space for the new word.
+    DiscardOldest,
+}
+
+/// A data sink with a hard memory cap that keeps only the most recently written words.
+///
+/// A `RingBuffer` has [`Stack`] semantics, i.e., it can be used as the backend of an
+/// [`AnsCoder`], and reading from it returns the most recently written words first. It
+/// holds at most `capacity` words, and its [`OverflowPolicy`] determines what happens when
+/// a client writes to a full `RingBuffer`: either the write fails, or the oldest word gets
+/// discarded. The latter is useful for "sliding window" compression where you only ever
+/// need to decode the most recently encoded symbols, e.g., for telemetry data. Note that
+/// discarding words is lossy: once a word has been discarded, an `AnsCoder` can no longer
+/// decode the symbols that were encoded before (and including) the discarded word, and
+/// attempting to do so leads to garbage (but not to undefined behavior).
+///
+/// The [position](Pos::pos) of a `RingBuffer` counts all words that are logically on the
+/// stack, including the ones that have been discarded. Thus, positions remain valid (and
+/// can be used for [seeking](Seek::seek)) as long as they don't refer to discarded
+/// words.
+///
+/// # Example
+///
+/// ```
+/// use constriction::{
+///     backends::{OverflowPolicy, RingBuffer},
+///     stream::{model::DefaultLeakyQuantizer, stack::AnsCoder, Decode, Encode},
+///     Pos,
+/// };
+/// use probability::distribution::Gaussian;
+///
+/// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+/// let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+/// let symbols = (0..1000).map(|i| (i % 37) - 18).collect::<Vec<i32>>();
+///
+/// // Keep at most 100 words of compressed data.
+/// let backend = RingBuffer::new(100, OverflowPolicy::DiscardOldest);
+/// let mut ans = AnsCoder::<u32, u64, _>::from_raw_parts(backend, 0);
+/// ans.encode_iid_symbols(&symbols, model).unwrap();
+/// assert_eq!(ans.bulk().len(), 100);
+/// assert!(ans.bulk().num_discarded() > 0);
+/// assert_eq!(ans.bulk().pos(), ans.bulk().num_discarded() + 100);
+///
+/// // The most recently encoded symbols can still be decoded (in reverse order).
+/// for &expected in symbols.iter().rev().take(100) {
+///     assert_eq!(ans.decode_symbol(model).unwrap(), expected);
+/// }
+/// ```
+///
+/// [`AnsCoder`]: crate::stream::stack::AnsCoder
+#[derive(Debug, Clone)]
+pub struct RingBuffer<Word> {
+    buf: VecDeque<Word>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    num_discarded: usize,
+}
+
+impl<Word> RingBuffer<Word> {
+    /// Creates an empty `RingBuffer` that can hold up to `capacity` words.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        assert!(capacity != 0, "`RingBuffer` must have a nonzero capacity.");
+        Self {
+            buf: VecDeque::with_capacity(capacity),
+            capacity,
+            policy,
+            num_discarded: 0,
+        }
+    }
+
+    /// Returns the maximum number of words that the `RingBuffer` can hold at a time.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the [`OverflowPolicy`] that was provided to the constructor.
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Returns the number of words that are currently held in the `RingBuffer`.
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns `true` if the `RingBuffer` currently holds no words.
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Returns the total number of words that have been discarded so far due to
+    /// [`OverflowPolicy::DiscardOldest`].
+    pub fn num_discarded(&self) -> usize {
+        self.num_discarded
+    }
+
+    /// Returns an iterator over the words currently held in the `RingBuffer`, from the
+    /// oldest to the most recently written one.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &Word> + DoubleEndedIterator {
+        self.buf.iter()
+    }
+}
+
+impl<Word> WriteWords<Word> for RingBuffer<Word> {
+    type WriteError = BoundedWriteError;
+
+    #[inline]
+    fn write(&mut self, word: Word) -> Result<(), Self::WriteError> {
+        if self.buf.len() == self.capacity {
+            match self.policy {
+                OverflowPolicy::Fail => return Err(BoundedWriteError::OutOfSpace),
+                OverflowPolicy::DiscardOldest => {
+                    self.buf.pop_front();
+                    self.num_discarded += 1;
+                }
+            }
+        }
+        self.buf.push_back(word);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn maybe_full(&self) -> bool {
+        self.policy == OverflowPolicy::Fail && self.is_full()
+    }
+}
+
+impl<Word> BoundedWriteWords<Word> for RingBuffer<Word> {
+    /// Returns the number of words that can be written before the `RingBuffer` either
+    /// fails or starts discarding words (depending on its [`OverflowPolicy`]).
+    #[inline(always)]
+    fn space_left(&self) -> usize {
+        self.capacity - self.buf.len()
+    }
+}
+
+impl<Word> ReadWords<Word, Stack> for RingBuffer<Word> {
+    type ReadError = Infallible;
+
+    #[inline(always)]
+    fn read(&mut self) -> Result<Option<Word>, Self::ReadError> {
+        Ok(self.buf.pop_back())
+    }
+
+    #[inline(always)]
+    fn maybe_exhausted(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<Word> BoundedReadWords<Word, Stack> for RingBuffer<Word> {
+    #[inline(always)]
+    fn remaining(&self) -> usize {
+        self.buf.len()
+    }
+}
+
+impl<Word> PosSeek for RingBuffer<Word> {
+    type Position = usize;
+}
+
+impl<Word> Pos for RingBuffer<Word> {
+    /// Returns the total number of words on the stack, including discarded ones.
+    fn pos(&self) -> usize {
+        self.num_discarded + self.buf.len()
+    }
+}
+
+impl<Word> Seek for RingBuffer<Word> {
+    /// Truncates the stack to position `pos`, analogous to seeking in a `Vec`. Fails if
+    /// `pos` is beyond the current position or if it refers to discarded words.
+    fn seek(&mut self, pos: usize) -> Result<(), ()> {
+        let len = pos.checked_sub(self.num_discarded).ok_or(())?;
+        if len <= self.buf.len() {
+            self.buf.truncate(len);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+}
+
 // READ ADAPTER FOR ITERATORS =================================================
 
 /// Adapter that turns an iterator over `Result<Word, ReadError>` into a data source.
@@ -1822,6 +2157,542 @@ where
     }
 }
 
+// WRITE ADAPTER FOR COUNTING =================================================
+
+/// A data sink that discards all written words and only counts them.
+///
+/// Use a `CountingWriteWords` as the backend of an encoder if you only want to know how
+/// large the compressed data would be, e.g., when comparing different entropy models,
+/// but you don't need the compressed data itself. Writing to a `CountingWriteWords` is
+/// infallible and doesn't allocate.
+///
+/// Remember that entropy coders hold some compressed data in their internal state. To
+/// count these words too, call the encoder's `into_compressed` method, which flushes the
+/// internal state to the backend and then returns the backend.
+///
+/// # Example
+///
+/// ```
+/// use constriction::{
+///     backends::CountingWriteWords,
+///     stream::{model::DefaultLeakyQuantizer, stack::AnsCoder, Encode},
+///     UnwrapInfallible,
+/// };
+/// use probability::distribution::Gaussian;
+///
+/// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+/// let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+/// let symbols = (0..1000).map(|i| (i % 37) - 18).collect::<Vec<i32>>();
+///
+/// let mut counting_coder = AnsCoder::<u32, u64, CountingWriteWords<u32>>::default();
+/// counting_coder.encode_iid_symbols_reverse(&symbols, model).unwrap();
+/// let num_words = counting_coder.into_compressed().unwrap_infallible().num_words();
+///
+/// // Compare to actually compressing the data.
+/// let mut coder = AnsCoder::<u32, u64>::new();
+/// coder.encode_iid_symbols_reverse(&symbols, model).unwrap();
+/// assert_eq!(num_words, coder.into_compressed().unwrap_infallible().len());
+/// ```
+#[derive(Debug)]
+pub struct CountingWriteWords<Word> {
+    num_words: usize,
+    phantom: PhantomData<fn(Word)>,
+}
+
+impl<Word> CountingWriteWords<Word> {
+    /// Creates a `CountingWriteWords` that hasn't counted any words yet.
+    pub fn new() -> Self {
+        Self {
+            num_words: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the number of words that have been written so far.
+    pub fn num_words(&self) -> usize {
+        self.num_words
+    }
+}
+
+impl<Word> Default for CountingWriteWords<Word> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Word> Clone for CountingWriteWords<Word> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Word> Copy for CountingWriteWords<Word> {}
+
+impl<Word> WriteWords<Word> for CountingWriteWords<Word> {
+    type WriteError = Infallible;
+
+    #[inline(always)]
+    fn write(&mut self, _word: Word) -> Result<(), Self::WriteError> {
+        self.num_words += 1;
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn extend_from_iter(
+        &mut self,
+        iter: impl Iterator<Item = Word>,
+    ) -> Result<(), Self::WriteError> {
+        self.num_words += iter.count();
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn maybe_full(&self) -> bool {
+        false
+    }
+}
+
+impl<Word> PosSeek for CountingWriteWords<Word> {
+    type Position = usize;
+}
+
+impl<Word> Pos for CountingWriteWords<Word> {
+    /// Returns the number of words that have been written so far.
+    fn pos(&self) -> usize {
+        self.num_words
+    }
+}
+
+// ADAPTERS FOR `std::io` =====================================================
+
+/// Adapter that turns a [`std::io::Write`] into a data sink of compressed words.
+///
+/// Wraps a writer (e.g., a [`File`](std::fs::File), a network socket, or a
+/// [`BufWriter`](std::io::BufWriter)) and implements [`WriteWords<Word>`](WriteWords) by
+/// serializing each word to `Word::BITS / 8` bytes in the byte order specified by the
+/// provided [`Endianness`]. This allows you to stream compressed data directly to its
+/// destination instead of accumulating it in memory first. Each call to [`write`] issues
+/// a small write to the underlying writer, so you'll usually want to wrap unbuffered
+/// writers in a [`BufWriter`](std::io::BufWriter).
+///
+/// # Words Are Committed Incrementally
+///
+/// Entropy coders write out words as soon as their internal state fills up, so the
+/// written data is incomplete until you also flush the coder's internal state, e.g., by
+/// calling [`AnsCoder::into_compressed`] or [`RangeEncoder::into_compressed`]. These
+/// methods return the backend, which you can then turn back into the wrapped writer by
+/// calling [`into_inner`](Self::into_inner). Note that an [`AnsCoder`] writes words in the
+/// order in which they are produced, but it decodes them in *reverse* order (since it is a
+/// stack). Thus, to decode on the fly, you'll have to read the words back starting from
+/// the end of the written data.
+///
+/// # Example
+///
+/// ```
+/// use constriction::{
+///     backends::IoWriteWords,
+///     stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode, Encode},
+///     Endianness,
+/// };
+/// use probability::distribution::Gaussian;
+///
+/// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+/// let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+/// let symbols = [3, -8, 12, 0, -1, 25, 7];
+///
+/// // Stream the compressed words into some `std::io::Write` (here: a `Vec<u8>`).
+/// let backend = IoWriteWords::new(Vec::<u8>::new(), Endianness::LittleEndian);
+/// let mut ans = DefaultAnsCoder::from_raw_parts(backend, 0);
+/// ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+/// let bytes = ans.into_compressed().unwrap().into_inner(); // Flushes the coder's state.
+///
+/// // The bytes are the same as those returned by `into_compressed_bytes`.
+/// let mut decoder = DefaultAnsCoder::from_compressed_bytes(&bytes, Endianness::LittleEndian)
+///     .unwrap();
+/// let decoded = decoder
+///     .decode_iid_symbols(symbols.len(), model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// ```
+///
+/// [`write`]: WriteWords::write
+/// [`AnsCoder`]: crate::stream::stack::AnsCoder
+/// [`AnsCoder::into_compressed`]: crate::stream::stack::AnsCoder::into_compressed
+/// [`RangeEncoder::into_compressed`]: crate::stream::queue::RangeEncoder::into_compressed
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct IoWriteWords<W, Word> {
+    writer: W,
+    endianness: Endianness,
+    phantom: PhantomData<Word>,
+}
+
+#[cfg(feature = "std")]
+impl<W, Word> IoWriteWords<W, Word> {
+    /// Creates the adapter for the provided writer.
+    pub fn new(writer: W, endianness: Endianness) -> Self {
+        Self {
+            writer,
+            endianness,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the byte order with which words are serialized.
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Returns a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a mutable reference to the wrapped writer.
+    ///
+    /// Writing directly to the wrapped writer will corrupt the compressed data.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Consumes the adapter and returns the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W, Word> WriteWords<Word> for IoWriteWords<W, Word>
+where
+    W: std::io::Write,
+    Word: BitArray + AsPrimitive<u8>,
+{
+    type WriteError = std::io::Error;
+
+    fn write(&mut self, word: Word) -> Result<(), Self::WriteError> {
+        let mut bytes = [0u8; 16];
+        let bytes = &mut bytes[..Word::BITS / 8];
+        word_to_bytes(word, bytes, self.endianness);
+        self.writer.write_all(bytes)
+    }
+
+    #[inline(always)]
+    fn maybe_full(&self) -> bool {
+        false
+    }
+}
+
+/// Serializes `word` into `bytes` (whose length is typically `Word::BITS / 8`) in the byte
+/// order given by `endianness`.
+pub(crate) fn word_to_bytes<Word>(word: Word, bytes: &mut [u8], endianness: Endianness)
+where
+    Word: BitArray + AsPrimitive<u8>,
+{
+    let len = bytes.len();
+    for (k, byte) in bytes.iter_mut().enumerate() {
+        let shift = match endianness {
+            Endianness::LittleEndian => 8 * k,
+            Endianness::BigEndian => 8 * (len - 1 - k),
+        };
+        *byte = (word >> shift).as_();
+    }
+}
+
+/// Inverse of [`word_to_bytes`].
+pub(crate) fn bytes_to_word<Word>(bytes: &[u8], endianness: Endianness) -> Word
+where
+    Word: BitArray,
+    u8: Into<Word>,
+{
+    let accumulate = |word: Word, &byte: &u8| (word << 8) | byte.into();
+    match endianness {
+        Endianness::LittleEndian => bytes.iter().rev().fold(Word::zero(), accumulate),
+        Endianness::BigEndian => bytes.iter().fold(Word::zero(), accumulate),
+    }
+}
+
+/// Adapter that turns a [`std::io::Read`] into a data source of compressed words.
+///
+/// Wraps a reader (e.g., a [`File`](std::fs::File), a network socket, or a
+/// [`BufReader`](std::io::BufReader)) and implements [`ReadWords<Word, S>`](ReadWords)
+/// for both [`Stack`] and [`Queue`] semantics by deserializing consecutive groups of
+/// `Word::BITS / 8` bytes in the byte order specified by the provided [`Endianness`]. This
+/// allows you to decode compressed data lazily without loading it into memory first.
+/// Reading returns `Ok(None)` once the reader reaches its end, and it returns an error if
+/// the reader fails or if it ends in the middle of a word.
+///
+/// # Ordering Requirements
+///
+/// An `IoReadWords` always reads words in the order in which they appear in the wrapped
+/// reader. This is the correct order for entropy coders with [`Queue`] semantics (e.g., a
+/// [`RangeDecoder`]). But an [`AnsCoder`] is a stack, i.e., it reads compressed words in
+/// the reverse order of how it wrote them. Thus, to decode with an `AnsCoder` from an
+/// `IoReadWords`, the data must have been written in reversed order (i.e., starting with
+/// the last word of [`AnsCoder::into_compressed`]). If your data is stored in normal order
+/// (e.g., if it was written with an [`IoWriteWords`]) and the reader implements
+/// [`std::io::Seek`] (e.g., a `File`), then use a [`BackwardIoReadWords`] instead.
+///
+/// # Example
+///
+/// ```
+/// use constriction::{
+///     backends::IoReadWords,
+///     stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode, Encode},
+///     Endianness,
+/// };
+/// use probability::distribution::Gaussian;
+///
+/// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+/// let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+/// let symbols = [3, -8, 12, 0, -1, 25, 7];
+///
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+/// let mut compressed = ans.into_compressed().unwrap();
+///
+/// // Serialize in reverse order so that the `AnsCoder` can read the words front to back.
+/// compressed.reverse();
+/// let bytes = compressed.iter().flat_map(|word| word.to_be_bytes()).collect::<Vec<u8>>();
+///
+/// let backend = IoReadWords::new(&bytes[..], Endianness::BigEndian);
+/// let mut decoder = DefaultAnsCoder::from_compressed(backend).unwrap();
+/// let decoded = decoder
+///     .decode_iid_symbols(symbols.len(), model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// assert!(decoder.is_empty());
+/// ```
+///
+/// [`RangeDecoder`]: crate::stream::queue::RangeDecoder
+/// [`AnsCoder`]: crate::stream::stack::AnsCoder
+/// [`AnsCoder::into_compressed`]: crate::stream::stack::AnsCoder::into_compressed
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct IoReadWords<R, Word> {
+    reader: R,
+    endianness: Endianness,
+    phantom: PhantomData<Word>,
+}
+
+#[cfg(feature = "std")]
+impl<R, Word> IoReadWords<R, Word> {
+    /// Creates the adapter for the provided reader.
+    pub fn new(reader: R, endianness: Endianness) -> Self {
+        Self {
+            reader,
+            endianness,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the byte order with which words are deserialized.
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Returns a reference to the wrapped reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns a mutable reference to the wrapped reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Consumes the adapter and returns the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R, Word, S> ReadWords<Word, S> for IoReadWords<R, Word>
+where
+    R: std::io::Read,
+    Word: BitArray,
+    u8: Into<Word>,
+    S: Semantics,
+{
+    type ReadError = std::io::Error;
+
+    fn read(&mut self) -> Result<Option<Word>, Self::ReadError> {
+        let mut bytes = [0u8; 16];
+        let bytes = &mut bytes[..Word::BITS / 8];
+        let mut len = 0;
+        while len < bytes.len() {
+            match self.reader.read(&mut bytes[len..]) {
+                Ok(0) if len == 0 => return Ok(None),
+                Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+                Ok(amt) => len += amt,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(Some(bytes_to_word(bytes, self.endianness)))
+    }
+}
+
+/// Adapter that reads compressed words from the end of a seekable [`std::io::Read`].
+///
+/// This is a variant of [`IoReadWords`] for [`AnsCoder`]s, which read compressed words in
+/// the reverse order of how they were written. A `BackwardIoReadWords` reads the words
+/// between the reader's position at construction time and the end of the reader, starting
+/// from the end. It reads the data in chunks of [`CHUNK_SIZE`](Self::CHUNK_SIZE) words and
+/// buffers each chunk in memory, so only a small part of the compressed data has to be held
+/// in memory at a time.
+///
+/// Thus, a `BackwardIoReadWords` can decode data that was written with an [`IoWriteWords`]
+/// (using the same [`Endianness`]), or, equivalently, data returned by
+/// [`AnsCoder::into_compressed_bytes`].
+///
+/// # Example
+///
+/// ```
+/// use constriction::{
+///     backends::BackwardIoReadWords,
+///     stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode, Encode},
+///     Endianness,
+/// };
+/// use probability::distribution::Gaussian;
+///
+/// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+/// let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+/// let symbols = [3, -8, 12, 0, -1, 25, 7];
+///
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+/// let bytes = ans.into_compressed_bytes(Endianness::LittleEndian);
+///
+/// // `std::io::Cursor` implements `Read` and `Seek`, just like a `File`.
+/// let reader = std::io::Cursor::new(bytes);
+/// let backend = BackwardIoReadWords::new(reader, Endianness::LittleEndian).unwrap();
+/// let mut decoder = DefaultAnsCoder::from_compressed(backend).unwrap();
+/// let decoded = decoder
+///     .decode_iid_symbols(symbols.len(), model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// assert!(decoder.is_empty());
+/// ```
+///
+/// [`AnsCoder`]: crate::stream::stack::AnsCoder
+/// [`AnsCoder::into_compressed_bytes`]: crate::stream::stack::AnsCoder::into_compressed_bytes
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct BackwardIoReadWords<R, Word> {
+    reader: R,
+    endianness: Endianness,
+    /// Position in `reader` where the compressed data starts.
+    start: u64,
+    /// Position in `reader` up to which the data has not yet been read into `buf`.
+    pos: u64,
+    buf: Vec<u8>,
+    phantom: PhantomData<Word>,
+}
+
+#[cfg(feature = "std")]
+impl<R, Word> BackwardIoReadWords<R, Word>
+where
+    R: std::io::Read + std::io::Seek,
+    Word: BitArray,
+{
+    /// The number of words that get read into memory at once.
+    pub const CHUNK_SIZE: usize = 1024;
+
+    /// Creates the adapter for the data between the current position of `reader` and its
+    /// end.
+    ///
+    /// Returns an error if seeking fails, or if the length of the data is not a multiple
+    /// of `Word::BITS / 8` bytes (in which case the error kind is
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData)).
+    pub fn new(mut reader: R, endianness: Endianness) -> std::io::Result<Self> {
+        let start = reader.stream_position()?;
+        let pos = reader.seek(std::io::SeekFrom::End(0))?;
+        if (pos - start) % (Word::BITS / 8) as u64 != 0 {
+            return Err(std::io::ErrorKind::InvalidData.into());
+        }
+
+        Ok(Self {
+            reader,
+            endianness,
+            start,
+            pos,
+            buf: Vec::new(),
+            phantom: PhantomData,
+        })
+    }
+
+    /// Returns the byte order with which words are deserialized.
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Consumes the adapter and returns the wrapped reader.
+    ///
+    /// The position of the returned reader is unspecified.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn refill(&mut self) -> std::io::Result<()> {
+        let chunk_bytes = (Self::CHUNK_SIZE * (Word::BITS / 8)) as u64;
+        let chunk_start = core::cmp::max(self.start, self.pos.saturating_sub(chunk_bytes));
+        self.reader.seek(std::io::SeekFrom::Start(chunk_start))?;
+        self.buf.resize((self.pos - chunk_start) as usize, 0);
+        if let Err(err) = self.reader.read_exact(&mut self.buf) {
+            self.buf.clear();
+            return Err(err);
+        }
+        self.pos = chunk_start;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R, Word> ReadWords<Word, Stack> for BackwardIoReadWords<R, Word>
+where
+    R: std::io::Read + std::io::Seek,
+    Word: BitArray,
+    u8: Into<Word>,
+{
+    type ReadError = std::io::Error;
+
+    fn read(&mut self) -> Result<Option<Word>, Self::ReadError> {
+        if self.buf.is_empty() {
+            if self.pos == self.start {
+                return Ok(None);
+            }
+            self.refill()?;
+        }
+
+        let split = self.buf.len() - Word::BITS / 8;
+        let word = bytes_to_word(&self.buf[split..], self.endianness);
+        self.buf.truncate(split);
+        Ok(Some(word))
+    }
+
+    #[inline(always)]
+    fn maybe_exhausted(&self) -> bool {
+        BoundedReadWords::<Word, Stack>::is_exhausted(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R, Word> BoundedReadWords<Word, Stack> for BackwardIoReadWords<R, Word>
+where
+    R: std::io::Read + std::io::Seek,
+    Word: BitArray,
+    u8: Into<Word>,
+{
+    #[inline(always)]
+    fn remaining(&self) -> usize {
+        (self.buf.len() + (self.pos - self.start) as usize) / (Word::BITS / 8)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode};
@@ -1832,6 +2703,314 @@ mod tests {
         io::{BufReader, BufWriter},
     };
 
+    #[test]
+    fn io_write_words() {
+        use super::*;
+        use crate::{
+            stream::{stack::AnsCoder, Encode},
+            Endianness,
+        };
+
+        let quantizer = DefaultLeakyQuantizer::new(-256..=255);
+        let model = quantizer.quantize(Gaussian::new(0.0, 100.0));
+        let symbols = (0..1000u32)
+            .map(|i| {
+                let cheap_hash = i.wrapping_mul(0x6979_E2F3).wrapping_add(0x0059_0E91);
+                (cheap_hash >> (32 - 9)) as i32 - 256
+            })
+            .collect::<Vec<_>>();
+
+        for endianness in [Endianness::LittleEndian, Endianness::BigEndian] {
+            let mut reference = DefaultAnsCoder::new();
+            reference
+                .encode_iid_symbols_reverse(&symbols, model)
+                .unwrap();
+            let expected = reference.into_compressed_bytes(endianness);
+
+            let backend = IoWriteWords::new(std::io::Cursor::new(Vec::new()), endianness);
+            let mut ans = AnsCoder::<u32, u64, _>::from_raw_parts(backend, 0);
+            ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+            let written = ans.bulk().get_ref().get_ref().len();
+            assert!(written < expected.len());
+            assert_eq!(written % 4, 0);
+            let bytes = ans.into_compressed().unwrap().into_inner().into_inner();
+            assert_eq!(bytes, expected);
+
+            let mut decoder = DefaultAnsCoder::from_compressed_bytes(&bytes, endianness).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(decoder.is_empty());
+        }
+
+        // Errors of the underlying writer are propagated.
+        let mut buf = [0u8; 10];
+        let backend = IoWriteWords::new(&mut buf[..], Endianness::LittleEndian);
+        let mut ans = AnsCoder::<u32, u64, _>::from_raw_parts(backend, 0);
+        assert!(ans.encode_iid_symbols(&symbols, model).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn io_file_round_trip() {
+        use super::*;
+        use crate::{
+            stream::{
+                queue::{RangeDecoder, RangeEncoder},
+                stack::AnsCoder,
+                Encode,
+            },
+            Endianness,
+        };
+
+        let quantizer = DefaultLeakyQuantizer::new(-256..=255);
+        let model = quantizer.quantize(Gaussian::new(0.0, 100.0));
+        let symbols = (0..10_000u32)
+            .map(|i| {
+                let cheap_hash = i.wrapping_mul(0x6979_E2F3).wrapping_add(0x0059_0E91);
+                (cheap_hash >> (32 - 9)) as i32 - 256
+            })
+            .collect::<Vec<_>>();
+
+        for endianness in [Endianness::LittleEndian, Endianness::BigEndian] {
+            // Stack: write in normal order, read back from the end of the file.
+            let file = BufWriter::new(File::create("backend_io_stack.tmp").unwrap());
+            let mut ans =
+                AnsCoder::<u32, u64, _>::from_raw_parts(IoWriteWords::new(file, endianness), 0);
+            ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+            ans.into_compressed()
+                .unwrap()
+                .into_inner()
+                .into_inner()
+                .unwrap();
+
+            let file = File::open("backend_io_stack.tmp").unwrap();
+            let backend = BackwardIoReadWords::<_, u32>::new(file, endianness).unwrap();
+            let num_words = backend.remaining();
+            assert!(num_words > BackwardIoReadWords::<File, u32>::CHUNK_SIZE);
+            let mut decoder = AnsCoder::<u32, u64, _>::from_compressed(backend).unwrap();
+            assert_eq!(decoder.bulk().remaining(), num_words - 2);
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(decoder.is_empty());
+            assert!(decoder.bulk().is_exhausted());
+
+            // Queue: write and read in the same order.
+            let file = BufWriter::new(File::create("backend_io_queue.tmp").unwrap());
+            let mut encoder =
+                RangeEncoder::<u32, u64, _>::with_backend(IoWriteWords::new(file, endianness));
+            encoder.encode_iid_symbols(&symbols, model).unwrap();
+            encoder
+                .into_compressed()
+                .unwrap()
+                .into_inner()
+                .into_inner()
+                .unwrap();
+
+            let file = BufReader::new(File::open("backend_io_queue.tmp").unwrap());
+            let mut decoder =
+                RangeDecoder::<u32, u64, _>::with_backend(IoReadWords::new(file, endianness))
+                    .unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+        }
+
+        std::fs::remove_file("backend_io_stack.tmp").unwrap();
+        std::fs::remove_file("backend_io_queue.tmp").unwrap();
+
+        // Data that ends in the middle of a word is detected.
+        let bytes = [1u8, 2, 3, 4, 5, 6];
+        let mut backend = IoReadWords::<_, u32>::new(&bytes[..], Endianness::BigEndian);
+        assert_eq!(
+            ReadWords::<u32, Queue>::read(&mut backend).unwrap(),
+            Some(0x0102_0304)
+        );
+        assert!(ReadWords::<u32, Queue>::read(&mut backend).is_err());
+        assert!(BackwardIoReadWords::<_, u32>::new(
+            std::io::Cursor::new(bytes),
+            Endianness::BigEndian
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn counting_write_words() {
+        use super::*;
+        use crate::{
+            stream::{queue::RangeEncoder, stack::AnsCoder, Encode},
+            UnwrapInfallible,
+        };
+
+        let quantizer = DefaultLeakyQuantizer::new(-256..=255);
+        for (amt, std_dev) in [(0, 10.0), (1, 10.0), (10, 0.1), (1000, 100.0), (1000, 3.0)] {
+            let model = quantizer.quantize(Gaussian::new(0.0, std_dev));
+            let symbols = (0..amt)
+                .map(|i: u32| {
+                    let cheap_hash = i.wrapping_mul(0x6979_E2F3).wrapping_add(0x0059_0E91);
+                    (cheap_hash >> (32 - 5)) as i32 - 16
+                })
+                .collect::<Vec<_>>();
+
+            let mut counting = AnsCoder::<u32, u64, CountingWriteWords<u32>>::default();
+            let mut coder = DefaultAnsCoder::new();
+            counting
+                .encode_iid_symbols_reverse(&symbols, model)
+                .unwrap();
+            coder.encode_iid_symbols_reverse(&symbols, model).unwrap();
+            assert_eq!(counting.bulk().pos(), coder.bulk().len());
+            let num_words = coder.num_words();
+            let compressed = coder.into_compressed().unwrap_infallible();
+            assert_eq!(compressed.len(), num_words);
+            let counted = counting.into_compressed().unwrap_infallible();
+            assert_eq!(counted.num_words(), compressed.len());
+
+            let mut counting =
+                RangeEncoder::<u32, u64, _>::with_backend(CountingWriteWords::<u32>::new());
+            let mut encoder = RangeEncoder::<u32, u64>::new();
+            counting.encode_iid_symbols(&symbols, model).unwrap();
+            encoder.encode_iid_symbols(&symbols, model).unwrap();
+            let counted = counting.into_compressed().unwrap_infallible();
+            let compressed = encoder.into_compressed().unwrap_infallible();
+            assert_eq!(counted.num_words(), compressed.len());
+        }
+    }
+
+    #[test]
+    fn ring_buffer() {
+        use super::*;
+        use crate::stream::{stack::AnsCoder, Encode};
+
+        let quantizer = DefaultLeakyQuantizer::new(-256..=255);
+        let model = quantizer.quantize(Gaussian::new(0.0, 100.0));
+        let symbols = (0..1000u32)
+            .map(|i| {
+                let cheap_hash = i.wrapping_mul(0x6979_E2F3).wrapping_add(0x0059_0E91);
+                (cheap_hash >> (32 - 9)) as i32 - 256
+            })
+            .collect::<Vec<_>>();
+
+        // With `OverflowPolicy::Fail`, writing beyond the capacity fails.
+        let mut ans =
+            AnsCoder::<u32, u64, _>::from_raw_parts(RingBuffer::new(50, OverflowPolicy::Fail), 0);
+        let mut result = Ok(());
+        for &symbol in &symbols {
+            result = ans.encode_symbol(symbol, model);
+            if result.is_err() {
+                break;
+            }
+            let bulk = ans.bulk();
+            assert_eq!(bulk.pos(), bulk.len());
+            assert_eq!(bulk.remaining() + bulk.space_left(), 50);
+            assert_eq!(bulk.is_full(), bulk.maybe_full());
+        }
+        assert!(result.is_err());
+        assert_eq!(ans.bulk().len(), 50);
+        assert_eq!(ans.bulk().num_discarded(), 0);
+
+        // With `OverflowPolicy::DiscardOldest`, the most recent words are retained.
+        let mut ans = AnsCoder::<u32, u64, _>::from_raw_parts(
+            RingBuffer::new(50, OverflowPolicy::DiscardOldest),
+            0,
+        );
+        let mut reference = AnsCoder::<u32, u64>::new();
+        let mut previous_pos = 0;
+        for &symbol in &symbols {
+            ans.encode_symbol(symbol, model).unwrap();
+            reference.encode_symbol(symbol, model).unwrap();
+            let bulk = ans.bulk();
+            assert!(bulk.len() <= 50);
+            assert_eq!(bulk.remaining(), bulk.len());
+            assert_eq!(bulk.pos(), reference.bulk().len());
+            assert!(bulk.pos() >= previous_pos);
+            assert!(!bulk.maybe_full());
+            previous_pos = bulk.pos();
+        }
+        let bulk = ans.bulk();
+        assert_eq!(bulk.len(), 50);
+        assert_eq!(bulk.num_discarded() + 50, reference.bulk().len());
+        assert!(bulk
+            .iter()
+            .eq(&reference.bulk()[reference.bulk().len() - 50..]));
+
+        // Decoding the most recent symbols works.
+        for &expected in symbols.iter().rev().take(50) {
+            assert_eq!(ans.decode_symbol(model).unwrap(), expected);
+            let bulk = ans.bulk();
+            assert_eq!(bulk.pos(), bulk.num_discarded() + bulk.remaining());
+        }
+
+        // Seeking can't go to discarded positions.
+        let mut ring_buffer = ans.into_raw_parts().0;
+        let num_discarded = ring_buffer.num_discarded();
+        let pos = ring_buffer.pos();
+        assert!(ring_buffer.seek(pos + 1).is_err());
+        assert!(ring_buffer.seek(num_discarded - 1).is_err());
+        ring_buffer.seek(num_discarded + 1).unwrap();
+        assert_eq!(ring_buffer.len(), 1);
+        assert_eq!(ring_buffer.pos(), num_discarded + 1);
+    }
+
+    #[test]
+    fn vec_deque() {
+        use super::*;
+        use crate::stream::{stack::AnsCoder, Code};
+
+        let quantizer = DefaultLeakyQuantizer::new(-256..=255);
+        let model = quantizer.quantize(Gaussian::new(0.0, 100.0));
+        let symbols = (0..1000u32)
+            .map(|i| {
+                let cheap_hash = i.wrapping_mul(0x6979_E2F3).wrapping_add(0x0059_0E91);
+                (cheap_hash >> (32 - 9)) as i32 - 256
+            })
+            .collect::<Vec<_>>();
+
+        let mut reference = DefaultAnsCoder::new();
+        reference
+            .encode_iid_symbols_reverse(&symbols, model)
+            .unwrap();
+
+        let mut ans = AnsCoder::<u32, u64, _>::from_raw_parts(VecDeque::new(), 0);
+        ans.encode_iid_symbols_reverse(&symbols[500..], model)
+            .unwrap();
+        let checkpoint = ans.pos_and_state();
+        ans.encode_iid_symbols_reverse(&symbols[..500], model)
+            .unwrap();
+
+        // The words are stored in the same order as in a `Vec` backend.
+        assert!(ans.bulk().iter().eq(reference.bulk()));
+        assert_eq!(ans.state(), reference.state());
+        assert_eq!(ans.bulk().pos(), ans.bulk().remaining());
+
+        let decoded = ans
+            .decode_iid_symbols(500, model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols[..500]);
+        assert_eq!(ans.pos_and_state(), checkpoint);
+
+        // Seeking truncates the `VecDeque` from the back, like for a `Vec`.
+        let (mut bulk, state) = ans.into_raw_parts();
+        let pos = bulk.pos();
+        assert!(bulk.seek(pos + 1).is_err());
+        bulk.seek(pos).unwrap();
+        let mut ans = AnsCoder::<u32, u64, _>::from_raw_parts(bulk, state);
+        let decoded = ans
+            .decode_iid_symbols(500, model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols[500..]);
+        assert!(ans.is_empty());
+        assert!(ans.bulk().is_empty());
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn decode_on_the_fly_stack() {
@@ -307,6 +307,30 @@ impl Semantics for Stack {}
 pub struct Queue {}
 impl Semantics for Queue {}
 
+// BYTE ORDER =================================================================
+
+/// Byte order used when serializing compressed words to bytes.
+///
+/// See, e.g., [`AnsCoder::into_compressed_bytes`](stream::stack::AnsCoder::into_compressed_bytes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    /// Least significant byte first.
+    LittleEndian,
+
+    /// Most significant byte first.
+    BigEndian,
+}
+
+impl Endianness {
+    /// The native byte order of the target platform.
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: Self = Self::LittleEndian;
+
+    /// The native byte order of the target platform.
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: Self = Self::BigEndian;
+}
+
 // GENERIC ERROR TYPES ========================================================
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -729,6 +753,30 @@ where
         .map(move |shift| (data >> shift).as_())
 }
 
+/// Returns the number of bits in `num_words` words of type `Word`.
+///
+/// Uses `u64` arithmetic so that the result doesn't overflow on platforms where `usize` has
+/// only 32 bits (e.g., wasm32), where more than 512 MiB of compressed data would overflow
+/// a `usize` bit count.
+#[inline(always)]
+fn num_bits_u64<Word: BitArray>(num_words: usize) -> u64 {
+    Word::BITS as u64 * num_words as u64
+}
+
+/// Converts a bit count obtained from [`num_bits_u64`] to `usize`.
+///
+/// # Panics
+///
+/// Panics if `num_bits` does not fit into a `usize`, which can only happen on platforms
+/// where `usize` has fewer than 64 bits.
+#[inline(always)]
+fn bit_count_to_usize(num_bits: u64) -> usize {
+    use core::convert::TryInto;
+    num_bits
+        .try_into()
+        .expect("Number of bits overflows `usize`. Use the `_u64` variant of the method instead.")
+}
+
 #[inline(always)]
 fn wrapping_pow2<T: BitArray>(exponent: usize) -> T {
     if exponent >= T::BITS {
@@ -4,7 +4,11 @@ pub mod symbol;
 use std::prelude::v1::*;
 
 use alloc::borrow::Cow;
-use numpy::{ndarray, PyArrayMethods, PyReadonlyArray, PyReadonlyArray1, PyUntypedArrayMethods};
+use core::convert::TryFrom;
+use numpy::{
+    dtype_bound, ndarray, PyArray1, PyArrayDescr, PyArrayDescrMethods, PyArrayMethods,
+    PyReadonlyArray, PyReadonlyArray1, PyUntypedArrayMethods,
+};
 use pyo3::{prelude::*, wrap_pymodule};
 
 use crate::NanError;
@@ -233,6 +237,120 @@ impl<'py, D: ndarray::Dimension> PyReadonlyFloatArray<'py, D> {
     }
 }
 
+/// A rank-1 numpy array of symbols with one of the supported integer `dtype`s.
+///
+/// All entropy models use `i32` symbols internally, but we also accept symbols with `dtype`
+/// `int16` (to save memory) and `int64` (for convenience). An `int64` array is only a
+/// container, i.e., its values must still fit into an `i32`. See [`SymbolDtype`] for the
+/// reverse direction.
+#[derive(Debug, Clone)]
+pub enum PyReadonlySymbolArray1<'py> {
+    I16(PyReadonlyArray1<'py, i16>),
+    I32(PyReadonlyArray1<'py, i32>),
+    I64(PyReadonlyArray1<'py, i64>),
+}
+
+impl<'py> FromPyObject<'py> for PyReadonlySymbolArray1<'py> {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        if let Ok(x) = PyReadonlyArray1::<'py, i32>::extract_bound(ob) {
+            Ok(PyReadonlySymbolArray1::I32(x))
+        } else if let Ok(x) = PyReadonlyArray1::<'py, i64>::extract_bound(ob) {
+            Ok(PyReadonlySymbolArray1::I64(x))
+        } else if let Ok(x) = PyReadonlyArray1::<'py, i16>::extract_bound(ob) {
+            Ok(PyReadonlySymbolArray1::I16(x))
+        } else {
+            Err(pyo3::exceptions::PyTypeError::new_err(
+                "Symbols must be a rank-1 numpy array with dtype `np.int32`, `np.int64`, or \
+                `np.int16`.",
+            ))
+        }
+    }
+}
+
+impl PyReadonlySymbolArray1<'_> {
+    /// Converts the symbols to `i32` (without copying if they already have `dtype` `int32`).
+    fn cast_i32(&self) -> PyResult<ndarray::CowArray<'_, i32, numpy::Ix1>> {
+        match self {
+            PyReadonlySymbolArray1::I16(x) => Ok(x.as_array().mapv(i32::from).into()),
+            PyReadonlySymbolArray1::I32(x) => Ok(x.as_array().into()),
+            PyReadonlySymbolArray1::I64(x) => x
+                .as_array()
+                .iter()
+                .map(|&symbol| i32::try_from(symbol))
+                .collect::<Result<Vec<_>, _>>()
+                .map(|symbols| ndarray::Array1::from_vec(symbols).into())
+                .map_err(|_| {
+                    pyo3::exceptions::PyOverflowError::new_err(
+                        "Symbol out of range: entropy models only support symbols that fit into \
+                        an `int32`.",
+                    )
+                }),
+        }
+    }
+}
+
+/// The `dtype` of a numpy array of decoded symbols (defaults to `int32`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymbolDtype {
+    I16,
+    #[default]
+    I32,
+    I64,
+}
+
+impl<'py> FromPyObject<'py> for SymbolDtype {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let py = ob.py();
+        let dtype = PyArrayDescr::new_bound(py, ob)?;
+        if dtype.is_equiv_to(&dtype_bound::<i32>(py)) {
+            Ok(SymbolDtype::I32)
+        } else if dtype.is_equiv_to(&dtype_bound::<i64>(py)) {
+            Ok(SymbolDtype::I64)
+        } else if dtype.is_equiv_to(&dtype_bound::<i16>(py)) {
+            Ok(SymbolDtype::I16)
+        } else {
+            Err(pyo3::exceptions::PyTypeError::new_err(
+                "Unsupported dtype for symbols: use `np.int32`, `np.int64`, or `np.int16`.",
+            ))
+        }
+    }
+}
+
+impl SymbolDtype {
+    /// Converts decoded symbols to a numpy array of the given `dtype`.
+    ///
+    /// Raises an `OverflowError` if a symbol doesn't fit into the `dtype`. Since this can
+    /// only be detected after decoding, callers that decode into `I16` should take a
+    /// snapshot of the coder beforehand (see [`needs_snapshot`](Self::needs_snapshot)) and
+    /// restore it if this method fails, so that no data is lost.
+    fn to_pyarray(self, py: Python<'_>, symbols: Vec<i32>) -> PyResult<PyObject> {
+        let array = match self {
+            SymbolDtype::I32 => PyArray1::from_vec_bound(py, symbols).into_any(),
+            SymbolDtype::I64 => {
+                PyArray1::from_iter_bound(py, symbols.into_iter().map(i64::from)).into_any()
+            }
+            SymbolDtype::I16 => {
+                let symbols = symbols
+                    .into_iter()
+                    .map(i16::try_from)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| {
+                        pyo3::exceptions::PyOverflowError::new_err(
+                            "Decoded symbol doesn't fit into dtype `np.int16`.",
+                        )
+                    })?;
+                PyArray1::from_vec_bound(py, symbols).into_any()
+            }
+        };
+        Ok(array.unbind())
+    }
+
+    /// Returns `true` if [`to_pyarray`](Self::to_pyarray) can fail for this `dtype`.
+    fn needs_snapshot(self) -> bool {
+        self == SymbolDtype::I16
+    }
+}
+
 fn array1_to_vec<T: numpy::Element + Clone>(x: PyReadonlyArray1<'_, T>) -> Vec<T> {
     x.to_vec()
         .unwrap_or_else(|_| x.as_array().iter().cloned().collect())
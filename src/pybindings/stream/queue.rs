@@ -4,7 +4,7 @@ use numpy::{PyArray1, PyReadonlyArray1};
 use pyo3::{prelude::*, types::PyTuple};
 
 use crate::{
-    pybindings::array1_to_vec,
+    pybindings::{array1_to_vec, PyReadonlySymbolArray1, SymbolDtype},
     stream::{
         queue::{DecoderFrontendError, RangeCoderState},
         Decode, Encode,
@@ -293,6 +293,12 @@ impl RangeEncoder {
     /// print(encoder.get_compressed()) # (prints: [369323576])
     /// ```
     ///
+    /// The array `symbols` may have `dtype=np.int32`, `np.int64`, or `np.int16` (this also
+    /// applies to option 3 below). Note, however, that entropy models internally represent
+    /// symbols as 32-bit integers, so `np.int64` is only supported as a convenience container:
+    /// encoding an `np.int64` array raises an `OverflowError` if it contains values outside the
+    /// range of `np.int32`. Arrays with any other `dtype` raise a `TypeError`.
+    ///
     /// ## Option 3: encode(symbols, model_family, params1, params2, ...)
     ///
     /// Encodes multiple symbols, using the same *family* of entropy models (e.g., categorical or
@@ -371,8 +377,9 @@ impl RangeEncoder {
 
         // Don't use an `else` branch here because, if the following `extract` fails, the returned
         // error message is actually pretty user friendly.
-        let symbols = symbols.extract::<PyReadonlyArray1<'_, i32>>()?;
-        let symbols = symbols.as_array();
+        let symbols = symbols.extract::<PyReadonlySymbolArray1<'_>>()?;
+        let symbols = symbols.cast_i32()?;
+        let symbols = symbols.view();
 
         if optional_model_params.is_empty() {
             model.0.as_parameterized(py, &mut |model| {
@@ -547,6 +554,14 @@ impl RangeDecoder {
     /// print(symbols) # (prints: [0, 2, 1, 2, 0, 2, 0, 2, 1])
     /// ```
     ///
+    /// To obtain an array with a different `dtype`, pass the optional keyword argument `dtype`,
+    /// which may be `np.int32` (the default), `np.int64`, or `np.int16`; this also applies to
+    /// option 3 below. For example, `decoder.decode(model, 9, dtype=np.int16)` returns an array
+    /// with `dtype=np.int16`. Decoding into `np.int16` raises an `OverflowError` if a decoded
+    /// symbol doesn't fit into 16 bits; in this case, the decoder is left unchanged, so you can
+    /// decode the same symbols again with a wider `dtype`. Any other `dtype` raises a
+    /// `TypeError`.
+    ///
     /// ## Option 3: decode(model_family, params1, params2, ...)
     ///
     /// Decodes multiple symbols, using the same *family* of entropy models (e.g., categorical or
@@ -595,13 +610,16 @@ impl RangeDecoder {
     /// symbols = decoder.decode(model_family, probabilities)
     /// print(symbols) # (prints: [3, 1])
     /// ```
-    #[pyo3(signature = (model, *optional_amt_or_model_params))]
+    #[pyo3(signature = (model, *optional_amt_or_model_params, dtype=None))]
     pub fn decode(
         &mut self,
         py: Python<'_>,
         model: &Model,
         optional_amt_or_model_params: &Bound<'_, PyTuple>,
+        dtype: Option<SymbolDtype>,
     ) -> PyResult<PyObject> {
+        let dtype = dtype.unwrap_or_default();
+        let snapshot = dtype.needs_snapshot().then(|| self.inner.clone());
         match optional_amt_or_model_params.len() {
             0 => {
                 let mut symbol = 0;
@@ -627,7 +645,11 @@ impl RangeDecoder {
                         }
                         Ok(())
                     })?;
-                    return Ok(PyArray1::from_iter_bound(py, symbols).into_any().unbind());
+                    let result = dtype.to_pyarray(py, symbols);
+                    if let (Err(_), Some(snapshot)) = (&result, snapshot) {
+                        self.inner = snapshot;
+                    }
+                    return result;
                 }
             }
             _ => {} // Fall through to code below.
@@ -648,7 +670,11 @@ impl RangeDecoder {
                 Ok(())
             })?;
 
-        Ok(PyArray1::from_vec_bound(py, symbols).into_any().unbind())
+        let result = dtype.to_pyarray(py, symbols);
+        if let (Err(_), Some(snapshot)) = (&result, snapshot) {
+            self.inner = snapshot;
+        }
+        result
     }
 
     /// Creates a deep copy of the coder and returns it.
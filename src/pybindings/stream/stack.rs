@@ -1,10 +1,10 @@
-use std::prelude::v1::*;
+use std::{io::Write, path::PathBuf, prelude::v1::*};
 
 use numpy::{PyArray1, PyReadonlyArray1};
 use pyo3::{prelude::*, types::PyTuple};
 
 use crate::{
-    pybindings::array1_to_vec,
+    pybindings::{array1_to_vec, PyReadonlySymbolArray1, SymbolDtype},
     stream::{Decode, Encode},
     Pos, Seek, UnwrapInfallible,
 };
@@ -103,6 +103,7 @@ use super::model::{internals::EncoderDecoderModel, Model};
 #[pyo3(name = "stack")]
 pub fn init_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
     module.add_class::<AnsCoder>()?;
+    module.add_class::<CompressedChunks>()?;
     Ok(())
 }
 
@@ -196,6 +197,11 @@ pub fn init_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
 #[derive(Debug, Clone)]
 pub struct AnsCoder {
     inner: crate::stream::stack::DefaultAnsCoder,
+    output: Option<(PathBuf, bool)>,
+
+    /// Incremented whenever `inner` may get modified, so that iterators returned by
+    /// `iter_compressed_chunks` can detect modifications.
+    generation: u64,
 }
 
 #[pymethods]
@@ -214,14 +220,34 @@ impl AnsCoder {
     ///   then the `AnsCoder` will truncate any trailing zero words from `compressed`). Once you've
     ///   decoded and re-encoded some symbols, you can get back the original `compressed` data by
     ///   calling `.get_compressed(unseal=True)`.
+    /// - If you provide the optional keyword argument `path`, then the `AnsCoder` can be used as
+    ///   a context manager that writes its compressed data to the file at `path` when the `with`
+    ///   block exits without an exception (see
+    ///   [`__exit__`](#constriction.stream.stack.AnsCoder.__exit__)). The optional argument
+    ///   `endianness` (either `"little"` or `"big"`, default: `"little"`) sets the byte order of
+    ///   the written file.
     #[new]
-    #[pyo3(signature = (compressed=None, seal=false))]
-    pub fn new(compressed: Option<PyReadonlyArray1<'_, u32>>, seal: bool) -> PyResult<Self> {
+    #[pyo3(signature = (compressed=None, seal=false, path=None, endianness="little"))]
+    pub fn new(
+        compressed: Option<PyReadonlyArray1<'_, u32>>,
+        seal: bool,
+        path: Option<PathBuf>,
+        endianness: &str,
+    ) -> PyResult<Self> {
         if compressed.is_none() && seal {
             return Err(pyo3::exceptions::PyValueError::new_err(
                 "Need compressed data to seal.",
             ));
         }
+        let little_endian = match endianness {
+            "little" => true,
+            "big" => false,
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "`endianness` must be either \"little\" or \"big\".",
+                ))
+            }
+        };
         let inner = if let Some(compressed) = compressed {
             let compressed = array1_to_vec(compressed);
             if seal {
@@ -237,7 +263,59 @@ impl AnsCoder {
             crate::stream::stack::AnsCoder::new()
         };
 
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            output: path.map(|path| (path, little_endian)),
+            generation: 0,
+        })
+    }
+
+    /// Enters a `with` block; returns the coder itself.
+    ///
+    /// ## Example
+    ///
+    /// ```python
+    /// model = constriction.stream.model.QuantizedGaussian(-100, 100, 0.0, 10.0)
+    /// symbols = np.array([3, -8, 12, 0], dtype=np.int32)
+    ///
+    /// with constriction.stream.stack.AnsCoder(path="out.bin") as coder:
+    ///     coder.encode_reverse(symbols, model)
+    /// # The compressed data is now in "out.bin" (in little-endian byte order).
+    ///
+    /// compressed = np.fromfile("out.bin", dtype="<u4").astype(np.uint32)
+    /// decoded = constriction.stream.stack.AnsCoder(compressed).decode(model, 4)
+    /// assert np.all(decoded == symbols)
+    /// ```
+    pub fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Exits a `with` block.
+    ///
+    /// If the coder was constructed with a `path` and the `with` block exits without an
+    /// exception, then this writes the compressed data (i.e., the same data that
+    /// [`get_compressed`](#constriction.stream.stack.AnsCoder.get_compressed) returns) to the
+    /// file at `path`, replacing any existing content. If the `with` block raises an exception,
+    /// then the file is left untouched and the exception is propagated.
+    #[pyo3(signature = (exc_type, _exc_value, _traceback))]
+    pub fn __exit__(
+        &mut self,
+        exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        if let (None, Some((path, little_endian))) = (exc_type, &self.output) {
+            let mut bytes = Vec::with_capacity(4 * self.inner.num_words());
+            for word in self.inner.iter_compressed() {
+                if *little_endian {
+                    bytes.extend_from_slice(&word.to_le_bytes());
+                } else {
+                    bytes.extend_from_slice(&word.to_be_bytes());
+                }
+            }
+            std::fs::File::create(path)?.write_all(&bytes)?;
+        }
+        Ok(false)
     }
 
     /// Records a checkpoint to which you can jump during decoding using
@@ -299,6 +377,7 @@ impl AnsCoder {
     /// ```
     #[pyo3(signature = (position, state))]
     pub fn seek(&mut self, position: usize, state: u64) -> PyResult<()> {
+        self.generation += 1;
         self.inner.seek((position, state)).map_err(|()| {
             pyo3::exceptions::PyValueError::new_err(
                 "Tried to seek past end of stream. Note: in an ANS coder,\n\
@@ -314,6 +393,7 @@ impl AnsCoder {
     /// encoder with a new one but slightly more efficient.
     #[pyo3(signature = ())]
     pub fn clear(&mut self) {
+        self.generation += 1;
         self.inner.clear();
     }
 
@@ -428,6 +508,53 @@ impl AnsCoder {
         }
     }
 
+    /// Returns an iterator over the compressed data in chunks of at most `chunk_size` words.
+    ///
+    /// This is an alternative to [`get_compressed`](#constriction.stream.stack.AnsCoder.get_compressed)
+    /// for very large amounts of compressed data. Each item is a rank-1 numpy array with
+    /// `dtype=np.uint32`, and concatenating all items yields the same array that
+    /// `get_compressed()` would return. Since each chunk is copied out of the coder only when
+    /// it is requested, you can write compressed data to a file without holding a second copy
+    /// of the entire compressed data in memory:
+    ///
+    /// ```python
+    /// import sys
+    ///
+    /// encoder = constriction.stream.stack.AnsCoder()
+    /// # ... encode some message (skipped here) ...
+    /// with open('compressed-file.bin', 'wb') as file:
+    ///     for chunk in encoder.iter_compressed_chunks(1 << 20):
+    ///         if sys.byteorder != 'little':
+    ///             chunk.byteswap(inplace=True)
+    ///         chunk.tofile(file)
+    /// ```
+    ///
+    /// Don't encode or decode with the coder while you iterate over its chunks. The iterator
+    /// raises a `RuntimeError` if the coder has been modified (by encoding, decoding, seeking,
+    /// clearing, or unpickling) since the iterator was created.
+    #[pyo3(signature = (chunk_size))]
+    pub fn iter_compressed_chunks(
+        slf: Bound<'_, Self>,
+        chunk_size: usize,
+    ) -> PyResult<CompressedChunks> {
+        if chunk_size == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "`chunk_size` must be positive.",
+            ));
+        }
+        let (num_words, generation) = {
+            let coder = slf.borrow();
+            (coder.inner.num_words(), coder.generation)
+        };
+        Ok(CompressedChunks {
+            coder: slf.unbind(),
+            chunk_size,
+            pos: 0,
+            num_words,
+            generation,
+        })
+    }
+
     /// Encodes one or more symbols, appending them to the encapsulated compressed data.
     ///
     /// This method can be called in 3 different ways:
@@ -475,6 +602,12 @@ impl AnsCoder {
     /// print(coder.get_compressed()) # (prints: [1276732052, 172])
     /// ```
     ///
+    /// The array `symbols` may have `dtype=np.int32`, `np.int64`, or `np.int16` (this also
+    /// applies to option 3 below). Note, however, that entropy models internally represent
+    /// symbols as 32-bit integers, so `np.int64` is only supported as a convenience container:
+    /// encoding an `np.int64` array raises an `OverflowError` if it contains values outside the
+    /// range of `np.int32`. Arrays with any other `dtype` raise a `TypeError`.
+    ///
     /// ## Option 3: encode_reverse(symbols, model_family, params1, params2, ...)
     ///
     /// Encodes multiple symbols, using the same *family* of entropy models (e.g., categorical or
@@ -534,6 +667,7 @@ impl AnsCoder {
         model: &Model,
         optional_model_params: &Bound<'_, PyTuple>,
     ) -> PyResult<()> {
+        self.generation += 1;
         if let Ok(symbol) = symbols.extract::<i32>() {
             if !optional_model_params.is_empty() {
                 return Err(pyo3::exceptions::PyValueError::new_err(
@@ -555,8 +689,9 @@ impl AnsCoder {
 
         // Don't use an `else` branch here because, if the following `extract` fails, the returned
         // error message is actually pretty user friendly.
-        let symbols = symbols.extract::<PyReadonlyArray1<'_, i32>>()?;
-        let symbols = symbols.as_array();
+        let symbols = symbols.extract::<PyReadonlySymbolArray1<'_>>()?;
+        let symbols = symbols.cast_i32()?;
+        let symbols = symbols.view();
 
         if optional_model_params.is_empty() {
             model.0.as_parameterized(py, &mut |model| {
@@ -637,6 +772,14 @@ impl AnsCoder {
     /// print(symbols) # (prints: [2, 0, 0, 1, 2, 2, 1, 2, 2])
     /// ```
     ///
+    /// To obtain an array with a different `dtype`, pass the optional keyword argument `dtype`,
+    /// which may be `np.int32` (the default), `np.int64`, or `np.int16`; this also applies to
+    /// option 3 below. For example, `coder.decode(model, 9, dtype=np.int16)` returns an array
+    /// with `dtype=np.int16`. Decoding into `np.int16` raises an `OverflowError` if a decoded
+    /// symbol doesn't fit into 16 bits; in this case, the coder is left unchanged, so you can
+    /// decode the same symbols again with a wider `dtype`. Any other `dtype` raises a
+    /// `TypeError`.
+    ///
     /// ## Option 3: decode(model_family, params1, params2, ...)
     ///
     /// Decodes multiple symbols, using the same *family* of entropy models (e.g., categorical or
@@ -685,13 +828,17 @@ impl AnsCoder {
     /// symbols = coder.decode(model_family, probabilities)
     /// print(symbols) # (prints: [3, 1])
     /// ```
-    #[pyo3(signature = (model, *optional_amt_or_model_params))]
+    #[pyo3(signature = (model, *optional_amt_or_model_params, dtype=None))]
     pub fn decode(
         &mut self,
         py: Python<'_>,
         model: &Model,
         optional_amt_or_model_params: &Bound<'_, PyTuple>,
+        dtype: Option<SymbolDtype>,
     ) -> PyResult<PyObject> {
+        self.generation += 1;
+        let dtype = dtype.unwrap_or_default();
+        let snapshot = dtype.needs_snapshot().then(|| self.inner.clone());
         match optional_amt_or_model_params.len() {
             0 => {
                 let mut symbol = 0;
@@ -720,7 +867,11 @@ impl AnsCoder {
                         }
                         Ok(())
                     })?;
-                    return Ok(PyArray1::from_iter_bound(py, symbols).into_any().unbind());
+                    let result = dtype.to_pyarray(py, symbols);
+                    if let (Err(_), Some(snapshot)) = (&result, snapshot) {
+                        self.inner = snapshot;
+                    }
+                    return result;
                 }
             }
             _ => {} // Fall through to code below.
@@ -744,7 +895,70 @@ impl AnsCoder {
                 Ok(())
             })?;
 
-        Ok(PyArray1::from_vec_bound(py, symbols).into_any().unbind())
+        let result = dtype.to_pyarray(py, symbols);
+        if let (Err(_), Some(snapshot)) = (&result, snapshot) {
+            self.inner = snapshot;
+        }
+        result
+    }
+
+    /// Returns the internal state of the coder for pickling.
+    ///
+    /// This method and [`__setstate__`](#constriction.stream.stack.AnsCoder.__setstate__)
+    /// allow you to `pickle` an `AnsCoder`, e.g., to send a partially filled coder to a
+    /// different process with the `multiprocessing` module. An unpickled coder decodes the same
+    /// symbols as the original one.
+    ///
+    /// ## Example
+    ///
+    /// ```python
+    /// import pickle
+    ///
+    /// model = constriction.stream.model.QuantizedGaussian(-100, 100, 0.0, 10.0)
+    /// coder = constriction.stream.stack.AnsCoder()
+    /// coder.encode_reverse(np.array([3, -8, 12, 0], dtype=np.int32), model)
+    ///
+    /// restored = pickle.loads(pickle.dumps(coder))
+    /// assert np.all(restored.decode(model, 4) == coder.decode(model, 4))
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn __getstate__<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> (Bound<'py, PyArray1<u32>>, u64, Option<PathBuf>, bool) {
+        let (path, little_endian) = match &self.output {
+            Some((path, little_endian)) => (Some(path.clone()), *little_endian),
+            None => (None, true),
+        };
+        let (_, state) = self.inner.pos();
+        (
+            PyArray1::from_slice_bound(py, self.inner.bulk()),
+            state,
+            path,
+            little_endian,
+        )
+    }
+
+    /// Restores the internal state of the coder when unpickling.
+    ///
+    /// Raises a `ValueError` if `state` is not a valid state as returned by
+    /// [`__getstate__`](#constriction.stream.stack.AnsCoder.__getstate__).
+    #[allow(clippy::type_complexity)]
+    pub fn __setstate__(
+        &mut self,
+        state: (PyReadonlyArray1<'_, u32>, u64, Option<PathBuf>, bool),
+    ) -> PyResult<()> {
+        let (bulk, state, path, little_endian) = state;
+        let bulk = array1_to_vec(bulk);
+        if !bulk.is_empty() && state < 1 << 32 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Invalid state: an `AnsCoder` with nonempty bulk must have a state of at least 2^32.",
+            ));
+        }
+        self.generation += 1;
+        self.inner = crate::stream::stack::AnsCoder::from_raw_parts(bulk, state);
+        self.output = path.map(|path| (path, little_endian));
+        Ok(())
     }
 
     /// Creates a deep copy of the coder and returns it.
@@ -757,3 +971,56 @@ impl AnsCoder {
         Clone::clone(self)
     }
 }
+
+/// Iterator over chunks of compressed data, as returned by
+/// [`AnsCoder.iter_compressed_chunks`](#constriction.stream.stack.AnsCoder.iter_compressed_chunks).
+#[pyclass]
+#[derive(Debug)]
+pub struct CompressedChunks {
+    coder: Py<AnsCoder>,
+    chunk_size: usize,
+
+    /// Offset (in words) of the next chunk within the compressed data.
+    pos: usize,
+    num_words: usize,
+
+    /// The `generation` of `coder` when the iterator was created.
+    generation: u64,
+}
+
+#[pymethods]
+impl CompressedChunks {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(&mut self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyArray1<u32>>>> {
+        let coder = self.coder.borrow(py);
+        if coder.generation != self.generation {
+            return Err(pyo3::exceptions::PyRuntimeError::new_err(
+                "The `AnsCoder` was modified while iterating over its compressed data.",
+            ));
+        }
+        if self.pos == self.num_words {
+            return Ok(None);
+        }
+
+        // The compressed data consists of the bulk followed by the (up to two) words of the
+        // state, see `crate::stream::stack::AnsCoder::iter_compressed`. Index into the bulk
+        // directly so that each chunk costs time proportional to its size.
+        let end = self.num_words.min(self.pos.saturating_add(self.chunk_size));
+        let bulk = coder.inner.bulk();
+        let mut chunk = Vec::with_capacity(end - self.pos);
+        if self.pos < bulk.len() {
+            chunk.extend_from_slice(&bulk[self.pos..end.min(bulk.len())]);
+        }
+        if end > bulk.len() {
+            let (_, state) = coder.inner.pos();
+            let state_words = crate::bit_array_to_chunks_truncated::<_, u32>(state);
+            let skip = self.pos.saturating_sub(bulk.len());
+            chunk.extend(state_words.rev().skip(skip).take(end - bulk.len() - skip));
+        }
+        self.pos = end;
+        Ok(Some(PyArray1::from_vec_bound(py, chunk)))
+    }
+}
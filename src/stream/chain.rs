@@ -1301,6 +1301,61 @@ mod tests {
         generic_restore_many::<u8, u32, u8, 8>(1024, 1000);
     }
 
+    #[test]
+    fn independent_symbols() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2274);
+        let mut compressed = (0..100).map(|_| rng.next_u32()).collect::<Vec<_>>();
+        *compressed.last_mut().unwrap() |= 1 << 31;
+
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-100..=100);
+        let mut distributions = (0..50)
+            .map(|_| {
+                let mean = (200.0 / u32::MAX as f64) * rng.next_u32() as f64 - 100.0;
+                let std_dev = (10.0 / u32::MAX as f64) * rng.next_u32() as f64 + 0.001;
+                Gaussian::new(mean, std_dev)
+            })
+            .collect::<Vec<_>>();
+
+        let decode = |distributions: &[Gaussian]| {
+            let mut coder = DefaultChainCoder::from_compressed(compressed.clone()).unwrap();
+            let symbols = coder
+                .decode_symbols(
+                    distributions
+                        .iter()
+                        .map(|&distribution| quantizer.quantize(distribution)),
+                )
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            (coder, symbols)
+        };
+
+        let (_, symbols) = decode(&distributions);
+
+        // Change the entropy model for one symbol in the middle. This changes the decoded
+        // value of this symbol but leaves all other decoded symbols unchanged.
+        distributions[20] = Gaussian::new(-distributions[20].mu(), 0.5);
+        let (mut coder, modified_symbols) = decode(&distributions);
+        assert_ne!(symbols[20], modified_symbols[20]);
+        for (i, (symbol, modified_symbol)) in symbols.iter().zip(&modified_symbols).enumerate() {
+            if i != 20 {
+                assert_eq!(symbol, modified_symbol);
+            }
+        }
+
+        // Re-encoding with the modified models still restores the original compressed data.
+        coder
+            .encode_symbols_reverse(
+                modified_symbols
+                    .iter()
+                    .zip(&distributions)
+                    .map(|(&symbol, &distribution)| (symbol, quantizer.quantize(distribution))),
+            )
+            .unwrap();
+        let (mut reconstructed, compressed_suffix) = coder.into_compressed().unwrap();
+        reconstructed.extend(compressed_suffix);
+        assert_eq!(reconstructed, compressed);
+    }
+
     fn generic_restore_many<Word, State, Probability, const PRECISION: usize>(
         amt_compressed_words: usize,
         amt_symbols: usize,
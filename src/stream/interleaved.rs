@@ -0,0 +1,376 @@
+//! Interleaved Asymmetric Numeral Systems (ANS) for higher throughput
+//!
+//! This module provides the [`InterleavedAnsCoder`], a variant of the [`AnsCoder`] that
+//! maintains `LANES` independent ANS states and distributes symbols round-robin across
+//! them. All lanes share a single stack of compressed words.
+//!
+//! # Motivation
+//!
+//! Encoding and decoding with an [`AnsCoder`] requires one division (encoding) or
+//! multiplication (decoding) per symbol, and each of these operations depends on the
+//! result of the previous one because it updates the coder's single internal state. This
+//! long chain of dependent operations limits throughput for large amounts of iid symbols,
+//! especially for encoding, which is usually bound by the latency of integer divisions.
+//! An `InterleavedAnsCoder` breaks this dependency chain: consecutive symbols are coded on
+//! different lanes, i.e., with independent states, so that the CPU can execute the
+//! operations for several lanes in parallel (and the compiler can better schedule them).
+//!
+//! # Compatibility
+//!
+//! The compressed data of an `InterleavedAnsCoder` is *not* compatible with the compressed
+//! data of an `AnsCoder`, and it is only compatible with other `InterleavedAnsCoder`s that
+//! use the same number of `LANES`. Further, the lane of a symbol is determined by its
+//! position within the batch passed to [`encode_iid_symbols`], so you have to decode the
+//! same batches (i.e., with the same numbers of symbols) as you encoded, in reverse order
+//! of encoding (like with an `AnsCoder`, the batches are placed on a stack). The bitrate is
+//! the same as with an `AnsCoder` up to a small constant overhead of at most
+//! `State::BITS` bits per lane.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{interleaved::InterleavedAnsCoder, model::DefaultLeakyQuantizer};
+//! use probability::distribution::Gaussian;
+//!
+//! let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+//! let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+//! let symbols = (0..1000).map(|i| (i % 41) - 20).collect::<Vec<i32>>();
+//!
+//! let mut coder = InterleavedAnsCoder::<u32, u64, 4>::new();
+//! coder.encode_iid_symbols(&symbols, model).unwrap();
+//! let compressed = coder.into_compressed();
+//!
+//! let mut coder = InterleavedAnsCoder::<u32, u64, 4>::from_compressed(compressed).unwrap();
+//! let decoded = coder.decode_iid_symbols(symbols.len(), model).collect::<Vec<_>>();
+//! assert_eq!(decoded, symbols);
+//! assert!(coder.is_empty());
+//! ```
+//!
+//! [`AnsCoder`]: super::stack::AnsCoder
+//! [`encode_iid_symbols`]: InterleavedAnsCoder::encode_iid_symbols
+
+use alloc::vec::Vec;
+use core::{borrow::Borrow, convert::Infallible};
+
+use num_traits::AsPrimitive;
+
+use super::model::{DecoderModel, EncoderModel};
+use crate::{
+    generic_static_asserts, BitArray, CoderError, DefaultEncoderFrontendError, NonZeroBitArray,
+};
+
+/// Type alias for an [`InterleavedAnsCoder`] with sane parameters for typical use cases.
+///
+/// Uses the same word and state sizes as a
+/// [`DefaultAnsCoder`](super::stack::DefaultAnsCoder).
+pub type DefaultInterleavedAnsCoder<const LANES: usize = 4> = InterleavedAnsCoder<u32, u64, LANES>;
+
+/// An ANS coder with `LANES` interleaved states for higher throughput.
+///
+/// See [module level documentation](self) for a discussion and an example.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InterleavedAnsCoder<Word, State, const LANES: usize>
+where
+    Word: BitArray,
+    State: BitArray,
+{
+    bulk: Vec<Word>,
+
+    /// Invariant: `states[i] >= State::one() << (State::BITS - Word::BITS)` for all `i`.
+    states: [State; LANES],
+}
+
+impl<Word, State, const LANES: usize> Default for InterleavedAnsCoder<Word, State, LANES>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Word, State, const LANES: usize> InterleavedAnsCoder<Word, State, LANES>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    const INITIAL_STATE: fn() -> State = || State::one() << (State::BITS - Word::BITS);
+
+    /// Creates an empty coder for encoding.
+    pub fn new() -> Self {
+        generic_static_asserts!(
+            (Word: BitArray, State:BitArray; const LANES: usize);
+            STATE_SUPPORTS_AT_LEAST_TWO_WORDS: State::BITS >= 2 * Word::BITS;
+            AT_LEAST_ONE_LANE: LANES > 0;
+        );
+
+        Self {
+            bulk: Vec::new(),
+            states: [Self::INITIAL_STATE(); LANES],
+        }
+    }
+
+    /// Creates a coder for decoding from compressed data returned by
+    /// [`into_compressed`](Self::into_compressed).
+    ///
+    /// Returns the argument `compressed` back to the caller, wrapped in an `Err` variant,
+    /// if it is too short or if it doesn't end in valid coder states.
+    pub fn from_compressed(mut compressed: Vec<Word>) -> Result<Self, Vec<Word>> {
+        generic_static_asserts!(
+            (Word: BitArray, State:BitArray; const LANES: usize);
+            STATE_SUPPORTS_AT_LEAST_TWO_WORDS: State::BITS >= 2 * Word::BITS;
+            AT_LEAST_ONE_LANE: LANES > 0;
+        );
+
+        let words_per_state = State::BITS / Word::BITS;
+        let Some(bulk_len) = compressed.len().checked_sub(LANES * words_per_state) else {
+            return Err(compressed);
+        };
+
+        let mut states = [State::zero(); LANES];
+        for (state, chunk) in states
+            .iter_mut()
+            .zip(compressed[bulk_len..].chunks_exact(words_per_state))
+        {
+            for &word in chunk.iter().rev() {
+                *state = *state << Word::BITS | word.into();
+            }
+            if *state < Self::INITIAL_STATE() {
+                return Err(compressed);
+            }
+        }
+
+        compressed.truncate(bulk_len);
+        Ok(Self {
+            bulk: compressed,
+            states,
+        })
+    }
+
+    /// Consumes the coder and returns the compressed data.
+    ///
+    /// The returned data consists of the words that the lanes have flushed, followed by
+    /// the full internal state of each lane (with `State::BITS / Word::BITS` words per
+    /// lane, starting with the least significant word of the first lane). Pass the
+    /// returned data to [`from_compressed`](Self::from_compressed) for decoding.
+    pub fn into_compressed(mut self) -> Vec<Word> {
+        let words_per_state = State::BITS / Word::BITS;
+        self.bulk.reserve(LANES * words_per_state);
+        for state in self.states {
+            for i in 0..words_per_state {
+                self.bulk.push((state >> (i * Word::BITS)).as_());
+            }
+        }
+        self.bulk
+    }
+
+    /// Returns the number of compressed words that [`into_compressed`] would return.
+    ///
+    /// [`into_compressed`]: Self::into_compressed
+    pub fn num_words(&self) -> usize {
+        self.bulk.len() + LANES * (State::BITS / Word::BITS)
+    }
+
+    /// Returns `true` if no symbols are left to decode.
+    pub fn is_empty(&self) -> bool {
+        self.bulk.is_empty()
+            && self
+                .states
+                .iter()
+                .all(|&state| state == Self::INITIAL_STATE())
+    }
+
+    /// Encodes a batch of iid symbols, distributing them round-robin across the lanes.
+    ///
+    /// The symbol at index `i` within `symbols` is encoded on lane `i % LANES`. Since the
+    /// coder operates as a stack, this method internally encodes the symbols in reverse
+    /// order so that [`decode_iid_symbols`] will decode them in the original order. To
+    /// decode the batch, call `decode_iid_symbols` with `amt == symbols.len()` (after
+    /// decoding any batches that were encoded after this one).
+    ///
+    /// Returns an error if any symbol has zero probability under the model. In this case,
+    /// the symbols that follow the offending symbol in `symbols` have already been encoded.
+    ///
+    /// [`decode_iid_symbols`]: Self::decode_iid_symbols
+    pub fn encode_iid_symbols<S, M, I, const PRECISION: usize>(
+        &mut self,
+        symbols: I,
+        model: M,
+    ) -> Result<(), CoderError<DefaultEncoderFrontendError, Infallible>>
+    where
+        S: Borrow<M::Symbol>,
+        M: EncoderModel<PRECISION> + Copy,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+        I: IntoIterator<Item = S>,
+        I::IntoIter: ExactSizeIterator + DoubleEndedIterator,
+    {
+        generic_static_asserts!(
+            (Word: BitArray, State:BitArray; const PRECISION: usize);
+            PROBABILITY_SUPPORTS_PRECISION: State::BITS >= Word::BITS + PRECISION;
+            NON_ZERO_PRECISION: PRECISION > 0;
+        );
+
+        let symbols = symbols.into_iter();
+        let mut lane = symbols.len() % LANES;
+        for symbol in symbols.rev() {
+            lane = if lane == 0 { LANES - 1 } else { lane - 1 };
+            let (left_sided_cumulative, probability) = model
+                .left_cumulative_and_probability(symbol)
+                .ok_or(CoderError::Frontend(
+                    DefaultEncoderFrontendError::ImpossibleSymbol,
+                ))?;
+            let probability = probability.get().into().into();
+
+            // Same as `AnsCoder::encode_symbol`, but on the state of the current lane.
+            let state = &mut self.states[lane];
+            if (*state >> (State::BITS - PRECISION)) >= probability {
+                self.bulk.push((*state).as_());
+                *state = *state >> Word::BITS;
+            }
+            let remainder = *state % probability;
+            let prefix = *state / probability;
+            *state = prefix << PRECISION | (left_sided_cumulative.into().into() + remainder);
+        }
+
+        Ok(())
+    }
+
+    /// Decodes a batch of `amt` iid symbols that was encoded with [`encode_iid_symbols`].
+    ///
+    /// Decodes the symbol at index `i` within the batch from lane `i % LANES`. Decoding is
+    /// infallible (like with an `AnsCoder`, decoding past the end of the compressed data
+    /// is well defined but yields meaningless symbols).
+    ///
+    /// [`encode_iid_symbols`]: Self::encode_iid_symbols
+    pub fn decode_iid_symbols<'a, M, const PRECISION: usize>(
+        &'a mut self,
+        amt: usize,
+        model: M,
+    ) -> impl ExactSizeIterator<Item = M::Symbol> + 'a
+    where
+        M: DecoderModel<PRECISION> + Copy + 'a,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+    {
+        generic_static_asserts!(
+            (Word: BitArray, State:BitArray; const PRECISION: usize);
+            PROBABILITY_SUPPORTS_PRECISION: State::BITS >= Word::BITS + PRECISION;
+            NON_ZERO_PRECISION: PRECISION > 0;
+        );
+
+        (0..amt).map(move |i| {
+            // Same as `AnsCoder::decode_symbol`, but on the state of the current lane.
+            let state = &mut self.states[i % LANES];
+            let mask = (State::one() << PRECISION) - State::one();
+            let quantile = (*state & mask).as_().as_();
+            let (symbol, left_sided_cumulative, probability) = model.quantile_function(quantile);
+            let remainder = quantile - left_sided_cumulative;
+            *state =
+                (*state >> PRECISION) * probability.get().into().into() + remainder.into().into();
+            if *state < Self::INITIAL_STATE() {
+                if let Some(word) = self.bulk.pop() {
+                    *state = (*state << Word::BITS) | word.into();
+                }
+            }
+            symbol
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        model::{DefaultContiguousCategoricalEntropyModel, DefaultLeakyQuantizer},
+        stack::DefaultAnsCoder,
+    };
+    use super::*;
+
+    use probability::distribution::Gaussian;
+    use rand_xoshiro::{
+        rand_core::{RngCore, SeedableRng},
+        Xoshiro256StarStar,
+    };
+
+    #[test]
+    fn round_trip() {
+        round_trip_with_lanes::<1>();
+        round_trip_with_lanes::<2>();
+        round_trip_with_lanes::<3>();
+        round_trip_with_lanes::<4>();
+        round_trip_with_lanes::<8>();
+        round_trip_with_lanes::<16>();
+    }
+
+    fn round_trip_with_lanes<const LANES: usize>() {
+        #[cfg(not(miri))]
+        let amts = [0, 1, 2, 7, 100, 10_000];
+
+        #[cfg(miri)]
+        let amts = [0, 1, 2, 7, 100];
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2282 + LANES as u64);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(3.2, 12.5));
+        let batches = amts
+            .iter()
+            .map(|&amt| {
+                (0..amt)
+                    .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut coder = DefaultInterleavedAnsCoder::<LANES>::new();
+        assert!(coder.is_empty());
+        for batch in &batches {
+            coder.encode_iid_symbols(batch, model).unwrap();
+        }
+        let num_words = coder.num_words();
+        let compressed = coder.clone().into_compressed();
+        assert_eq!(compressed.len(), num_words);
+
+        // Compare the bitrate to a (scalar) `AnsCoder`.
+        let mut ans = DefaultAnsCoder::new();
+        for batch in &batches {
+            ans.encode_iid_symbols_reverse(batch, model).unwrap();
+        }
+        assert!(num_words <= ans.num_words() + 2 * LANES + 1);
+
+        let mut decoder = DefaultInterleavedAnsCoder::<LANES>::from_compressed(compressed).unwrap();
+        assert_eq!(decoder, coder);
+        for batch in batches.iter().rev() {
+            let decoded = decoder
+                .decode_iid_symbols(batch.len(), model)
+                .collect::<Vec<_>>();
+            assert_eq!(&decoded, batch);
+        }
+        assert!(decoder.is_empty());
+    }
+
+    #[test]
+    fn impossible_symbol_and_invalid_data() {
+        let model =
+            DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                &[0.2f64, 0.5, 0.3],
+                None,
+            )
+            .unwrap();
+        let mut coder = DefaultInterleavedAnsCoder::<4>::new();
+        assert_eq!(
+            coder.encode_iid_symbols([0, 1, 3], &model),
+            Err(CoderError::Frontend(
+                DefaultEncoderFrontendError::ImpossibleSymbol
+            ))
+        );
+
+        assert!(DefaultInterleavedAnsCoder::<4>::from_compressed(alloc::vec![1; 7]).is_err());
+        assert!(DefaultInterleavedAnsCoder::<4>::from_compressed(alloc::vec![0; 8]).is_err());
+        let empty = DefaultInterleavedAnsCoder::<4>::new().into_compressed();
+        assert_eq!(empty.len(), 8);
+        assert!(DefaultInterleavedAnsCoder::<4>::from_compressed(empty)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -23,9 +23,13 @@
 //!   effectiveness of stream codes with the locality of symbol codes; it is meant for
 //!   experimental new compression approaches that perform joint inference, quantization,
 //!   and bits-back coding in an end-to-end optimization. See submodule [`chain`].
+//! - **Interleaved ANS:** a variant of ANS Coding that distributes iid symbols across
+//!   several independent coder states for higher throughput on large amounts of data. See
+//!   submodule [`interleaved`].
 //!
-//! All of these stream codes are provided through types that implement the [`Encode`] and
-//! [`Decode`] traits defined in this module.
+//! ANS Coding, Range Coding, and Chain Coding are provided through types that implement
+//! the [`Encode`] and [`Decode`] traits defined in this module. Interleaved ANS only
+//! supports i.i.d. symbols and therefore provides its own inherent methods instead.
 //!
 //! ## Provided Utilities for Entropy Models
 //!
@@ -316,6 +320,7 @@
 #![allow(clippy::type_complexity)]
 
 pub mod chain;
+pub mod interleaved;
 pub mod model;
 pub mod queue;
 pub mod stack;
@@ -323,8 +328,17 @@ pub mod stack;
 use core::{
     borrow::Borrow,
     fmt::{Debug, Display},
+    hash::Hash,
 };
 
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+#[cfg(not(feature = "std"))]
+use hashbrown::HashMap;
+
+use alloc::vec::Vec;
+
 use crate::{BitArray, CoderError};
 use model::{DecoderModel, EncoderModel, EntropyModel};
 use num_traits::AsPrimitive;
@@ -1030,6 +1044,298 @@ pub trait Decode<const PRECISION: usize>: Code {
         }
     }
 
+    /// Decodes `amt` i.i.d. symbols and counts how often each distinct symbol occurred.
+    ///
+    /// Returns the decoded symbols (in the same order as [`decode_iid_symbols`] would yield
+    /// them) together with a histogram that maps each decoded symbol to the number of times
+    /// it was decoded. The histogram is built up from the decoded symbols in the same pass,
+    /// so you don't have to iterate over the symbols a second time, e.g., if you want to
+    /// fit an updated entropy model to the realized symbol frequencies.
+    ///
+    /// This is purely a convenience method. It is equivalent to collecting the iterator
+    /// returned by [`decode_iid_symbols`] and counting the symbols manually.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultContiguousCategoricalEntropyModel, stack::DefaultAnsCoder, Decode
+    /// };
+    ///
+    /// let probabilities = [0.1, 0.6, 0.3];
+    /// let model = DefaultContiguousCategoricalEntropyModel
+    ///     ::from_floating_point_probabilities_fast(&probabilities, None).unwrap();
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse([1, 2, 1, 1, 0], &model).unwrap();
+    ///
+    /// let (symbols, histogram) = ans.decode_iid_symbols_with_histogram(5, &model).unwrap();
+    /// assert_eq!(symbols, [1, 2, 1, 1, 0]);
+    /// assert_eq!(histogram[&0], 1);
+    /// assert_eq!(histogram[&1], 3);
+    /// assert_eq!(histogram[&2], 1);
+    /// ```
+    ///
+    /// [`decode_iid_symbols`]: Self::decode_iid_symbols
+    fn decode_iid_symbols_with_histogram<M>(
+        &mut self,
+        amt: usize,
+        model: M,
+    ) -> Result<
+        (Vec<M::Symbol>, HashMap<M::Symbol, u64>),
+        CoderError<Self::FrontendError, Self::BackendError>,
+    >
+    where
+        M: DecoderModel<PRECISION> + Copy,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+        M::Symbol: Clone + Hash + Eq,
+    {
+        let mut symbols = Vec::with_capacity(amt);
+        let mut histogram = HashMap::new();
+        for _ in 0..amt {
+            let symbol = self.decode_symbol(model)?;
+            *histogram.entry(symbol.clone()).or_insert(0) += 1;
+            symbols.push(symbol);
+        }
+
+        Ok((symbols, histogram))
+    }
+
+    /// Decodes `amt` i.i.d. symbols and returns them grouped into chunks of `chunk_size`.
+    ///
+    /// This is purely an output-shaping convenience: concatenating the returned chunks
+    /// results in the same sequence of symbols that [`decode_iid_symbols`] would yield. All
+    /// chunks contain exactly `chunk_size` symbols except possibly the last one. If `amt` is
+    /// not a multiple of `chunk_size` then the argument `short_chunk` controls how the
+    /// remaining `amt % chunk_size` symbols are handled:
+    ///
+    /// - [`ShortChunk::Keep`] decodes them and returns them as a final, shorter chunk; and
+    /// - [`ShortChunk::Omit`] doesn't decode them, so only full chunks are returned and the
+    ///   remaining symbols are left on the coder (e.g., for decoding them with
+    ///   [`decode_iid_symbols`] later).
+    ///
+    /// Returns an empty vector if `amt == 0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size == 0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultUniformModel, stack::DefaultAnsCoder, Decode, ShortChunk,
+    /// };
+    ///
+    /// let model = DefaultUniformModel::new(10);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse([3, 1, 4, 1, 5, 9, 2], model).unwrap();
+    ///
+    /// let chunks = ans.clone().decode_iid_chunks(7, 3, ShortChunk::Keep, model).unwrap();
+    /// assert_eq!(chunks, [vec![3, 1, 4], vec![1, 5, 9], vec![2]]);
+    ///
+    /// let chunks = ans.decode_iid_chunks(7, 3, ShortChunk::Omit, model).unwrap();
+    /// assert_eq!(chunks, [vec![3, 1, 4], vec![1, 5, 9]]);
+    /// assert_eq!(ans.decode_symbol(model).unwrap(), 2);
+    /// assert!(ans.is_empty());
+    /// ```
+    ///
+    /// [`decode_iid_symbols`]: Self::decode_iid_symbols
+    fn decode_iid_chunks<M>(
+        &mut self,
+        amt: usize,
+        chunk_size: usize,
+        short_chunk: ShortChunk,
+        model: M,
+    ) -> Result<Vec<Vec<M::Symbol>>, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        M: DecoderModel<PRECISION> + Copy,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        assert!(chunk_size != 0);
+
+        let amt = match short_chunk {
+            ShortChunk::Keep => amt,
+            ShortChunk::Omit => amt - amt % chunk_size,
+        };
+        let mut chunks = Vec::with_capacity(amt.div_ceil(chunk_size));
+        let mut remaining = amt;
+        while remaining != 0 {
+            let len = core::cmp::min(chunk_size, remaining);
+            let mut chunk = Vec::with_capacity(len);
+            for _ in 0..len {
+                chunk.push(self.decode_symbol(model)?);
+            }
+            chunks.push(chunk);
+            remaining -= len;
+        }
+
+        Ok(chunks)
+    }
+
+    /// Decodes `amt` i.i.d. symbols and folds them into an accumulator on the fly.
+    ///
+    /// Starts from the accumulator `init` and calls `f(accumulator, symbol)` for each
+    /// decoded symbol in turn, replacing the accumulator with the return value of `f`.
+    /// Returns the final accumulator, or the first error encountered while decoding (in
+    /// which case the remaining symbols are not decoded).
+    ///
+    /// This is an explicit entry point for aggregations (e.g., sums, maxima, or histograms
+    /// of the decoded symbols) that don't need to store the symbols. It is equivalent to
+    /// calling `try_fold` on the iterator returned by [`decode_iid_symbols`] but it saves
+    /// you from having to thread the error through the closure.
+    ///
+    /// # Example
+    ///
+    /// Calculate the sum of the decoded symbols without allocating:
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode};
+    ///
+    /// let model = DefaultUniformModel::new(10);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse([3, 1, 4, 1, 5, 9, 2], model).unwrap();
+    ///
+    /// let sum = ans.decode_iid_fold(7, model, 0, |sum, symbol| sum + symbol).unwrap();
+    /// assert_eq!(sum, 25);
+    /// assert!(ans.is_empty());
+    /// ```
+    ///
+    /// [`decode_iid_symbols`]: Self::decode_iid_symbols
+    fn decode_iid_fold<M, B, F>(
+        &mut self,
+        amt: usize,
+        model: M,
+        init: B,
+        mut f: F,
+    ) -> Result<B, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        M: DecoderModel<PRECISION> + Copy,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+        F: FnMut(B, M::Symbol) -> B,
+    {
+        let mut accumulator = init;
+        for _ in 0..amt {
+            accumulator = f(accumulator, self.decode_symbol(model)?);
+        }
+
+        Ok(accumulator)
+    }
+
+    /// Decodes `out.len()` i.i.d. symbols into a caller-provided slice.
+    ///
+    /// Decodes the same symbols as [`decode_iid_symbols`] but writes them into `out` rather
+    /// than returning an iterator. This allows you to reuse a buffer across calls, e.g., in
+    /// a hot loop of a real-time pipeline, without allocating a new `Vec` on each call. If
+    /// decoding fails then this method returns the error immediately, and only the symbols
+    /// before the failing one have been written to `out`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode};
+    ///
+    /// let model = DefaultUniformModel::new(10);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse([3, 1, 4, 1, 5, 9, 2, 6], model).unwrap();
+    ///
+    /// let mut buf = [0; 4];
+    /// ans.decode_iid_symbols_into(&mut buf, model).unwrap();
+    /// assert_eq!(buf, [3, 1, 4, 1]);
+    /// ans.decode_iid_symbols_into(&mut buf, model).unwrap(); // Reuses `buf`.
+    /// assert_eq!(buf, [5, 9, 2, 6]);
+    /// assert!(ans.is_empty());
+    /// ```
+    ///
+    /// [`decode_iid_symbols`]: Self::decode_iid_symbols
+    fn decode_iid_symbols_into<M>(
+        &mut self,
+        out: &mut [M::Symbol],
+        model: M,
+    ) -> Result<(), CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        M: DecoderModel<PRECISION> + Copy,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        for slot in out {
+            *slot = self.decode_symbol(model)?;
+        }
+
+        Ok(())
+    }
+
+    /// Decodes i.i.d. symbols until `keep_going` returns `false`.
+    ///
+    /// This is useful for self-delimiting messages, e.g., messages that end in a sentinel
+    /// symbol, whose length isn't known to the decoder in advance. The method decodes one
+    /// symbol at a time with `model` and calls `keep_going` on it. As long as `keep_going`
+    /// returns `true`, the symbol is appended to the returned `Vec`. The first symbol for
+    /// which `keep_going` returns `false` ends decoding; it is appended to the returned
+    /// `Vec` only if `include_terminator` is `true`. Either way, the terminating symbol has
+    /// been decoded, i.e., it is no longer on the coder, so the coder is left right after
+    /// the terminating symbol.
+    ///
+    /// Decodes at most `max_symbols` symbols (including the terminating symbol). Some
+    /// decoders (e.g., an [`AnsCoder`](stack::AnsCoder)) can decode an unlimited number of
+    /// (meaningless) symbols past the end of the encoded data, so this limit guarantees
+    /// termination even if the data does not contain a terminating symbol.
+    ///
+    /// Returns a tuple `(symbols, terminated)`, where `terminated` is `true` if decoding
+    /// ended on a terminating symbol, and `false` if it ended because `max_symbols` symbols
+    /// were decoded without encountering one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode};
+    ///
+    /// let model = DefaultUniformModel::new(10);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// // Two messages, each terminated by the sentinel symbol `0`.
+    /// ans.encode_iid_symbols_reverse([3, 1, 4, 0, 1, 5, 9, 0], model).unwrap();
+    ///
+    /// let (first, terminated) =
+    ///     ans.decode_symbols_while(model, 100, false, |&symbol| symbol != 0).unwrap();
+    /// assert_eq!(first, [3, 1, 4]);
+    /// assert!(terminated);
+    /// let (second, terminated) =
+    ///     ans.decode_symbols_while(model, 100, true, |&symbol| symbol != 0).unwrap();
+    /// assert_eq!(second, [1, 5, 9, 0]);
+    /// assert!(terminated);
+    /// assert!(ans.is_empty());
+    /// ```
+    fn decode_symbols_while<M, F>(
+        &mut self,
+        model: M,
+        max_symbols: usize,
+        include_terminator: bool,
+        mut keep_going: F,
+    ) -> Result<(Vec<M::Symbol>, bool), CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        M: DecoderModel<PRECISION> + Copy,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+        F: FnMut(&M::Symbol) -> bool,
+    {
+        let mut symbols = Vec::new();
+        for _ in 0..max_symbols {
+            let symbol = self.decode_symbol(model)?;
+            if keep_going(&symbol) {
+                symbols.push(symbol);
+            } else {
+                if include_terminator {
+                    symbols.push(symbol);
+                }
+                return Ok((symbols, true));
+            }
+        }
+
+        Ok((symbols, false))
+    }
+
     /// Checks if there might be no compressed data left for decoding.
     ///
     /// If this method returns `false` then there must be additional data left to decode. If
@@ -1308,6 +1614,17 @@ where
 {
 }
 
+/// Controls how [`Decode::decode_iid_chunks`] handles a final chunk that would be shorter
+/// than the requested chunk size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShortChunk {
+    /// Decode the remaining symbols and return them as a final, shorter chunk.
+    Keep,
+
+    /// Don't decode the remaining symbols, i.e., leave them on the coder.
+    Omit,
+}
+
 /// The error type for [`Encode::try_encode_symbols`] and [`Decode::try_decode_symbols`].
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub enum TryCodingError<CodingError, ModelError> {
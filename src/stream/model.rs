@@ -148,17 +148,102 @@ pub use probability::distribution::Distribution;
 /// [`probability`]: https://docs.rs/probability/latest/probability/
 pub use probability::distribution::Inverse;
 
+/// Implements the entropy model traits for a model type `$name<Probability, PRECISION>`
+/// over `usize` symbols that wraps a `ContiguousCategoricalEntropyModel` in a field
+/// `model`, by forwarding to that field. Also adds a method `as_contiguous_categorical`.
+macro_rules! forward_to_contiguous_categorical {
+    ($name:ident) => {
+        impl<Probability: $crate::BitArray, const PRECISION: usize> $name<Probability, PRECISION> {
+            /// Returns a view of the model as a [`ContiguousCategoricalEntropyModel`].
+            ///
+            /// [`ContiguousCategoricalEntropyModel`]:
+            ///     crate::stream::model::ContiguousCategoricalEntropyModel
+            pub fn as_contiguous_categorical(
+                &self,
+            ) -> &$crate::stream::model::ContiguousCategoricalEntropyModel<
+                Probability,
+                alloc::vec::Vec<Probability>,
+                PRECISION,
+            > {
+                &self.model
+            }
+        }
+
+        impl<Probability: $crate::BitArray, const PRECISION: usize>
+            $crate::stream::model::EntropyModel<PRECISION> for $name<Probability, PRECISION>
+        {
+            type Symbol = usize;
+            type Probability = Probability;
+        }
+
+        impl<Probability: $crate::BitArray, const PRECISION: usize>
+            $crate::stream::model::EncoderModel<PRECISION> for $name<Probability, PRECISION>
+        {
+            #[inline(always)]
+            fn left_cumulative_and_probability(
+                &self,
+                symbol: impl core::borrow::Borrow<usize>,
+            ) -> Option<(Probability, Probability::NonZero)> {
+                self.model.left_cumulative_and_probability(symbol)
+            }
+        }
+
+        impl<Probability: $crate::BitArray, const PRECISION: usize>
+            $crate::stream::model::DecoderModel<PRECISION> for $name<Probability, PRECISION>
+        {
+            #[inline(always)]
+            fn quantile_function(
+                &self,
+                quantile: Probability,
+            ) -> (usize, Probability, Probability::NonZero) {
+                self.model.quantile_function(quantile)
+            }
+        }
+
+        impl<'m, Probability: $crate::BitArray, const PRECISION: usize>
+            $crate::stream::model::IterableEntropyModel<'m, PRECISION>
+            for $name<Probability, PRECISION>
+        {
+            fn symbol_table(
+                &'m self,
+            ) -> impl Iterator<Item = (usize, Probability, Probability::NonZero)> {
+                self.model.symbol_table()
+            }
+
+            #[inline(always)]
+            fn support_size(&'m self) -> usize {
+                self.model.support_size()
+            }
+        }
+    };
+}
+
+mod bernoulli;
+mod binomial;
+mod capped;
 mod categorical;
+mod codebook;
+mod context;
+mod custom;
+mod division;
+mod dyn_precision;
+mod geometric;
+mod mixture;
+mod poisson;
 mod quantize;
+mod remapped;
+mod smoothed;
+mod symbol_table;
 mod uniform;
+mod zero_inflated;
 
 use core::{borrow::Borrow, hash::Hash};
 
 use alloc::{boxed::Box, vec::Vec};
 
-use num_traits::{float::FloatCore, AsPrimitive, One, Zero};
+use num_traits::{float::FloatCore, AsPrimitive, One, WrappingAdd, Zero};
 
-use crate::{BitArray, NonZeroBitArray};
+use crate::{wrapping_pow2, BitArray, NonZeroBitArray};
 
 /// Base trait for probabilistic models of a data source.
 ///
@@ -268,6 +353,24 @@ pub trait EntropyModel<const PRECISION: usize> {
     /// interpretations of the integer `p = 0` always turned out to be easy to disambiguate
     /// statically.
     type Probability: BitArray;
+
+    /// Returns the const generic parameter `PRECISION` at runtime.
+    ///
+    /// This is useful in generic code that needs to record the configuration of an entropy
+    /// model (e.g., for logging or serialization) without knowing its concrete type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::model::{DefaultUniformModel, EntropyModel, SmallUniformModel};
+    ///
+    /// assert_eq!(DefaultUniformModel::new(10).precision(), 24);
+    /// assert_eq!(SmallUniformModel::new(10).precision(), 12);
+    /// ```
+    #[inline(always)]
+    fn precision(&self) -> usize {
+        PRECISION
+    }
 }
 
 /// A trait for [`EntropyModel`]s that can be used for encoding (compressing) data.
@@ -335,6 +438,24 @@ pub trait EncoderModel<const PRECISION: usize>: EntropyModel<PRECISION> {
         symbol: impl Borrow<Self::Symbol>,
     ) -> Option<(Self::Probability, <Self::Probability as BitArray>::NonZero)>;
 
+    /// Returns precomputed data for dividing by the probability of `symbol`, if available.
+    ///
+    /// Encoders that have to divide by the probability of a symbol (such as an
+    /// [`AnsCoder`](super::stack::AnsCoder)) call this method in addition to
+    /// [`left_cumulative_and_probability`](Self::left_cumulative_and_probability). If it
+    /// returns `Some(hint)`, they replace the division by a faster multiplication and
+    /// shifts, which doesn't change the compressed data. If you implement this method, then
+    /// the returned hint's [`divisor`](DivisionHint::divisor) must be the probability of
+    /// `symbol` as returned by `left_cumulative_and_probability`.
+    ///
+    /// The default implementation returns `None`. See [`WithDivisionHints`] for a
+    /// decorator that provides division hints for an existing entropy model.
+    #[inline(always)]
+    fn division_hint(&self, symbol: impl Borrow<Self::Symbol>) -> Option<DivisionHint> {
+        let _ = symbol;
+        None
+    }
+
     /// Returns the probability of the given symbol in floating point representation.
     ///
     /// The trait bound `Self::Probability: Into<F>` guarantees that no rounding occurs in
@@ -558,6 +679,57 @@ pub trait IterableEntropyModel<'m, const PRECISION: usize>: EntropyModel<PRECISI
             })
     }
 
+    /// Iterates over all symbols with nonzero probability, together with their
+    /// probabilities.
+    ///
+    /// This is a simplified version of [`symbol_table`] that omits the left-sided
+    /// cumulatives. The symbols are yielded in the same order, and the probabilities
+    /// (in fixed-point representation) always sum to exactly `2^PRECISION`. For models
+    /// created by a [`LeakyQuantizer`], the yielded symbols are exactly the quantization
+    /// range.
+    ///
+    /// This method is not called `support` because some types already have an inherent
+    /// method of that name that returns the range of symbols (e.g.,
+    /// [`LeakilyQuantizedDistribution::support`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::model::{DefaultUniformModel, IterableEntropyModel};
+    ///
+    /// let model = DefaultUniformModel::new(3);
+    /// let support = model
+    ///     .support_iter()
+    ///     .map(|(symbol, probability)| (symbol, probability.get()))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(support, [(0, 5592405), (1, 5592405), (2, 5592406)]);
+    /// assert_eq!(support.iter().map(|&(_, probability)| probability).sum::<u32>(), 1 << 24);
+    /// assert_eq!(model.support_size(), 3);
+    /// ```
+    ///
+    /// [`symbol_table`]: Self::symbol_table
+    fn support_iter(
+        &'m self,
+    ) -> impl Iterator<Item = (Self::Symbol, <Self::Probability as BitArray>::NonZero)> {
+        self.symbol_table()
+            .map(|(symbol, _, probability)| (symbol, probability))
+    }
+
+    /// Returns the number of symbols with nonzero probability.
+    ///
+    /// This is the number of items yielded by [`support_iter`](Self::support_iter) and by
+    /// [`symbol_table`](Self::symbol_table). The default implementation counts the items
+    /// of `symbol_table`, but most models override it with a constant-time implementation.
+    ///
+    /// # Panics
+    ///
+    /// May panic if the number of symbols with nonzero probability exceeds `usize::MAX`,
+    /// which is only possible if `PRECISION` is at least the pointer width of the target
+    /// platform.
+    fn support_size(&'m self) -> usize {
+        self.symbol_table().count()
+    }
+
     /// Returns the entropy in units of bits (i.e., base 2).
     ///
     /// The entropy is the expected amortized bit rate per symbol of an optimal lossless
@@ -744,6 +916,59 @@ pub trait IterableEntropyModel<'m, const PRECISION: usize>: EntropyModel<PRECISI
         scaled_shifted / whole - F::from(PRECISION).unwrap()
     }
 
+    /// Returns the expected number of bits per symbol when using this model to encode
+    /// symbols drawn from a (possibly different) true distribution.
+    ///
+    /// This is the same quantity as [`cross_entropy_base2`](Self::cross_entropy_base2),
+    /// i.e., `- sum_s true_probability(s) * log2(self[s])`, except that the true
+    /// distribution is provided as a function of the symbol rather than as a sequence in
+    /// the order of [`symbol_table`](Self::symbol_table). This makes it easy to diagnose
+    /// model mismatch, e.g., when the true distribution is known analytically. The sum runs
+    /// over the support of the model; any probability mass of the true distribution outside
+    /// of this support is ignored (such symbols could not be encoded with this model
+    /// anyway).
+    ///
+    /// To get the expected bit rate when the true distribution coincides with the model,
+    /// call [`entropy_base2`](Self::entropy_base2).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::model::{DefaultLeakyQuantizer, IterableEntropyModel};
+    /// use probability::distribution::{Distribution, Gaussian};
+    ///
+    /// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+    /// let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+    ///
+    /// // The data actually follow a Gaussian distribution with a different mean.
+    /// let truth = Gaussian::new(5.0, 10.0);
+    /// let true_probability =
+    ///     |&x: &i32| truth.distribution(x as f64 + 0.5) - truth.distribution(x as f64 - 0.5);
+    ///
+    /// let expected_bits = model.expected_bits(true_probability);
+    /// let entropy = model.entropy_base2::<f64>();
+    /// // The mismatch costs `0.5 * (5 / 10)^2 / ln(2) ≈ 0.18` bits per symbol.
+    /// assert!((expected_bits - entropy - 0.18).abs() < 0.01);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`cross_entropy_base2`](Self::cross_entropy_base2)
+    /// - [`entropy_base2`](Self::entropy_base2)
+    fn expected_bits<F>(&'m self, true_probability: impl Fn(&Self::Symbol) -> F) -> F
+    where
+        F: num_traits::Float + core::iter::Sum,
+        Self::Probability: Into<F>,
+    {
+        let shift = F::from(PRECISION).unwrap();
+        self.symbol_table()
+            .map(|(symbol, _, probability)| {
+                let probability = probability.get().into();
+                true_probability(&symbol) * (shift - probability.log2())
+            })
+            .sum::<F>()
+    }
+
     /// Creates an [`EncoderModel`] from this `EntropyModel`
     ///
     /// This is a fallback method that should only be used if no more specialized
@@ -820,6 +1045,96 @@ pub trait IterableEntropyModel<'m, const PRECISION: usize>: EntropyModel<PRECISI
     }
 }
 
+/// Verifies that an [`EncoderModel`] and a [`DecoderModel`] are inverse to each other.
+///
+/// This is a testing utility for authors of custom entropy models. Entropy coding can only
+/// reconstruct the encoded data if the encoder and decoder agree *exactly* on the
+/// fixed-point representation of the probability distribution. This function checks this
+/// for each provided symbol: it looks up the symbol's left-sided cumulative and probability
+/// with [`EncoderModel::left_cumulative_and_probability`] and then verifies that
+/// [`DecoderModel::quantile_function`] maps *every* quantile within the symbol's interval
+/// `left_cumulative..left_cumulative + probability` back to the same symbol, left-sided
+/// cumulative, and probability. Mismatches of this kind are the most common bug in custom
+/// entropy models.
+///
+/// The encoder and decoder side may be provided by the same type (if it implements both
+/// traits) or by two different types (e.g., a [`NonContiguousCategoricalEncoderModel`] and
+/// a [`NonContiguousCategoricalDecoderModel`]), which can be passed in as `encoder_model`
+/// and `decoder_model`, respectively.
+///
+/// Note that the runtime of this function is proportional to the total probability mass of
+/// all provided `symbols`, i.e., up to `2^PRECISION` calls of `quantile_function`. You may
+/// want to test your model at a low `PRECISION` or in a release build.
+///
+/// # Panics
+///
+/// Panics with a descriptive message if the check fails, or if any of the provided
+/// `symbols` has zero probability under `encoder_model`. This makes it convenient to call
+/// this function within a unit test.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::model::{
+///     check_encoder_decoder_consistency, SmallContiguousCategoricalEntropyModel,
+/// };
+///
+/// let model = SmallContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+///     &[0.2, 0.5, 0.3], None
+/// ).unwrap();
+/// check_encoder_decoder_consistency(&model, &model, 0..3);
+/// ```
+///
+/// [`NonContiguousCategoricalEncoderModel`]: self::NonContiguousCategoricalEncoderModel
+/// [`NonContiguousCategoricalDecoderModel`]: self::NonContiguousCategoricalDecoderModel
+pub fn check_encoder_decoder_consistency<E, D, const PRECISION: usize>(
+    encoder_model: E,
+    decoder_model: D,
+    symbols: impl IntoIterator<Item = E::Symbol>,
+) where
+    E: EncoderModel<PRECISION>,
+    D: DecoderModel<PRECISION, Symbol = E::Symbol, Probability = E::Probability>,
+    E::Symbol: PartialEq + core::fmt::Debug,
+{
+    for symbol in symbols {
+        let (left_cumulative, probability) = encoder_model
+            .left_cumulative_and_probability(&symbol)
+            .unwrap_or_else(|| panic!("symbol {:?} has zero probability", symbol));
+        let probability = probability.get();
+        let right_cumulative = left_cumulative.wrapping_add(&probability);
+        let one = wrapping_pow2::<E::Probability>(PRECISION);
+        assert!(
+            right_cumulative == one
+                || (right_cumulative > left_cumulative
+                    && (PRECISION == E::Probability::BITS || right_cumulative < one)),
+            "interval of symbol {:?} exceeds `1 << PRECISION`",
+            symbol
+        );
+
+        let mut offset = E::Probability::zero();
+        while offset != probability {
+            let quantile = left_cumulative + offset;
+            let (decoded_symbol, decoded_left_cumulative, decoded_probability) =
+                decoder_model.quantile_function(quantile);
+            assert!(
+                decoded_symbol == symbol
+                    && decoded_left_cumulative == left_cumulative
+                    && decoded_probability.get() == probability,
+                "quantile {} of symbol {:?} (left cumulative {}, probability {}) decodes to \
+                symbol {:?} (left cumulative {}, probability {})",
+                quantile,
+                symbol,
+                left_cumulative,
+                probability,
+                decoded_symbol,
+                decoded_left_cumulative,
+                decoded_probability
+            );
+            offset = offset + E::Probability::one();
+        }
+    }
+}
+
 impl<M, const PRECISION: usize> EntropyModel<PRECISION> for &M
 where
     M: EntropyModel<PRECISION> + ?Sized,
@@ -839,6 +1154,11 @@ where
     ) -> Option<(Self::Probability, <Self::Probability as BitArray>::NonZero)> {
         (*self).left_cumulative_and_probability(symbol)
     }
+
+    #[inline(always)]
+    fn division_hint(&self, symbol: impl Borrow<Self::Symbol>) -> Option<DivisionHint> {
+        (*self).division_hint(symbol)
+    }
 }
 
 impl<M, const PRECISION: usize> DecoderModel<PRECISION> for &M
@@ -874,6 +1194,11 @@ where
         (*self).symbol_table()
     }
 
+    #[inline(always)]
+    fn support_size(&'m self) -> usize {
+        (*self).support_size()
+    }
+
     fn entropy_base2<F>(&'m self) -> F
     where
         F: num_traits::Float + core::iter::Sum,
@@ -908,7 +1233,11 @@ where
     }
 }
 
+pub use bernoulli::{Bernoulli, DefaultBernoulli, SmallBernoulli};
+pub use binomial::{Binomial, DefaultBinomial, SmallBinomial};
+pub use capped::Capped;
 pub use categorical::{
+    adaptive::{AdaptiveCategorical, DefaultAdaptiveCategorical, SmallAdaptiveCategorical},
     contiguous::{
         ContiguousCategoricalEntropyModel, DefaultContiguousCategoricalEntropyModel,
         SmallContiguousCategoricalEntropyModel,
@@ -925,10 +1254,26 @@ pub use categorical::{
         SmallNonContiguousCategoricalDecoderModel, SmallNonContiguousCategoricalEncoderModel,
     },
 };
+pub use codebook::{Codebook, DefaultCodebook, SmallCodebook};
+pub use context::ContextModel;
+pub use custom::CustomModel;
+pub use division::{DivisionHint, WithDivisionHints};
+pub use dyn_precision::DynPrecisionModel;
+pub use geometric::{DefaultGeometric, Geometric, SmallGeometric};
+pub use mixture::MixtureModel;
+pub use poisson::{DefaultPoisson, Poisson, SmallPoisson};
 pub use quantize::{
     DefaultLeakyQuantizer, LeakilyQuantizedDistribution, LeakyQuantizer, SmallLeakyQuantizer,
 };
-pub use uniform::{DefaultUniformModel, SmallUniformModel, UniformModel};
+pub use smoothed::Smoothed;
+pub use symbol_table::SymbolTableModel;
+pub use uniform::{
+    DefaultUniformIntegerModel, DefaultUniformModel, SmallUniformIntegerModel, SmallUniformModel,
+    UniformIntegerModel, UniformModel,
+};
+pub use zero_inflated::{
+    DefaultZeroInflatedGeometric, SmallZeroInflatedGeometric, ZeroInflatedGeometric,
+};
 
 #[cfg(test)]
 mod tests {
@@ -957,6 +1302,176 @@ mod tests {
         }
     }
 
+    #[test]
+    fn expected_bits() {
+        use crate::stream::{stack::DefaultAnsCoder, Encode};
+        use rand::{Rng, SeedableRng};
+        use rand_xoshiro::Xoshiro256StarStar;
+
+        #[cfg(not(miri))]
+        let amt = 100_000;
+        #[cfg(miri)]
+        let amt = 100;
+
+        let true_probabilities = [0.05, 0.1, 0.4, 0.3, 0.1, 0.05];
+        let model_probabilities = [0.2, 0.2, 0.2, 0.2, 0.1, 0.1];
+        let model =
+            DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                &model_probabilities,
+                None,
+            )
+            .unwrap();
+
+        let expected_bits = model.expected_bits(|&symbol| true_probabilities[symbol]);
+        let cross_entropy = model.cross_entropy_base2(true_probabilities);
+        assert!((expected_bits - cross_entropy).abs() < 1e-12);
+
+        let own_probabilities = model
+            .floating_point_symbol_table::<f64>()
+            .map(|(_, _, probability)| probability)
+            .collect::<Vec<_>>();
+        let self_entropy = model.expected_bits(|&symbol| own_probabilities[symbol]);
+        assert!((self_entropy - model.entropy_base2::<f64>()).abs() < 1e-12);
+        let true_entropy = true_probabilities
+            .iter()
+            .map(|&p: &f64| -p * p.log2())
+            .sum::<f64>();
+        assert!(expected_bits > true_entropy);
+
+        // Compare to the empirical bit rate on a sample from the true distribution.
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2265);
+        let symbols = (0..amt)
+            .map(|_| {
+                let mut x = rng.gen::<f64>();
+                true_probabilities
+                    .iter()
+                    .position(|&p| {
+                        x -= p;
+                        x < 0.0
+                    })
+                    .unwrap_or(true_probabilities.len() - 1)
+            })
+            .collect::<Vec<_>>();
+        let mut coder = DefaultAnsCoder::new();
+        coder.encode_iid_symbols(&symbols, &model).unwrap();
+        let bitrate = coder.num_valid_bits() as f64 / amt as f64;
+        #[cfg(not(miri))]
+        assert!((bitrate - expected_bits).abs() < 0.01 * expected_bits);
+        #[cfg(miri)]
+        let _ = bitrate;
+    }
+
+    #[test]
+    fn encoder_decoder_consistency() {
+        let probabilities = [0.001, 0.3, 0.0, 0.2, 0.4, 0.099];
+        let categorical =
+            SmallContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                &probabilities,
+                None,
+            )
+            .unwrap();
+        check_encoder_decoder_consistency(&categorical, &categorical, 0..6);
+        check_encoder_decoder_consistency(
+            &categorical,
+            categorical.to_lookup_decoder_model(),
+            0..6,
+        );
+
+        let symbols = ['a', 'b', 'x', 'y', 'z'];
+        let encoder_model =
+            SmallNonContiguousCategoricalEncoderModel::from_symbols_and_floating_point_probabilities_fast(
+                symbols.iter().copied(),
+                &[0.1, 0.2, 0.3, 0.25, 0.15],
+                None,
+            )
+            .unwrap();
+        let decoder_model =
+            SmallNonContiguousCategoricalDecoderModel::from_symbols_and_floating_point_probabilities_fast(
+                symbols.iter().copied(),
+                &[0.1, 0.2, 0.3, 0.25, 0.15],
+                None,
+            )
+            .unwrap();
+        check_encoder_decoder_consistency(&encoder_model, &decoder_model, symbols);
+
+        let quantizer = SmallLeakyQuantizer::new(-20..=20);
+        let gaussian = quantizer.quantize(Gaussian::new(2.5, 4.0));
+        check_encoder_decoder_consistency(&gaussian, &gaussian, -20..=20);
+
+        for range in [2, 3, 17, 64] {
+            let uniform = UniformModel::<u8, 6>::new(range);
+            check_encoder_decoder_consistency(uniform, uniform, 0..range);
+        }
+        let uniform = UniformModel::<u16, 16>::new(7);
+        check_encoder_decoder_consistency(uniform, uniform, 0..7);
+    }
+
+    #[test]
+    fn support_iter() {
+        fn check<'m, M, const PRECISION: usize>(model: &'m M, expected_size: usize)
+        where
+            M: IterableEntropyModel<'m, PRECISION> + EncoderModel<PRECISION>,
+            M::Symbol: PartialEq + core::fmt::Debug,
+            M::Probability: Into<u64>,
+        {
+            let mut count = 0;
+            let mut total = 0u64;
+            for (symbol, probability) in model.support_iter() {
+                let (_, expected_probability) =
+                    model.left_cumulative_and_probability(symbol).unwrap();
+                assert_eq!(probability, expected_probability);
+                total += probability.get().into();
+                count += 1;
+            }
+            assert_eq!(total, 1 << PRECISION);
+            assert_eq!(count, expected_size);
+            assert_eq!(model.support_size(), expected_size);
+        }
+
+        let categorical =
+            DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                &[0.1, 0.2, 0.0, 0.7],
+                None,
+            )
+            .unwrap();
+        check(&categorical, 4);
+
+        check(&DefaultUniformModel::new(3), 3);
+        check(&SmallUniformModel::new(4096), 4096);
+
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(3.2, 10.0));
+        check(&model, 201);
+        assert_eq!(model.support(), -100..=100);
+        assert!(model
+            .support_iter()
+            .map(|(symbol, _)| symbol)
+            .eq(-100..=100));
+
+        // A signed symbol type that is smaller than `Probability`.
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-20i8..=30);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        check(&model, 51);
+    }
+
+    #[test]
+    #[should_panic]
+    fn encoder_decoder_inconsistency() {
+        let encoder_model =
+            SmallContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                &[0.2, 0.5, 0.3],
+                None,
+            )
+            .unwrap();
+        let decoder_model =
+            SmallContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                &[0.21, 0.49, 0.3],
+                None,
+            )
+            .unwrap();
+        check_encoder_decoder_consistency(&encoder_model, &decoder_model, 0..3);
+    }
+
     pub(super) fn test_entropy_model<'m, D, const PRECISION: usize>(
         model: &'m D,
         support: impl Clone + Iterator<Item = D::Symbol>,
@@ -1009,6 +1524,14 @@ mod tests {
         }
         assert_eq!(count, support.size_hint().0);
         assert_eq!(expected_cumulative, 1 << PRECISION);
+
+        assert_eq!(model.support_size(), count);
+        let mut total = 0u64;
+        for ((symbol, probability), expected_symbol) in model.support_iter().zip(support) {
+            assert_eq!(symbol, expected_symbol);
+            total += probability.get().into();
+        }
+        assert_eq!(total, 1 << PRECISION);
     }
 
     /// Verifies that the model is close to a provided probability mass function (in
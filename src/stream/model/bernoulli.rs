@@ -0,0 +1,300 @@
+use core::borrow::Borrow;
+
+use num_traits::AsPrimitive;
+
+use crate::{generic_static_asserts, wrapping_pow2, BitArray, NonZeroBitArray};
+
+use super::{DecoderModel, EncoderModel, EntropyModel, IterableEntropyModel};
+
+/// Type alias for a typical [`Bernoulli`] model.
+///
+/// See:
+/// - [`Bernoulli`]
+/// - [discussion of presets](crate::stream#presets)
+pub type DefaultBernoulli = Bernoulli<u32, 24>;
+
+/// Type alias for a [`Bernoulli`] model that is easier to use within a sequence of
+/// compressed symbols that also involves some lookup models.
+///
+/// See:
+/// - [`Bernoulli`]
+/// - [discussion of presets](crate::stream#presets)
+pub type SmallBernoulli = Bernoulli<u16, 12>;
+
+/// An entropy model for binary symbols `0` and `1`.
+///
+/// This is a specialized (and faster) alternative to a
+/// [`ContiguousCategoricalEntropyModel`](super::ContiguousCategoricalEntropyModel) with two
+/// symbols. It is cheap to construct, so you can construct a new `Bernoulli` model for
+/// each bit, e.g., when encoding a bit mask where each bit has its own probability of
+/// being set. Encoding and decoding require only a single comparison.
+///
+/// The probability of the symbol `1` is rounded to the nearest multiple of
+/// `2^-PRECISION` and then clamped to `[2^-PRECISION, 1 - 2^-PRECISION]`, so that neither
+/// symbol has zero probability (even if you construct the model with a probability of
+/// exactly `0.0` or `1.0`).
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{model::DefaultBernoulli, stack::DefaultAnsCoder, Decode};
+///
+/// let bits = [1, 0, 0, 1, 1, 1, 0, 1];
+/// let probabilities = [0.9, 0.1, 0.5, 0.7, 0.99, 0.8, 0.0, 1.0];
+///
+/// let models = probabilities.map(|probability| DefaultBernoulli::new(probability).unwrap());
+///
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_symbols_reverse(bits.iter().zip(&models)).unwrap();
+///
+/// let decoded = ans
+///     .decode_symbols(&models)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, bits);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Bernoulli<Probability: BitArray, const PRECISION: usize> {
+    /// The probability of the symbol `0`, which is also the left-sided cumulative of the
+    /// symbol `1`. Always satisfies `0 < probability_of_zero < 2^PRECISION`.
+    probability_of_zero: Probability::NonZero,
+}
+
+impl<Probability: BitArray, const PRECISION: usize> Bernoulli<Probability, PRECISION>
+where
+    u128: AsPrimitive<Probability>,
+{
+    /// Constructs a model where the symbol `1` has (approximately) probability
+    /// `probability_of_one`.
+    ///
+    /// Returns `Err(())` unless `0.0 <= probability_of_one <= 1.0`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(probability_of_one: f64) -> Result<Self, ()> {
+        generic_static_asserts!(
+            (Probability: BitArray; const PRECISION: usize);
+            PROBABILITY_MUST_SUPPORT_PRECISION: PRECISION <= Probability::BITS;
+            PRECISION_MUST_BE_NONZERO: PRECISION > 0;
+        );
+
+        if !(0.0..=1.0).contains(&probability_of_one) {
+            return Err(());
+        }
+
+        let total = 1u128 << PRECISION;
+        let fixed_point = (probability_of_one * total as f64 + 0.5) as u128;
+        let probability_of_one = fixed_point.clamp(1, total - 1);
+        let probability_of_zero = (total - probability_of_one).as_();
+
+        Ok(Self {
+            probability_of_zero: probability_of_zero
+                .into_nonzero()
+                .expect("clamped to nonzero"),
+        })
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> Bernoulli<Probability, PRECISION> {
+    /// Constructs a model from the fixed-point probability of the symbol `1`, in units of
+    /// `2^-PRECISION`.
+    ///
+    /// Returns `None` unless `0 < probability_of_one < 2^PRECISION`.
+    pub fn from_fixed_point_probability_of_one(probability_of_one: Probability) -> Option<Self> {
+        generic_static_asserts!(
+            (Probability: BitArray; const PRECISION: usize);
+            PROBABILITY_MUST_SUPPORT_PRECISION: PRECISION <= Probability::BITS;
+            PRECISION_MUST_BE_NONZERO: PRECISION > 0;
+        );
+
+        let max_probability = Probability::max_value() >> (Probability::BITS - PRECISION);
+        if probability_of_one == Probability::zero() || probability_of_one > max_probability {
+            return None;
+        }
+
+        let probability_of_zero = wrapping_pow2::<Probability>(PRECISION)
+            .wrapping_sub(&probability_of_one)
+            .into_nonzero()?;
+        Some(Self {
+            probability_of_zero,
+        })
+    }
+
+    /// Returns the fixed-point probability of the symbol `1`, in units of
+    /// `2^-PRECISION`.
+    pub fn fixed_point_probability_of_one(&self) -> Probability {
+        wrapping_pow2::<Probability>(PRECISION).wrapping_sub(&self.probability_of_zero.get())
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for Bernoulli<Probability, PRECISION>
+{
+    type Symbol = usize;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for Bernoulli<Probability, PRECISION>
+{
+    #[inline]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Self::Symbol>,
+    ) -> Option<(Self::Probability, <Self::Probability as BitArray>::NonZero)> {
+        match *symbol.borrow() {
+            0 => Some((Probability::zero(), self.probability_of_zero)),
+            1 => {
+                // SAFETY: the constructors ensure that `probability_of_zero < 2^PRECISION`.
+                let probability_of_one = unsafe {
+                    self.fixed_point_probability_of_one()
+                        .into_nonzero_unchecked()
+                };
+                Some((self.probability_of_zero.get(), probability_of_one))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for Bernoulli<Probability, PRECISION>
+{
+    #[inline]
+    fn quantile_function(
+        &self,
+        quantile: Self::Probability,
+    ) -> (
+        Self::Symbol,
+        Self::Probability,
+        <Self::Probability as BitArray>::NonZero,
+    ) {
+        let symbol = (quantile >= self.probability_of_zero.get()) as usize;
+        let (left_cumulative, probability) = self
+            .left_cumulative_and_probability(symbol)
+            .expect("symbol is either 0 or 1");
+        (symbol, left_cumulative, probability)
+    }
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for Bernoulli<Probability, PRECISION>
+{
+    fn symbol_table(
+        &'m self,
+    ) -> impl Iterator<
+        Item = (
+            Self::Symbol,
+            Self::Probability,
+            <Self::Probability as BitArray>::NonZero,
+        ),
+    > {
+        (0..2).map(move |symbol| {
+            let (left_cumulative, probability) = self
+                .left_cumulative_and_probability(symbol)
+                .expect("symbol is either 0 or 1");
+            (symbol, left_cumulative, probability)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand::{Rng, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    use super::{super::tests::test_entropy_model, *};
+    use crate::stream::{stack::DefaultAnsCoder, Decode};
+
+    #[test]
+    fn bernoulli() {
+        for probability in [0.0, 1e-9, 0.001, 0.1, 0.5, 0.7, 0.999, 1.0 - 1e-9, 1.0] {
+            let model = DefaultBernoulli::new(probability).unwrap();
+            test_entropy_model(&model, 0..2);
+            let model = SmallBernoulli::new(probability).unwrap();
+            test_entropy_model(&model, 0..2);
+            let model = Bernoulli::<u32, 32>::new(probability).unwrap();
+            test_entropy_model(&model, 0..2);
+            let model = Bernoulli::<u8, 8>::new(probability).unwrap();
+            test_entropy_model(&model, 0..2);
+            assert!(model.left_cumulative_and_probability(2).is_none());
+        }
+
+        assert_eq!(
+            DefaultBernoulli::new(0.0)
+                .unwrap()
+                .fixed_point_probability_of_one(),
+            1
+        );
+        assert_eq!(
+            DefaultBernoulli::new(1.0)
+                .unwrap()
+                .fixed_point_probability_of_one(),
+            (1 << 24) - 1
+        );
+        assert_eq!(
+            DefaultBernoulli::new(0.25)
+                .unwrap()
+                .fixed_point_probability_of_one(),
+            1 << 22
+        );
+
+        assert!(DefaultBernoulli::new(-0.1).is_err());
+        assert!(DefaultBernoulli::new(1.1).is_err());
+        assert!(DefaultBernoulli::new(f64::NAN).is_err());
+
+        let model = DefaultBernoulli::from_fixed_point_probability_of_one(12345).unwrap();
+        assert_eq!(model.fixed_point_probability_of_one(), 12345);
+        test_entropy_model(&model, 0..2);
+        assert!(DefaultBernoulli::from_fixed_point_probability_of_one(0).is_none());
+        assert!(DefaultBernoulli::from_fixed_point_probability_of_one(1 << 24).is_none());
+        assert!(Bernoulli::<u32, 32>::from_fixed_point_probability_of_one(u32::MAX).is_some());
+    }
+
+    #[test]
+    fn random_bits() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2260);
+        let probabilities = (0..10_000)
+            .map(|_| rng.gen::<f64>().powi(3))
+            .collect::<Vec<_>>();
+        let bits = probabilities
+            .iter()
+            .map(|&probability| (rng.gen::<f64>() < probability) as usize)
+            .collect::<Vec<_>>();
+
+        let models = probabilities
+            .iter()
+            .map(|&probability| DefaultBernoulli::new(probability).unwrap())
+            .collect::<Vec<_>>();
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_symbols_reverse(bits.iter().zip(&models))
+            .unwrap();
+
+        let information_content = bits
+            .iter()
+            .zip(&models)
+            .map(|(&bit, model)| {
+                let probability = model.left_cumulative_and_probability(bit).unwrap().1;
+                24.0 - (probability.get() as f64).log2()
+            })
+            .sum::<f64>();
+        let expected_entropy = probabilities
+            .iter()
+            .map(|&p| {
+                let p = p.clamp(1e-300, 1.0 - 1e-16);
+                -p * p.log2() - (1.0 - p) * (1.0 - p).log2()
+            })
+            .sum::<f64>();
+        let num_bits = ans.num_valid_bits() as f64;
+        assert!(num_bits < information_content + 64.0);
+        assert!(num_bits > information_content - 1.0);
+        assert!((num_bits - expected_entropy).abs() < 0.05 * expected_entropy);
+
+        let decoded = ans
+            .decode_symbols(&models)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, bits);
+        assert!(ans.is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use alloc::vec::Vec;
+use num_traits::AsPrimitive;
+
+use crate::BitArray;
+
+use super::ContiguousCategoricalEntropyModel;
+
+/// Type alias for a typical [`Binomial`] model.
+///
+/// See:
+/// - [`Binomial`]
+/// - [discussion of presets](crate::stream#presets)
+pub type DefaultBinomial = Binomial<u32, 24>;
+
+/// Type alias for a [`Binomial`] model that is easier to use within a sequence of
+/// compressed symbols that also involves some lookup models.
+///
+/// See:
+/// - [`Binomial`]
+/// - [discussion of presets](crate::stream#presets)
+pub type SmallBinomial = Binomial<u16, 12>;
+
+/// An entropy model for the number of successes in a fixed number of Bernoulli trials.
+///
+/// A `Binomial` model is defined over the symbols `0..=n`, where the symbol `k` has
+/// (approximately) probability `(n choose k) * p^k * (1 - p)^(n - k)`. The constructor
+/// evaluates this probability mass function once and stores the resulting fixed-point
+/// cumulative distribution function in a table of size `n + 2`, so encoding and decoding
+/// have the same cost as with a [`ContiguousCategoricalEntropyModel`].
+///
+/// The fixed-point representation is leaky: every symbol in `0..=n` has a nonzero
+/// probability (even if it has a very small or even zero probability under the exact
+/// binomial distribution, e.g., if `p == 0.0`), so any outcome within range can be encoded.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{model::DefaultBinomial, stack::DefaultAnsCoder, Decode};
+///
+/// // Number of successes out of 20 trials, each with success probability 0.3.
+/// let model = DefaultBinomial::new(20, 0.3).unwrap();
+///
+/// let counts = [6, 5, 8, 4, 0, 6, 20, 7];
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_iid_symbols_reverse(&counts, &model).unwrap();
+///
+/// let decoded = ans
+///     .decode_iid_symbols(counts.len(), &model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, counts);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Binomial<Probability: BitArray, const PRECISION: usize> {
+    model: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>,
+}
+
+impl<Probability: BitArray, const PRECISION: usize> Binomial<Probability, PRECISION>
+where
+    f64: AsPrimitive<Probability>,
+    Probability: AsPrimitive<usize>,
+    usize: AsPrimitive<Probability>,
+{
+    /// Constructs a model for the number of successes out of `n` trials with success
+    /// probability `p` each.
+    ///
+    /// Returns `Err(())` if `n == 0`, if `p` is not within `[0, 1]`, or if `n` is too large
+    /// to assign a nonzero probability to each symbol in `0..=n` at the given `PRECISION`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(n: u32, p: f64) -> Result<Self, ()> {
+        if n == 0 || !(0.0..=1.0).contains(&p) {
+            return Err(());
+        }
+
+        // Evaluate the PMF in log space to avoid overflow of the binomial coefficients.
+        let n_f64 = n as f64;
+        let (ln_p, ln_q) = (libm::log(p), libm::log1p(-p));
+        let ln_n_factorial = libm::lgamma(n_f64 + 1.0);
+        let probabilities = (0..=n)
+            .map(|k| {
+                let k_f64 = k as f64;
+                let mut ln_pmf =
+                    ln_n_factorial - libm::lgamma(k_f64 + 1.0) - libm::lgamma(n_f64 - k_f64 + 1.0);
+                if k != 0 {
+                    ln_pmf += k_f64 * ln_p;
+                }
+                if k != n {
+                    ln_pmf += (n_f64 - k_f64) * ln_q;
+                }
+                libm::exp(ln_pmf)
+            })
+            .collect::<Vec<_>>();
+
+        let model = ContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast::<f64>(
+            &probabilities,
+            None,
+        )?;
+        Ok(Self { model })
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> Binomial<Probability, PRECISION> {
+    /// Returns the number of trials `n`, i.e., the largest symbol supported by the model.
+    pub fn num_trials(&self) -> usize {
+        self.model.support_size() - 1
+    }
+}
+
+forward_to_contiguous_categorical!(Binomial);
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand::{Rng, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    use super::{
+        super::{tests::test_entropy_model, EncoderModel},
+        *,
+    };
+    use crate::stream::{stack::DefaultAnsCoder, Decode};
+
+    #[test]
+    fn binomial() {
+        for (n, p) in [
+            (1, 0.5),
+            (10, 0.3),
+            (20, 0.0),
+            (20, 1.0),
+            (100, 0.01),
+            (1000, 0.5),
+        ] {
+            let model = DefaultBinomial::new(n, p).unwrap();
+            assert_eq!(model.num_trials(), n as usize);
+            test_entropy_model(&model, 0..=n as usize);
+
+            let model = SmallBinomial::new(n, p).unwrap();
+            test_entropy_model(&model, 0..=n as usize);
+            assert!(model
+                .left_cumulative_and_probability(n as usize + 1)
+                .is_none());
+        }
+
+        assert!(DefaultBinomial::new(0, 0.5).is_err());
+        assert!(DefaultBinomial::new(10, -0.1).is_err());
+        assert!(DefaultBinomial::new(10, 1.1).is_err());
+        assert!(DefaultBinomial::new(10, f64::NAN).is_err());
+        assert!(SmallBinomial::new(2000, 0.5).is_ok());
+        assert!(SmallBinomial::new(4096, 0.5).is_err());
+    }
+
+    #[test]
+    fn bitrate() {
+        #[cfg(not(miri))]
+        let amt = 10_000;
+
+        #[cfg(miri)]
+        let amt = 100;
+
+        let (n, p) = (50u32, 0.2f64);
+        let model = DefaultBinomial::new(n, p).unwrap();
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2277);
+        let symbols = (0..amt)
+            .map(|_| (0..n).filter(|_| rng.gen::<f64>() < p).count())
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let bitrate = ans.num_valid_bits() as f64 / amt as f64;
+
+        let decoded = ans
+            .decode_iid_symbols(amt, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(ans.is_empty());
+
+        // Entropy of the exact binomial distribution.
+        let mut pmf = 1.0 - p;
+        for _ in 1..n {
+            pmf *= 1.0 - p;
+        }
+        let mut entropy = 0.0;
+        for k in 0..=n {
+            entropy -= pmf * pmf.log2();
+            pmf *= (n - k) as f64 / (k + 1) as f64 * p / (1.0 - p);
+        }
+
+        #[cfg(not(miri))]
+        assert!((bitrate - entropy).abs() < 0.02 * entropy);
+
+        #[cfg(miri)]
+        assert!((bitrate - entropy).abs() < 0.2 * entropy);
+    }
+}
@@ -0,0 +1,302 @@
+use core::borrow::Borrow;
+
+use alloc::vec::Vec;
+
+use num_traits::AsPrimitive;
+
+use crate::BitArray;
+
+use super::{
+    remapped::RemappedCdf, DecoderModel, EncoderModel, EntropyModel, IterableEntropyModel,
+};
+
+/// A decorator that caps the information content of every symbol under an entropy model.
+///
+/// Wraps an entropy model `M` and raises the probability of each symbol to at least
+/// `2^(PRECISION - max_bits)` (in fixed point arithmetic), so that no symbol costs more
+/// than `max_bits` bits to encode. The probability mass required to lift rare symbols up
+/// to this floor is taken from symbols whose probability lies above the floor,
+/// proportionally to their excess over the floor. Thus, probabilities still sum to
+/// exactly `2^PRECISION`, and symbols that already satisfy the cap only lose a little bit
+/// of probability mass.
+///
+/// This is useful for making the worst-case bitrate of a data stream predictable, e.g.,
+/// when an approximate model occasionally encounters outliers in its tails.
+///
+/// The constructor has to iterate over the entire support of the wrapped model, which is
+/// why `M` has to implement [`IterableEntropyModel`]. Encoding and decoding then delegate
+/// to `M` and map the result onto the capped distribution with a binary search.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::{Capped, DefaultLeakyQuantizer, EncoderModel},
+///     stack::DefaultAnsCoder,
+///     Decode,
+/// };
+///
+/// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+/// let model = quantizer.quantize(probability::distribution::Gaussian::new(0.0, 1.0));
+///
+/// // Under the narrow Gaussian, the outlier `100` has the smallest representable
+/// // probability `2^(-24)`.
+/// let (_, probability) = model.left_cumulative_and_probability(100).unwrap();
+/// assert_eq!(probability.get(), 1);
+///
+/// // Capping the information content at 12 bits guarantees a probability of at least
+/// // `2^(24 - 12)` for every symbol.
+/// let capped = Capped::new(model, 12).unwrap();
+/// let (_, probability) = capped.left_cumulative_and_probability(100).unwrap();
+/// assert!(probability.get() >= 1 << 12);
+///
+/// let symbols = [3, -100, 0, 100, 1];
+/// let mut coder = DefaultAnsCoder::new();
+/// coder.encode_iid_symbols_reverse(&symbols, &capped).unwrap();
+/// let decoded = coder
+///     .decode_iid_symbols(5, &capped)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Capped<M, const PRECISION: usize>
+where
+    M: EntropyModel<PRECISION>,
+{
+    inner: M,
+    cdf: RemappedCdf<M::Probability, PRECISION>,
+}
+
+impl<M, const PRECISION: usize> Capped<M, PRECISION>
+where
+    M: for<'m> IterableEntropyModel<'m, PRECISION>,
+    M::Probability: AsPrimitive<u128>,
+    u128: AsPrimitive<M::Probability>,
+{
+    /// Wraps `model` such that no symbol has an information content above `max_bits`.
+    ///
+    /// Returns `Err(())` if the cap is infeasible, i.e., if the support of `model` has more
+    /// than `2^max_bits` symbols (in which case it is impossible to assign a probability of
+    /// at least `2^(-max_bits)` to each symbol). A cap of `max_bits >= PRECISION` is always
+    /// feasible and leaves `model` unchanged.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(model: M, max_bits: usize) -> Result<Self, ()> {
+        let total = 1u128 << PRECISION;
+        let floor = 1u128 << PRECISION.saturating_sub(max_bits);
+
+        let cdf = RemappedCdf::new(&model, |probabilities| {
+            let num_symbols = probabilities.len() as u128;
+            if num_symbols.checked_mul(floor)? > total {
+                return None;
+            }
+
+            // Every symbol first gets `floor`, and the remaining mass `total - num_symbols *
+            // floor` is distributed proportionally to each symbol's excess over `floor`.
+            // This is implemented as a rescaling of the cumulative excess, which is exact in
+            // fixed point arithmetic and never lets a symbol drop below `floor`.
+            let excess = probabilities
+                .iter()
+                .map(|&probability| probability.saturating_sub(floor))
+                .sum::<u128>();
+            let remaining = total - num_symbols * floor;
+
+            let mut cumulative_excess = 0u128;
+            let capped_cdf = probabilities
+                .iter()
+                .enumerate()
+                .map(|(index, &probability)| {
+                    let scaled_excess = (cumulative_excess * remaining)
+                        .checked_div(excess)
+                        .unwrap_or(0);
+                    cumulative_excess += probability.saturating_sub(floor);
+                    index as u128 * floor + scaled_excess
+                })
+                .collect::<Vec<_>>();
+            Some(capped_cdf)
+        })
+        .ok_or(())?;
+
+        Ok(Self { inner: model, cdf })
+    }
+}
+
+impl<M, const PRECISION: usize> Capped<M, PRECISION>
+where
+    M: EntropyModel<PRECISION>,
+{
+    /// Returns a reference to the wrapped (uncapped) model.
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Consumes the decorator and returns the wrapped (uncapped) model.
+    pub fn into_inner(self) -> M {
+        self.inner
+    }
+}
+
+impl<M, const PRECISION: usize> EntropyModel<PRECISION> for Capped<M, PRECISION>
+where
+    M: EntropyModel<PRECISION>,
+{
+    type Symbol = M::Symbol;
+    type Probability = M::Probability;
+}
+
+impl<M, const PRECISION: usize> EncoderModel<PRECISION> for Capped<M, PRECISION>
+where
+    M: EncoderModel<PRECISION>,
+{
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Self::Symbol>,
+    ) -> Option<(Self::Probability, <Self::Probability as BitArray>::NonZero)> {
+        let (left_cumulative, _) = self.inner.left_cumulative_and_probability(symbol)?;
+        self.cdf.remap_interval(left_cumulative)
+    }
+}
+
+impl<M, const PRECISION: usize> DecoderModel<PRECISION> for Capped<M, PRECISION>
+where
+    M: DecoderModel<PRECISION>,
+{
+    fn quantile_function(
+        &self,
+        quantile: Self::Probability,
+    ) -> (
+        Self::Symbol,
+        Self::Probability,
+        <Self::Probability as BitArray>::NonZero,
+    ) {
+        let (original_left_cumulative, left_cumulative, probability) =
+            self.cdf.quantile_function(quantile);
+        let (symbol, _, _) = self.inner.quantile_function(original_left_cumulative);
+        (symbol, left_cumulative, probability)
+    }
+}
+
+impl<'m, M, const PRECISION: usize> IterableEntropyModel<'m, PRECISION> for Capped<M, PRECISION>
+where
+    M: IterableEntropyModel<'m, PRECISION> + 'm,
+{
+    fn symbol_table(
+        &'m self,
+    ) -> impl Iterator<
+        Item = (
+            Self::Symbol,
+            Self::Probability,
+            <Self::Probability as BitArray>::NonZero,
+        ),
+    > {
+        self.inner
+            .symbol_table()
+            .enumerate()
+            .map(move |(index, (symbol, _, _))| {
+                let (left_cumulative, probability) = self.cdf.interval(index);
+                (symbol, left_cumulative, probability)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use probability::distribution::Gaussian;
+
+    use super::{
+        super::{
+            tests::test_entropy_model, DefaultLeakyQuantizer, LeakyQuantizer,
+            SmallContiguousCategoricalEntropyModel, UniformModel,
+        },
+        *,
+    };
+    use crate::stream::{stack::DefaultAnsCoder, Decode};
+
+    #[test]
+    fn capped() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        for std_dev in [0.1, 1.0, 10.0, 100.0] {
+            let model = quantizer.quantize(Gaussian::new(2.5, std_dev));
+            for max_bits in [8, 10, 16, 24, 30] {
+                let capped = Capped::new(model, max_bits).unwrap();
+                test_entropy_model(&capped, -100..=100);
+
+                let min_probability = 1u32 << 24usize.saturating_sub(max_bits);
+                for (symbol, _, probability) in capped.symbol_table() {
+                    assert!(probability.get() >= min_probability);
+                    let (_, original_probability) =
+                        model.left_cumulative_and_probability(symbol).unwrap();
+                    if original_probability.get() <= min_probability {
+                        assert_eq!(probability.get(), min_probability);
+                    }
+                }
+
+                if max_bits >= 24 {
+                    assert!(capped
+                        .symbol_table()
+                        .zip(model.symbol_table())
+                        .all(|(capped, original)| capped == original));
+                }
+            }
+        }
+
+        let quantizer = LeakyQuantizer::<_, _, u16, 12>::new(-10..=10);
+        let model = quantizer.quantize(Gaussian::new(0.0, 0.5));
+        let capped = Capped::new(model, 5).unwrap();
+        test_entropy_model(&capped, -10..=10);
+
+        let categorical = SmallContiguousCategoricalEntropyModel::<Vec<u16>>::from_nonzero_fixed_point_probabilities(
+            [1u16, 4000, 95],
+            false,
+        )
+        .unwrap();
+        let capped = Capped::new(categorical.clone(), 2).unwrap();
+        test_entropy_model(&capped, 0..3);
+        assert!(capped
+            .symbol_table()
+            .all(|(_, _, probability)| probability.get() >= 1 << 10));
+    }
+
+    #[test]
+    fn infeasible_cap() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        assert!(Capped::new(model, 7).is_err()); // 201 symbols don't fit into 7 bits.
+        assert!(Capped::new(model, 8).is_ok());
+
+        let uniform = UniformModel::<u32, 24>::new(256);
+        let capped = Capped::new(uniform, 8).unwrap();
+        test_entropy_model(&capped, 0..256);
+        assert!(Capped::new(uniform, 7).is_err());
+        assert!(Capped::new(UniformModel::<u32, 24>::new(2), 0).is_err());
+    }
+
+    #[test]
+    fn realized_bits() {
+        const MAX_BITS: usize = 10;
+        let quantizer = DefaultLeakyQuantizer::new(-500..=500);
+        let model = quantizer.quantize(Gaussian::new(250.0, 2.0));
+        let capped = Capped::new(model, MAX_BITS).unwrap();
+
+        // Only encode far outliers, which would cost 24 bits each without the cap.
+        let symbols = (0..1000).map(|i| if i % 2 == 0 { -500 } else { 0 });
+        let symbols = symbols.collect::<Vec<_>>();
+        for &symbol in &symbols {
+            let (_, probability) = model.left_cumulative_and_probability(symbol).unwrap();
+            assert_eq!(probability.get(), 1);
+            let (_, probability) = capped.left_cumulative_and_probability(symbol).unwrap();
+            assert!(-(probability.get() as f64 / (1u32 << 24) as f64).log2() <= MAX_BITS as f64);
+        }
+
+        let mut coder = DefaultAnsCoder::new();
+        coder.encode_iid_symbols_reverse(&symbols, &capped).unwrap();
+        assert!(coder.num_valid_bits() <= symbols.len() * MAX_BITS + 64);
+
+        let decoded = coder
+            .decode_iid_symbols(symbols.len(), &capped)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(coder.is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod adaptive;
 pub mod contiguous;
 pub mod lazy_contiguous;
 pub mod lookup_contiguous;
@@ -9,7 +10,7 @@ use core::borrow::Borrow;
 use alloc::vec::Vec;
 
 use libm::log1p;
-use num_traits::{float::FloatCore, AsPrimitive};
+use num_traits::{float::FloatCore, AsPrimitive, PrimInt};
 
 use crate::{generic_static_asserts, wrapping_pow2, BitArray};
 
@@ -53,6 +54,58 @@ where
     }))
 }
 
+/// Quantizes integer counts directly, without a detour over floating point numbers.
+///
+/// Returns an iterator over the left-sided cumulatives (without the final `2^PRECISION`).
+/// The left-sided cumulative of symbol `i` is `i + floor(c_i * free_weight / total)`,
+/// where `c_i` is the sum of all counts before symbol `i`, `total` is the sum of all
+/// counts, and `free_weight = 2^PRECISION - counts.len()`. Since each count is nonzero,
+/// this assigns a fixed-point probability of at least one to each symbol.
+fn integer_counts_quantized_cdf<Probability, C, const PRECISION: usize>(
+    counts: &[C],
+) -> Result<impl ExactSizeIterator<Item = Probability> + '_, ()>
+where
+    Probability: BitArray,
+    C: PrimInt,
+    u128: AsPrimitive<Probability>,
+{
+    generic_static_asserts!(
+        (Probability: BitArray; const PRECISION: usize);
+        PROBABILITY_MUST_SUPPORT_PRECISION: PRECISION <= Probability::BITS;
+        PRECISION_MUST_BE_NONZERO: PRECISION > 0;
+    );
+
+    let len = counts.len() as u128;
+    if len < 2 || (PRECISION < 128 && len > 1 << PRECISION) {
+        return Err(());
+    }
+
+    let free_weight = 1u128
+        .checked_shl(PRECISION as u32)
+        .unwrap_or(0)
+        .wrapping_sub(len);
+    let total = counts
+        .iter()
+        .try_fold(0u128, |accum, count| {
+            let count = count.to_u128()?;
+            if count == 0 {
+                None
+            } else {
+                accum.checked_add(count)
+            }
+        })
+        .ok_or(())?;
+    // All intermediate products below are bounded by `total * free_weight`.
+    total.checked_mul(free_weight).ok_or(())?;
+
+    let mut cumulative = 0u128;
+    Ok(counts.iter().enumerate().map(move |(index, count)| {
+        let left_cumulative = cumulative * free_weight / total + index as u128;
+        cumulative += count.to_u128().expect("checked above");
+        left_cumulative.as_()
+    }))
+}
+
 fn perfectly_quantized_probabilities<Probability, F, const PRECISION: usize>(
     probabilities: &[F],
 ) -> Result<Vec<Slot<Probability>>, ()>
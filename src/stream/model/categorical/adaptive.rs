@@ -0,0 +1,305 @@
+use core::{borrow::Borrow, marker::PhantomData};
+
+use alloc::vec::Vec;
+use num_traits::{AsPrimitive, PrimInt};
+
+use crate::{
+    stream::model::{DecoderModel, EncoderModel, EntropyModel, IterableEntropyModel},
+    wrapping_pow2, BitArray,
+};
+
+use super::{contiguous::ContiguousCategoricalEntropyModel, integer_counts_quantized_cdf};
+
+/// Type alias for a typical [`AdaptiveCategorical`] model.
+///
+/// See:
+/// - [`AdaptiveCategorical`]
+/// - [discussion of presets](crate::stream#presets)
+pub type DefaultAdaptiveCategorical = AdaptiveCategorical<u32, 24>;
+
+/// Type alias for an [`AdaptiveCategorical`] model that is easier to use within a sequence
+/// of compressed symbols that also involves some lookup models.
+///
+/// See:
+/// - [`AdaptiveCategorical`]
+/// - [discussion of presets](crate::stream#presets)
+pub type SmallAdaptiveCategorical = AdaptiveCategorical<u16, 12>;
+
+/// A categorical entropy model that adapts to the data as symbols get encoded or decoded.
+///
+/// An `AdaptiveCategorical` model is defined over the symbols `0..support_size` and keeps
+/// an integer count for each symbol. The fixed-point probability of each symbol is
+/// (approximately) proportional to its count, calculated with exact integer arithmetic as
+/// in [`ContiguousCategoricalEntropyModel::from_nonzero_integer_counts`]. Calling
+/// [`update`](Self::update) with a symbol increments the count of this symbol and
+/// re-normalizes the model, so the model learns the symbol statistics of the data
+/// stream.
+///
+/// Since the model is deterministic, an encoder and a decoder that start from the same
+/// initial counts and call `update` with the same symbols in the same order always use
+/// identical models. Note that, for this to work with an [`AnsCoder`], you have to encode
+/// the symbols in reverse order *without* modifying the model in between (since an
+/// `AnsCoder` is a stack). Adaptive models therefore usually work best with a
+/// [`RangeEncoder`]/[`RangeDecoder`] (which operate as a queue), as in the example below.
+///
+/// # Runtime Complexity
+///
+/// Each call to [`update`](Self::update) takes time linear in the size of the support
+/// since it re-normalizes the entire model. Re-normalization is deliberately eager: the
+/// model is accessed through `&self` when encoding and decoding (see [`EncoderModel`] and
+/// [`DecoderModel`]), so deferring it to the next access would require interior
+/// mutability, which would make the model `!Sync` and add a check to every encoded or
+/// decoded symbol. Encoding and decoding have the same cost as with a
+/// [`ContiguousCategoricalEntropyModel`]. Counts saturate at `u32::MAX`; once any
+/// count would overflow, all counts get halved (rounding up, so that they remain nonzero).
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::DefaultAdaptiveCategorical, queue::DefaultRangeEncoder, Decode, Encode,
+/// };
+///
+/// let symbols = [2, 2, 0, 2, 1, 2, 2, 2, 0, 2];
+///
+/// // Both encoder and decoder start from uniform counts and update their models in sync.
+/// let mut model = DefaultAdaptiveCategorical::new_uniform(3).unwrap();
+/// let mut encoder = DefaultRangeEncoder::new();
+/// for &symbol in &symbols {
+///     encoder.encode_symbol(symbol, &model).unwrap();
+///     model.update(symbol);
+/// }
+///
+/// let mut model = DefaultAdaptiveCategorical::new_uniform(3).unwrap();
+/// let mut decoder = encoder.into_decoder().unwrap();
+/// let mut decoded = Vec::new();
+/// for _ in 0..symbols.len() {
+///     let symbol = decoder.decode_symbol(&model).unwrap();
+///     model.update(symbol);
+///     decoded.push(symbol);
+/// }
+/// assert_eq!(decoded, symbols);
+/// ```
+///
+/// [`AnsCoder`]: crate::stream::stack::AnsCoder
+/// [`RangeEncoder`]: crate::stream::queue::RangeEncoder
+/// [`RangeDecoder`]: crate::stream::queue::RangeDecoder
+#[derive(Debug, Clone)]
+pub struct AdaptiveCategorical<Probability: BitArray, const PRECISION: usize> {
+    counts: Vec<u32>,
+    model: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>,
+}
+
+impl<Probability: BitArray, const PRECISION: usize> AdaptiveCategorical<Probability, PRECISION>
+where
+    u128: AsPrimitive<Probability>,
+{
+    /// Constructs a model over the symbols `0..support_size` where all counts are one.
+    ///
+    /// Returns an error if `support_size < 2` or if `support_size > 2^PRECISION` (in
+    /// which case we could not assign a nonzero fixed-point probability to every symbol).
+    #[allow(clippy::result_unit_err)]
+    pub fn new_uniform(support_size: usize) -> Result<Self, ()> {
+        Self::from_counts(alloc::vec![1; support_size])
+    }
+
+    /// Constructs a model over the symbols `0..counts.len()` with the provided initial
+    /// counts.
+    ///
+    /// Returns an error if any of the counts is zero or negative, or if it doesn't fit into
+    /// a `u32`. Also returns an error if `counts.len() < 2` or if `counts.len() >
+    /// 2^PRECISION`.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_nonzero_integer_counts<C: PrimInt>(counts: &[C]) -> Result<Self, ()> {
+        let counts = counts
+            .iter()
+            .map(|count| count.to_u32().ok_or(()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_counts(counts)
+    }
+
+    fn from_counts(counts: Vec<u32>) -> Result<Self, ()> {
+        // Make sure that re-normalization can't overflow even if all counts saturate.
+        let len = counts.len() as u128;
+        let free_weight = 1u128
+            .checked_shl(PRECISION as u32)
+            .unwrap_or(0)
+            .wrapping_sub(len);
+        len.checked_mul(u32::MAX as u128)
+            .and_then(|max_total| max_total.checked_mul(free_weight))
+            .ok_or(())?;
+
+        let mut cdf = Vec::with_capacity(counts.len() + 1);
+        cdf.extend(integer_counts_quantized_cdf::<_, _, PRECISION>(&counts)?);
+        cdf.push(wrapping_pow2(PRECISION));
+
+        Ok(Self {
+            counts,
+            model: ContiguousCategoricalEntropyModel {
+                cdf,
+                phantom: PhantomData,
+            },
+        })
+    }
+
+    /// Increments the count of `symbol` by one and re-normalizes the model.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `symbol >= self.support_size()`.
+    pub fn update(&mut self, symbol: usize) {
+        let count = &mut self.counts[symbol];
+        if *count == u32::MAX {
+            for count in &mut self.counts {
+                *count = (*count >> 1) + (*count & 1);
+            }
+        }
+        self.counts[symbol] += 1;
+
+        let cdf = &mut self.model.cdf;
+        cdf.clear();
+        cdf.extend(
+            integer_counts_quantized_cdf::<_, _, PRECISION>(&self.counts)
+                .expect("validated in constructor"),
+        );
+        cdf.push(wrapping_pow2(PRECISION));
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> AdaptiveCategorical<Probability, PRECISION> {
+    /// Returns the current counts of all symbols.
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+
+    /// Returns the number of symbols supported by the model.
+    pub fn support_size(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns a view of the current state of the model as a non-adaptive
+    /// [`ContiguousCategoricalEntropyModel`].
+    pub fn as_contiguous_categorical(
+        &self,
+    ) -> &ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        &self.model
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for AdaptiveCategorical<Probability, PRECISION>
+{
+    type Symbol = usize;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for AdaptiveCategorical<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<usize>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        self.model.left_cumulative_and_probability(symbol)
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for AdaptiveCategorical<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn quantile_function(
+        &self,
+        quantile: Self::Probability,
+    ) -> (usize, Probability, Probability::NonZero) {
+        self.model.quantile_function(quantile)
+    }
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for AdaptiveCategorical<Probability, PRECISION>
+{
+    fn symbol_table(
+        &'m self,
+    ) -> impl Iterator<
+        Item = (
+            Self::Symbol,
+            Self::Probability,
+            <Self::Probability as BitArray>::NonZero,
+        ),
+    > {
+        self.model.symbol_table()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    use super::super::super::tests::test_entropy_model;
+    use super::*;
+    use crate::stream::{queue::DefaultRangeEncoder, Decode, Encode};
+
+    #[test]
+    fn adaptive_round_trip() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2264);
+        let true_probabilities = [0.02, 0.5, 0.03, 0.25, 0.15, 0.05];
+        let symbols = (0..10_000)
+            .map(|_| {
+                let mut x = rng.gen::<f64>();
+                true_probabilities
+                    .iter()
+                    .position(|&p| {
+                        x -= p;
+                        x < 0.0
+                    })
+                    .unwrap_or(true_probabilities.len() - 1)
+            })
+            .collect::<Vec<_>>();
+
+        let mut model = DefaultAdaptiveCategorical::new_uniform(6).unwrap();
+        let mut encoder = DefaultRangeEncoder::new();
+        for &symbol in &symbols {
+            encoder.encode_symbol(symbol, &model).unwrap();
+            model.update(symbol);
+        }
+        test_entropy_model(&model, 0..6);
+        assert_eq!(model.counts().iter().sum::<u32>(), 6 + symbols.len() as u32);
+
+        // The adaptive model should get close to the entropy of the true distribution.
+        let entropy = true_probabilities
+            .iter()
+            .map(|&p: &f64| -p * p.log2())
+            .sum::<f64>();
+        let bitrate = encoder.num_bits() as f64 / symbols.len() as f64;
+        assert!(bitrate < 1.02 * entropy);
+
+        let mut model = DefaultAdaptiveCategorical::new_uniform(6).unwrap();
+        let mut decoder = encoder.into_decoder().unwrap();
+        for &symbol in &symbols {
+            assert_eq!(decoder.decode_symbol(&model).unwrap(), symbol);
+            model.update(symbol);
+        }
+        assert!(decoder.maybe_exhausted());
+    }
+
+    #[test]
+    fn adaptive_saturation() {
+        let mut model =
+            SmallAdaptiveCategorical::from_nonzero_integer_counts(&[u32::MAX, 1, 4]).unwrap();
+        test_entropy_model(&model, 0..3);
+        model.update(0);
+        assert_eq!(model.counts(), &[(1 << 31) + 1, 1, 2]);
+        test_entropy_model(&model, 0..3);
+        model.update(1);
+        assert_eq!(model.counts(), &[(1 << 31) + 1, 2, 2]);
+        test_entropy_model(&model, 0..3);
+
+        assert!(SmallAdaptiveCategorical::new_uniform(1).is_err());
+        assert!(SmallAdaptiveCategorical::new_uniform(4097).is_err());
+        assert!(SmallAdaptiveCategorical::from_nonzero_integer_counts(&[1u32, 0]).is_err());
+        assert!(SmallAdaptiveCategorical::from_nonzero_integer_counts(&[1u64, 1 << 32]).is_err());
+    }
+}
@@ -1,16 +1,17 @@
 use core::{borrow::Borrow, marker::PhantomData};
 
 use alloc::{boxed::Box, vec::Vec};
-use num_traits::{float::FloatCore, AsPrimitive};
+use num_traits::{float::FloatCore, AsPrimitive, PrimInt};
 
 use crate::{
     stream::model::{DecoderModel, EncoderModel, EntropyModel, IterableEntropyModel},
-    wrapping_pow2, BitArray,
+    wrapping_pow2, BitArray, NonZeroBitArray,
 };
 
 use super::{
-    accumulate_nonzero_probabilities, fast_quantized_cdf, iter_extended_cdf,
-    lookup_contiguous::ContiguousLookupDecoderModel, perfectly_quantized_probabilities,
+    accumulate_nonzero_probabilities, fast_quantized_cdf, integer_counts_quantized_cdf,
+    iter_extended_cdf, lookup_contiguous::ContiguousLookupDecoderModel,
+    perfectly_quantized_probabilities,
 };
 
 /// Type alias for a typical [`ContiguousCategoricalEntropyModel`].
@@ -279,6 +280,42 @@ impl<Probability: BitArray, const PRECISION: usize>
     /// the provided (floating point) to the resulting (fixed point) probabilities subject
     /// to constraints (i)-(iii).
     ///
+    /// # Algorithm
+    ///
+    /// Minimizing the cross entropy `-sum_i p[i] * log(w[i])` over integer weights `w[i] >=
+    /// 1` with `sum_i w[i] == 2^PRECISION` is equivalent to minimizing the KL divergence
+    /// from `p` to the fixed-point distribution. The objective is a sum of convex functions
+    /// of the individual weights, which motivates the following greedy exchange algorithm:
+    ///
+    /// 1. Assign each symbol a weight of one (to guarantee leakiness) plus the floor of its
+    ///    share of the remaining `2^PRECISION - probabilities.len()` weight units.
+    /// 2. Hand out the (at most `probabilities.len()`) weight units that are left over due
+    ///    to rounding down, one unit each to the symbols whose cross entropy would decrease
+    ///    the most from an additional unit.
+    /// 3. Repeatedly move a single weight unit from the symbol where removing it costs the
+    ///    least to the symbol where adding it gains the most, as long as the gain exceeds
+    ///    the cost. To guarantee termination despite floating point rounding errors, a
+    ///    weight that was decreased in this step is never increased again and vice versa,
+    ///    and the step also stops if the best symbol to add to is the same as the best
+    ///    symbol to remove from (which can only happen due to rounding errors).
+    ///
+    /// Without the restrictions in step 3, the result would be a global minimum of the
+    /// cross entropy (up to rounding errors in the comparisons) since, due to convexity, a
+    /// weight assignment that no single move improves is globally optimal. With these
+    /// restrictions, the algorithm only guarantees that no single move between two symbols
+    /// that were not involved in a move in step 3 decreases the cross entropy. In practice,
+    /// the restrictions rarely matter, and the result is usually exactly or very nearly
+    /// KL-optimal.
+    ///
+    /// This differs from largest-remainder ("Hamilton") apportionment, which minimizes the
+    /// absolute rounding errors but not the KL divergence (which penalizes errors on small
+    /// probabilities much more heavily). The result is deterministic: the algorithm sorts
+    /// symbols with a stable sort and breaks ties by their position in the resulting order
+    /// (which depends only on the input, but generally isn't the symbol index), so the same
+    /// input always produces the same model. The per-symbol rounding errors of the
+    /// resulting model can be inspected with [`normalization_report`], and the total
+    /// overhead with [`kl_divergence_base2`].
+    ///
     /// # Error Handling
     ///
     /// Returns an error if the provided probability distribution cannot be normalized,
@@ -300,6 +337,8 @@ impl<Probability: BitArray, const PRECISION: usize>
     ///
     /// [`from_floating_point_probabilities_fast`]:
     ///     Self::from_floating_point_probabilities_fast
+    /// [`normalization_report`]: Self::normalization_report
+    /// [`kl_divergence_base2`]: IterableEntropyModel::kl_divergence_base2
     #[allow(clippy::result_unit_err)]
     pub fn from_floating_point_probabilities_perfect<F>(probabilities: &[F]) -> Result<Self, ()>
     where
@@ -368,6 +407,63 @@ impl<Probability: BitArray, const PRECISION: usize>
         Self::from_floating_point_probabilities_perfect(probabilities)
     }
 
+    /// Constructs a distribution whose PMF is proportional to given integer counts.
+    ///
+    /// This is a convenience constructor for the common case where the probabilities are
+    /// given as a histogram of integer counts. Unlike converting the counts to floating
+    /// point probabilities and calling [`from_floating_point_probabilities_fast`], this
+    /// method calculates the fixed-point probabilities with exact integer arithmetic, so
+    /// the result does not suffer from floating point rounding errors.
+    ///
+    /// The returned distribution will be defined for symbols of type `usize` from the range
+    /// `0..counts.len()`. Each symbol first gets assigned the smallest representable
+    /// probability, and the remaining probability mass is then distributed proportionally
+    /// to the counts (rounding down at each cumulative). Thus, every symbol is guaranteed to
+    /// have a nonzero probability, and symbols with equal counts get assigned probabilities
+    /// that differ by at most one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::model::{
+    ///     DefaultContiguousCategoricalEntropyModel, IterableEntropyModel
+    /// };
+    ///
+    /// let counts = [3u32, 1, 4, 1, 5, 9, 2, 6];
+    /// let model =
+    ///     DefaultContiguousCategoricalEntropyModel::from_nonzero_integer_counts(&counts).unwrap();
+    /// assert_eq!(model.support_size(), counts.len());
+    /// for ((_, _, probability), count) in model.symbol_table().zip(counts) {
+    ///     // Each fixed-point probability is (almost) exactly proportional to its count.
+    ///     let expected = count as f64 / 31.0 * (1 << 24) as f64;
+    ///     assert!((probability.get() as f64 - expected).abs() < 2.0);
+    /// }
+    /// ```
+    ///
+    /// # Error Handling
+    ///
+    /// Returns an error if any of the counts is zero or negative. If your histogram may
+    /// contain zeros, add one to each count (or filter out the zeros if the corresponding
+    /// symbols can never occur).
+    ///
+    /// Also returns an error if `counts` has fewer than two entries (degenerate probability
+    /// distributions are not supported by `constriction`), if it has more than
+    /// `2^PRECISION` entries (in which case we could not assign a nonzero fixed-point
+    /// probability to every symbol), or if the product of the sum of all counts with
+    /// `2^PRECISION` would overflow a `u128`.
+    ///
+    /// [`from_floating_point_probabilities_fast`]:
+    ///     Self::from_floating_point_probabilities_fast
+    #[allow(clippy::result_unit_err)]
+    pub fn from_nonzero_integer_counts<C>(counts: &[C]) -> Result<Self, ()>
+    where
+        C: PrimInt,
+        u128: AsPrimitive<Probability>,
+    {
+        let cdf = integer_counts_quantized_cdf::<_, _, PRECISION>(counts)?;
+        Self::from_fixed_point_cdf(cdf)
+    }
+
     /// Constructs a distribution with a PMF given in fixed point arithmetic.
     ///
     /// This is a low level method that allows, e.g,. reconstructing a probability
@@ -514,6 +610,30 @@ impl<Probability: BitArray, const PRECISION: usize>
     }
 }
 
+/// Generates a categorical model for property-based testing and fuzzing.
+///
+/// The generated model has between 2 and 256 symbols (but at most `2^PRECISION` symbols).
+/// Its probabilities are obtained from arbitrary nonzero integer counts via
+/// [`from_nonzero_integer_counts`], so they are all nonzero and sum up to `2^PRECISION`.
+///
+/// [`from_nonzero_integer_counts`]: Self::from_nonzero_integer_counts
+#[cfg(feature = "arbitrary")]
+impl<'a, Probability, const PRECISION: usize> arbitrary::Arbitrary<'a>
+    for ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>
+where
+    Probability: BitArray,
+    u128: AsPrimitive<Probability>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let max_len = if PRECISION < 8 { 1 << PRECISION } else { 256 };
+        let len = u.int_in_range(2..=max_len)?;
+        let counts = (0..len)
+            .map(|_| u.int_in_range(1..=u16::MAX))
+            .collect::<arbitrary::Result<Vec<_>>>()?;
+        Self::from_nonzero_integer_counts(&counts).map_err(|()| arbitrary::Error::IncorrectFormat)
+    }
+}
+
 impl<Probability, Cdf, const PRECISION: usize>
     ContiguousCategoricalEntropyModel<Probability, Cdf, PRECISION>
 where
@@ -573,6 +693,29 @@ where
     ///   memory overhead, so if you're going to use the resulting model only for a single
     ///   or a handful of symbols then you'll end up paying more than you gain.
     ///
+    /// The lookup table has `2^PRECISION` entries of type `Probability`. The trait bound
+    /// `Probability: Into<usize>` restricts `Probability` to `u8` or `u16`, so the table
+    /// can never take up more than 128 KiB (plus the size of the CDF). For the "Small"
+    /// preset, it takes up 8 KiB.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::model::{DecoderModel, SmallContiguousCategoricalEntropyModel};
+    ///
+    /// let model = SmallContiguousCategoricalEntropyModel::<Vec<u16>>
+    ///     ::from_floating_point_probabilities_fast(&[0.2, 0.5, 0.3], None).unwrap();
+    /// let lookup_decoder_model = model.to_lookup_decoder_model();
+    ///
+    /// // The two models are logically equivalent.
+    /// for quantile in 0..1 << 12 {
+    ///     assert_eq!(
+    ///         lookup_decoder_model.quantile_function(quantile),
+    ///         model.quantile_function(quantile)
+    ///     );
+    /// }
+    /// ```
+    ///
     /// [preset]: crate::stream#presets
     /// [`NonContiguousLookupDecoderModel`]: crate::stream::model::NonContiguousLookupDecoderModel
     #[inline(always)]
@@ -585,6 +728,56 @@ where
     {
         self.into()
     }
+
+    /// Reports how far the realized probabilities deviate from the requested ones.
+    ///
+    /// Returns a vector of the signed errors `realized_p - requested_p`, one per symbol,
+    /// where `realized_p` is the probability that the model assigns to the symbol in its
+    /// `PRECISION`-bit fixed-point representation, and `requested_p` is the corresponding
+    /// entry of `requested` after normalizing `requested` to sum to one (just like the
+    /// constructors [`from_floating_point_probabilities_fast`] and
+    /// [`from_floating_point_probabilities_perfect`] do if no explicit normalization is
+    /// provided). This is a diagnostic tool for understanding the bitrate overhead caused
+    /// by quantization, which is most pronounced for low `PRECISION` and for symbols with
+    /// tiny requested probabilities (which get lifted to a nonzero probability).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requested.len()` differs from [`support_size`](Self::support_size).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::model::SmallContiguousCategoricalEntropyModel;
+    ///
+    /// let requested = [0.3, 0.0001, 0.6999];
+    /// let model = SmallContiguousCategoricalEntropyModel::<Vec<u16>>
+    ///     ::from_floating_point_probabilities_fast(&requested, None).unwrap();
+    /// let errors = model.normalization_report(&requested);
+    ///
+    /// // The tiny probability of the middle symbol gets rounded up to `1 / 2^12`.
+    /// assert!(errors[1] > 0.0);
+    /// assert!(errors.iter().sum::<f64>().abs() < 1e-12);
+    /// ```
+    ///
+    /// [`from_floating_point_probabilities_fast`]: Self::from_floating_point_probabilities_fast
+    /// [`from_floating_point_probabilities_perfect`]:
+    ///     Self::from_floating_point_probabilities_perfect
+    pub fn normalization_report(&self, requested: &[f64]) -> Vec<f64>
+    where
+        Probability: AsPrimitive<f64>,
+    {
+        assert_eq!(requested.len(), self.support_size());
+
+        let normalization = requested.iter().sum::<f64>();
+        let whole = (1u128 << PRECISION) as f64;
+        self.symbol_table()
+            .zip(requested)
+            .map(|((_, _, probability), &requested)| {
+                probability.get().as_() / whole - requested / normalization
+            })
+            .collect()
+    }
 }
 
 impl<Probability, Cdf, const PRECISION: usize> EntropyModel<PRECISION>
@@ -619,6 +812,11 @@ where
                 .map(|(symbol, &cumulative)| (cumulative, symbol)),
         )
     }
+
+    #[inline(always)]
+    fn support_size(&'m self) -> usize {
+        ContiguousCategoricalEntropyModel::support_size(self)
+    }
 }
 
 impl<Probability, Cdf, const PRECISION: usize> DecoderModel<PRECISION>
@@ -902,4 +1100,188 @@ mod tests {
 
         assert!(kl_perfect < kl_fast);
     }
+
+    #[test]
+    fn from_nonzero_integer_counts() {
+        let hist = [
+            1u32, 186545, 237403, 295700, 361445, 433686, 509456, 586943, 663946, 737772, 1657269,
+            896675, 922197, 930672, 916665, 723031, 650522, 572300, 494702, 418703, 347600, 1,
+            283500, 226158, 178194, 136301, 103158, 76823, 55540, 39258, 27988, 54269,
+        ];
+        let model =
+            ContiguousCategoricalEntropyModel::<u32, _, 32>::from_nonzero_integer_counts(&hist)
+                .unwrap();
+        test_entropy_model(&model, 0..hist.len());
+        verify_iterable_entropy_model(&model, &hist, 1e-8);
+
+        // Compare to the floating point constructor, which uses the same quantization scheme.
+        let probabilities = hist.iter().map(|&x| x as f64).collect::<Vec<_>>();
+        let fast =
+            ContiguousCategoricalEntropyModel::<u32, _, 32>::from_floating_point_probabilities_fast(
+                &probabilities,
+                None,
+            )
+            .unwrap();
+        for ((symbol, left1, prob1), (_, left2, prob2)) in
+            model.symbol_table().zip(fast.symbol_table())
+        {
+            assert!(left1.abs_diff(left2) <= 1, "symbol {}", symbol);
+            assert!(prob1.get().abs_diff(prob2.get()) <= 2, "symbol {}", symbol);
+        }
+
+        // Huge counts (that can't be represented exactly as `f64`) must not make any symbol
+        // impossible.
+        let hist = [u64::MAX, 1, u64::MAX - 1, 1, 2, u64::MAX / 3];
+        let model =
+            ContiguousCategoricalEntropyModel::<u16, _, 12>::from_nonzero_integer_counts(&hist)
+                .unwrap();
+        test_entropy_model(&model, 0..hist.len());
+        let probabilities = model
+            .symbol_table()
+            .map(|(_, _, probability)| probability.get())
+            .collect::<Vec<_>>();
+        assert_eq!(probabilities[1], 1);
+        assert_eq!(probabilities[3], 1);
+        assert_eq!(probabilities[4], 1);
+        assert_eq!(probabilities.iter().sum::<u16>(), 1 << 12);
+
+        // Also works with `PRECISION == Probability::BITS` and with the maximum number of
+        // symbols.
+        let model =
+            ContiguousCategoricalEntropyModel::<u32, _, 32>::from_nonzero_integer_counts(&[1u8, 3])
+                .unwrap();
+        assert_eq!(model.left_cumulative_and_probability(1).unwrap().0, 1 << 30);
+        let model =
+            ContiguousCategoricalEntropyModel::<u8, _, 4>::from_nonzero_integer_counts(&[7u8; 16])
+                .unwrap();
+        test_entropy_model(&model, 0..16);
+
+        type Model = DefaultContiguousCategoricalEntropyModel;
+        assert!(Model::from_nonzero_integer_counts(&[3u32, 0, 5]).is_err());
+        assert!(Model::from_nonzero_integer_counts(&[3i32, -1, 5]).is_err());
+        assert!(Model::from_nonzero_integer_counts(&[3u32]).is_err());
+        assert!(Model::from_nonzero_integer_counts::<u32>(&[]).is_err());
+        assert!(
+            ContiguousCategoricalEntropyModel::<u8, _, 4>::from_nonzero_integer_counts(&[1u8; 17])
+                .is_err()
+        );
+        assert!(Model::from_nonzero_integer_counts(&[u128::MAX / 2, u128::MAX / 2]).is_err());
+    }
+
+    #[test]
+    fn normalization_report() {
+        let requested = [0.4, 1e-9, 0.35, 0.25 - 1e-9];
+        let model =
+            ContiguousCategoricalEntropyModel::<u16, _, 12>::from_floating_point_probabilities_fast(
+                &requested, None,
+            )
+            .unwrap();
+        let errors = model.normalization_report(&requested);
+        assert_eq!(errors.len(), requested.len());
+
+        // The near-zero symbol gets the smallest representable probability.
+        assert!((errors[1] - (1.0 / 4096.0 - 1e-9)).abs() < 1e-15);
+        for ((&error, &requested), (_, _, probability)) in
+            errors.iter().zip(&requested).zip(model.symbol_table())
+        {
+            assert_eq!(error, probability.get() as f64 / 4096.0 - requested);
+            assert!(error.abs() <= 2.0 / 4096.0);
+        }
+        assert!(errors.iter().sum::<f64>().abs() < 1e-12);
+
+        // Requested probabilities don't have to be normalized.
+        let unnormalized = requested.iter().map(|&p| 10.0 * p).collect::<Vec<_>>();
+        for (a, b) in model
+            .normalization_report(&unnormalized)
+            .iter()
+            .zip(&errors)
+        {
+            assert!((a - b).abs() < 1e-15);
+        }
+
+        // `PRECISION == 64` doesn't overflow.
+        let model =
+            ContiguousCategoricalEntropyModel::<u64, _, 64>::from_floating_point_probabilities_fast(
+                &requested, None,
+            )
+            .unwrap();
+        let errors = model.normalization_report(&requested);
+        assert!(errors.iter().all(|error| error.abs() < 1e-15));
+    }
+
+    #[test]
+    fn perfect_minimizes_kl_divergence() {
+        use rand::{RngCore, SeedableRng};
+        use rand_xoshiro::Xoshiro256StarStar;
+
+        #[cfg(not(miri))]
+        let num_symbols = 3000;
+        #[cfg(miri)]
+        let num_symbols = 30;
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2306);
+        let mut probabilities = (0..num_symbols)
+            .map(|_| {
+                // Heavy tailed, so that many probabilities are close to the resolution.
+                let x = rng.next_u32() as f64 / u32::MAX as f64;
+                x * x * x * x
+            })
+            .collect::<Vec<_>>();
+        let normalization = probabilities.iter().sum::<f64>();
+        for p in &mut probabilities {
+            *p /= normalization;
+        }
+
+        type Model = ContiguousCategoricalEntropyModel<u32, Vec<u32>, 16>;
+        let perfect = Model::from_floating_point_probabilities_perfect(&probabilities).unwrap();
+        let fast = Model::from_floating_point_probabilities_fast(&probabilities, None).unwrap();
+
+        // Naive baseline: round to nearest, but at least one, then fix the sum by adjusting
+        // the largest weights one unit at a time.
+        let mut naive_weights = probabilities
+            .iter()
+            .map(|&p| ((p * (1 << 16) as f64).round() as i64).max(1))
+            .collect::<Vec<_>>();
+        let mut excess = naive_weights.iter().sum::<i64>() - (1 << 16);
+        while excess != 0 {
+            *naive_weights.iter_mut().max().unwrap() -= excess.signum();
+            excess -= excess.signum();
+        }
+        let naive = Model::from_nonzero_fixed_point_probabilities(
+            naive_weights.iter().map(|&w| w as u32),
+            false,
+        )
+        .unwrap();
+
+        let kl_perfect = perfect.kl_divergence_base2::<f64>(probabilities.iter().copied());
+        let kl_fast = fast.kl_divergence_base2::<f64>(probabilities.iter().copied());
+        let kl_naive = naive.kl_divergence_base2::<f64>(probabilities.iter().copied());
+        assert!(kl_perfect >= -1e-10);
+        assert!(kl_perfect < kl_naive);
+        assert!(kl_perfect < kl_fast);
+
+        // For this input, no single move of a weight unit from one symbol to another reduces
+        // the cross entropy, which implies global optimality since the objective is convex.
+        // (The algorithm doesn't guarantee this in general, but it holds in typical cases.)
+        let weights = perfect
+            .symbol_table()
+            .map(|(_, _, probability)| probability.get() as f64)
+            .collect::<Vec<_>>();
+        let max_win = probabilities
+            .iter()
+            .zip(&weights)
+            .map(|(&p, &w)| p * (1.0 / w).ln_1p())
+            .fold(f64::NEG_INFINITY, f64::max);
+        let min_loss = probabilities
+            .iter()
+            .zip(&weights)
+            .filter(|(_, &w)| w > 1.0)
+            .map(|(&p, &w)| -p * (-1.0 / w).ln_1p())
+            .fold(f64::INFINITY, f64::min);
+        assert!(max_win <= min_loss * (1.0 + 1e-9));
+
+        // The result is deterministic.
+        let again = Model::from_floating_point_probabilities_perfect(&probabilities).unwrap();
+        assert!(perfect.symbol_table().eq(again.symbol_table()));
+    }
 }
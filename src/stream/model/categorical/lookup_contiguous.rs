@@ -616,6 +616,13 @@ where
     Cdf: AsRef<[Probability]>,
 {
     fn from(model: &'m ContiguousCategoricalEntropyModel<Probability, Cdf, PRECISION>) -> Self {
+        generic_static_asserts!(
+            (Probability: BitArray; const PRECISION: usize);
+            PROBABILITY_MUST_SUPPORT_PRECISION: PRECISION <= Probability::BITS;
+            PRECISION_MUST_BE_NONZERO: PRECISION > 0;
+            USIZE_MUST_STRICTLY_SUPPORT_PRECISION: PRECISION < <usize as BitArray>::BITS;
+        );
+
         let cdf = model.cdf.as_ref().to_vec();
         let mut lookup_table = Vec::with_capacity(1 << PRECISION);
         for (symbol, &cumulative) in model.cdf.as_ref()[1..model.cdf.as_ref().len() - 1]
@@ -666,7 +673,11 @@ where
 mod tests {
     use alloc::vec;
 
-    use crate::stream::{model::EncoderModel, stack::DefaultAnsCoder, Decode};
+    use crate::stream::{
+        model::{EncoderModel, SmallContiguousCategoricalEntropyModel},
+        stack::DefaultAnsCoder,
+        Decode,
+    };
 
     use super::*;
 
@@ -731,4 +742,32 @@ mod tests {
         assert_eq!(decoded, symbols);
         assert!(ans2.is_empty());
     }
+
+    #[test]
+    fn lookup_matches_categorical_for_every_quantile() {
+        use rand::{RngCore, SeedableRng};
+        use rand_xoshiro::Xoshiro256StarStar;
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2284);
+        for num_symbols in [2usize, 3, 17, 100, 1000, 4000] {
+            let probabilities = (0..num_symbols)
+                .map(|_| (rng.next_u32() % 1000) as f64 + 0.01)
+                .collect::<Vec<_>>();
+            let model =
+                SmallContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                    &probabilities,
+                    None,
+                )
+                .unwrap();
+            let lookup_decoder_model = model.to_lookup_decoder_model();
+
+            for quantile in 0..1 << 12 {
+                assert_eq!(
+                    lookup_decoder_model.quantile_function(quantile),
+                    model.quantile_function(quantile)
+                );
+            }
+            assert!(lookup_decoder_model.symbol_table().eq(model.symbol_table()));
+        }
+    }
 }
@@ -526,6 +526,11 @@ where
         iter_extended_cdf(self.cdf.as_ref().iter().cloned())
     }
 
+    #[inline(always)]
+    fn support_size(&'m self) -> usize {
+        NonContiguousCategoricalDecoderModel::support_size(self)
+    }
+
     fn floating_point_symbol_table<F>(&'m self) -> impl Iterator<Item = (Self::Symbol, F, F)>
     where
         F: FloatCore + From<Self::Probability> + 'm,
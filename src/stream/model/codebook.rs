@@ -0,0 +1,218 @@
+use core::borrow::Borrow;
+
+use alloc::{boxed::Box, vec::Vec};
+
+use num_traits::{float::FloatCore, AsPrimitive};
+
+use crate::{BitArray, NonZeroBitArray};
+
+use super::{
+    ContiguousCategoricalEntropyModel, ContiguousLookupDecoderModel, DecoderModel, EncoderModel,
+    EntropyModel, IterableEntropyModel,
+};
+
+/// Type alias for a typical [`Codebook`].
+///
+/// See:
+/// - [`Codebook`]
+/// - [discussion of presets](crate::stream#presets)
+pub type DefaultCodebook = Codebook<u32, 24>;
+
+/// Type alias for a [`Codebook`] optimized for compatibility with lookup decoder models.
+///
+/// See:
+/// - [`Codebook`]
+/// - [discussion of presets](crate::stream#presets)
+pub type SmallCodebook = Codebook<u16, 12>;
+
+/// An entropy model for the indices into a fixed codebook, e.g., for vector quantization.
+///
+/// In vector quantization (VQ), each data point is replaced by the index `k ∈ {0, ...,
+/// K-1}` of its nearest entry in a codebook of size `K`, and the indices are entropy coded
+/// with probabilities that were learned along with the codebook. A `Codebook` is an entropy
+/// model for such indices. It is logically equivalent to a
+/// [`ContiguousCategoricalEntropyModel`] over the symbols `0..K`, but it provides an API
+/// tailored to the VQ use case:
+///
+/// - construct it directly from the learned probabilities with
+///   [`from_learned_probabilities`](Self::from_learned_probabilities);
+/// - inspect it with [`num_indices`](Self::num_indices) and
+///   [`most_probable_index`](Self::most_probable_index); and
+/// - speed up decoding with [`to_lookup_decoder_model`](Self::to_lookup_decoder_model) if
+///   you use a low `PRECISION` (e.g., a [`SmallCodebook`] together with a
+///   [`SmallAnsCoder`](crate::stream::stack::SmallAnsCoder)).
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{model::SmallCodebook, stack::SmallAnsCoder, Decode};
+///
+/// // Learned probabilities for a codebook with 8 entries.
+/// let probabilities = [0.3, 0.2, 0.15, 0.1, 0.1, 0.08, 0.05, 0.02];
+/// let codebook = SmallCodebook::from_learned_probabilities(&probabilities).unwrap();
+/// assert_eq!(codebook.num_indices(), 8);
+/// assert_eq!(codebook.most_probable_index(), 0);
+///
+/// let indices = [0, 3, 0, 7, 1, 2, 0, 5];
+/// let mut ans = SmallAnsCoder::new();
+/// ans.encode_iid_symbols_reverse(&indices, &codebook).unwrap();
+///
+/// let lookup = codebook.to_lookup_decoder_model();
+/// let decoded = ans
+///     .decode_iid_symbols(indices.len(), &lookup)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, indices);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Codebook<Probability, const PRECISION: usize> {
+    categorical: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>,
+}
+
+impl<Probability: BitArray, const PRECISION: usize> Codebook<Probability, PRECISION> {
+    /// Constructs a codebook model from the learned probabilities of its entries.
+    ///
+    /// The entry `probabilities[k]` is the probability of codebook index `k`. The
+    /// probabilities don't need to be normalized. Every index gets a nonzero probability,
+    /// even if its learned probability is zero, so that all indices can be encoded. This
+    /// uses the same quantization as
+    /// [`ContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast`], and
+    /// it fails under the same conditions.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_learned_probabilities<F>(probabilities: &[F]) -> Result<Self, ()>
+    where
+        F: FloatCore + core::iter::Sum<F> + AsPrimitive<Probability>,
+        Probability: AsPrimitive<usize>,
+        usize: AsPrimitive<Probability> + AsPrimitive<F>,
+    {
+        let categorical =
+            ContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                probabilities,
+                None,
+            )?;
+        Ok(Self { categorical })
+    }
+
+    /// Returns the size `K` of the codebook, i.e., the number of valid indices `0..K`.
+    #[inline(always)]
+    pub fn num_indices(&self) -> usize {
+        self.categorical.support_size()
+    }
+
+    /// Returns the index with the highest (quantized) probability.
+    ///
+    /// If several indices have the same highest probability then the smallest of them is
+    /// returned. This is mainly useful for debugging, e.g., to detect codebook collapse.
+    pub fn most_probable_index(&self) -> usize {
+        let mut best = (0, Probability::zero());
+        for (index, _, probability) in self.categorical.symbol_table() {
+            if probability.get() > best.1 {
+                best = (index, probability.get());
+            }
+        }
+        best.0
+    }
+
+    /// Returns the underlying categorical distribution over codebook indices.
+    pub fn as_categorical(
+        &self,
+    ) -> &ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        &self.categorical
+    }
+
+    /// Creates a [`ContiguousLookupDecoderModel`] for fast decoding of many indices.
+    ///
+    /// See [`ContiguousCategoricalEntropyModel::to_lookup_decoder_model`] for when this is
+    /// worthwhile.
+    pub fn to_lookup_decoder_model(
+        &self,
+    ) -> ContiguousLookupDecoderModel<Probability, Vec<Probability>, Box<[Probability]>, PRECISION>
+    where
+        Probability: Into<usize>,
+        usize: AsPrimitive<Probability>,
+    {
+        self.categorical.to_lookup_decoder_model()
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for Codebook<Probability, PRECISION>
+{
+    type Symbol = usize;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for Codebook<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Self::Symbol>,
+    ) -> Option<(Self::Probability, <Self::Probability as BitArray>::NonZero)> {
+        self.categorical.left_cumulative_and_probability(symbol)
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for Codebook<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn quantile_function(
+        &self,
+        quantile: Self::Probability,
+    ) -> (
+        Self::Symbol,
+        Self::Probability,
+        <Self::Probability as BitArray>::NonZero,
+    ) {
+        self.categorical.quantile_function(quantile)
+    }
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for Codebook<Probability, PRECISION>
+{
+    fn symbol_table(
+        &'m self,
+    ) -> impl Iterator<
+        Item = (
+            Self::Symbol,
+            Self::Probability,
+            <Self::Probability as BitArray>::NonZero,
+        ),
+    > {
+        self.categorical.symbol_table()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::test_entropy_model;
+    use super::*;
+
+    #[test]
+    fn codebook() {
+        let probabilities = [0.05f64, 0.1, 0.4, 0.0, 0.2, 0.25];
+        let codebook = DefaultCodebook::from_learned_probabilities(&probabilities).unwrap();
+        test_entropy_model(&codebook, 0..6);
+        assert_eq!(codebook.num_indices(), 6);
+        assert_eq!(codebook.most_probable_index(), 2);
+
+        let small = SmallCodebook::from_learned_probabilities(&probabilities).unwrap();
+        test_entropy_model(&small, 0..6);
+        let lookup = small.to_lookup_decoder_model();
+        for quantile in 0..1 << 12 {
+            assert_eq!(
+                lookup.quantile_function(quantile),
+                small.quantile_function(quantile)
+            );
+        }
+
+        // Ties are resolved in favor of the smallest index.
+        let tied = SmallCodebook::from_learned_probabilities(&[0.1f32, 0.45, 0.45]).unwrap();
+        assert_eq!(tied.most_probable_index(), 1);
+
+        assert!(SmallCodebook::from_learned_probabilities(&[1.0f64]).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use alloc::vec::Vec;
+
+use super::EntropyModel;
+
+/// A collection of entropy models, one per context, for order-1 (Markov) models.
+///
+/// A `ContextModel` holds one sub-model for each context index `0..num_contexts()`, and
+/// [`model_for(context)`](Self::model_for) returns the sub-model for a given context. It
+/// doesn't implement the entropy model traits itself since encoding and decoding a symbol
+/// requires knowing its context. Instead, use it with
+/// [`AnsCoder::encode_contextual_symbols`] and [`AnsCoder::decode_contextual_symbols`],
+/// which use the previous symbol (converted to a `usize`) as the context for the next
+/// symbol, so that you don't have to keep track of the context by hand. For more general
+/// contexts, look up the sub-model for each symbol with `model_for` and pass it to
+/// [`encode_symbol`](crate::stream::Encode::encode_symbol) and
+/// [`decode_symbol`](crate::stream::Decode::decode_symbol).
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::{ContextModel, DefaultContiguousCategoricalEntropyModel},
+///     stack::DefaultAnsCoder,
+/// };
+///
+/// // A Markov chain over the symbols `{0, 1, 2}` that tends to repeat the previous symbol.
+/// let transition_probabilities = [[0.8, 0.1, 0.1], [0.1, 0.8, 0.1], [0.1, 0.1, 0.8]];
+/// let model = ContextModel::new(
+///     transition_probabilities
+///         .iter()
+///         .map(|row| {
+///             DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+///                 row, None,
+///             )
+///             .unwrap()
+///         })
+///         .collect(),
+/// );
+/// assert_eq!(model.num_contexts(), 3);
+///
+/// let symbols = [0, 0, 0, 2, 2, 1, 1, 1, 1];
+/// let mut ans = DefaultAnsCoder::new();
+/// // The first symbol is encoded with context `0`.
+/// ans.encode_contextual_symbols(&symbols, &model, 0).unwrap();
+///
+/// let decoded = ans.decode_contextual_symbols(symbols.len(), &model, 0).unwrap();
+/// assert_eq!(decoded, symbols);
+/// assert!(ans.is_empty());
+/// ```
+///
+/// [`AnsCoder::encode_contextual_symbols`]:
+///     crate::stream::stack::AnsCoder::encode_contextual_symbols
+/// [`AnsCoder::decode_contextual_symbols`]:
+///     crate::stream::stack::AnsCoder::decode_contextual_symbols
+#[derive(Debug, Clone)]
+pub struct ContextModel<M, const PRECISION: usize> {
+    models: Vec<M>,
+}
+
+impl<M, const PRECISION: usize> ContextModel<M, PRECISION>
+where
+    M: EntropyModel<PRECISION>,
+{
+    /// Creates a `ContextModel` that uses `models[context]` for each context index
+    /// `context`.
+    pub fn new(models: Vec<M>) -> Self {
+        Self { models }
+    }
+
+    /// Returns the sub-model for the given `context`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `context >= self.num_contexts()`.
+    #[inline(always)]
+    pub fn model_for(&self, context: usize) -> &M {
+        &self.models[context]
+    }
+
+    /// Returns the number of contexts, i.e., the number of sub-models.
+    pub fn num_contexts(&self) -> usize {
+        self.models.len()
+    }
+
+    /// Returns a slice of all sub-models, indexed by context.
+    pub fn models(&self) -> &[M] {
+        &self.models
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use rand::{RngCore, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    use super::{
+        super::{DecoderModel, DefaultContiguousCategoricalEntropyModel, DefaultUniformModel},
+        *,
+    };
+    use crate::{
+        stream::{
+            stack::{ContextOutOfRangeError, DefaultAnsCoder},
+            TryCodingError,
+        },
+        CoderError,
+    };
+
+    #[test]
+    fn markov_chain_round_trip() {
+        #[cfg(not(miri))]
+        let amt = 10_000;
+
+        #[cfg(miri)]
+        let amt = 100;
+
+        // A Markov chain over 8 states that usually stays in its current state or moves to
+        // a neighboring state.
+        let num_states = 8;
+        let transition_probabilities = (0..num_states)
+            .map(|previous| {
+                (0..num_states)
+                    .map(|next| match (next + num_states - previous) % num_states {
+                        0 => 0.7,
+                        1 | 7 => 0.13,
+                        _ => 0.01,
+                    })
+                    .collect::<Vec<f64>>()
+            })
+            .collect::<Vec<_>>();
+        let model = ContextModel::new(
+            transition_probabilities
+                .iter()
+                .map(|row| {
+                    DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                        row, None,
+                    )
+                    .unwrap()
+                })
+                .collect(),
+        );
+        assert_eq!(model.num_contexts(), num_states);
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2310);
+        let mut previous = 3;
+        let symbols = (0..amt)
+            .map(|_| {
+                let quantile = rng.next_u32() % (1 << 24);
+                previous = model.model_for(previous).quantile_function(quantile).0;
+                previous
+            })
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_contextual_symbols(&symbols, &model, 3).unwrap();
+        let num_bits = ans.num_valid_bits();
+
+        let decoded = ans
+            .decode_contextual_symbols(symbols.len(), &model, 3)
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(ans.is_empty());
+
+        // Exploiting the correlations beats an i.i.d. model by a large margin (the stationary
+        // distribution of the Markov chain is uniform).
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, DefaultUniformModel::new(num_states))
+            .unwrap();
+        assert!(num_bits < ans.num_valid_bits() / 2);
+
+        // Edge cases.
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_contextual_symbols(&[], &model, 0).unwrap();
+        assert!(ans.is_empty());
+        ans.encode_contextual_symbols(&[5], &model, 0).unwrap();
+        assert_eq!(ans.decode_contextual_symbols(1, &model, 0).unwrap(), [5]);
+        assert!(ans.is_empty());
+    }
+
+    #[test]
+    fn context_out_of_range() {
+        // Each sub-model has a support that is wider than the number of contexts.
+        let narrow = ContextModel::<_, 24>::new(vec![DefaultUniformModel::new(4); 2]);
+        let wide = ContextModel::<_, 24>::new(vec![DefaultUniformModel::new(4); 4]);
+        let symbols = [0, 3, 1];
+
+        let mut ans = DefaultAnsCoder::new();
+        assert_eq!(
+            ans.encode_contextual_symbols(&symbols, &narrow, 0),
+            Err(TryCodingError::InvalidEntropyModel(
+                ContextOutOfRangeError {
+                    context: 3,
+                    num_contexts: 2
+                }
+            ))
+        );
+        assert_eq!(
+            ans.encode_contextual_symbols(&[1], &narrow, 2),
+            Err(TryCodingError::InvalidEntropyModel(
+                ContextOutOfRangeError {
+                    context: 2,
+                    num_contexts: 2
+                }
+            ))
+        );
+        assert!(ans.is_empty());
+
+        ans.encode_contextual_symbols(&symbols, &wide, 0).unwrap();
+        let mut decoder = ans.clone();
+        assert_eq!(
+            decoder.decode_contextual_symbols(symbols.len(), &narrow, 0),
+            Err(CoderError::Frontend(ContextOutOfRangeError {
+                context: 3,
+                num_contexts: 2
+            }))
+        );
+        assert_eq!(
+            ans.decode_contextual_symbols(symbols.len(), &wide, 0)
+                .unwrap(),
+            symbols
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use core::{borrow::Borrow, marker::PhantomData};
+
+use crate::BitArray;
+
+use super::{DecoderModel, EncoderModel, EntropyModel};
+
+/// An entropy model that delegates to user-provided closures.
+///
+/// This adapter turns a pair of closures into an entropy model that implements both
+/// [`EncoderModel`] and [`DecoderModel`]. It is useful for distributions that don't fit
+/// any of the provided models (e.g., [`ContiguousCategoricalEntropyModel`] or
+/// [`LeakyQuantizer`]) but whose fixed-point cumulative distribution function and its
+/// inverse can be evaluated directly.
+///
+/// - `quantile_function: F` takes a quantile `q` with `0 <= q < 2^PRECISION` and returns
+///   a tuple `(symbol, left_cumulative, probability)` that describes the symbol whose
+///   interval `[left_cumulative, left_cumulative + probability)` contains `q`. See
+///   [`DecoderModel::quantile_function`].
+/// - `left_cumulative_and_probability: G` takes a symbol and returns
+///   `Ok((left_cumulative, probability))`, or `Err(())` if the symbol has zero
+///   probability. See [`EncoderModel::left_cumulative_and_probability`].
+///
+/// # Invariants
+///
+/// The closures must describe a valid fixed-point probability distribution, and they must
+/// be consistent with each other. Otherwise, encoding and decoding will not be inverses of
+/// each other. In detail:
+///
+/// - all returned probabilities of symbols with nonzero probability must be nonzero (the
+///   adapter panics if `quantile_function` returns a zero probability, and it treats a
+///   zero probability returned from `left_cumulative_and_probability` like an `Err`);
+/// - the intervals `[left_cumulative, left_cumulative + probability)` of all symbols
+///   must be disjoint and together cover exactly the range `0..2^PRECISION`, i.e., the
+///   probabilities of all symbols must add up to exactly `2^PRECISION` and the left
+///   cumulatives must be monotonically increasing when the symbols are ordered by their
+///   intervals; and
+/// - for each `q` in `0..2^PRECISION`, if `quantile_function(q)` returns
+///   `(symbol, left_cumulative, probability)` then `left_cumulative <= q <
+///   left_cumulative + probability` and `left_cumulative_and_probability(&symbol)`
+///   returns `Ok((left_cumulative, probability))`.
+///
+/// Violating these invariants doesn't lead to undefined behavior, but it leads to
+/// incorrect results (or panics).
+///
+/// # Example
+///
+/// A geometric distribution over the symbols `0..=10` where symbol `k < 10` has
+/// probability `2^-(k + 1)` and the last symbol takes up the remaining probability mass:
+///
+/// ```
+/// use constriction::stream::{model::CustomModel, stack::DefaultAnsCoder, Decode};
+///
+/// const PRECISION: usize = 24;
+/// let model = CustomModel::<_, _, u32, u32, PRECISION>::new(
+///     |quantile: u32| {
+///         // Symbol `k < 10` covers `[2^24 - 2^(24 - k), 2^24 - 2^(23 - k))`.
+///         let k = core::cmp::min((!quantile << 8).leading_zeros(), 10);
+///         let left_cumulative = (1 << PRECISION) - (1 << (PRECISION as u32 - k));
+///         let probability = if k == 10 { 1 << 14 } else { 1 << (23 - k) };
+///         (k, left_cumulative, probability)
+///     },
+///     |&symbol: &u32| {
+///         if symbol > 10 {
+///             return Err(());
+///         }
+///         let left_cumulative = (1 << PRECISION) - (1 << (PRECISION as u32 - symbol));
+///         let probability = if symbol == 10 { 1 << 14 } else { 1 << (23 - symbol) };
+///         Ok((left_cumulative, probability))
+///     },
+/// );
+///
+/// let symbols = [0, 3, 1, 0, 10, 0, 2];
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+/// let decoded = ans
+///     .decode_iid_symbols(symbols.len(), &model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// ```
+///
+/// [`ContiguousCategoricalEntropyModel`]: super::ContiguousCategoricalEntropyModel
+/// [`LeakyQuantizer`]: super::LeakyQuantizer
+#[derive(Debug, Clone, Copy)]
+pub struct CustomModel<F, G, Symbol, Probability, const PRECISION: usize> {
+    quantile_function: F,
+    left_cumulative_and_probability: G,
+    phantom: PhantomData<fn(Symbol) -> Probability>,
+}
+
+impl<F, G, Symbol, Probability, const PRECISION: usize>
+    CustomModel<F, G, Symbol, Probability, PRECISION>
+where
+    Probability: BitArray,
+    F: Fn(Probability) -> (Symbol, Probability, Probability),
+    G: Fn(&Symbol) -> Result<(Probability, Probability), ()>,
+{
+    /// Creates an entropy model from the two closures. See [struct level
+    /// documentation](Self) for the invariants that the closures must satisfy.
+    pub fn new(quantile_function: F, left_cumulative_and_probability: G) -> Self {
+        Self {
+            quantile_function,
+            left_cumulative_and_probability,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<F, G, Symbol, Probability, const PRECISION: usize> EntropyModel<PRECISION>
+    for CustomModel<F, G, Symbol, Probability, PRECISION>
+where
+    Probability: BitArray,
+{
+    type Symbol = Symbol;
+    type Probability = Probability;
+}
+
+impl<F, G, Symbol, Probability, const PRECISION: usize> EncoderModel<PRECISION>
+    for CustomModel<F, G, Symbol, Probability, PRECISION>
+where
+    Probability: BitArray,
+    G: Fn(&Symbol) -> Result<(Probability, Probability), ()>,
+{
+    #[inline]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Self::Symbol>,
+    ) -> Option<(Self::Probability, <Self::Probability as BitArray>::NonZero)> {
+        let (left_cumulative, probability) =
+            (self.left_cumulative_and_probability)(symbol.borrow()).ok()?;
+        Some((left_cumulative, probability.into_nonzero()?))
+    }
+}
+
+impl<F, G, Symbol, Probability, const PRECISION: usize> DecoderModel<PRECISION>
+    for CustomModel<F, G, Symbol, Probability, PRECISION>
+where
+    Probability: BitArray,
+    F: Fn(Probability) -> (Symbol, Probability, Probability),
+{
+    #[inline]
+    fn quantile_function(
+        &self,
+        quantile: Self::Probability,
+    ) -> (
+        Self::Symbol,
+        Self::Probability,
+        <Self::Probability as BitArray>::NonZero,
+    ) {
+        let (symbol, left_cumulative, probability) = (self.quantile_function)(quantile);
+        let probability = probability
+            .into_nonzero()
+            .expect("`quantile_function` must not return zero probability.");
+        (symbol, left_cumulative, probability)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand::{RngCore, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    use super::*;
+    use crate::stream::{stack::DefaultAnsCoder, Decode};
+
+    /// Geometric distribution over `0..=max_symbol` where symbol `k < max_symbol` has
+    /// probability `2^-(k + 1)` and `max_symbol` takes the remaining probability mass.
+    fn geometric(
+        max_symbol: u32,
+    ) -> CustomModel<
+        impl Fn(u32) -> (u32, u32, u32) + Copy,
+        impl Fn(&u32) -> Result<(u32, u32), ()> + Copy,
+        u32,
+        u32,
+        24,
+    > {
+        let interval = move |symbol: u32| {
+            let left_cumulative = (1 << 24) - (1 << (24 - symbol));
+            let probability = if symbol == max_symbol {
+                1 << (24 - max_symbol)
+            } else {
+                1 << (23 - symbol)
+            };
+            (left_cumulative, probability)
+        };
+        CustomModel::new(
+            move |quantile: u32| {
+                let symbol = core::cmp::min((!quantile << 8).leading_zeros(), max_symbol);
+                let (left_cumulative, probability) = interval(symbol);
+                (symbol, left_cumulative, probability)
+            },
+            move |&symbol: &u32| {
+                if symbol > max_symbol {
+                    Err(())
+                } else {
+                    Ok(interval(symbol))
+                }
+            },
+        )
+    }
+
+    #[test]
+    fn custom_geometric() {
+        for max_symbol in [1, 2, 10, 23] {
+            let model = geometric(max_symbol);
+
+            let mut sum = 0;
+            for symbol in 0..=max_symbol {
+                let (left_cumulative, probability) =
+                    model.left_cumulative_and_probability(symbol).unwrap();
+                assert_eq!(left_cumulative, sum);
+                sum += probability.get();
+
+                let expected = (symbol, left_cumulative, probability);
+                assert_eq!(model.quantile_function(left_cumulative), expected);
+                assert_eq!(model.quantile_function(sum - 1), expected);
+            }
+            assert_eq!(sum, 1 << 24);
+            assert!(model
+                .left_cumulative_and_probability(max_symbol + 1)
+                .is_none());
+
+            let mut rng = Xoshiro256StarStar::seed_from_u64(2261);
+            let symbols = (0..1000)
+                .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                .collect::<Vec<_>>();
+            let mut ans = DefaultAnsCoder::new();
+            ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+
+            // Symbol `k` costs `k + 1` bits (except for `max_symbol`).
+            let information_content = symbols
+                .iter()
+                .map(|&symbol| core::cmp::min(symbol + 1, max_symbol) as usize)
+                .sum::<usize>();
+            assert!(ans.num_valid_bits() <= information_content + 64);
+
+            let decoded = ans
+                .decode_iid_symbols(symbols.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(ans.is_empty());
+        }
+    }
+}
@@ -0,0 +1,351 @@
+use core::{borrow::Borrow, num::NonZeroU64};
+
+use alloc::vec::Vec;
+
+use num_traits::AsPrimitive;
+
+use crate::{BitArray, NonZeroBitArray};
+
+use super::{DecoderModel, EncoderModel, EntropyModel, IterableEntropyModel};
+
+/// Precomputed data for dividing by a fixed divisor with a multiplication and shifts.
+///
+/// Encoding a symbol with an [`AnsCoder`] requires dividing the coder's state by the
+/// symbol's probability. Integer divisions are slow on most CPUs, and they are usually the
+/// bottleneck when encoding long sequences of symbols. If the same probabilities are used
+/// over and over again, it can pay off to precompute a `DivisionHint` for each probability.
+/// An encoder can then replace the division by a multiplication and two shifts (following
+/// Granlund and Montgomery, "Division by Invariant Integers using Multiplication", 1994).
+///
+/// The result is exact for all dividends that fit into a `u64`, so using a `DivisionHint`
+/// never changes the compressed data. Entropy models provide hints to encoders via
+/// [`EncoderModel::division_hint`]; see [`WithDivisionHints`] for a decorator that adds
+/// division hints to an existing entropy model.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::model::DivisionHint;
+/// use core::num::NonZeroU64;
+///
+/// let hint = DivisionHint::new(NonZeroU64::new(7).unwrap());
+/// assert_eq!(hint.divisor(), 7);
+/// assert_eq!(hint.quotient(100), 14);
+/// assert_eq!(hint.quotient(u64::MAX), u64::MAX / 7);
+/// ```
+///
+/// [`AnsCoder`]: crate::stream::stack::AnsCoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DivisionHint {
+    divisor: NonZeroU64,
+    multiplier: u64,
+    shift1: u32,
+    shift2: u32,
+}
+
+impl DivisionHint {
+    /// Precomputes the multiplier and shifts for dividing by `divisor`.
+    pub fn new(divisor: NonZeroU64) -> Self {
+        let d = divisor.get();
+
+        // `l = ceil(log2(d))`, so that `2^(l-1) < d <= 2^l`.
+        let l = u64::BITS - (d - 1).leading_zeros();
+        let multiplier = ((((1u128 << l) - d as u128) << 64) / d as u128) as u64 + 1;
+
+        Self {
+            divisor,
+            multiplier,
+            shift1: l.min(1),
+            shift2: l.saturating_sub(1),
+        }
+    }
+
+    /// Returns the divisor for which the hint was computed.
+    #[inline(always)]
+    pub fn divisor(&self) -> u64 {
+        self.divisor.get()
+    }
+
+    /// Returns `dividend / self.divisor()`, rounded down.
+    #[inline(always)]
+    pub fn quotient(&self, dividend: u64) -> u64 {
+        let high = ((self.multiplier as u128 * dividend as u128) >> 64) as u64;
+        (high + ((dividend - high) >> self.shift1)) >> self.shift2
+    }
+}
+
+/// A decorator that precomputes [`DivisionHint`]s for all symbols of an entropy model.
+///
+/// Wraps an entropy model `M` over the symbols `0..n` and stores one [`DivisionHint`] per
+/// symbol, which [`AnsCoder`]s use to speed up encoding. Encoding and decoding otherwise
+/// delegate to `M`, and the compressed data is exactly the same as without the decorator.
+/// The decorator pays off if you encode many symbols with the same model; it requires
+/// memory proportional to `n`.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::{DefaultContiguousCategoricalEntropyModel, WithDivisionHints},
+///     stack::DefaultAnsCoder,
+///     Decode,
+/// };
+///
+/// let probabilities = [0.1, 0.4, 0.2, 0.3];
+/// let model =
+///     DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+///         &probabilities,
+///         None,
+///     )
+///     .unwrap();
+/// let symbols = [2, 1, 3, 0, 1, 1];
+///
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+/// let compressed = ans.into_compressed().unwrap();
+///
+/// let model = WithDivisionHints::new(model).unwrap();
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+/// assert_eq!(ans.clone().into_compressed().unwrap(), compressed);
+///
+/// let decoded = ans
+///     .decode_iid_symbols(symbols.len(), &model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// ```
+///
+/// [`AnsCoder`]: crate::stream::stack::AnsCoder
+#[derive(Debug, Clone)]
+pub struct WithDivisionHints<M, const PRECISION: usize> {
+    inner: M,
+    hints: Vec<DivisionHint>,
+}
+
+impl<M, const PRECISION: usize> WithDivisionHints<M, PRECISION>
+where
+    M: for<'m> IterableEntropyModel<'m, PRECISION, Symbol = usize>,
+    M::Probability: AsPrimitive<u64>,
+{
+    /// Precomputes division hints for all symbols of `model`.
+    ///
+    /// Returns the argument `model` back to the caller, wrapped in an `Err` variant, if the
+    /// symbols of `model` aren't exactly `0..n` (in this order) for some `n`.
+    pub fn new(model: M) -> Result<Self, M> {
+        let hints = model
+            .symbol_table()
+            .enumerate()
+            .map(|(index, (symbol, _, probability))| {
+                let divisor = NonZeroU64::new(probability.get().as_())?;
+                (symbol == index).then(|| DivisionHint::new(divisor))
+            })
+            .collect::<Option<Vec<_>>>();
+
+        match hints {
+            Some(hints) => Ok(Self {
+                inner: model,
+                hints,
+            }),
+            None => Err(model),
+        }
+    }
+}
+
+impl<M, const PRECISION: usize> WithDivisionHints<M, PRECISION> {
+    /// Returns a reference to the wrapped model.
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Consumes the decorator and returns the wrapped model.
+    pub fn into_inner(self) -> M {
+        self.inner
+    }
+}
+
+impl<M, const PRECISION: usize> EntropyModel<PRECISION> for WithDivisionHints<M, PRECISION>
+where
+    M: EntropyModel<PRECISION>,
+{
+    type Symbol = M::Symbol;
+    type Probability = M::Probability;
+}
+
+impl<M, const PRECISION: usize> EncoderModel<PRECISION> for WithDivisionHints<M, PRECISION>
+where
+    M: EncoderModel<PRECISION, Symbol = usize>,
+{
+    #[inline(always)]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Self::Symbol>,
+    ) -> Option<(Self::Probability, <Self::Probability as BitArray>::NonZero)> {
+        self.inner.left_cumulative_and_probability(symbol)
+    }
+
+    #[inline(always)]
+    fn division_hint(&self, symbol: impl Borrow<Self::Symbol>) -> Option<DivisionHint> {
+        self.hints.get(*symbol.borrow()).copied()
+    }
+}
+
+impl<M, const PRECISION: usize> DecoderModel<PRECISION> for WithDivisionHints<M, PRECISION>
+where
+    M: DecoderModel<PRECISION>,
+{
+    #[inline(always)]
+    fn quantile_function(
+        &self,
+        quantile: Self::Probability,
+    ) -> (
+        Self::Symbol,
+        Self::Probability,
+        <Self::Probability as BitArray>::NonZero,
+    ) {
+        self.inner.quantile_function(quantile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand::{RngCore, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    use super::*;
+    use crate::stream::{
+        model::{DefaultContiguousCategoricalEntropyModel, SmallContiguousCategoricalEntropyModel},
+        stack::{DefaultAnsCoder, SmallAnsCoder},
+        Decode, Encode,
+    };
+
+    #[test]
+    fn quotient() {
+        #[cfg(not(miri))]
+        let (max_divisor, num_random) = (1 << 16, 16);
+
+        #[cfg(miri)]
+        let (max_divisor, num_random) = (1 << 6, 2);
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2283);
+        let divisors = (1..=max_divisor)
+            .chain([
+                (1 << 24) - 1,
+                1 << 24,
+                (1 << 32) - 1,
+                1 << 32,
+                (1 << 63) - 1,
+                1 << 63,
+                (1 << 63) + 1,
+                u64::MAX - 1,
+                u64::MAX,
+            ])
+            .chain((0..100).map(|_| rng.next_u64().max(1)).collect::<Vec<_>>());
+
+        for divisor in divisors {
+            let hint = DivisionHint::new(NonZeroU64::new(divisor).unwrap());
+            assert_eq!(hint.divisor(), divisor);
+            let check = |dividend: u64| assert_eq!(hint.quotient(dividend), dividend / divisor);
+            for dividend in [0, 1, divisor - 1, divisor, u64::MAX - 1, u64::MAX] {
+                check(dividend);
+            }
+            for _ in 0..num_random {
+                // Dividends just below and at a random multiple of the divisor are the
+                // boundary cases where a rounding error would show up first.
+                let multiple = (rng.next_u64() / divisor).max(1) * divisor;
+                check(multiple - 1);
+                check(multiple);
+                check(rng.next_u64());
+                check(rng.next_u64() >> (rng.next_u32() % 64));
+            }
+        }
+    }
+
+    #[test]
+    fn same_compressed_data() {
+        #[cfg(not(miri))]
+        let amt = 10_000;
+
+        #[cfg(miri)]
+        let amt = 100;
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2283);
+
+        for num_symbols in [2usize, 3, 10, 100, 1000] {
+            let probabilities = (0..num_symbols)
+                .map(|_| (rng.next_u32() % 1000 + 1) as f64)
+                .collect::<Vec<_>>();
+            let symbols = (0..amt)
+                .map(|_| rng.next_u32() as usize % num_symbols)
+                .collect::<Vec<_>>();
+
+            let model =
+                SmallContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                    &probabilities,
+                    None,
+                )
+                .unwrap();
+            let mut ans = SmallAnsCoder::new();
+            ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+            let expected = ans.into_compressed().unwrap();
+
+            let model = WithDivisionHints::new(model).unwrap();
+            let mut ans = SmallAnsCoder::new();
+            ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+            assert_eq!(ans.clone().into_compressed().unwrap(), expected);
+            let decoded = ans
+                .decode_iid_symbols(amt, &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+
+            let model =
+                DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                    &probabilities,
+                    None,
+                )
+                .unwrap();
+            let mut ans = DefaultAnsCoder::new();
+            ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+            let expected = ans.into_compressed().unwrap();
+
+            let model = WithDivisionHints::new(model).unwrap();
+            let mut ans = DefaultAnsCoder::new();
+            ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+            assert_eq!(ans.into_compressed().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn all_probabilities() {
+        #[cfg(not(miri))]
+        let step = 1;
+
+        #[cfg(miri)]
+        let step = 257;
+
+        // Encode with every possible probability at `PRECISION = 12` and from a variety of
+        // coder states, and compare to encoding without division hints after every step.
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2283);
+        for probability in (1u16..1 << 12).step_by(step) {
+            let model =
+                SmallContiguousCategoricalEntropyModel::from_nonzero_fixed_point_probabilities(
+                    [probability, (1 << 12) - probability],
+                    false,
+                )
+                .unwrap();
+            let hinted = WithDivisionHints::new(model.clone()).unwrap();
+
+            let mut plain_ans = SmallAnsCoder::new();
+            let mut hinted_ans = SmallAnsCoder::new();
+            for _ in 0..20 {
+                let symbol = (rng.next_u32() % 2) as usize;
+                plain_ans.encode_symbol(symbol, &model).unwrap();
+                hinted_ans.encode_symbol(symbol, &hinted).unwrap();
+                assert_eq!(plain_ans, hinted_ans);
+            }
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use alloc::boxed::Box;
+use core::borrow::Borrow;
+
+use crate::{generic_static_asserts, BitArray};
+
+use super::{ContiguousCategoricalEntropyModel, DecoderModel, EncoderModel, EntropyModel};
+
+/// Type-erased entropy model whose fixed-point precision is only known at runtime.
+///
+/// All entropy models in `constriction` specify their fixed-point precision as a const
+/// generic parameter `PRECISION`. This allows the compiler to optimize encoding and
+/// decoding for the specific precision, but it also means that you have to monomorphize
+/// your code for each precision you want to support. A `DynPrecisionModel` wraps an
+/// arbitrary entropy model in a trait object and remembers its precision as a runtime
+/// value. It implements [`EncoderModel`] and [`DecoderModel`] with `PRECISION ==
+/// Probability::BITS` (e.g., `PRECISION == 32` for `Probability == u32`) by scaling up
+/// all fixed-point probabilities of the wrapped model. So, for example, you can encode and
+/// decode symbols with a [`DefaultAnsCoder`] regardless of the wrapped model's precision
+/// without any generic code on your side.
+///
+/// Scaling up probabilities by a power of two doesn't change the relative size of any
+/// intervals, so the bitrate is the same as if you had used the wrapped model directly
+/// (up to a constant overhead due to the coder's internal state). However, the compressed
+/// data *differs* from the data that you would get with the wrapped model directly, so
+/// you have to use a `DynPrecisionModel` for both encoding and decoding.
+///
+/// # Performance Tradeoff
+///
+/// Every call to the entropy model goes through dynamic dispatch and can therefore not be
+/// inlined. This typically makes encoding and decoding noticeably slower than with a
+/// model whose precision is known at compile time, especially for simple models where the
+/// model evaluation is cheap compared to the function call. Further, the model is always
+/// used at the full precision `Probability::BITS`, which rules out some optimizations
+/// (e.g., the lookup tables of [`ContiguousLookupDecoderModel`] require a low precision).
+/// Prefer models with a compile-time precision in performance critical code, and use a
+/// `DynPrecisionModel` where flexibility matters more, e.g., in a plugin system.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::{DynPrecisionModel, EntropyModel},
+///     stack::DefaultAnsCoder,
+///     Decode,
+/// };
+///
+/// // Pretend that we learn the precision only at runtime.
+/// let precision = "12".parse::<usize>().unwrap();
+/// let probabilities = [0.2, 0.4, 0.1, 0.3];
+/// let model =
+///     DynPrecisionModel::from_floating_point_probabilities(&probabilities, precision).unwrap();
+/// assert_eq!(model.wrapped_precision(), 12);
+/// assert_eq!(EntropyModel::<32>::precision(&model), 32);
+///
+/// let symbols = [1, 3, 0, 1, 2, 1, 3];
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+///
+/// let decoded = ans
+///     .decode_iid_symbols(symbols.len(), &model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// ```
+///
+/// [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+/// [`ContiguousLookupDecoderModel`]: super::ContiguousLookupDecoderModel
+pub struct DynPrecisionModel<'m, Symbol, Probability: BitArray> {
+    inner: Box<dyn ObjectSafeModel<Symbol, Probability> + 'm>,
+    wrapped_precision: usize,
+}
+
+impl<Symbol, Probability: BitArray> core::fmt::Debug
+    for DynPrecisionModel<'_, Symbol, Probability>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DynPrecisionModel")
+            .field("wrapped_precision", &self.wrapped_precision)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'m, Symbol, Probability: BitArray> DynPrecisionModel<'m, Symbol, Probability> {
+    /// Wraps `model`, whose precision is known at compile time, in a `DynPrecisionModel`.
+    ///
+    /// The precision `PRECISION` must not exceed `Probability::BITS`, which is enforced at
+    /// compile time.
+    pub fn new<M, const PRECISION: usize>(model: M) -> Self
+    where
+        M: EncoderModel<PRECISION, Symbol = Symbol, Probability = Probability>
+            + DecoderModel<PRECISION>
+            + 'm,
+    {
+        generic_static_asserts!(
+            (Probability: BitArray; const PRECISION: usize);
+            PROBABILITY_MUST_SUPPORT_PRECISION: PRECISION <= Probability::BITS;
+            PRECISION_MUST_BE_NONZERO: PRECISION > 0;
+        );
+
+        Self {
+            inner: Box::new(WithPrecision::<M, PRECISION>(model)),
+            wrapped_precision: PRECISION,
+        }
+    }
+
+    /// Returns the fixed-point precision of the wrapped model.
+    ///
+    /// This is generally *not* the const generic parameter `PRECISION` under which the
+    /// `DynPrecisionModel` itself implements the entropy model traits, which is always
+    /// `Probability::BITS` (and which [`EntropyModel::precision`] returns). Use the
+    /// precision returned by this method when you need to reconstruct the wrapped model.
+    pub fn wrapped_precision(&self) -> usize {
+        self.wrapped_precision
+    }
+}
+
+impl DynPrecisionModel<'static, usize, u32> {
+    /// Constructs a [`ContiguousCategoricalEntropyModel`] with a `precision` that is chosen
+    /// at runtime, and wraps it in a `DynPrecisionModel`.
+    ///
+    /// The model is constructed with
+    /// [`ContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast`], so it
+    /// is defined over the symbols `0..probabilities.len()` and every symbol has a nonzero
+    /// probability.
+    ///
+    /// Returns `Err(())` if `precision` is zero or larger than 32 (the number of bits of the
+    /// compressed words of a [`DefaultAnsCoder`] or [`DefaultRangeEncoder`]), or if the
+    /// model can't be constructed at the requested precision (see
+    /// [`from_floating_point_probabilities_fast`]).
+    ///
+    /// [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+    /// [`DefaultRangeEncoder`]: crate::stream::queue::DefaultRangeEncoder
+    /// [`from_floating_point_probabilities_fast`]:
+    ///     ContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast
+    #[allow(clippy::result_unit_err)]
+    pub fn from_floating_point_probabilities(
+        probabilities: &[f64],
+        precision: usize,
+    ) -> Result<Self, ()> {
+        fn construct<const PRECISION: usize>(
+            probabilities: &[f64],
+        ) -> Result<DynPrecisionModel<'static, usize, u32>, ()> {
+            let model = ContiguousCategoricalEntropyModel::<u32, _, PRECISION>
+                ::from_floating_point_probabilities_fast(probabilities, None)?;
+            Ok(DynPrecisionModel::new(model))
+        }
+
+        macro_rules! dispatch {
+            ($precision:expr; $($p:literal),*) => {
+                match $precision {
+                    $($p => construct::<$p>(probabilities),)*
+                    _ => Err(()),
+                }
+            };
+        }
+
+        dispatch!(
+            precision;
+            1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+            17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32
+        )
+    }
+}
+
+/// Object safe version of the combination of [`EncoderModel`] and [`DecoderModel`].
+trait ObjectSafeModel<Symbol, Probability: BitArray> {
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: &Symbol,
+    ) -> Option<(Probability, Probability::NonZero)>;
+
+    fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (Symbol, Probability, Probability::NonZero);
+}
+
+struct WithPrecision<M, const PRECISION: usize>(M);
+
+impl<M, const PRECISION: usize> ObjectSafeModel<M::Symbol, M::Probability>
+    for WithPrecision<M, PRECISION>
+where
+    M: EncoderModel<PRECISION> + DecoderModel<PRECISION>,
+{
+    #[inline]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: &M::Symbol,
+    ) -> Option<(M::Probability, <M::Probability as BitArray>::NonZero)> {
+        self.0.left_cumulative_and_probability(symbol)
+    }
+
+    #[inline]
+    fn quantile_function(
+        &self,
+        quantile: M::Probability,
+    ) -> (
+        M::Symbol,
+        M::Probability,
+        <M::Probability as BitArray>::NonZero,
+    ) {
+        self.0.quantile_function(quantile)
+    }
+}
+
+macro_rules! impl_dyn_precision_model {
+    ($($probability:ty),*) => {
+        $(
+            impl<Symbol> EntropyModel<{ <$probability>::BITS as usize }>
+                for DynPrecisionModel<'_, Symbol, $probability>
+            {
+                type Symbol = Symbol;
+                type Probability = $probability;
+            }
+
+            impl<Symbol> EncoderModel<{ <$probability>::BITS as usize }>
+                for DynPrecisionModel<'_, Symbol, $probability>
+            {
+                #[inline]
+                fn left_cumulative_and_probability(
+                    &self,
+                    symbol: impl Borrow<Symbol>,
+                ) -> Option<(
+                    $probability,
+                    <$probability as BitArray>::NonZero,
+                )> {
+                    let shift = <$probability>::BITS as usize - self.wrapped_precision;
+                    let (left_cumulative, probability) =
+                        self.inner.left_cumulative_and_probability(symbol.borrow())?;
+                    let probability = (probability.get() << shift)
+                        .into_nonzero()
+                        .expect("scaled probability fits into `Probability::BITS` bits");
+                    Some((left_cumulative << shift, probability))
+                }
+            }
+
+            impl<Symbol> DecoderModel<{ <$probability>::BITS as usize }>
+                for DynPrecisionModel<'_, Symbol, $probability>
+            {
+                #[inline]
+                fn quantile_function(
+                    &self,
+                    quantile: $probability,
+                ) -> (
+                    Symbol,
+                    $probability,
+                    <$probability as BitArray>::NonZero,
+                ) {
+                    let shift = <$probability>::BITS as usize - self.wrapped_precision;
+                    let (symbol, left_cumulative, probability) =
+                        self.inner.quantile_function(quantile >> shift);
+                    let probability = (probability.get() << shift)
+                        .into_nonzero()
+                        .expect("scaled probability fits into `Probability::BITS` bits");
+                    (symbol, left_cumulative << shift, probability)
+                }
+            }
+        )*
+    };
+}
+
+impl_dyn_precision_model!(u8, u16, u32, u64);
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec::Vec};
+
+    use probability::distribution::Gaussian;
+    use rand::{RngCore, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    use super::{super::DefaultLeakyQuantizer, *};
+    use crate::stream::{queue::DefaultRangeEncoder, stack::DefaultAnsCoder, Decode, Encode};
+
+    /// Checks that the scaled intervals of all `symbols` tile the range `0..2^32`.
+    fn verify_intervals<Symbol: PartialEq + core::fmt::Debug>(
+        model: &DynPrecisionModel<'_, Symbol, u32>,
+        symbols: impl IntoIterator<Item = Symbol>,
+    ) {
+        let mut expected_left_cumulative = 0u64;
+        for symbol in symbols {
+            let (left_cumulative, probability) =
+                model.left_cumulative_and_probability(&symbol).unwrap();
+            assert_eq!(left_cumulative as u64, expected_left_cumulative);
+            let right = left_cumulative
+                .wrapping_add(probability.get())
+                .wrapping_sub(1);
+            for quantile in [left_cumulative, right] {
+                let (decoded, decoded_left_cumulative, decoded_probability) =
+                    model.quantile_function(quantile);
+                assert_eq!(decoded, symbol);
+                assert_eq!(decoded_left_cumulative, left_cumulative);
+                assert_eq!(decoded_probability, probability);
+            }
+            expected_left_cumulative += probability.get() as u64;
+        }
+        assert_eq!(expected_left_cumulative, 1 << 32);
+    }
+
+    #[test]
+    fn runtime_precision() {
+        let probabilities = [0.05, 0.3, 0.01, 0.24, 0.4];
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2278);
+        let symbols = (0..1000)
+            .map(|_| rng.next_u32() as usize % probabilities.len())
+            .collect::<Vec<_>>();
+
+        for precision in [3, 8, 12, 16, 24, 31, 32] {
+            // Simulate a precision that is only known at runtime.
+            let precision = precision.to_string().parse::<usize>().unwrap();
+            let model =
+                DynPrecisionModel::from_floating_point_probabilities(&probabilities, precision)
+                    .unwrap();
+            assert_eq!(model.wrapped_precision(), precision);
+            verify_intervals(&model, 0..probabilities.len());
+
+            let mut ans = DefaultAnsCoder::new();
+            ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+            let decoded = ans
+                .decode_iid_symbols(symbols.len(), &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(ans.is_empty());
+
+            let mut encoder = DefaultRangeEncoder::new();
+            encoder.encode_iid_symbols(&symbols, &model).unwrap();
+            let mut decoder = encoder.into_decoder().unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+        }
+
+        for precision in [0, 2, 33] {
+            assert!(DynPrecisionModel::from_floating_point_probabilities(
+                &probabilities,
+                precision
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn wrap_static_precision() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = DynPrecisionModel::new(quantizer.quantize(Gaussian::new(3.2, 5.1)));
+        assert_eq!(model.wrapped_precision(), 24);
+        verify_intervals(&model, -100..=100);
+
+        let symbols = [-5, 3, 0, 12, -100, 100, 7];
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let decoded = ans
+            .decode_iid_symbols(symbols.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(ans.encode_symbol(101, &model).is_err());
+    }
+}
@@ -0,0 +1,335 @@
+use core::borrow::Borrow;
+
+use num_traits::AsPrimitive;
+
+use crate::{generic_static_asserts, BitArray};
+
+use super::{DecoderModel, EncoderModel, EntropyModel, IterableEntropyModel};
+
+/// Type alias for a typical [`Geometric`] model.
+///
+/// See:
+/// - [`Geometric`]
+/// - [discussion of presets](crate::stream#presets)
+pub type DefaultGeometric = Geometric<u32, 24>;
+
+/// Type alias for a [`Geometric`] model that is easier to use within a sequence of
+/// compressed symbols that also involves some lookup models.
+///
+/// See:
+/// - [`Geometric`]
+/// - [discussion of presets](crate::stream#presets)
+pub type SmallGeometric = Geometric<u16, 12>;
+
+/// An entropy model for geometrically distributed non-negative integers.
+///
+/// Models the number of failures `k = 0, 1, 2, ...` before the first success in a sequence
+/// of Bernoulli trials with a given `success_probability`, i.e., the symbol `k` has
+/// probability `success_probability * (1 - success_probability)^k`. This is typical for,
+/// e.g., run lengths. The support is clamped to `0..=max_symbol`, where the symbol
+/// `max_symbol` takes up the entire probability mass of the tail `k >= max_symbol`. Thus,
+/// encoding a symbol `k > max_symbol` encodes `max_symbol` instead (so it decodes to
+/// `max_symbol`).
+///
+/// The fixed-point representation is leak-free: every symbol in `0..=max_symbol` has a
+/// nonzero probability, and the probabilities add up to exactly `2^PRECISION`. Both
+/// encoding and decoding evaluate the cumulative distribution function (or its inverse) in
+/// closed form, so they take constant time in the typical case (decoding falls back to a
+/// binary search if rounding errors make the closed-form inverse miss the correct symbol).
+/// The model doesn't need any memory proportional to `max_symbol`.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{model::DefaultGeometric, stack::DefaultAnsCoder, Decode, Encode};
+///
+/// // Run lengths with mean (1 - 0.2) / 0.2 = 4.
+/// let model = DefaultGeometric::new(0.2, 1000).unwrap();
+///
+/// let run_lengths = [3, 0, 7, 1, 4, 12, 0, 2];
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_iid_symbols_reverse(&run_lengths, &model).unwrap();
+///
+/// let decoded = ans
+///     .decode_iid_symbols(run_lengths.len(), &model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, run_lengths);
+///
+/// // Symbols beyond `max_symbol` are clamped to `max_symbol`.
+/// ans.encode_symbol(1234, &model).unwrap();
+/// assert_eq!(ans.decode_symbol(&model).unwrap(), 1000);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Geometric<Probability: BitArray, const PRECISION: usize> {
+    /// Probability mass in excess of the minimal mass of one for each symbol.
+    slack: Probability,
+
+    /// `1 - success_probability`.
+    ratio: f64,
+
+    /// `ln(ratio)`, cached for the inverse cumulative distribution function.
+    ln_ratio: f64,
+
+    max_symbol: usize,
+}
+
+impl<Probability: BitArray, const PRECISION: usize> Geometric<Probability, PRECISION>
+where
+    Probability: AsPrimitive<u128>,
+    u128: AsPrimitive<Probability>,
+{
+    /// Creates a model over the symbols `0..=max_symbol`, where `max_symbol` absorbs the
+    /// probability mass of all larger symbols.
+    ///
+    /// Returns `Err(())` if `success_probability` is not within `(0, 1]`, if
+    /// `max_symbol == 0`, or if `max_symbol` is too large to assign a nonzero probability
+    /// to each symbol at the given `PRECISION`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(success_probability: f64, max_symbol: usize) -> Result<Self, ()> {
+        generic_static_asserts!(
+            (Probability: BitArray; const PRECISION: usize);
+            PROBABILITY_MUST_SUPPORT_PRECISION: PRECISION <= Probability::BITS;
+            PRECISION_MUST_BE_NONZERO: PRECISION > 0;
+        );
+
+        if !(0.0..=1.0).contains(&success_probability)
+            || success_probability == 0.0
+            || max_symbol == 0
+        {
+            return Err(());
+        }
+
+        let total = 1u128 << PRECISION;
+        let max_symbol_u128 = max_symbol as u128;
+        if max_symbol_u128 >= total {
+            return Err(());
+        }
+
+        let ratio = 1.0 - success_probability;
+        Ok(Self {
+            slack: (total - max_symbol_u128 - 1).as_(),
+            ratio,
+            ln_ratio: libm::log(ratio),
+            max_symbol,
+        })
+    }
+
+    /// Returns the left-sided cumulative of `symbol`, where `symbol <= max_symbol + 1`.
+    #[inline]
+    fn left_cumulative(&self, symbol: usize) -> u128 {
+        if symbol > self.max_symbol {
+            return 1u128 << PRECISION;
+        }
+
+        // Each symbol gets a probability mass of one plus its share of `slack`.
+        let slack: u128 = self.slack.as_();
+        let cdf = 1.0 - libm::pow(self.ratio, symbol as f64);
+        let share = core::cmp::min((slack as f64 * cdf) as u128, slack);
+        symbol as u128 + share
+    }
+
+    #[inline]
+    fn interval(&self, symbol: usize) -> (Probability, Probability::NonZero) {
+        let left_cumulative = self.left_cumulative(symbol);
+        let probability = self.left_cumulative(symbol + 1) - left_cumulative;
+        (
+            left_cumulative.as_(),
+            probability.as_().into_nonzero().expect("CDF is monotonic"),
+        )
+    }
+
+    /// Returns the largest symbol that has nonzero probability under the model.
+    pub fn max_symbol(&self) -> usize {
+        self.max_symbol
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for Geometric<Probability, PRECISION>
+{
+    type Symbol = usize;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for Geometric<Probability, PRECISION>
+where
+    Probability: AsPrimitive<u128>,
+    u128: AsPrimitive<Probability>,
+{
+    #[inline]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Self::Symbol>,
+    ) -> Option<(Self::Probability, <Self::Probability as BitArray>::NonZero)> {
+        let symbol = core::cmp::min(*symbol.borrow(), self.max_symbol);
+        Some(self.interval(symbol))
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for Geometric<Probability, PRECISION>
+where
+    Probability: AsPrimitive<u128>,
+    u128: AsPrimitive<Probability>,
+{
+    fn quantile_function(
+        &self,
+        quantile: Self::Probability,
+    ) -> (
+        Self::Symbol,
+        Self::Probability,
+        <Self::Probability as BitArray>::NonZero,
+    ) {
+        let quantile: u128 = quantile.as_();
+
+        // Invariant: `left_cumulative(low) <= quantile < left_cumulative(high)`.
+        let mut low = 0;
+        let mut high = self.max_symbol + 1;
+
+        // Guess the symbol by inverting the closed-form CDF while ignoring the minimal mass
+        // of one per symbol. The cast saturates (and maps `NaN` to zero), so the guess is
+        // always within bounds after clamping. Use it as the first pivot of a binary search.
+        let slack: u128 = self.slack.as_();
+        let fraction = 1.0 - quantile as f64 / slack as f64;
+        let guess = (libm::log(fraction) / self.ln_ratio) as usize;
+        let guess = core::cmp::min(guess, self.max_symbol);
+        if self.left_cumulative(guess) <= quantile {
+            low = guess;
+            if self.left_cumulative(guess + 1) > quantile {
+                high = guess + 1;
+            }
+        } else {
+            high = guess;
+        }
+
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if self.left_cumulative(mid) <= quantile {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        let (left_cumulative, probability) = self.interval(low);
+        (low, left_cumulative, probability)
+    }
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for Geometric<Probability, PRECISION>
+where
+    Probability: AsPrimitive<u128>,
+    u128: AsPrimitive<Probability>,
+{
+    fn symbol_table(
+        &'m self,
+    ) -> impl Iterator<
+        Item = (
+            Self::Symbol,
+            Self::Probability,
+            <Self::Probability as BitArray>::NonZero,
+        ),
+    > {
+        (0..=self.max_symbol).map(move |symbol| {
+            let (left_cumulative, probability) = self.interval(symbol);
+            (symbol, left_cumulative, probability)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand::{Rng, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    use super::{super::tests::test_entropy_model, *};
+    use crate::stream::{stack::DefaultAnsCoder, Decode};
+
+    #[test]
+    fn geometric() {
+        for (success_probability, max_symbol) in [
+            (0.2, 100),
+            (0.5, 10),
+            (1.0, 10),
+            (1e-3, 1000),
+            (0.999, 1),
+            (0.01, 4095),
+        ] {
+            let model = DefaultGeometric::new(success_probability, max_symbol).unwrap();
+            test_entropy_model(&model, 0..=max_symbol);
+
+            let model = SmallGeometric::new(success_probability, max_symbol).unwrap();
+            test_entropy_model(&model, 0..=max_symbol);
+            for symbol in [max_symbol + 1, max_symbol + 100, usize::MAX] {
+                assert_eq!(
+                    model.left_cumulative_and_probability(symbol),
+                    model.left_cumulative_and_probability(max_symbol)
+                );
+            }
+
+            // Every quantile maps to the interval that contains it.
+            for quantile in 0..1 << 12 {
+                let (symbol, left_cumulative, probability) = model.quantile_function(quantile);
+                assert!(left_cumulative <= quantile);
+                assert!(quantile - left_cumulative < probability.get());
+                assert_eq!(
+                    model.left_cumulative_and_probability(symbol),
+                    Some((left_cumulative, probability))
+                );
+            }
+        }
+
+        assert!(DefaultGeometric::new(0.5, 0).is_err());
+        assert!(DefaultGeometric::new(0.0, 10).is_err());
+        assert!(DefaultGeometric::new(-0.1, 10).is_err());
+        assert!(DefaultGeometric::new(1.5, 10).is_err());
+        assert!(DefaultGeometric::new(f64::NAN, 10).is_err());
+        assert!(SmallGeometric::new(0.5, 4095).is_ok());
+        assert!(SmallGeometric::new(0.5, 4096).is_err());
+    }
+
+    #[test]
+    fn bitrate() {
+        #[cfg(not(miri))]
+        let amt = 10_000;
+
+        #[cfg(miri)]
+        let amt = 100;
+
+        let success_probability = 0.1f64;
+        let model = DefaultGeometric::new(success_probability, 1000).unwrap();
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2276);
+        let ratio = 1.0 - success_probability;
+        let symbols = (0..amt)
+            .map(|_| {
+                let uniform = 1.0 - rng.gen::<f64>();
+                core::cmp::min((uniform.ln() / ratio.ln()) as usize, 1000)
+            })
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+        let entropy = (-success_probability * success_probability.log2() - ratio * ratio.log2())
+            / success_probability;
+        let bitrate = ans.num_valid_bits() as f64 / amt as f64;
+
+        let decoded = ans
+            .decode_iid_symbols(amt, model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(ans.is_empty());
+
+        #[cfg(not(miri))]
+        assert!((bitrate - entropy).abs() < 0.02 * entropy);
+
+        #[cfg(miri)]
+        assert!((bitrate - entropy).abs() < 0.2 * entropy);
+    }
+}
@@ -0,0 +1,377 @@
+use core::borrow::Borrow;
+
+use alloc::vec::Vec;
+
+use num_traits::AsPrimitive;
+
+use crate::BitArray;
+
+use super::{
+    remapped::RemappedCdf, DecoderModel, EncoderModel, EntropyModel, IterableEntropyModel,
+};
+
+/// A linear combination of two entropy models over the same symbols.
+///
+/// Combines two entropy models `A` and `B` into the mixture `(1 - weight) * A + weight *
+/// B`, where `weight` is given in fixed-point representation with `PRECISION` bits (see
+/// [`from_fixed_point_weight`](Self::from_fixed_point_weight)). The mixture is calculated
+/// in fixed point arithmetic such that probabilities still sum up to exactly
+/// `2^PRECISION`, and such that every symbol retains a nonzero probability (even if it has
+/// zero weight under one of the components or if `weight` is zero or one). To this end,
+/// the constructor reserves a probability of one (in fixed-point representation) for each
+/// symbol, so the mixture's probabilities deviate from the exact weighted sum by a relative
+/// error of about `N / 2^PRECISION`, where `N` is the number of symbols, plus rounding
+/// errors of a few units in the last place.
+///
+/// A typical use case is robust coding: mixing a learned model `A` with a simple
+/// fallback model `B` (e.g., a [`UniformModel`](super::UniformModel)) at a small weight
+/// guarantees that no symbol costs more than about `log2(1 / (weight * p_B))` bits, even
+/// if the learned model is badly wrong. For the special case where `B` is uniform over the
+/// support of `A`, you can also use [`Smoothed`](super::Smoothed).
+///
+/// Both models have to have finite support, and they have to assign nonzero probability to
+/// the same symbols, in the same order (as reported by
+/// [`symbol_table`](IterableEntropyModel::symbol_table)). This is, e.g., the case for any
+/// two [`LeakilyQuantizedDistribution`](super::LeakilyQuantizedDistribution)s from the same
+/// quantizer, or for any two contiguous categorical or uniform models with the same number
+/// of symbols. The constructor has to iterate over the support of both models, and
+/// encoding and decoding then delegate to `A` and map the result onto the mixture with a
+/// binary search.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::{
+///         DefaultContiguousCategoricalEntropyModel, DefaultUniformModel, EncoderModel,
+///         MixtureModel,
+///     },
+///     stack::DefaultAnsCoder,
+///     Decode,
+/// };
+///
+/// // A learned model that turns out to be overconfident ...
+/// let learned =
+///     DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+///         &[0.9, 0.1, 0.0, 0.0],
+///         None,
+///     )
+///     .unwrap();
+/// // ... mixed with a uniform fallback model at a weight of 1/16.
+/// let fallback = DefaultUniformModel::new(4);
+/// let mixture = MixtureModel::new(learned, fallback, 1.0 / 16.0).unwrap();
+///
+/// // Every symbol now has a probability of at least about (1/16) * (1/4) = 1/64.
+/// let (_, probability) = mixture.left_cumulative_and_probability(3).unwrap();
+/// assert!(probability.get() >= (1 << 24) / 64 - 4);
+///
+/// let symbols = [0, 3, 1, 2, 0];
+/// let mut coder = DefaultAnsCoder::new();
+/// coder.encode_iid_symbols_reverse(&symbols, &mixture).unwrap();
+/// let decoded = coder
+///     .decode_iid_symbols(5, &mixture)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MixtureModel<A, B, const PRECISION: usize>
+where
+    A: EntropyModel<PRECISION>,
+{
+    first: A,
+    second: B,
+    cdf: RemappedCdf<A::Probability, PRECISION>,
+}
+
+impl<A, B, const PRECISION: usize> MixtureModel<A, B, PRECISION>
+where
+    A: for<'m> IterableEntropyModel<'m, PRECISION>,
+    B: for<'m> IterableEntropyModel<
+        'm,
+        PRECISION,
+        Symbol = A::Symbol,
+        Probability = A::Probability,
+    >,
+    A::Symbol: PartialEq,
+    A::Probability: AsPrimitive<u128>,
+    u128: AsPrimitive<A::Probability>,
+{
+    /// Mixes `first` and `second` with weights `1.0 - weight` and `weight`, respectively.
+    ///
+    /// Rounds `weight` to the nearest fixed-point value and then calls
+    /// [`from_fixed_point_weight`](Self::from_fixed_point_weight). Returns `Err(())` unless
+    /// `0.0 <= weight <= 1.0`, or if `from_fixed_point_weight` would return an error.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(first: A, second: B, weight: f64) -> Result<Self, ()> {
+        if !(0.0..=1.0).contains(&weight) {
+            return Err(());
+        }
+
+        let weight = libm::round(weight * (1u128 << PRECISION) as f64) as u128;
+        Self::mix(first, second, weight)
+    }
+
+    /// Mixes `first` and `second` with a fixed-point weight.
+    ///
+    /// The mixture is `(1 - w) * first + w * second`, where `w = weight / 2^PRECISION`.
+    /// The argument `weight` is a `u128` rather than an `A::Probability` so that it can
+    /// represent the full range `0..=2^PRECISION` even if `PRECISION` equals the number of
+    /// bits of `A::Probability`. Returns `Err(())` if `weight > 2^PRECISION` or if `first`
+    /// and `second` don't assign nonzero probability to the same symbols in the same order.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_fixed_point_weight(first: A, second: B, weight: u128) -> Result<Self, ()> {
+        Self::mix(first, second, weight)
+    }
+
+    fn mix(first: A, second: B, weight: u128) -> Result<Self, ()> {
+        let total = 1u128 << PRECISION;
+        if weight > total {
+            return Err(());
+        }
+
+        let mut second_cdf = Vec::new();
+        {
+            let mut first_symbols = first.symbol_table();
+            for (symbol, left_cumulative, _) in second.symbol_table() {
+                match first_symbols.next() {
+                    Some((first_symbol, _, _)) if first_symbol == symbol => {}
+                    _ => return Err(()),
+                }
+                second_cdf.push(left_cumulative.as_());
+            }
+            if first_symbols.next().is_some() {
+                return Err(());
+            }
+        }
+
+        let cdf = RemappedCdf::new(&first, |probabilities| {
+            let num_symbols = probabilities.len() as u128;
+
+            // Each symbol first gets a probability of one, and the remaining mass is
+            // distributed according to the (rounded) exact mixture. As in `Smoothed`,
+            // rescaling cumulatives rather than probabilities makes sure that the total is
+            // exactly `2^PRECISION`. The mixed cumulatives are monotonic, so the rescaled
+            // cumulatives (plus `index`) are strictly increasing.
+            let remaining = total - num_symbols;
+            let mut first_cumulative = 0u128;
+            let mixed_cdf = probabilities
+                .iter()
+                .zip(&second_cdf)
+                .enumerate()
+                .map(|(index, (&probability, &second_cumulative))| {
+                    let mixed =
+                        (first_cumulative * (total - weight) + second_cumulative * weight) / total;
+                    first_cumulative += probability;
+                    index as u128 + mixed * remaining / total
+                })
+                .collect::<Vec<_>>();
+            Some(mixed_cdf)
+        })
+        .ok_or(())?;
+
+        Ok(Self { first, second, cdf })
+    }
+}
+
+impl<A, B, const PRECISION: usize> MixtureModel<A, B, PRECISION>
+where
+    A: EntropyModel<PRECISION>,
+{
+    /// Returns a reference to the first component (with weight `1 - weight`).
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// Returns a reference to the second component (with weight `weight`).
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    /// Consumes the mixture and returns both components.
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A, B, const PRECISION: usize> EntropyModel<PRECISION> for MixtureModel<A, B, PRECISION>
+where
+    A: EntropyModel<PRECISION>,
+{
+    type Symbol = A::Symbol;
+    type Probability = A::Probability;
+}
+
+impl<A, B, const PRECISION: usize> EncoderModel<PRECISION> for MixtureModel<A, B, PRECISION>
+where
+    A: EncoderModel<PRECISION>,
+{
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Self::Symbol>,
+    ) -> Option<(Self::Probability, <Self::Probability as BitArray>::NonZero)> {
+        let (left_cumulative, _) = self.first.left_cumulative_and_probability(symbol)?;
+        self.cdf.remap_interval(left_cumulative)
+    }
+}
+
+impl<A, B, const PRECISION: usize> DecoderModel<PRECISION> for MixtureModel<A, B, PRECISION>
+where
+    A: DecoderModel<PRECISION>,
+{
+    fn quantile_function(
+        &self,
+        quantile: Self::Probability,
+    ) -> (
+        Self::Symbol,
+        Self::Probability,
+        <Self::Probability as BitArray>::NonZero,
+    ) {
+        let (original_left_cumulative, left_cumulative, probability) =
+            self.cdf.quantile_function(quantile);
+        let (symbol, _, _) = self.first.quantile_function(original_left_cumulative);
+        (symbol, left_cumulative, probability)
+    }
+}
+
+impl<'m, A, B, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for MixtureModel<A, B, PRECISION>
+where
+    A: IterableEntropyModel<'m, PRECISION> + 'm,
+    B: 'm,
+{
+    fn symbol_table(
+        &'m self,
+    ) -> impl Iterator<
+        Item = (
+            Self::Symbol,
+            Self::Probability,
+            <Self::Probability as BitArray>::NonZero,
+        ),
+    > {
+        self.first
+            .symbol_table()
+            .enumerate()
+            .map(move |(index, (symbol, _, _))| {
+                let (left_cumulative, probability) = self.cdf.interval(index);
+                (symbol, left_cumulative, probability)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use probability::distribution::Gaussian;
+
+    use super::{
+        super::{
+            tests::test_entropy_model, ContiguousCategoricalEntropyModel,
+            DefaultContiguousCategoricalEntropyModel, DefaultLeakyQuantizer, DefaultUniformModel,
+            SmallContiguousCategoricalEntropyModel, UniformModel,
+        },
+        *,
+    };
+    use crate::stream::{stack::DefaultAnsCoder, Decode};
+
+    #[test]
+    fn weighted_combination() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let first = quantizer.quantize(Gaussian::new(-20.0, 5.0));
+        let second = quantizer.quantize(Gaussian::new(30.0, 40.0));
+
+        for weight in [0.0, 0.01, 0.3, 0.5, 0.99, 1.0] {
+            let mixture = MixtureModel::new(first, second, weight).unwrap();
+            test_entropy_model(&mixture, -100..=100);
+
+            // The rescaling to make room for a minimal probability of one per symbol
+            // introduces a relative error of `201 / 2^24`, plus rounding errors.
+            for ((symbol, _, probability), ((_, _, p1), (_, _, p2))) in mixture
+                .symbol_table()
+                .zip(first.symbol_table().zip(second.symbol_table()))
+            {
+                let expected = (1.0 - weight) * p1.get() as f64 + weight * p2.get() as f64;
+                let probability = probability.get() as f64;
+                assert!(
+                    (probability - expected).abs() <= 3.0 + expected * 201.0 / (1 << 24) as f64,
+                    "symbol {}: {} vs. {}",
+                    symbol,
+                    probability,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn leaky_and_round_trip() {
+        // The first component assigns the smallest representable probability to some
+        // symbols, and the second one to different symbols.
+        let first = SmallContiguousCategoricalEntropyModel::<Vec<u16>>::from_nonzero_fixed_point_probabilities(
+            [4000u16, 1, 1, 94],
+            false,
+        )
+        .unwrap();
+        let second = SmallContiguousCategoricalEntropyModel::<Vec<u16>>::from_nonzero_fixed_point_probabilities(
+            [1u16, 1, 4093, 1],
+            false,
+        )
+        .unwrap();
+        for weight in [0, 1, 100, 2048, 4000, 4096] {
+            let mixture =
+                MixtureModel::from_fixed_point_weight(first.clone(), second.clone(), weight)
+                    .unwrap();
+            test_entropy_model(&mixture, 0..4);
+        }
+        assert!(
+            MixtureModel::from_fixed_point_weight(first.clone(), second.clone(), 4097).is_err()
+        );
+
+        let mixture = MixtureModel::new(first, second, 0.5).unwrap();
+        let symbols = (0..1000).map(|i| (i * 7) % 4).collect::<Vec<_>>();
+        let mut coder = DefaultAnsCoder::new();
+        coder
+            .encode_iid_symbols_reverse(&symbols, &mixture)
+            .unwrap();
+        let decoded = coder
+            .decode_iid_symbols(symbols.len(), &mixture)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(coder.is_empty());
+    }
+
+    #[test]
+    fn incompatible_models() {
+        let categorical =
+            DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                &[0.2f64, 0.5, 0.3],
+                None,
+            )
+            .unwrap();
+        assert!(MixtureModel::new(categorical.clone(), DefaultUniformModel::new(3), 0.1).is_ok());
+        assert!(MixtureModel::new(categorical.clone(), DefaultUniformModel::new(4), 0.1).is_err());
+        assert!(MixtureModel::new(categorical.clone(), DefaultUniformModel::new(2), 0.1).is_err());
+        assert!(MixtureModel::new(categorical.clone(), DefaultUniformModel::new(3), 1.5).is_err());
+        assert!(MixtureModel::new(categorical.clone(), DefaultUniformModel::new(3), -0.1).is_err());
+    }
+
+    #[test]
+    fn full_precision_weight() {
+        // For `PRECISION == Probability::BITS`, the weight `2^PRECISION` doesn't fit into a
+        // `Probability`.
+        let first = ContiguousCategoricalEntropyModel::<u16, Vec<u16>, 16>::from_floating_point_probabilities_fast(
+            &[0.7f64, 0.2, 0.1],
+            None,
+        )
+        .unwrap();
+        let second = UniformModel::<u16, 16>::new(3);
+        for weight in [0, 1 << 15, 1 << 16] {
+            let mixture =
+                MixtureModel::from_fixed_point_weight(first.clone(), second, weight).unwrap();
+            test_entropy_model(&mixture, 0..3);
+        }
+        assert!(MixtureModel::from_fixed_point_weight(first, second, (1 << 16) + 1).is_err());
+    }
+}
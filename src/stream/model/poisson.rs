@@ -0,0 +1,223 @@
+use alloc::vec::Vec;
+use num_traits::AsPrimitive;
+
+use crate::BitArray;
+
+use super::ContiguousCategoricalEntropyModel;
+
+/// Type alias for a typical [`Poisson`] model.
+///
+/// See:
+/// - [`Poisson`]
+/// - [discussion of presets](crate::stream#presets)
+pub type DefaultPoisson = Poisson<u32, 24>;
+
+/// Type alias for a [`Poisson`] model that is easier to use within a sequence of
+/// compressed symbols that also involves some lookup models.
+///
+/// See:
+/// - [`Poisson`]
+/// - [discussion of presets](crate::stream#presets)
+pub type SmallPoisson = Poisson<u16, 12>;
+
+/// An entropy model for count data, truncated to a finite range.
+///
+/// A `Poisson` model with rate `lambda` is defined over the symbols `0..=max_symbol`, where
+/// each symbol `k < max_symbol` has (approximately) probability `lambda^k * exp(-lambda) /
+/// k!`, and the symbol `max_symbol` additionally absorbs the entire tail mass of the
+/// Poisson distribution, i.e., it stands for "`max_symbol` or more". The constructor
+/// evaluates the probability mass function once (in log space, so that the factorials
+/// can't overflow) and stores the resulting fixed-point cumulative distribution function
+/// in a table of size `max_symbol + 2`, so encoding and decoding have the same cost as with
+/// a [`ContiguousCategoricalEntropyModel`].
+///
+/// The fixed-point representation is leaky: every symbol in `0..=max_symbol` has a nonzero
+/// probability, so any count within range can be encoded. If you need to encode counts
+/// larger than `max_symbol`, encode `max_symbol` as an escape symbol followed by the
+/// excess with some other entropy model.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{model::DefaultPoisson, stack::DefaultAnsCoder, Decode};
+///
+/// // Photon counts with an expected value of 3.5 per pixel, capped at 50.
+/// let model = DefaultPoisson::new(3.5, 50).unwrap();
+///
+/// let counts = [3, 5, 2, 4, 0, 3, 11, 50];
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_iid_symbols_reverse(&counts, &model).unwrap();
+///
+/// let decoded = ans
+///     .decode_iid_symbols(counts.len(), &model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, counts);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Poisson<Probability: BitArray, const PRECISION: usize> {
+    model: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>,
+}
+
+impl<Probability: BitArray, const PRECISION: usize> Poisson<Probability, PRECISION>
+where
+    f64: AsPrimitive<Probability>,
+    Probability: AsPrimitive<usize>,
+    usize: AsPrimitive<Probability>,
+{
+    /// Constructs a model for counts with rate (i.e., expected value) `lambda`, truncated
+    /// to the range `0..=max_symbol`.
+    ///
+    /// Returns `Err(())` if `max_symbol == 0`, if `lambda` is negative or not finite, or if
+    /// `max_symbol` is too large to assign a nonzero probability to each symbol in
+    /// `0..=max_symbol` at the given `PRECISION`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(lambda: f64, max_symbol: u32) -> Result<Self, ()> {
+        if max_symbol == 0 || !lambda.is_finite() || lambda < 0.0 {
+            return Err(());
+        }
+
+        // Evaluate the PMF in log space to avoid overflow of the factorials.
+        let ln_lambda = libm::log(lambda);
+        let mut probabilities = (0..max_symbol)
+            .map(|k| {
+                let k_f64 = k as f64;
+                let mut ln_pmf = -lambda - libm::lgamma(k_f64 + 1.0);
+                if k != 0 {
+                    ln_pmf += k_f64 * ln_lambda;
+                }
+                libm::exp(ln_pmf)
+            })
+            .collect::<Vec<_>>();
+
+        // Fold the tail mass into the last symbol (rounding errors may make the sum slightly
+        // exceed one, but the fixed-point model is leaky anyway).
+        let tail = 1.0 - probabilities.iter().sum::<f64>();
+        probabilities.push(if tail > 0.0 { tail } else { 0.0 });
+
+        let model = ContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast::<f64>(
+            &probabilities,
+            None,
+        )?;
+        Ok(Self { model })
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> Poisson<Probability, PRECISION> {
+    /// Returns the largest symbol supported by the model, which also represents all larger
+    /// counts.
+    pub fn max_symbol(&self) -> usize {
+        self.model.support_size() - 1
+    }
+}
+
+forward_to_contiguous_categorical!(Poisson);
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand::{Rng, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    use super::{
+        super::{tests::test_entropy_model, EncoderModel, IterableEntropyModel},
+        *,
+    };
+    use crate::stream::{stack::DefaultAnsCoder, Decode};
+
+    #[test]
+    fn poisson() {
+        for (lambda, max_symbol) in [
+            (0.0, 1),
+            (0.0, 10),
+            (0.5, 1),
+            (3.5, 20),
+            (3.5, 2),
+            (100.0, 300),
+            (1000.0, 2000),
+            (1e6, 100),
+        ] {
+            let model = DefaultPoisson::new(lambda, max_symbol).unwrap();
+            assert_eq!(model.max_symbol(), max_symbol as usize);
+            test_entropy_model(&model, 0..=max_symbol as usize);
+
+            let model = SmallPoisson::new(lambda, max_symbol).unwrap();
+            test_entropy_model(&model, 0..=max_symbol as usize);
+            assert!(model
+                .left_cumulative_and_probability(max_symbol as usize + 1)
+                .is_none());
+        }
+
+        // The tail mass goes to the last symbol.
+        let model = DefaultPoisson::new(10.0, 5).unwrap();
+        let probabilities = model
+            .symbol_table()
+            .map(|(_, _, probability)| probability.get())
+            .collect::<Vec<_>>();
+        assert!(probabilities[5] > probabilities[4]);
+
+        assert!(DefaultPoisson::new(3.0, 0).is_err());
+        assert!(DefaultPoisson::new(-0.1, 10).is_err());
+        assert!(DefaultPoisson::new(f64::NAN, 10).is_err());
+        assert!(DefaultPoisson::new(f64::INFINITY, 10).is_err());
+        assert!(SmallPoisson::new(3.0, 2000).is_ok());
+        assert!(SmallPoisson::new(3.0, 4096).is_err());
+    }
+
+    #[test]
+    fn bitrate() {
+        #[cfg(not(miri))]
+        let amt = 10_000;
+
+        #[cfg(miri)]
+        let amt = 100;
+
+        let (lambda, max_symbol) = (4.2f64, 15u32);
+        let model = DefaultPoisson::new(lambda, max_symbol).unwrap();
+
+        // Sample by inversion of the CDF, truncating at `max_symbol`.
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2308);
+        let symbols = (0..amt)
+            .map(|_| {
+                let u = rng.gen::<f64>();
+                let (mut k, mut pmf) = (0, (-lambda).exp());
+                let mut cdf = pmf;
+                while cdf < u && k < max_symbol as usize {
+                    k += 1;
+                    pmf *= lambda / k as f64;
+                    cdf += pmf;
+                }
+                k
+            })
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let bitrate = ans.num_valid_bits() as f64 / amt as f64;
+
+        let decoded = ans
+            .decode_iid_symbols(amt, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(ans.is_empty());
+
+        // Entropy of the Poisson distribution, truncated at `max_symbol`.
+        let mut pmf = (-lambda).exp();
+        let mut entropy = 0.0;
+        let mut remaining = 1.0;
+        for k in 0..max_symbol {
+            entropy -= pmf * pmf.log2();
+            remaining -= pmf;
+            pmf *= lambda / (k + 1) as f64;
+        }
+        entropy -= remaining * remaining.log2();
+
+        #[cfg(not(miri))]
+        assert!((bitrate - entropy).abs() < 0.02 * entropy);
+
+        #[cfg(miri)]
+        assert!((bitrate - entropy).abs() < 0.2 * entropy);
+    }
+}
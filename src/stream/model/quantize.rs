@@ -803,6 +803,21 @@ where
             left_sided_cumulative: Probability::zero(),
         }
     }
+
+    #[inline(always)]
+    fn support_size(&'m self) -> usize {
+        let span = slack::<Probability, Symbol>(
+            self.quantizer.max_symbol_inclusive,
+            self.quantizer.min_symbol_inclusive,
+        );
+        // The support contains `span + 1 <= 1 << PRECISION` symbols (see
+        // `LeakyQuantizer::new`), which doesn't fit into a `usize` if `PRECISION` and the
+        // size of the support are both as large as the address space (e.g., a support of
+        // 2^32 symbols on a 32-bit platform).
+        span.to_usize()
+            .and_then(|span| span.checked_add(1))
+            .expect("support size must not exceed `usize::MAX`")
+    }
 }
 
 /// Iterator over the [`symbol_table`] of a [`LeakilyQuantizedDistribution`].
@@ -815,11 +830,11 @@ struct LeakilyQuantizedDistributionIter<Symbol, Probability, M, const PRECISION:
     left_sided_cumulative: Probability,
 }
 
-impl<'m, Symbol, Probability, D, const PRECISION: usize> Iterator
+impl<Symbol, Probability, D, const PRECISION: usize> Iterator
     for LeakilyQuantizedDistributionIter<
         Symbol,
         Probability,
-        &'m LeakilyQuantizedDistribution<f64, Symbol, Probability, D, PRECISION>,
+        &LeakilyQuantizedDistribution<f64, Symbol, Probability, D, PRECISION>,
         PRECISION,
     >
 where
@@ -841,7 +856,7 @@ where
             let next_symbol = symbol + Symbol::one();
             self.symbol = Some(next_symbol);
             let non_leaky: Probability = (self.model.quantizer.free_weight
-                * self.model.inner.distribution((symbol).into() - 0.5))
+                * self.model.inner.distribution(symbol.into() + 0.5))
             .as_();
             non_leaky + slack(next_symbol, self.model.quantizer.min_symbol_inclusive)
         };
@@ -903,6 +918,29 @@ mod tests {
         inner(Laplace::new(2.5, 1e-40));
     }
 
+    #[test]
+    fn symbol_table_matches_left_cumulative_and_probability() {
+        // Use distributions that are asymmetric with respect to the support so that
+        // evaluating the cumulative at the wrong side of a symbol would be detected.
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-20..=30);
+        let models = [
+            quantizer.quantize(Gaussian::new(3.7, 4.2)),
+            quantizer.quantize(Gaussian::new(-12.3, 1.5)),
+            quantizer.quantize(Gaussian::new(25.1, 10.0)),
+        ];
+        for model in &models {
+            let mut count = 0;
+            for (symbol, left_cumulative, probability) in model.symbol_table() {
+                assert_eq!(
+                    model.left_cumulative_and_probability(symbol),
+                    Some((left_cumulative, probability))
+                );
+                count += 1;
+            }
+            assert_eq!(count, 51);
+        }
+    }
+
     #[test]
     fn leakily_quantized_normal() {
         #[cfg(not(miri))]
@@ -994,6 +1032,162 @@ mod tests {
         }
     }
 
+    #[test]
+    fn laplace_bitrate() {
+        use alloc::vec::Vec;
+
+        use rand::{Rng, SeedableRng};
+        use rand_xoshiro::Xoshiro256StarStar;
+
+        use crate::stream::{stack::DefaultAnsCoder, Decode};
+
+        #[cfg(not(miri))]
+        let amt = 10_000;
+        #[cfg(miri)]
+        let amt = 100;
+
+        let support = -127..=127;
+        let quantizer = DefaultLeakyQuantizer::new(support.clone());
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2262);
+
+        for &(mean, b) in &[(0.0, 0.5), (-3.7, 2.0), (12.3, 10.0)] {
+            let distribution = Laplace::new(mean, b);
+            let model = quantizer.quantize(distribution);
+
+            // Sample from the continuous distribution via its closed-form inverse CDF.
+            let symbols = (0..amt)
+                .map(|_| {
+                    let x = distribution.inverse(rng.gen::<f64>());
+                    (x.round() as i32).clamp(*support.start(), *support.end())
+                })
+                .collect::<Vec<_>>();
+
+            let mut ans = DefaultAnsCoder::new();
+            ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+
+            let entropy = support
+                .clone()
+                .map(|x| {
+                    let x = x as f64;
+                    distribution.distribution(x + 0.5) - distribution.distribution(x - 0.5)
+                })
+                .filter(|&p| p > 0.0)
+                .map(|p| -p * p.log2())
+                .sum::<f64>();
+            let bitrate = ans.num_valid_bits() as f64 / amt as f64;
+
+            #[cfg(not(miri))]
+            assert!(
+                (bitrate - entropy).abs() < 0.02 * entropy,
+                "bitrate = {}, entropy = {}",
+                bitrate,
+                entropy
+            );
+            #[cfg(miri)]
+            let _ = (bitrate, entropy);
+
+            let decoded = ans
+                .decode_iid_symbols(amt, model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(ans.is_empty());
+        }
+    }
+
+    #[test]
+    fn leakily_quantized_logistic() {
+        #[cfg(not(miri))]
+        let (support, scales, means) = (
+            -127..=127,
+            [1e-40, 0.0001, 0.1, 3.5, 123.45, 1234.56],
+            [
+                -300.6, -127.5, -100.2, -4.5, 0.0, 50.3, 127.5, 180.2, 2000.0,
+            ],
+        );
+
+        // We use different settings when testing on miri so that the test time stays reasonable.
+        #[cfg(miri)]
+        let (support, scales, means) = (
+            -20..=20,
+            [1e-40, 0.0001, 3.5, 1234.56],
+            [-300.6, -20.5, -5.2, 8.5, 20.5, 2000.0],
+        );
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(support.clone());
+        for &scale in &scales {
+            for &mean in &means {
+                let distribution = Logistic::new(mean, scale);
+                super::super::tests::test_entropy_model(
+                    &quantizer.quantize(distribution),
+                    *support.start()..*support.end() + 1,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn logistic_bitrate() {
+        use alloc::vec::Vec;
+
+        use rand::{Rng, SeedableRng};
+        use rand_xoshiro::Xoshiro256StarStar;
+
+        use crate::stream::{stack::DefaultAnsCoder, Decode};
+
+        #[cfg(not(miri))]
+        let amt = 10_000;
+        #[cfg(miri)]
+        let amt = 100;
+
+        let support = -127..=127;
+        let quantizer = DefaultLeakyQuantizer::new(support.clone());
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2300);
+
+        for &(mean, scale) in &[(0.0, 0.3), (-3.7, 1.5), (12.3, 8.0)] {
+            let distribution = Logistic::new(mean, scale);
+            let model = quantizer.quantize(distribution);
+
+            // Sample from the continuous distribution via its closed-form inverse CDF.
+            let symbols = (0..amt)
+                .map(|_| {
+                    let x = distribution.inverse(rng.gen::<f64>());
+                    (x.round() as i32).clamp(*support.start(), *support.end())
+                })
+                .collect::<Vec<_>>();
+
+            let mut ans = DefaultAnsCoder::new();
+            ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+
+            let entropy = support
+                .clone()
+                .map(|x| {
+                    let x = x as f64;
+                    distribution.distribution(x + 0.5) - distribution.distribution(x - 0.5)
+                })
+                .filter(|&p| p > 0.0)
+                .map(|p| -p * p.log2())
+                .sum::<f64>();
+            let bitrate = ans.num_valid_bits() as f64 / amt as f64;
+
+            #[cfg(not(miri))]
+            assert!(
+                (bitrate - entropy).abs() < 0.02 * entropy,
+                "bitrate = {}, entropy = {}",
+                bitrate,
+                entropy
+            );
+            #[cfg(miri)]
+            let _ = (bitrate, entropy);
+
+            let decoded = ans
+                .decode_iid_symbols(amt, model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(ans.is_empty());
+        }
+    }
+
     #[test]
     fn leakily_quantized_binomial() {
         #[cfg(not(miri))]
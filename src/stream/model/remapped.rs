@@ -0,0 +1,116 @@
+use alloc::vec::Vec;
+
+use num_traits::AsPrimitive;
+
+use crate::{wrapping_pow2, BitArray, NonZeroBitArray};
+
+use super::IterableEntropyModel;
+
+/// Shared implementation of model decorators that redistribute the probability mass of a
+/// wrapped model with finite support (e.g., [`Capped`](super::Capped) and
+/// [`Smoothed`](super::Smoothed)).
+///
+/// Maps between the cumulatives of the wrapped model and the cumulatives of the
+/// redistributed model. Symbols are identified by their position in the `symbol_table` of
+/// the wrapped model, which we find by binary search over the wrapped model's cumulatives.
+#[derive(Debug, Clone)]
+pub(super) struct RemappedCdf<Probability, const PRECISION: usize> {
+    /// Left-sided cumulatives of the wrapped model, in the order of its `symbol_table`.
+    original_cdf: Vec<Probability>,
+
+    /// Left-sided cumulatives of the redistributed model, in the same order.
+    remapped_cdf: Vec<Probability>,
+}
+
+impl<Probability, const PRECISION: usize> RemappedCdf<Probability, PRECISION>
+where
+    Probability: BitArray + AsPrimitive<u128>,
+    u128: AsPrimitive<Probability>,
+{
+    /// Iterates over the `symbol_table` of `model` and redistributes its probabilities.
+    ///
+    /// The callback `remap` receives the probabilities of all symbols (in the order of the
+    /// `symbol_table`) and returns the left-sided cumulatives of the redistributed model.
+    /// The returned cumulatives must start at zero, be strictly increasing, and be smaller
+    /// than `2^PRECISION` so that every symbol retains a nonzero probability. Returns
+    /// `None` if `remap` returns `None`.
+    pub(super) fn new<'m, M>(
+        model: &'m M,
+        remap: impl FnOnce(&[u128]) -> Option<Vec<u128>>,
+    ) -> Option<Self>
+    where
+        M: IterableEntropyModel<'m, PRECISION, Probability = Probability>,
+    {
+        let mut original_cdf = Vec::new();
+        let mut probabilities = Vec::new();
+        for (_, left_cumulative, probability) in model.symbol_table() {
+            original_cdf.push(left_cumulative);
+            probabilities.push(probability.get().as_());
+        }
+
+        let remapped_cdf = remap(&probabilities)?;
+        debug_assert_eq!(remapped_cdf.len(), original_cdf.len());
+        debug_assert!(remapped_cdf.first() == Some(&0));
+        debug_assert!(remapped_cdf.windows(2).all(|pair| pair[0] < pair[1]));
+        debug_assert!(remapped_cdf
+            .last()
+            .map_or(true, |&last| last < 1 << PRECISION));
+
+        Some(Self {
+            original_cdf,
+            remapped_cdf: remapped_cdf.into_iter().map(AsPrimitive::as_).collect(),
+        })
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> RemappedCdf<Probability, PRECISION> {
+    /// Returns the left-sided cumulative and the probability of the symbol at position
+    /// `index` under the redistributed model.
+    #[inline]
+    pub(super) fn interval(&self, index: usize) -> (Probability, Probability::NonZero) {
+        let left_cumulative = self.remapped_cdf[index];
+        let right_cumulative = self
+            .remapped_cdf
+            .get(index + 1)
+            .copied()
+            .unwrap_or(wrapping_pow2(PRECISION));
+        let probability = right_cumulative.wrapping_sub(&left_cumulative);
+
+        // SAFETY: the constructor ensures that every symbol has a nonzero probability.
+        let probability = unsafe { probability.into_nonzero_unchecked() };
+        (left_cumulative, probability)
+    }
+
+    /// Maps the left-sided cumulative of a symbol under the wrapped model to its interval
+    /// under the redistributed model.
+    #[inline]
+    pub(super) fn remap_interval(
+        &self,
+        original_left_cumulative: Probability,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        let index = self
+            .original_cdf
+            .binary_search(&original_left_cumulative)
+            .ok()?;
+        Some(self.interval(index))
+    }
+
+    /// Finds the symbol whose interval under the redistributed model contains `quantile`.
+    ///
+    /// Returns the tuple `(original_left_cumulative, left_cumulative, probability)`, where
+    /// `original_left_cumulative` can be passed to the wrapped model's quantile function to
+    /// identify the symbol.
+    #[inline]
+    pub(super) fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (Probability, Probability, Probability::NonZero) {
+        // The first entry of `remapped_cdf` is zero, so `partition_point` returns at least 1.
+        let index = self
+            .remapped_cdf
+            .partition_point(|&left_cumulative| left_cumulative <= quantile)
+            - 1;
+        let (left_cumulative, probability) = self.interval(index);
+        (self.original_cdf[index], left_cumulative, probability)
+    }
+}
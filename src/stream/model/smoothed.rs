@@ -0,0 +1,278 @@
+use core::borrow::Borrow;
+
+use alloc::vec::Vec;
+
+use num_traits::AsPrimitive;
+
+use crate::BitArray;
+
+use super::{
+    remapped::RemappedCdf, DecoderModel, EncoderModel, EntropyModel, IterableEntropyModel,
+};
+
+/// A decorator that mixes an entropy model with a uniform distribution over its support.
+///
+/// Wraps an entropy model `M` with finite support and replaces it with the mixture `(1 -
+/// weight) * M + weight * Uniform`, where `Uniform` is the uniform distribution over the
+/// support of `M`. The mixture is calculated in fixed point arithmetic such that
+/// probabilities still sum up to exactly `2^PRECISION`. Every symbol receives at least
+/// the probability `u = floor(weight * 2^PRECISION / N)`, where `N` is the size of the
+/// support, so no symbol costs more than `PRECISION - log2(u)` bits to encode.
+///
+/// This is useful for models that are estimated from limited data (e.g., adaptive models
+/// or models learned on a training set), where an unexpected symbol would otherwise cost
+/// an excessive number of bits. Compared to [`Capped`](super::Capped), which only moves
+/// probability mass to symbols that fall below a threshold, `Smoothed` shrinks all
+/// probabilities of `M` by the same factor.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::{DefaultContiguousCategoricalEntropyModel, EncoderModel, Smoothed},
+///     stack::DefaultAnsCoder,
+///     Decode,
+/// };
+///
+/// // A model that was estimated from few samples and assigns the smallest representable
+/// // probability to symbol 3.
+/// let probabilities = [0.5, 0.3, 0.2, 0.0];
+/// let model =
+///     DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+///         &probabilities,
+///         None,
+///     )
+///     .unwrap();
+///
+/// // Mixing in 1% of a uniform distribution gives each symbol at least a probability of
+/// // 0.01 / 4, i.e., no symbol costs more than about 8.6 bits.
+/// let smoothed = Smoothed::with_weight(model, 0.01).unwrap();
+/// let (_, probability) = smoothed.left_cumulative_and_probability(3).unwrap();
+/// assert!(probability.get() >= (0.01 / 4.0 * (1 << 24) as f64) as u32);
+///
+/// let symbols = [0, 3, 1, 3, 2];
+/// let mut coder = DefaultAnsCoder::new();
+/// coder.encode_iid_symbols_reverse(&symbols, &smoothed).unwrap();
+/// let decoded = coder
+///     .decode_iid_symbols(5, &smoothed)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Smoothed<M, const PRECISION: usize>
+where
+    M: EntropyModel<PRECISION>,
+{
+    inner: M,
+    cdf: RemappedCdf<M::Probability, PRECISION>,
+}
+
+impl<M, const PRECISION: usize> Smoothed<M, PRECISION>
+where
+    M: for<'m> IterableEntropyModel<'m, PRECISION>,
+    M::Probability: AsPrimitive<u128>,
+    u128: AsPrimitive<M::Probability>,
+{
+    /// The weight of the uniform distribution used by [`new`](Self::new).
+    pub const DEFAULT_WEIGHT: f64 = 1.0 / 256.0;
+
+    /// Mixes `model` with a uniform distribution of weight [`DEFAULT_WEIGHT`].
+    ///
+    /// [`DEFAULT_WEIGHT`]: Self::DEFAULT_WEIGHT
+    pub fn new(model: M) -> Self {
+        Self::with_weight(model, Self::DEFAULT_WEIGHT).expect("valid weight")
+    }
+
+    /// Mixes `model` with a uniform distribution of weight `weight`.
+    ///
+    /// Returns `Err(())` unless `0.0 <= weight < 1.0`. If the support of `model` is so
+    /// large that `weight * 2^PRECISION` is smaller than the number of symbols, then the
+    /// uniform component rounds to zero and `model` remains unchanged.
+    #[allow(clippy::result_unit_err)]
+    pub fn with_weight(model: M, weight: f64) -> Result<Self, ()> {
+        if !(0.0..1.0).contains(&weight) {
+            return Err(());
+        }
+
+        let total = 1u128 << PRECISION;
+        let uniform_mass = (weight * total as f64) as u128;
+
+        let cdf = RemappedCdf::new(&model, |probabilities| {
+            let num_symbols = probabilities.len() as u128;
+            let uniform = uniform_mass.checked_div(num_symbols).unwrap_or(0);
+            let remaining = total - num_symbols * uniform;
+
+            // Each symbol gets `uniform` plus its original probability rescaled to the
+            // `remaining` mass. Rescaling the cumulatives rather than the individual
+            // probabilities makes sure that the total is exactly `2^PRECISION`, and it
+            // never lets a symbol drop to zero since `remaining == total` if `uniform == 0`.
+            let mut left_cumulative = 0u128;
+            let smoothed_cdf = probabilities
+                .iter()
+                .enumerate()
+                .map(|(index, &probability)| {
+                    let scaled = left_cumulative * remaining / total;
+                    left_cumulative += probability;
+                    index as u128 * uniform + scaled
+                })
+                .collect::<Vec<_>>();
+            Some(smoothed_cdf)
+        })
+        .ok_or(())?;
+
+        Ok(Self { inner: model, cdf })
+    }
+}
+
+impl<M, const PRECISION: usize> Smoothed<M, PRECISION>
+where
+    M: EntropyModel<PRECISION>,
+{
+    /// Returns a reference to the wrapped (unsmoothed) model.
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Consumes the decorator and returns the wrapped (unsmoothed) model.
+    pub fn into_inner(self) -> M {
+        self.inner
+    }
+}
+
+impl<M, const PRECISION: usize> EntropyModel<PRECISION> for Smoothed<M, PRECISION>
+where
+    M: EntropyModel<PRECISION>,
+{
+    type Symbol = M::Symbol;
+    type Probability = M::Probability;
+}
+
+impl<M, const PRECISION: usize> EncoderModel<PRECISION> for Smoothed<M, PRECISION>
+where
+    M: EncoderModel<PRECISION>,
+{
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Self::Symbol>,
+    ) -> Option<(Self::Probability, <Self::Probability as BitArray>::NonZero)> {
+        let (left_cumulative, _) = self.inner.left_cumulative_and_probability(symbol)?;
+        self.cdf.remap_interval(left_cumulative)
+    }
+}
+
+impl<M, const PRECISION: usize> DecoderModel<PRECISION> for Smoothed<M, PRECISION>
+where
+    M: DecoderModel<PRECISION>,
+{
+    fn quantile_function(
+        &self,
+        quantile: Self::Probability,
+    ) -> (
+        Self::Symbol,
+        Self::Probability,
+        <Self::Probability as BitArray>::NonZero,
+    ) {
+        let (original_left_cumulative, left_cumulative, probability) =
+            self.cdf.quantile_function(quantile);
+        let (symbol, _, _) = self.inner.quantile_function(original_left_cumulative);
+        (symbol, left_cumulative, probability)
+    }
+}
+
+impl<'m, M, const PRECISION: usize> IterableEntropyModel<'m, PRECISION> for Smoothed<M, PRECISION>
+where
+    M: IterableEntropyModel<'m, PRECISION> + 'm,
+{
+    fn symbol_table(
+        &'m self,
+    ) -> impl Iterator<
+        Item = (
+            Self::Symbol,
+            Self::Probability,
+            <Self::Probability as BitArray>::NonZero,
+        ),
+    > {
+        self.inner
+            .symbol_table()
+            .enumerate()
+            .map(move |(index, (symbol, _, _))| {
+                let (left_cumulative, probability) = self.cdf.interval(index);
+                (symbol, left_cumulative, probability)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use probability::distribution::Gaussian;
+
+    use super::{
+        super::{
+            tests::test_entropy_model, DefaultLeakyQuantizer, LeakyQuantizer,
+            SmallContiguousCategoricalEntropyModel,
+        },
+        *,
+    };
+
+    #[test]
+    fn smoothed() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        for std_dev in [0.1, 1.0, 10.0, 100.0] {
+            let model = quantizer.quantize(Gaussian::new(2.5, std_dev));
+            for weight in [0.0, 1e-6, 1.0 / 256.0, 0.1, 0.9] {
+                let smoothed = Smoothed::with_weight(model, weight).unwrap();
+                test_entropy_model(&smoothed, -100..=100);
+
+                if weight == 0.0 {
+                    assert!(smoothed
+                        .symbol_table()
+                        .zip(model.symbol_table())
+                        .all(|(smoothed, original)| smoothed == original));
+                }
+            }
+        }
+
+        let quantizer = LeakyQuantizer::<_, _, u16, 12>::new(-10..=10);
+        let model = quantizer.quantize(Gaussian::new(0.0, 0.5));
+        let smoothed = Smoothed::new(model);
+        test_entropy_model(&smoothed, -10..=10);
+
+        let categorical = SmallContiguousCategoricalEntropyModel::<Vec<u16>>::from_nonzero_fixed_point_probabilities(
+            [1u16, 4000, 95],
+            false,
+        )
+        .unwrap();
+        let smoothed = Smoothed::with_weight(categorical, 0.5).unwrap();
+        test_entropy_model(&smoothed, 0..3);
+
+        assert!(Smoothed::with_weight(model, 1.0).is_err());
+        assert!(Smoothed::with_weight(model, -0.1).is_err());
+        assert!(Smoothed::with_weight(model, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn worst_case_cost() {
+        let quantizer = DefaultLeakyQuantizer::new(-500..=500);
+        let model = quantizer.quantize(Gaussian::new(250.0, 2.0));
+        let (_, probability) = model.left_cumulative_and_probability(-500).unwrap();
+        assert_eq!(probability.get(), 1); // Would cost 24 bits.
+
+        for weight in [1.0 / 1024.0, 1.0 / 256.0, 0.01, 0.5] {
+            let smoothed = Smoothed::with_weight(model, weight).unwrap();
+            let uniform = (weight * (1u32 << 24) as f64) as u32 / 1001;
+            let max_bits = 24.0 - (uniform as f64).log2();
+
+            for (symbol, _, probability) in smoothed.symbol_table() {
+                assert!(probability.get() >= uniform);
+                let bits = 24.0 - (probability.get() as f64).log2();
+                assert!(bits <= max_bits);
+
+                // Smoothing never costs more than `-log2(1 - weight)` bits (plus rounding).
+                let (_, original) = model.left_cumulative_and_probability(symbol).unwrap();
+                let original_bits = 24.0 - (original.get() as f64).log2();
+                assert!(bits <= original_bits - (1.0 - weight).log2() + 1e-3);
+            }
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use core::{borrow::Borrow, hash::Hash};
+
+#[cfg(feature = "std")]
+use std::collections::{
+    hash_map::Entry::{Occupied, Vacant},
+    HashMap,
+};
+
+#[cfg(not(feature = "std"))]
+use hashbrown::hash_map::{
+    Entry::{Occupied, Vacant},
+    HashMap,
+};
+
+use alloc::vec::Vec;
+
+use crate::BitArray;
+
+use super::{DecoderModel, EncoderModel, EntropyModel, IterableEntropyModel};
+
+/// An entropy model over arbitrary (e.g., owned or non-`Copy`) symbols, defined by a
+/// bijection to the indices of an index-based model.
+///
+/// Most entropy models in `constriction` are defined over small integers. A
+/// `SymbolTableModel` wraps such an entropy model `M` over the indices `0..n` together
+/// with a table of `n` distinct symbols of an arbitrary type `Symbol` (e.g., `String`s for
+/// a dictionary coder). Encoding a symbol looks up its index in a [`HashMap`] and then
+/// encodes the index with `M`, and decoding maps the decoded index back to a clone of the
+/// symbol. Thus, the `i`th symbol has the same probability under the `SymbolTableModel` as
+/// the index `i` has under `M`, and the compressed data is exactly the same as if you
+/// encoded the indices with `M`.
+///
+/// Encoding a symbol that is not in the symbol table fails with an
+/// [`ImpossibleSymbol`](crate::DefaultEncoderFrontendError::ImpossibleSymbol) error.
+///
+/// See also [`NonContiguousCategoricalEncoderModel`] and
+/// [`NonContiguousCategoricalDecoderModel`], which combine the symbol table and the
+/// probabilities into a single categorical model, but as two separate types for encoding
+/// and decoding.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::{DefaultContiguousCategoricalEntropyModel, SymbolTableModel},
+///     stack::DefaultAnsCoder,
+///     Decode, Encode,
+/// };
+///
+/// let words = ["the", "a", "dog", "cat", "sleeps"].map(String::from);
+/// let index_model =
+///     DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+///         &[0.3, 0.2, 0.2, 0.2, 0.1],
+///         None,
+///     )
+///     .unwrap();
+/// let model = SymbolTableModel::new(words, index_model).unwrap();
+///
+/// let message = ["the", "cat", "sleeps"].map(String::from);
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_iid_symbols_reverse(&message, &model).unwrap();
+///
+/// let decoded = ans
+///     .decode_iid_symbols(3, &model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, message);
+///
+/// // "dog" is in the symbol table but "bird" isn't.
+/// assert!(ans.encode_symbol(String::from("dog"), &model).is_ok());
+/// assert!(ans.encode_symbol(String::from("bird"), &model).is_err());
+/// ```
+///
+/// [`HashMap`]: std::collections::HashMap
+/// [`NonContiguousCategoricalEncoderModel`]: super::NonContiguousCategoricalEncoderModel
+/// [`NonContiguousCategoricalDecoderModel`]: super::NonContiguousCategoricalDecoderModel
+#[derive(Debug, Clone)]
+pub struct SymbolTableModel<Symbol, M, const PRECISION: usize> {
+    inner: M,
+    symbols: Vec<Symbol>,
+    indices: HashMap<Symbol, usize>,
+}
+
+impl<Symbol, M, const PRECISION: usize> SymbolTableModel<Symbol, M, PRECISION>
+where
+    Symbol: Hash + Eq + Clone,
+    M: for<'m> IterableEntropyModel<'m, PRECISION, Symbol = usize>,
+{
+    /// Combines a table of distinct `symbols` with an entropy model over their indices.
+    ///
+    /// The `i`th item of `symbols` gets the probability of index `i` under `model`. Returns
+    /// `Err(())` if `symbols` contains duplicates, or if the symbols of `model` (as reported
+    /// by [`symbol_table`](IterableEntropyModel::symbol_table)) aren't exactly
+    /// `0..symbols.len()`, in this order.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(symbols: impl IntoIterator<Item = Symbol>, model: M) -> Result<Self, ()> {
+        let symbols = symbols.into_iter().collect::<Vec<_>>();
+        let mut indices = HashMap::with_capacity(symbols.len());
+        for (index, symbol) in symbols.iter().enumerate() {
+            match indices.entry(symbol.clone()) {
+                Occupied(_) => return Err(()),
+                Vacant(slot) => {
+                    slot.insert(index);
+                }
+            }
+        }
+
+        let mut num_indices = 0;
+        for (expected, (index, _, _)) in model.symbol_table().enumerate() {
+            if index != expected {
+                return Err(());
+            }
+            num_indices += 1;
+        }
+        if num_indices != symbols.len() {
+            return Err(());
+        }
+
+        Ok(Self {
+            inner: model,
+            symbols,
+            indices,
+        })
+    }
+}
+
+impl<Symbol, M, const PRECISION: usize> SymbolTableModel<Symbol, M, PRECISION> {
+    /// Returns the symbol table, i.e., the symbol for each index.
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Returns a reference to the wrapped model over indices.
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Consumes the model and returns the symbol table and the wrapped model.
+    pub fn into_parts(self) -> (Vec<Symbol>, M) {
+        (self.symbols, self.inner)
+    }
+}
+
+impl<Symbol, M, const PRECISION: usize> EntropyModel<PRECISION>
+    for SymbolTableModel<Symbol, M, PRECISION>
+where
+    M: EntropyModel<PRECISION>,
+{
+    type Symbol = Symbol;
+    type Probability = M::Probability;
+}
+
+impl<Symbol, M, const PRECISION: usize> EncoderModel<PRECISION>
+    for SymbolTableModel<Symbol, M, PRECISION>
+where
+    Symbol: Hash + Eq,
+    M: EncoderModel<PRECISION, Symbol = usize>,
+{
+    #[inline(always)]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Self::Symbol>,
+    ) -> Option<(Self::Probability, <Self::Probability as BitArray>::NonZero)> {
+        let index = *self.indices.get(symbol.borrow())?;
+        self.inner.left_cumulative_and_probability(index)
+    }
+}
+
+impl<Symbol, M, const PRECISION: usize> DecoderModel<PRECISION>
+    for SymbolTableModel<Symbol, M, PRECISION>
+where
+    Symbol: Clone,
+    M: DecoderModel<PRECISION, Symbol = usize>,
+{
+    #[inline(always)]
+    fn quantile_function(
+        &self,
+        quantile: Self::Probability,
+    ) -> (
+        Self::Symbol,
+        Self::Probability,
+        <Self::Probability as BitArray>::NonZero,
+    ) {
+        let (index, left_cumulative, probability) = self.inner.quantile_function(quantile);
+        (self.symbols[index].clone(), left_cumulative, probability)
+    }
+}
+
+impl<'m, Symbol, M, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for SymbolTableModel<Symbol, M, PRECISION>
+where
+    Symbol: Clone + 'm,
+    M: IterableEntropyModel<'m, PRECISION, Symbol = usize> + 'm,
+{
+    fn symbol_table(
+        &'m self,
+    ) -> impl Iterator<
+        Item = (
+            Self::Symbol,
+            Self::Probability,
+            <Self::Probability as BitArray>::NonZero,
+        ),
+    > {
+        self.inner
+            .symbol_table()
+            .map(move |(index, left_cumulative, probability)| {
+                (self.symbols[index].clone(), left_cumulative, probability)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec, vec::Vec};
+
+    use super::{
+        super::{DefaultContiguousCategoricalEntropyModel, DefaultUniformModel},
+        *,
+    };
+    use crate::{
+        stream::{stack::DefaultAnsCoder, Decode, Encode},
+        CoderError, DefaultEncoderFrontendError,
+    };
+
+    #[test]
+    fn strings() {
+        let words = ["lorem", "ipsum", "dolor", "sit", "amet", "consectetur"]
+            .iter()
+            .map(|&word| String::from(word))
+            .collect::<Vec<_>>();
+        let index_model =
+            DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                &[0.3f64, 0.25, 0.2, 0.1, 0.1, 0.05],
+                None,
+            )
+            .unwrap();
+        let model = SymbolTableModel::new(words.clone(), index_model.clone()).unwrap();
+        assert_eq!(model.symbols(), &words[..]);
+
+        for ((word, left_cumulative, probability), (index, expected_left, expected_prob)) in
+            model.symbol_table().zip(index_model.symbol_table())
+        {
+            assert_eq!(word, words[index]);
+            assert_eq!(left_cumulative, expected_left);
+            assert_eq!(probability, expected_prob);
+            assert_eq!(
+                model.left_cumulative_and_probability(&word),
+                Some((expected_left, expected_prob))
+            );
+            assert_eq!(
+                model.quantile_function(left_cumulative),
+                (word, expected_left, expected_prob)
+            );
+        }
+
+        let message = [3, 0, 0, 5, 2, 1, 4, 0, 2, 1]
+            .iter()
+            .map(|&index| words[index].clone())
+            .collect::<Vec<_>>();
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&message, &model).unwrap();
+
+        // The compressed data is the same as if we had encoded the indices directly.
+        let mut reference = DefaultAnsCoder::new();
+        reference
+            .encode_iid_symbols_reverse([3, 0, 0, 5, 2, 1, 4, 0, 2, 1], &index_model)
+            .unwrap();
+        assert_eq!(ans, reference);
+
+        let decoded = ans
+            .decode_iid_symbols(message.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, message);
+        assert!(ans.is_empty());
+    }
+
+    #[test]
+    fn unknown_symbol() {
+        let model = SymbolTableModel::new(
+            vec![String::from("yes"), String::from("no")],
+            DefaultUniformModel::new(2),
+        )
+        .unwrap();
+        assert!(model
+            .left_cumulative_and_probability(String::from("maybe"))
+            .is_none());
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_symbol(String::from("yes"), &model).unwrap();
+        assert_eq!(
+            ans.encode_symbol(String::from("maybe"), &model),
+            Err(CoderError::Frontend(
+                DefaultEncoderFrontendError::ImpossibleSymbol
+            ))
+        );
+        assert_eq!(ans.decode_symbol(&model).unwrap(), "yes");
+    }
+
+    #[test]
+    fn invalid_symbol_table() {
+        let symbols = || ["a", "b", "c"].iter().map(|&s| String::from(s));
+        assert!(SymbolTableModel::new(symbols(), DefaultUniformModel::new(3)).is_ok());
+        assert!(SymbolTableModel::new(symbols(), DefaultUniformModel::new(2)).is_err());
+        assert!(SymbolTableModel::new(symbols(), DefaultUniformModel::new(4)).is_err());
+        assert!(SymbolTableModel::new(
+            ["a", "b", "a"].iter().map(|&s| String::from(s)),
+            DefaultUniformModel::new(3)
+        )
+        .is_err());
+    }
+}
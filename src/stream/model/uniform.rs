@@ -1,6 +1,6 @@
-use core::borrow::Borrow;
+use core::{borrow::Borrow, ops::RangeInclusive};
 
-use num_traits::AsPrimitive;
+use num_traits::{AsPrimitive, PrimInt, WrappingAdd, WrappingSub};
 
 use crate::{generic_static_asserts, wrapping_pow2, BitArray, NonZeroBitArray};
 
@@ -183,6 +183,242 @@ where
             (symbol, left_cumulative, probability)
         })
     }
+
+    #[inline(always)]
+    fn support_size(&'m self) -> usize {
+        // Doesn't overflow since the constructor checked that `last_symbol + 1` fits into a
+        // `usize` (see comment in `symbol_table`).
+        self.last_symbol.as_() + 1
+    }
+}
+
+/// Type alias for a typical [`UniformIntegerModel`].
+///
+/// See:
+/// - [`UniformIntegerModel`]
+/// - [discussion of presets](crate::stream#presets)
+pub type DefaultUniformIntegerModel<Symbol> = UniformIntegerModel<Symbol, u32, 24>;
+
+/// Type alias for a [`UniformIntegerModel`] that is easier to use within a sequence of
+/// compressed symbols that also involves some lookup models.
+///
+/// See:
+/// - [`UniformIntegerModel`]
+/// - [discussion of presets](crate::stream#presets)
+pub type SmallUniformIntegerModel<Symbol> = UniformIntegerModel<Symbol, u16, 12>;
+
+/// A uniform distribution over an arbitrary inclusive range of integers.
+///
+/// Unlike [`UniformModel`], whose support always starts at zero and which assigns all
+/// probability mass that is left over from rounding to the last symbol, this model has
+/// an arbitrary support `min..=max` of any primitive integer type, and it distributes the
+/// left over probability mass as evenly as possible: if the support has `n` elements, the
+/// first `2^PRECISION % n` symbols have probability `floor(2^PRECISION / n) + 1` and
+/// all other symbols have probability `floor(2^PRECISION / n)` (in units of
+/// `2^-PRECISION`). Thus, the probabilities of any two symbols differ by at most one unit.
+///
+/// Both encoding and decoding run in constant time and without any lookup tables.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::DefaultUniformIntegerModel, stack::DefaultAnsCoder, Decode,
+/// };
+///
+/// let model = DefaultUniformIntegerModel::new(-10i32..=89); // 100 symbols.
+/// let symbols = [-10, 0, 42, 89, -3];
+///
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_iid_symbols_reverse(symbols, model).unwrap();
+/// let decoded = ans
+///     .decode_iid_symbols(symbols.len(), model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct UniformIntegerModel<Symbol, Probability: BitArray, const PRECISION: usize> {
+    min_symbol: Symbol,
+    max_symbol: Symbol,
+
+    /// The probability of all symbols except for the first `num_larger` ones.
+    probability: Probability::NonZero,
+
+    /// The number of symbols (at the beginning of the range) with probability
+    /// `probability + 1`.
+    num_larger: Probability,
+
+    /// `num_larger * (probability + 1)`, i.e., the total probability mass of the first
+    /// `num_larger` symbols.
+    threshold: Probability,
+}
+
+impl<Symbol, Probability, const PRECISION: usize>
+    UniformIntegerModel<Symbol, Probability, PRECISION>
+where
+    Symbol: PrimInt + AsPrimitive<Probability> + WrappingSub,
+    Probability: BitArray + AsPrimitive<Symbol>,
+{
+    /// Constructs a uniform distribution over the inclusive range `support`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `support` is empty or contains only a single value (we do not support
+    /// degenerate probability distributions), or if `support` has more than
+    /// `2^PRECISION` elements.
+    pub fn new(support: RangeInclusive<Symbol>) -> Self {
+        generic_static_asserts!(
+            (Probability: BitArray; const PRECISION: usize);
+            PROBABILITY_MUST_SUPPORT_PRECISION: PRECISION <= Probability::BITS;
+            PRECISION_MUST_BE_NONZERO: PRECISION > 0;
+        );
+
+        // We don't support degenerate probability distributions (i.e., distributions that
+        // place all probability mass on a single symbol).
+        assert!(support.end() > support.start());
+
+        let last_index_symbol = support.end().wrapping_sub(support.start());
+        let last_index: Probability = last_index_symbol.as_();
+        let max_probability = Probability::max_value() >> (Probability::BITS - PRECISION);
+        assert!(
+            last_index <= max_probability && last_index.as_() == last_index_symbol,
+            "The support is too large to assign a nonzero probability to each element."
+        );
+
+        // Calculate `2^PRECISION / n` and `2^PRECISION % n` in a way that doesn't overflow
+        // if `PRECISION == Probability::BITS`.
+        let n = last_index.wrapping_add(&Probability::one());
+        let (probability, num_larger) = if n == Probability::zero() {
+            // `n == 2^PRECISION == 2^Probability::BITS`.
+            (Probability::one(), Probability::zero())
+        } else {
+            let total = wrapping_pow2::<Probability>(PRECISION);
+            let probability = total.wrapping_sub(&n) / n + Probability::one();
+            (
+                probability,
+                total.wrapping_sub(&probability.wrapping_mul(&n)),
+            )
+        };
+
+        Self {
+            min_symbol: *support.start(),
+            max_symbol: *support.end(),
+            probability: probability.into_nonzero().expect("n <= 2^PRECISION"),
+            num_larger,
+            threshold: num_larger * (probability + Probability::one()),
+        }
+    }
+}
+
+impl<Symbol, Probability: BitArray, const PRECISION: usize>
+    UniformIntegerModel<Symbol, Probability, PRECISION>
+{
+    #[inline(always)]
+    fn interval(&self, index: Probability) -> (Probability, Probability::NonZero) {
+        if index < self.num_larger {
+            let probability = self.probability.get() + Probability::one();
+            // SAFETY: `probability + 1` doesn't overflow since the `num_larger` symbols with
+            // this probability fit into `2^PRECISION`.
+            let probability = unsafe { probability.into_nonzero_unchecked() };
+            (index * probability.get(), probability)
+        } else {
+            (
+                index * self.probability.get() + self.num_larger,
+                self.probability,
+            )
+        }
+    }
+}
+
+impl<Symbol, Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for UniformIntegerModel<Symbol, Probability, PRECISION>
+{
+    type Symbol = Symbol;
+    type Probability = Probability;
+}
+
+impl<Symbol, Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for UniformIntegerModel<Symbol, Probability, PRECISION>
+where
+    Symbol: PrimInt + AsPrimitive<Probability> + WrappingSub,
+{
+    #[inline]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Self::Symbol>,
+    ) -> Option<(Self::Probability, <Self::Probability as BitArray>::NonZero)> {
+        let symbol = *symbol.borrow();
+        if symbol < self.min_symbol || symbol > self.max_symbol {
+            return None;
+        }
+
+        // Doesn't truncate since the constructor checked the size of the support.
+        Some(self.interval(symbol.wrapping_sub(&self.min_symbol).as_()))
+    }
+}
+
+impl<Symbol, Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for UniformIntegerModel<Symbol, Probability, PRECISION>
+where
+    Symbol: PrimInt + WrappingAdd + 'static,
+    Probability: AsPrimitive<Symbol>,
+{
+    #[inline]
+    fn quantile_function(
+        &self,
+        quantile: Self::Probability,
+    ) -> (
+        Self::Symbol,
+        Self::Probability,
+        <Self::Probability as BitArray>::NonZero,
+    ) {
+        let index = if quantile < self.threshold {
+            quantile / (self.probability.get() + Probability::one())
+        } else {
+            self.num_larger + (quantile - self.threshold) / self.probability.get()
+        };
+        let (left_cumulative, probability) = self.interval(index);
+        (
+            self.min_symbol.wrapping_add(&index.as_()),
+            left_cumulative,
+            probability,
+        )
+    }
+}
+
+impl<'m, Symbol, Probability: BitArray, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for UniformIntegerModel<Symbol, Probability, PRECISION>
+where
+    Symbol: PrimInt + AsPrimitive<Probability> + WrappingSub + WrappingAdd,
+    Probability: AsPrimitive<Symbol>,
+{
+    fn symbol_table(
+        &'m self,
+    ) -> impl Iterator<
+        Item = (
+            Self::Symbol,
+            Self::Probability,
+            <Self::Probability as BitArray>::NonZero,
+        ),
+    > {
+        let last_index: Probability = self.max_symbol.wrapping_sub(&self.min_symbol).as_();
+        let mut next_index = Some(Probability::zero());
+        core::iter::from_fn(move || {
+            let index = next_index?;
+            next_index = if index == last_index {
+                None
+            } else {
+                Some(index + Probability::one())
+            };
+            let (left_cumulative, probability) = self.interval(index);
+            Some((
+                self.min_symbol.wrapping_add(&index.as_()),
+                left_cumulative,
+                probability,
+            ))
+        })
+    }
 }
 
 #[cfg(test)]
@@ -191,6 +427,10 @@ mod tests {
 
     use super::super::tests::test_entropy_model;
 
+    use alloc::vec::Vec;
+
+    use crate::stream::{stack::DefaultAnsCoder, Decode};
+
     #[test]
     fn uniform() {
         for range in [2, 3, 4, 5, 6, 7, 8, 9, 62, 63, 64, 254, 255, 256] {
@@ -206,4 +446,72 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn uniform_integer() {
+        for (min, max) in [(0, 1), (-7, 12), (-50, 49), (3, 1002), (-1000, 1000)] {
+            test_entropy_model(
+                &UniformIntegerModel::<i32, u32, 24>::new(min..=max),
+                min..=max,
+            );
+            test_entropy_model(
+                &UniformIntegerModel::<i32, u16, 12>::new(min..=max),
+                min..=max,
+            );
+            test_entropy_model(
+                &UniformIntegerModel::<i32, u32, 32>::new(min..=max),
+                min..=max,
+            );
+            test_entropy_model(
+                &UniformIntegerModel::<i32, u16, 16>::new(min..=max),
+                min..=max,
+            );
+        }
+        test_entropy_model(&UniformIntegerModel::<u8, u8, 8>::new(0..=255), 0..=255);
+        test_entropy_model(
+            &UniformIntegerModel::<i8, u8, 8>::new(-128..=127),
+            -128..=127,
+        );
+        test_entropy_model(&UniformIntegerModel::<u8, u8, 8>::new(3..=200), 3..=200);
+        test_entropy_model(
+            &UniformIntegerModel::<u16, u16, 12>::new(0..=4095),
+            0..=4095,
+        );
+
+        // Probabilities differ by at most one unit, and the larger ones come first.
+        let model = DefaultUniformIntegerModel::new(-7..=12);
+        let probabilities = model
+            .symbol_table()
+            .map(|(_, _, probability)| probability.get())
+            .collect::<Vec<_>>();
+        let base = (1u32 << 24) / 20;
+        let num_larger = (1u32 << 24) % 20;
+        assert_eq!(num_larger, 16);
+        for (index, &probability) in probabilities.iter().enumerate() {
+            assert_eq!(probability, base + ((index as u32) < num_larger) as u32);
+        }
+
+        assert!(model.left_cumulative_and_probability(-8).is_none());
+        assert!(model.left_cumulative_and_probability(13).is_none());
+    }
+
+    #[test]
+    fn uniform_integer_bitrate() {
+        let model = DefaultUniformIntegerModel::new(-333..=666); // 1000 symbols.
+        let symbols = (0..10_000)
+            .map(|i| (i * 7919) % 1000 - 333)
+            .collect::<Vec<i32>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+        let bits_per_symbol = ans.num_valid_bits() as f64 / symbols.len() as f64;
+        assert!((bits_per_symbol - 1000f64.log2()).abs() < 1e-2);
+
+        let decoded = ans
+            .decode_iid_symbols(symbols.len(), model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(ans.is_empty());
+    }
 }
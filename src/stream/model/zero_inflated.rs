@@ -0,0 +1,387 @@
+use core::borrow::Borrow;
+
+use num_traits::AsPrimitive;
+
+use crate::{generic_static_asserts, BitArray, NonZeroBitArray};
+
+use super::{DecoderModel, EncoderModel, EntropyModel, IterableEntropyModel};
+
+/// Type alias for a typical [`ZeroInflatedGeometric`].
+///
+/// See:
+/// - [`ZeroInflatedGeometric`]
+/// - [discussion of presets](crate::stream#presets)
+pub type DefaultZeroInflatedGeometric = ZeroInflatedGeometric<u32, 24>;
+
+/// Type alias for a [`ZeroInflatedGeometric`] that is easier to use within a sequence of
+/// compressed symbols that also involves some lookup models.
+///
+/// See:
+/// - [`ZeroInflatedGeometric`]
+/// - [discussion of presets](crate::stream#presets)
+pub type SmallZeroInflatedGeometric = ZeroInflatedGeometric<u16, 12>;
+
+/// An entropy model for zero-inflated count data.
+///
+/// Models counts `0..=max_count` as a mixture of a spike at zero and a geometric
+/// distribution over the positive counts. The count zero has probability
+/// `zero_probability`, and a positive count `k` has a probability proportional to
+/// `(1 - zero_probability) * success_probability * (1 - success_probability)^(k - 1)`,
+/// truncated to `k <= max_count`. This is typical for, e.g., the number of nonzero
+/// coefficients in a block of transformed image data or the number of events per time
+/// interval.
+///
+/// Compared to a [`ContiguousCategoricalEntropyModel`](super::ContiguousCategoricalEntropyModel)
+/// fitted to the same data, this model has only two parameters, so it can be estimated
+/// reliably from few samples and it doesn't need any memory proportional to `max_count`.
+/// Compared to encoding an "is zero" flag and a geometrically distributed count separately,
+/// it encodes each count with a single call to the entropy coder.
+///
+/// The fixed-point representation is leak-free: every count in `0..=max_count` has a
+/// nonzero probability, and the probabilities add up to exactly `2^PRECISION`. Encoding
+/// evaluates the cumulative distribution function in closed form, and decoding performs a
+/// binary search over the counts, i.e., it takes `O(log(max_count))` time.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::DefaultZeroInflatedGeometric, stack::DefaultAnsCoder, Decode, Encode,
+/// };
+///
+/// // 70% zeros; positive counts have mean 1 / 0.25 = 4.
+/// let model = DefaultZeroInflatedGeometric::new(0.7, 0.25, 1000).unwrap();
+///
+/// let counts = [0, 0, 3, 0, 1, 0, 0, 12, 0, 5];
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_iid_symbols_reverse(&counts, &model).unwrap();
+///
+/// let decoded = ans
+///     .decode_iid_symbols(counts.len(), &model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, counts);
+///
+/// // Counts outside of the support can't be encoded.
+/// assert!(ans.encode_symbol(1001, &model).is_err());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ZeroInflatedGeometric<Probability: BitArray, const PRECISION: usize> {
+    /// Probability mass of the count zero.
+    zero_mass: Probability::NonZero,
+
+    /// Probability mass of the positive counts in excess of the minimal mass of one each.
+    slack: Probability,
+
+    /// `1 - success_probability`.
+    ratio: f64,
+
+    /// `1 - ratio^max_count`, i.e., the normalization of the truncated geometric tail.
+    normalization: f64,
+
+    max_count: usize,
+}
+
+impl<Probability: BitArray, const PRECISION: usize> ZeroInflatedGeometric<Probability, PRECISION>
+where
+    Probability: AsPrimitive<u128>,
+    u128: AsPrimitive<Probability>,
+{
+    /// Creates a model over the counts `0..=max_count`.
+    ///
+    /// - `zero_probability` is the probability of the count zero; it is rounded to the
+    ///   nearest representable value that leaves enough probability mass for all positive
+    ///   counts (so even `0.0` and `1.0` are allowed).
+    /// - `success_probability` is the parameter of the geometric distribution over the
+    ///   positive counts, i.e., the mean of the (untruncated) positive counts is
+    ///   `1 / success_probability`.
+    ///
+    /// Returns `Err(())` if `zero_probability` is not within `[0, 1]`, if
+    /// `success_probability` is not within `(0, 1]`, if `max_count == 0`, or if
+    /// `max_count` is too large to assign a nonzero probability to each count at the
+    /// given `PRECISION`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(
+        zero_probability: f64,
+        success_probability: f64,
+        max_count: usize,
+    ) -> Result<Self, ()> {
+        generic_static_asserts!(
+            (Probability: BitArray; const PRECISION: usize);
+            PROBABILITY_MUST_SUPPORT_PRECISION: PRECISION <= Probability::BITS;
+            PRECISION_MUST_BE_NONZERO: PRECISION > 0;
+        );
+
+        if !(0.0..=1.0).contains(&zero_probability)
+            || !(0.0..=1.0).contains(&success_probability)
+            || success_probability == 0.0
+            || max_count == 0
+        {
+            return Err(());
+        }
+
+        let total = 1u128 << PRECISION;
+        let max_count_u128 = max_count as u128;
+        if max_count_u128 >= total {
+            return Err(());
+        }
+
+        let ratio = 1.0 - success_probability;
+        let normalization = 1.0 - libm::pow(ratio, max_count as f64);
+        if normalization <= 0.0 {
+            // `success_probability` is so small that it rounds to zero.
+            return Err(());
+        }
+
+        let zero_mass =
+            ((zero_probability * total as f64) as u128).clamp(1, total - max_count_u128);
+        let slack = total - zero_mass - max_count_u128;
+
+        Ok(Self {
+            zero_mass: zero_mass.as_().into_nonzero().expect("zero_mass >= 1"),
+            slack: slack.as_(),
+            ratio,
+            normalization,
+            max_count,
+        })
+    }
+
+    /// Returns the left-sided cumulative of the positive count `count`, where
+    /// `1 <= count <= max_count + 1`.
+    #[inline]
+    fn left_cumulative(&self, count: usize) -> u128 {
+        if count > self.max_count {
+            return 1u128 << PRECISION;
+        }
+
+        // Each positive count gets a probability mass of one plus its share of `slack`.
+        let slack: u128 = self.slack.as_();
+        let tail_cdf = (1.0 - libm::pow(self.ratio, (count - 1) as f64)) / self.normalization;
+        let share = core::cmp::min((slack as f64 * tail_cdf) as u128, slack);
+        let zero_mass: u128 = self.zero_mass.get().as_();
+        zero_mass + (count - 1) as u128 + share
+    }
+
+    #[inline]
+    fn interval(&self, count: usize) -> (Probability, Probability::NonZero) {
+        let left_cumulative = self.left_cumulative(count);
+        let probability = self.left_cumulative(count + 1) - left_cumulative;
+        (
+            left_cumulative.as_(),
+            probability
+                .as_()
+                .into_nonzero()
+                .expect("tail CDF is monotonic"),
+        )
+    }
+
+    /// Returns the largest count that has nonzero probability under the model.
+    pub fn max_count(&self) -> usize {
+        self.max_count
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for ZeroInflatedGeometric<Probability, PRECISION>
+{
+    type Symbol = usize;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for ZeroInflatedGeometric<Probability, PRECISION>
+where
+    Probability: AsPrimitive<u128>,
+    u128: AsPrimitive<Probability>,
+{
+    #[inline]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Self::Symbol>,
+    ) -> Option<(Self::Probability, <Self::Probability as BitArray>::NonZero)> {
+        match *symbol.borrow() {
+            0 => Some((Probability::zero(), self.zero_mass)),
+            count if count <= self.max_count => Some(self.interval(count)),
+            _ => None,
+        }
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for ZeroInflatedGeometric<Probability, PRECISION>
+where
+    Probability: AsPrimitive<u128>,
+    u128: AsPrimitive<Probability>,
+{
+    fn quantile_function(
+        &self,
+        quantile: Self::Probability,
+    ) -> (
+        Self::Symbol,
+        Self::Probability,
+        <Self::Probability as BitArray>::NonZero,
+    ) {
+        if quantile < self.zero_mass.get() {
+            return (0, Probability::zero(), self.zero_mass);
+        }
+
+        // Invariant: `left_cumulative(low) <= quantile < left_cumulative(high)`.
+        let quantile = quantile.as_();
+        let mut low = 1;
+        let mut high = self.max_count + 1;
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if self.left_cumulative(mid) <= quantile {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        let (left_cumulative, probability) = self.interval(low);
+        (low, left_cumulative, probability)
+    }
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for ZeroInflatedGeometric<Probability, PRECISION>
+where
+    Probability: AsPrimitive<u128>,
+    u128: AsPrimitive<Probability>,
+{
+    fn symbol_table(
+        &'m self,
+    ) -> impl Iterator<
+        Item = (
+            Self::Symbol,
+            Self::Probability,
+            <Self::Probability as BitArray>::NonZero,
+        ),
+    > {
+        (0..=self.max_count).map(move |count| {
+            let (left_cumulative, probability) = self
+                .left_cumulative_and_probability(count)
+                .expect("count is within support");
+            (count, left_cumulative, probability)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand::{Rng, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    use super::{
+        super::{tests::test_entropy_model, DefaultContiguousCategoricalEntropyModel},
+        *,
+    };
+
+    #[test]
+    fn zero_inflated_geometric() {
+        for (zero_probability, success_probability, max_count) in [
+            (0.7, 0.25, 100),
+            (0.0, 0.5, 10),
+            (1.0, 0.5, 10),
+            (0.5, 1.0, 20),
+            (0.9, 1e-3, 1000),
+            (0.3, 0.999, 1),
+        ] {
+            let model =
+                DefaultZeroInflatedGeometric::new(zero_probability, success_probability, max_count)
+                    .unwrap();
+            test_entropy_model(&model, 0..=max_count);
+
+            let model =
+                SmallZeroInflatedGeometric::new(zero_probability, success_probability, max_count)
+                    .unwrap();
+            test_entropy_model(&model, 0..=max_count);
+            assert!(model
+                .left_cumulative_and_probability(max_count + 1)
+                .is_none());
+        }
+
+        assert!(DefaultZeroInflatedGeometric::new(0.5, 0.5, 0).is_err());
+        assert!(DefaultZeroInflatedGeometric::new(-0.1, 0.5, 10).is_err());
+        assert!(DefaultZeroInflatedGeometric::new(0.5, 0.0, 10).is_err());
+        assert!(DefaultZeroInflatedGeometric::new(0.5, 1.5, 10).is_err());
+        assert!(DefaultZeroInflatedGeometric::new(f64::NAN, 0.5, 10).is_err());
+        assert!(SmallZeroInflatedGeometric::new(0.5, 0.5, 4095).is_ok());
+        assert!(SmallZeroInflatedGeometric::new(0.5, 0.5, 4096).is_err());
+    }
+
+    #[test]
+    fn bitrate_vs_categorical() {
+        const MAX_COUNT: usize = 200;
+        let (zero_probability, success_probability) = (0.8f64, 0.1f64);
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2256);
+        let mut sample = |amt| {
+            (0..amt)
+                .map(|_| {
+                    if rng.gen::<f64>() < zero_probability {
+                        0
+                    } else {
+                        loop {
+                            let uniform = 1.0 - rng.gen::<f64>();
+                            let count =
+                                1 + (uniform.ln() / (1.0 - success_probability).ln()) as usize;
+                            if count <= MAX_COUNT {
+                                break count;
+                            }
+                        }
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        let training = sample(500);
+        let test = sample(10_000);
+
+        // Fit both models to the (small) training set.
+        let num_zeros = training.iter().filter(|&&count| count == 0).count();
+        let positive_sum = training.iter().sum::<usize>();
+        let zig = DefaultZeroInflatedGeometric::new(
+            num_zeros as f64 / training.len() as f64,
+            (training.len() - num_zeros) as f64 / positive_sum as f64,
+            MAX_COUNT,
+        )
+        .unwrap();
+
+        let mut histogram = [0.0f64; MAX_COUNT + 1];
+        for &count in &training {
+            histogram[count] += 1.0;
+        }
+        let categorical =
+            DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast(
+                &histogram, None,
+            )
+            .unwrap();
+
+        let bits = |model: &dyn Fn(usize) -> u32| {
+            test.iter()
+                .map(|&count| 24.0 - (model(count) as f64).log2())
+                .sum::<f64>()
+                / test.len() as f64
+        };
+        let zig_bits = bits(&|count| zig.left_cumulative_and_probability(count).unwrap().1.get());
+        let categorical_bits = bits(&|count| {
+            categorical
+                .left_cumulative_and_probability(count)
+                .unwrap()
+                .1
+                .get()
+        });
+
+        // Entropy of the true (truncation is negligible) distribution.
+        let ratio: f64 = 1.0 - success_probability;
+        let entropy = -zero_probability * zero_probability.log2()
+            - (1.0 - zero_probability) * (1.0 - zero_probability).log2()
+            + (1.0 - zero_probability)
+                * (-success_probability.log2() - ratio.log2() * ratio / success_probability);
+
+        assert!(zig_bits < categorical_bits);
+        assert!(zig_bits < entropy + 0.05);
+    }
+}
@@ -47,9 +47,13 @@ use super::{
     Code, Decode, Encode, IntoDecoder,
 };
 use crate::{
-    backends::{AsReadWords, BoundedReadWords, Cursor, IntoReadWords, ReadWords, WriteWords},
-    generic_static_asserts, BitArray, CoderError, DefaultEncoderError, DefaultEncoderFrontendError,
-    NonZeroBitArray, Pos, PosSeek, Queue, Seek, UnwrapInfallible,
+    backends::{
+        AsReadWords, BoundedReadWords, Cursor, FallibleIteratorReadWords, IntoReadWords, ReadWords,
+        WriteWords,
+    },
+    bit_count_to_usize, generic_static_asserts, num_bits_u64, BitArray, CoderError,
+    DefaultEncoderError, DefaultEncoderFrontendError, NonZeroBitArray, Pos, PosSeek, Queue, Seek,
+    UnwrapInfallible,
 };
 
 /// Type of the internal state used by [`RangeEncoder<Word, State>`] and
@@ -120,11 +124,12 @@ where
 ///
 /// This type is mostly for internal use. It is only expsed via
 /// [`RangeEncoder::into_raw_parts`] and [`RangeEncoder::from_raw_parts`].
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum EncoderSituation<Word> {
     /// In the `Normal` situation, all full `Words` of compressed data have been written to
     /// the backend (or "bulk"), and the internal coder state holds less than one word of
     /// additional information content.
+    #[default]
     Normal,
 
     /// The `Inverted` situation occurs only rarely. In this situation, some full words of
@@ -138,12 +143,6 @@ pub enum EncoderSituation<Word> {
     Inverted(NonZeroUsize, Word),
 }
 
-impl<Word> Default for EncoderSituation<Word> {
-    fn default() -> Self {
-        Self::Normal
-    }
-}
-
 /// Type alias for an [`RangeEncoder`] with sane parameters for typical use cases.
 pub type DefaultRangeEncoder<Backend = Vec<u32>> = RangeEncoder<u32, u64, Backend>;
 
@@ -413,12 +412,29 @@ where
     ///
     /// The returned value is a multiple of the bitlength of the compressed word
     /// type `Word`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result does not fit into a `usize`. This can only happen on platforms
+    /// where `usize` has 32 bits (e.g., wasm32) and if there are more than 512 MiB of
+    /// compressed data. Call [`num_bits_u64`](#method.num_bits_u64) if you need to support
+    /// this case.
     pub fn num_bits<'a>(&'a self) -> usize
     where
         Backend: AsReadWords<'a, Word, Queue>,
         Backend::AsReadWords: BoundedReadWords<Word, Queue>,
     {
-        Word::BITS * self.num_words()
+        bit_count_to_usize(self.num_bits_u64())
+    }
+
+    /// Same as [`num_bits`](#method.num_bits) but returns a `u64`, which doesn't overflow
+    /// even on platforms where `usize` has only 32 bits.
+    pub fn num_bits_u64<'a>(&'a self) -> u64
+    where
+        Backend: AsReadWords<'a, Word, Queue>,
+        Backend::AsReadWords: BoundedReadWords<Word, Queue>,
+    {
+        num_bits_u64::<Word>(self.num_words())
     }
 
     pub fn bulk(&self) -> &Backend {
@@ -654,6 +670,10 @@ where
 
     /// Invariant: `point.wrapping_sub(&state.lower) < state.range`
     point: State,
+
+    /// Number of zero words that we shifted into `point` because we tried to read past the
+    /// end of `bulk`. Needed to make `Pos` consistent with the encoder.
+    num_padding_words: usize,
 }
 
 /// Type alias for a [`RangeDecoder`] with sane parameters for typical use cases.
@@ -695,12 +715,13 @@ where
         );
 
         let mut bulk = compressed.into_read_words();
-        let point = Self::read_point(&mut bulk)?;
+        let (point, num_padding_words) = Self::read_point(&mut bulk)?;
 
         Ok(RangeDecoder {
             bulk,
             state: RangeCoderState::default(),
             point,
+            num_padding_words,
         })
     }
 
@@ -712,18 +733,19 @@ where
         );
 
         let mut bulk = backend;
-        let point = Self::read_point(&mut bulk)?;
+        let (point, num_padding_words) = Self::read_point(&mut bulk)?;
 
         Ok(RangeDecoder {
             bulk,
             state: RangeCoderState::default(),
             point,
+            num_padding_words,
         })
     }
 
-    pub fn for_compressed<'a, Buf: ?Sized>(compressed: &'a Buf) -> Result<Self, Backend::ReadError>
+    pub fn for_compressed<'a, Buf>(compressed: &'a Buf) -> Result<Self, Backend::ReadError>
     where
-        Buf: AsReadWords<'a, Word, Queue, AsReadWords = Backend>,
+        Buf: ?Sized + AsReadWords<'a, Word, Queue, AsReadWords = Backend>,
     {
         generic_static_asserts!(
             (Word: BitArray, State:BitArray);
@@ -732,19 +754,23 @@ where
         );
 
         let mut bulk = compressed.as_read_words();
-        let point = Self::read_point(&mut bulk)?;
+        let (point, num_padding_words) = Self::read_point(&mut bulk)?;
 
         Ok(RangeDecoder {
             bulk,
             state: RangeCoderState::default(),
             point,
+            num_padding_words,
         })
     }
 
     /// Low-level constructor that assembles a `RangeDecoder` from its internal components.
     ///
-    /// The arguments `bulk`, `state`, and `point` correspond to the three return values of
-    /// the method [`into_raw_parts`](Self::into_raw_parts).
+    /// The arguments `bulk`, `state`, `point`, and `num_padding_words` correspond to the
+    /// four return values of the method [`into_raw_parts`](Self::into_raw_parts). Here,
+    /// `num_padding_words` is the number of zero words that the decoder shifted into `point`
+    /// because `bulk` had no more data to read. Thus, all but the last `num_padding_words`
+    /// of the `State::BITS / Word::BITS` words in `point` must have been read from `bulk`.
     ///
     /// The construction fails if the argument `point` lies outside of the range represented
     /// by `state`. In this case, the method returns the (unmodified) argument `bulk` back
@@ -753,6 +779,7 @@ where
         bulk: Backend,
         state: RangeCoderState<Word, State>,
         point: State,
+        num_padding_words: usize,
     ) -> Result<Self, Backend> {
         generic_static_asserts!(
             (Word: BitArray, State:BitArray);
@@ -765,18 +792,26 @@ where
         if point.wrapping_sub(&state.lower) >= state.range.get() {
             Err(bulk)
         } else {
-            Ok(Self { bulk, state, point })
+            Ok(Self {
+                bulk,
+                state,
+                point,
+                num_padding_words,
+            })
         }
     }
 
     /// Low-level method that disassembles the `RangeDecoder` into its internal components.
     ///
-    /// Can be used together with [`from_raw_parts`](Self::from_raw_parts).
-    pub fn into_raw_parts(self) -> (Backend, RangeCoderState<Word, State>, State) {
-        (self.bulk, self.state, self.point)
+    /// Returns the tuple `(bulk, state, point, num_padding_words)`. Can be used together
+    /// with [`from_raw_parts`](Self::from_raw_parts), see documentation there.
+    pub fn into_raw_parts(self) -> (Backend, RangeCoderState<Word, State>, State, usize) {
+        (self.bulk, self.state, self.point, self.num_padding_words)
     }
 
-    fn read_point<B: ReadWords<Word, Queue>>(bulk: &mut B) -> Result<State, B::ReadError> {
+    /// Reads the first `State::BITS / Word::BITS` words into `point`, padding with zero
+    /// words if `bulk` runs out of data. Returns `point` and the number of padding words.
+    fn read_point<B: ReadWords<Word, Queue>>(bulk: &mut B) -> Result<(State, usize), B::ReadError> {
         let mut num_read = 0;
         let mut point = State::zero();
         while let Some(word) = bulk.read()? {
@@ -787,16 +822,12 @@ where
             }
         }
 
-        #[allow(clippy::collapsible_if)]
-        if num_read < State::BITS / Word::BITS {
-            if num_read != 0 {
-                point = point << (State::BITS - num_read * Word::BITS);
-            }
-            // TODO: do we need to advance the Backend's `pos` beyond the end to make
-            // `PosBackend` consistent with its implementation for the encoder?
+        let num_padding_words = State::BITS / Word::BITS - num_read;
+        if num_padding_words != 0 && num_read != 0 {
+            point = point << (num_padding_words * Word::BITS);
         }
 
-        Ok(point)
+        Ok((point, num_padding_words))
     }
 
     /// Same as `Decoder::maybe_exhausted`, but can be called on a concrete type without
@@ -840,6 +871,28 @@ where
     type Position = (Backend::Position, <Self as Code>::State);
 }
 
+impl<Word, State, Backend> Pos for RangeDecoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: ReadWords<Word, Queue> + Pos<Position = usize>,
+{
+    /// Returns the position in the same format as the `Pos` implementation of a
+    /// [`RangeEncoder`]. Thus, decoding from a position that was recorded on the encoder
+    /// side leads to the same position on the decoder side.
+    ///
+    /// The decoder keeps `State::BITS / Word::BITS` words of lookahead in its `point`, so
+    /// the reported position lags behind the position of the backend by this amount (minus
+    /// the number of zero words that were padded into `point` after the backend ran out of
+    /// data).
+    fn pos(&self) -> Self::Position {
+        (
+            self.bulk.pos() + self.num_padding_words - State::BITS / Word::BITS,
+            self.state(),
+        )
+    }
+}
+
 impl<Word, State, Backend> Seek for RangeDecoder<Word, State, Backend>
 where
     Word: BitArray + Into<State>,
@@ -850,11 +903,11 @@ where
         let (pos, state) = pos_and_state;
 
         self.bulk.seek(pos)?;
-        self.point = Self::read_point(&mut self.bulk).map_err(|_| ())?;
+        let (point, num_padding_words) = Self::read_point(&mut self.bulk).map_err(|_| ())?;
+        self.point = point;
+        self.num_padding_words = num_padding_words;
         self.state = state;
 
-        // TODO: deal with positions very close to end.
-
         Ok(())
     }
 }
@@ -887,6 +940,76 @@ where
 //     }
 // }
 
+impl<Word, State, Iter, ReadError> RangeDecoder<Word, State, FallibleIteratorReadWords<Iter>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Iter: Iterator<Item = Result<Word, ReadError>>,
+    FallibleIteratorReadWords<Iter>: ReadWords<Word, Queue, ReadError = ReadError>,
+{
+    /// Creates a decoder that pulls compressed words from an iterator as it needs them.
+    ///
+    /// This is the FIFO analog of [`AnsCoder::from_reversed_compressed_iter`]. It allows
+    /// you to decode data as it arrives (e.g., from a network stream or a file) without
+    /// first buffering all compressed data in memory. The iterator yields `Ok(word)` for
+    /// each compressed word in the order in which the [`RangeEncoder`] wrote them, or
+    /// `Err(err)` to report an I/O error, which the decoder forwards as a
+    /// [`CoderError::Backend`] from the next decoding operation (or from this constructor).
+    /// You can get the iterator back by calling [`into_raw_parts`](Self::into_raw_parts)
+    /// and then `.0.into_iter()`.
+    ///
+    /// # Lookahead
+    ///
+    /// The decoder reads words eagerly but never more than it needs:
+    ///
+    /// - the constructor immediately reads the first `State::BITS / Word::BITS` words (e.g.,
+    ///   two words for a [`DefaultRangeDecoder`]); and
+    /// - each call to [`decode_symbol`](Decode::decode_symbol) reads at most one more word.
+    ///
+    /// Thus, the decoder is always ahead of the symbols it has decoded so far by up to
+    /// `State::BITS / Word::BITS` words (plus any words that the encoder had to defer due
+    /// to carry propagation). If you stream compressed data while it is being encoded, you
+    /// therefore generally can't decode the last few encoded symbols until the encoder has
+    /// written a few more words (or until it has been sealed with
+    /// [`RangeEncoder::into_compressed`]).
+    ///
+    /// The iterator must only return `None` at the actual end of the compressed data: the
+    /// decoder interprets `None` as the end of the data and pads it with zero words, so it
+    /// won't pick up any words that the iterator might return later. If words aren't
+    /// available yet, the iterator should block until they are (or return an error).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultUniformModel,
+    ///     queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+    ///     Decode, Encode,
+    /// };
+    ///
+    /// let model = DefaultUniformModel::new(100);
+    /// let mut encoder = DefaultRangeEncoder::new();
+    /// encoder.encode_iid_symbols([3, 14, 15, 92, 65, 35], model).unwrap();
+    /// let compressed = encoder.into_compressed().unwrap();
+    ///
+    /// // Simulate a stream of compressed words that may fail, e.g., due to network errors.
+    /// let word_stream = compressed.iter().map(|&word| Ok::<u32, &str>(word));
+    /// let mut decoder = DefaultRangeDecoder::from_compressed_iter(word_stream).unwrap();
+    /// let decoded = decoder
+    ///     .decode_iid_symbols(6, model)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(decoded, [3, 14, 15, 92, 65, 35]);
+    /// assert!(decoder.maybe_exhausted());
+    /// ```
+    ///
+    /// [`AnsCoder::from_reversed_compressed_iter`]:
+    ///     super::stack::AnsCoder::from_reversed_compressed_iter
+    pub fn from_compressed_iter(compressed: Iter) -> Result<Self, ReadError> {
+        Self::with_backend(FallibleIteratorReadWords::new(compressed))
+    }
+}
+
 impl<Word, State, Backend, const PRECISION: usize> Decode<PRECISION>
     for RangeDecoder<Word, State, Backend>
 where
@@ -974,9 +1097,9 @@ where
             self.point = self.point << Word::BITS;
             if let Some(word) = self.bulk.read()? {
                 self.point = self.point | word.into();
+            } else {
+                self.num_padding_words += 1;
             }
-
-            // TODO: register reads past end?
         }
 
         Ok(symbol)
@@ -1242,10 +1365,12 @@ mod tests {
         }
 
         let mut encoder = RangeEncoder::<Word, State>::new();
+        let initial_pos = encoder.pos();
 
         encoder
             .encode_iid_symbols(&symbols_categorical, &categorical)
             .unwrap();
+        let intermediate_pos = encoder.pos();
         dbg!(
             encoder.num_bits(),
             AMT as f64 * categorical.entropy_base2::<f64>()
@@ -1258,13 +1383,16 @@ mod tests {
             ))
             .unwrap();
         dbg!(encoder.num_bits());
+        let final_pos = encoder.pos();
 
         let mut decoder = encoder.into_decoder().unwrap();
+        assert_eq!(decoder.pos(), initial_pos);
 
         let reconstructed_categorical = decoder
             .decode_iid_symbols(AMT, &categorical)
             .collect::<Result<Vec<_>, _>>()
             .unwrap();
+        assert_eq!(decoder.pos(), intermediate_pos);
         let reconstructed_gaussian = decoder
             .decode_symbols(
                 means
@@ -1274,6 +1402,7 @@ mod tests {
             )
             .collect::<Result<Vec<_>, _>>()
             .unwrap();
+        assert_eq!(decoder.pos(), final_pos);
 
         assert!(decoder.maybe_exhausted());
 
@@ -1310,16 +1439,17 @@ mod tests {
 
         let mut decoder = encoder.decoder();
 
-        // Verify we can decode the chunks normally (we can't veryify that coding and
-        // decoding lead to same `pos_and_state` because the range decoder currently doesn't
-        // implement `Pos` due to complications at the stream end.)
-        for (chunk, _) in symbols.iter().zip(&jump_table) {
+        // Verify we can decode the chunks normally and that coding and decoding lead to the
+        // same `pos_and_state`.
+        for (chunk, &pos_and_state) in symbols.iter().zip(&jump_table) {
+            assert_eq!(decoder.pos(), pos_and_state);
             let decoded = decoder
                 .decode_iid_symbols(symbols_per_chunk, &model)
                 .collect::<Result<Vec<_>, _>>()
                 .unwrap();
             assert_eq!(&decoded, chunk);
         }
+        assert_eq!(decoder.pos(), final_pos_and_state);
         assert!(decoder.maybe_exhausted());
 
         // Seek to some random offsets in the jump table and decode one chunk
@@ -1344,7 +1474,109 @@ mod tests {
         decoder.seek(jump_table[0]).unwrap();
         assert!(!decoder.maybe_exhausted());
         decoder.seek(final_pos_and_state).unwrap();
+        assert_eq!(decoder.pos(), final_pos_and_state);
+        assert!(decoder.maybe_exhausted());
+    }
+
+    #[test]
+    fn pos_from_raw_parts() {
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2272);
+        let symbols = (0..100)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder.encode_iid_symbols(&symbols[..50], &model).unwrap();
+        let middle = encoder.pos();
+        encoder.encode_iid_symbols(&symbols[50..], &model).unwrap();
+        let end = encoder.pos();
+        let compressed = encoder.into_compressed().unwrap();
+
+        // Disassembling and reassembling a decoder preserves its position.
+        let mut decoder = DefaultRangeDecoder::from_compressed(&compressed[..]).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(50, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols[..50]);
+        assert_eq!(decoder.pos(), middle);
+        let (bulk, state, point, num_padding_words) = decoder.into_raw_parts();
+        assert_eq!(num_padding_words, 0);
+        let mut decoder =
+            DefaultRangeDecoder::from_raw_parts(bulk, state, point, num_padding_words).unwrap();
+        assert_eq!(decoder.pos(), middle);
+
+        // This also holds after the backend is exhausted, i.e., when `point` contains padding.
+        let decoded = decoder
+            .decode_iid_symbols(50, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols[50..]);
+        assert_eq!(decoder.pos(), end);
+        let (bulk, state, point, num_padding_words) = decoder.into_raw_parts();
+        assert_ne!(num_padding_words, 0);
+        let decoder =
+            DefaultRangeDecoder::from_raw_parts(bulk, state, point, num_padding_words).unwrap();
+        assert_eq!(decoder.pos(), end);
+        assert!(decoder.maybe_exhausted());
+    }
+
+    #[test]
+    fn from_compressed_iter() {
+        #[cfg(not(miri))]
+        let amt = 1000;
+
+        #[cfg(miri)]
+        let amt = 100;
+
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2312);
+        let symbols = (0..amt)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder.encode_iid_symbols(&symbols, &model).unwrap();
+        let compressed = encoder.into_compressed().unwrap();
+
+        let mut decoder = DefaultRangeDecoder::from_compressed(&compressed[..]).unwrap();
+        let expected = decoder
+            .decode_iid_symbols(amt, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(expected, symbols);
+
+        // Feed the compressed words one by one and keep track of how many have been read.
+        let num_read = core::cell::Cell::new(0);
+        let word_stream = compressed.iter().map(|&word| {
+            num_read.set(num_read.get() + 1);
+            Ok::<_, core::convert::Infallible>(word)
+        });
+        let mut decoder = DefaultRangeDecoder::from_compressed_iter(word_stream).unwrap();
+        assert_eq!(num_read.get(), 2);
+
+        for (i, &expected_symbol) in expected.iter().enumerate() {
+            assert_eq!(decoder.decode_symbol(&model).unwrap(), expected_symbol);
+            assert!(num_read.get() <= 2 + i + 1);
+        }
+        assert_eq!(num_read.get(), compressed.len());
         assert!(decoder.maybe_exhausted());
+        assert!(decoder.into_raw_parts().0.into_iter().next().is_none());
+
+        // Errors from the iterator are propagated.
+        let word_stream =
+            compressed
+                .iter()
+                .enumerate()
+                .map(|(i, &word)| if i == 10 { Err(i) } else { Ok(word) });
+        let mut decoder = DefaultRangeDecoder::from_compressed_iter(word_stream).unwrap();
+        let result = decoder
+            .decode_iid_symbols(amt, &model)
+            .collect::<Result<Vec<_>, _>>();
+        assert!(matches!(result, Err(CoderError::Backend(10))));
     }
 }
 
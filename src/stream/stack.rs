@@ -16,6 +16,54 @@
 //! The parent module contains a more detailed discussion of the [differences between ANS
 //! Coding and Range Coding](super#which-stream-code-should-i-use) .
 //!
+//! # Splitting a Message Across Several Coders
+//!
+//! The compressed data of two independently populated `AnsCoder`s cannot be concatenated
+//! into a single stack. A decoder refills its `state` from the compressed data whenever the
+//! `state` drops below a threshold. While decoding the last few symbols of a coder that
+//! was populated from scratch, the `state` drops below this threshold at points where the
+//! stand-alone decoder finds no more compressed words. If the compressed data of another
+//! coder followed, the decoder would read its words into the `state` and decode garbage.
+//! Re-encoding the symbols onto a single coder is the only way to merge two stacks, and
+//! that requires the symbols and entropy models.
+//!
+//! If you want to encode parts of a long message in parallel, keep the compressed data of
+//! each part as a separate chunk (e.g., in a container that stores the length of each
+//! chunk) and decode each chunk with its own `AnsCoder`:
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::DefaultUniformModel, stack::DefaultAnsCoder, Decode,
+//! };
+//!
+//! let model = DefaultUniformModel::new(10);
+//! let parts: [&[usize]; 2] = [&[1, 2, 3], &[4, 5]];
+//!
+//! // Encode each part independently (e.g., on separate worker threads).
+//! let chunks = parts
+//!     .iter()
+//!     .map(|part| {
+//!         let mut coder = DefaultAnsCoder::new();
+//!         coder.encode_iid_symbols_reverse(*part, model).unwrap();
+//!         coder.into_compressed().unwrap()
+//!     })
+//!     .collect::<Vec<_>>();
+//!
+//! // Decode the chunks in order to reproduce the full message.
+//! let decoded = chunks
+//!     .into_iter()
+//!     .zip(parts.iter())
+//!     .flat_map(|(chunk, part)| {
+//!         let mut coder = DefaultAnsCoder::from_compressed(chunk).unwrap();
+//!         coder
+//!             .decode_iid_symbols(part.len(), model)
+//!             .collect::<Result<Vec<_>, _>>()
+//!             .unwrap()
+//!     })
+//!     .collect::<Vec<_>>();
+//! assert_eq!(decoded, [1, 2, 3, 4, 5]);
+//! ```
+//!
 //! # References
 //!
 //! \[1] Duda, Jarek, et al. "The use of asymmetric numeral systems as an accurate
@@ -23,24 +71,34 @@
 //!
 //! [`queue`]: super::queue
 
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 use core::{
-    borrow::Borrow, convert::Infallible, fmt::Debug, iter::Fuse, marker::PhantomData, ops::Deref,
+    borrow::Borrow,
+    convert::{Infallible, TryFrom},
+    fmt::{Debug, Display},
+    hash::Hasher,
+    iter::{FromIterator, Fuse},
+    marker::PhantomData,
+    ops::Deref,
 };
-use num_traits::AsPrimitive;
+use num_traits::{AsPrimitive, NumCast, WrappingAdd, WrappingSub};
 
 use super::{
-    model::{DecoderModel, EncoderModel},
+    model::{
+        ContextModel, ContiguousCategoricalEntropyModel, DecoderModel, DivisionHint, EncoderModel,
+        UniformModel,
+    },
     AsDecoder, Code, Decode, Encode, IntoDecoder, TryCodingError,
 };
 use crate::{
     backends::{
-        self, AsReadWords, AsSeekReadWords, BoundedReadWords, Cursor, FallibleIteratorReadWords,
-        IntoReadWords, IntoSeekReadWords, ReadWords, Reverse, WriteWords,
+        self, bytes_to_word, word_to_bytes, AsReadWords, AsSeekReadWords, BoundedReadWords, Cursor,
+        FallibleIteratorReadWords, IntoReadWords, IntoSeekReadWords, ReadWords, Reverse, SafeBuf,
+        WriteWords,
     },
-    bit_array_to_chunks_truncated, generic_static_asserts, BitArray, CoderError,
-    DefaultEncoderError, DefaultEncoderFrontendError, NonZeroBitArray, Pos, PosSeek, Seek, Stack,
-    UnwrapInfallible,
+    bit_array_to_chunks_truncated, bit_count_to_usize, generic_static_asserts, num_bits_u64,
+    BitArray, CoderError, DefaultEncoderError, DefaultEncoderFrontendError, Endianness,
+    NonZeroBitArray, Pos, PosSeek, Seek, Stack, UnwrapInfallible,
 };
 
 /// Entropy coder for both encoding and decoding on a stack.
@@ -111,9 +169,23 @@ use crate::{
 /// models. Such random samples, which consume parts of the compressed data, are useful in
 /// the bits-back algorithm.
 ///
+/// # Width of the Internal State
+///
+/// The type parameter `State` has to be at least twice as wide as `Word`, but it may also
+/// be wider (e.g., `AnsCoder<u16, u64>` or, with the `std` feature, `AnsCoder<u32,
+/// u128>`). The coder maintains the invariant that a nonempty coder's internal state
+/// always holds between `State::BITS - Word::BITS + 1` and `State::BITS` bits, and it only
+/// spills whole `Word`s to the backend. A wider `State` therefore doesn't change the
+/// bitrate of the bulk of the data, but it increases the constant overhead: when the
+/// compressed data is exported (e.g., with [`into_compressed`]), the internal state
+/// contributes up to `State::BITS / Word::BITS` words (i.e., between one and two words for
+/// the default `State::BITS == 2 * Word::BITS`, but up to four words for `AnsCoder<u16,
+/// u64>`).
+///
 /// [range Asymmetric Numeral Systems (rANS)]:
 /// https://en.wikipedia.org/wiki/Asymmetric_numeral_systems#Range_variants_(rANS)_and_streaming
 /// [`is_empty`]: #method.is_empty`
+/// [`into_compressed`]: #method.into_compressed
 /// [`Cursor`]: crate::backends::Cursor
 #[derive(Clone)]
 pub struct AnsCoder<Word, State, Backend = Vec<Word>>
@@ -163,6 +235,64 @@ where
     }
 }
 
+/// Formats the compressed data as a compact lowercase hexadecimal string.
+///
+/// Renders the words of [`iter_compressed`](AnsCoder::iter_compressed) in order, each
+/// zero-padded to `Word::BITS / 4` hex digits, so the output doesn't depend on the
+/// platform's endianness. The alternate flag (`{:#}`) separates words by a space for
+/// readability. This is convenient for pasting compressed data into bug reports.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::stack::DefaultAnsCoder;
+///
+/// let ans = DefaultAnsCoder::from_compressed(vec![0xab, 0x1234_5678, 0x9abc_def0]).unwrap();
+/// assert_eq!(format!("{}", ans), "000000ab123456789abcdef0");
+/// assert_eq!(format!("{:#}", ans), "000000ab 12345678 9abcdef0");
+/// ```
+impl<Word, State, Backend> Display for AnsCoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    for<'a> &'a Backend: IntoIterator<Item = &'a Word>,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let width = Word::BITS / 4;
+        for (i, word) in self.iter_compressed().enumerate() {
+            if i != 0 && f.alternate() {
+                f.write_str(" ")?;
+            }
+            write!(f, "{:0width$x}", word, width = width)?;
+        }
+        Ok(())
+    }
+}
+
+/// Two coders are equal if they hold the same compressed data, i.e., if
+/// [`iter_compressed`](AnsCoder::iter_compressed) yields the same sequence of words for
+/// both. The coders may use different backends.
+impl<Word, State, Backend, OtherBackend> PartialEq<AnsCoder<Word, State, OtherBackend>>
+    for AnsCoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    for<'a> &'a Backend: IntoIterator<Item = &'a Word>,
+    for<'a> &'a OtherBackend: IntoIterator<Item = &'a Word>,
+{
+    fn eq(&self, other: &AnsCoder<Word, State, OtherBackend>) -> bool {
+        self.iter_compressed().eq(other.iter_compressed())
+    }
+}
+
+impl<Word, State, Backend> Eq for AnsCoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    for<'a> &'a Backend: IntoIterator<Item = &'a Word>,
+{
+}
+
 impl<Word, State, Backend, const PRECISION: usize> IntoDecoder<PRECISION>
     for AnsCoder<Word, State, Backend>
 where
@@ -221,6 +351,27 @@ where
     }
 }
 
+/// Collects compressed words into an `AnsCoder`, like [`AnsCoder::from_compressed`].
+///
+/// This allows writing `let coder: DefaultAnsCoder = words.into_iter().collect();`.
+///
+/// # Panics
+///
+/// Panics if the last collected word is zero, since an `AnsCoder` cannot represent
+/// trailing zero words (see [`AnsCoder::from_compressed`]). This cannot happen for data
+/// obtained from [`AnsCoder::into_compressed`]. Use [`AnsCoder::try_collect_compressed`]
+/// for a fallible alternative if the data comes from an untrusted source.
+impl<Word, State> FromIterator<Word> for AnsCoder<Word, State, Vec<Word>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    fn from_iter<I: IntoIterator<Item = Word>>(iter: I) -> Self {
+        Self::try_collect_compressed(iter)
+            .expect("Compressed data for an `AnsCoder` must not end in a zero word.")
+    }
+}
+
 impl<Word, State> AnsCoder<Word, State, Vec<Word>>
 where
     Word: BitArray + Into<State>,
@@ -249,6 +400,32 @@ where
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Fallible alternative to collecting compressed words via [`FromIterator`].
+    ///
+    /// Similar to `AnsCoder::from_compressed(words.into_iter().collect())`. Returns
+    /// `Err(compressed)` with all collected words if they end in a zero word.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::stack::DefaultAnsCoder;
+    ///
+    /// let coder: DefaultAnsCoder = [0x1234_5678, 0x9abc_def0].iter().copied().collect();
+    /// assert_eq!(coder.into_compressed().unwrap(), [0x1234_5678, 0x9abc_def0]);
+    ///
+    /// assert!(DefaultAnsCoder::try_collect_compressed([0x1234_5678, 0]).is_err());
+    /// ```
+    pub fn try_collect_compressed(
+        words: impl IntoIterator<Item = Word>,
+    ) -> Result<Self, Vec<Word>> {
+        let compressed = words.into_iter().collect::<Vec<_>>();
+        if compressed.last() == Some(&Word::zero()) {
+            // Check explicitly since `from_compressed` would pop the zero word off.
+            return Err(compressed);
+        }
+        Self::from_compressed(compressed)
+    }
 }
 
 impl<Word, State, Backend> Default for AnsCoder<Word, State, Backend>
@@ -271,6 +448,28 @@ where
     }
 }
 
+/// Generates an `AnsCoder` with arbitrary compressed data for property-based testing and
+/// fuzzing.
+///
+/// The generated coder always upholds the invariant that its state is at least
+/// `2^(State::BITS - Word::BITS)` unless its bulk is empty (see [`AnsCoder::validate`]),
+/// so it can be used for both encoding and decoding.
+#[cfg(feature = "arbitrary")]
+impl<'a, Word, State> arbitrary::Arbitrary<'a> for AnsCoder<Word, State>
+where
+    Word: BitArray + Into<State> + arbitrary::Arbitrary<'a>,
+    State: BitArray + AsPrimitive<Word> + arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let bulk = Vec::<Word>::arbitrary(u)?;
+        let mut state = State::arbitrary(u)?;
+        if !bulk.is_empty() {
+            state = state | State::one() << (State::BITS - Word::BITS);
+        }
+        Ok(Self::from_raw_parts(bulk, state))
+    }
+}
+
 impl<Word, State, Backend> AnsCoder<Word, State, Backend>
 where
     Word: BitArray + Into<State>,
@@ -407,6 +606,46 @@ where
         (self.bulk, self.state)
     }
 
+    /// Returns the current position in the compressed data together with the internal
+    /// coder state.
+    ///
+    /// The returned tuple is a checkpoint that you can pass to [`Seek::seek`] (e.g., via
+    /// [`seekable_decoder_at`]) to jump back to the current point in the compressed data
+    /// later. It is the same as the tuple returned by [`Pos::pos`], but this inherent
+    /// method doesn't require importing the `Pos` trait.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Code, Decode},
+    ///     Pos, Seek,
+    /// };
+    ///
+    /// let model = DefaultUniformModel::new(100);
+    /// let mut encoder = DefaultAnsCoder::new();
+    /// encoder.encode_iid_symbols_reverse([7, 8, 9], model).unwrap();
+    /// let checkpoint = encoder.pos_and_state();
+    /// assert_eq!(checkpoint, (encoder.pos().0, encoder.state()));
+    ///
+    /// encoder.encode_iid_symbols_reverse([1, 2, 3], model).unwrap();
+    ///
+    /// let mut decoder = encoder.as_seekable_decoder();
+    /// decoder.seek(checkpoint).unwrap();
+    /// let decoded = decoder.decode_iid_symbols(3, model).collect::<Result<Vec<_>, _>>();
+    /// assert_eq!(decoded.unwrap(), [7, 8, 9]);
+    /// ```
+    ///
+    /// [`Seek::seek`]: crate::Seek::seek
+    /// [`Pos::pos`]: crate::Pos::pos
+    /// [`seekable_decoder_at`]: Self::seekable_decoder_at
+    pub fn pos_and_state(&self) -> (Backend::Position, State)
+    where
+        Backend: Pos,
+    {
+        (self.bulk.pos(), self.state)
+    }
+
     /// Check if no data for decoding is left.
     ///
     /// Note that you can still pop symbols off an empty stack, but this is only
@@ -420,6 +659,105 @@ where
         self.state == State::zero()
     }
 
+    /// Returns the symbol that [`decode_symbol`] would decode next, without decoding it.
+    ///
+    /// This method inspects the internal state but leaves the coder completely unchanged,
+    /// and it never reads from the backend. This is useful, e.g., for speculative decoding
+    /// or for debugging. Calling `peek_symbol` followed by `decode_symbol` with the same
+    /// `model` always decodes the peeked symbol.
+    ///
+    /// Like `decode_symbol`, this method also works on an [empty](Self::is_empty) coder,
+    /// where it returns the same deterministic (but meaningless) symbol that
+    /// `decode_symbol` would return.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode};
+    ///
+    /// let model = DefaultUniformModel::new(10);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse([3, 7], model).unwrap();
+    ///
+    /// assert_eq!(ans.peek_symbol(model), 3);
+    /// assert_eq!(ans.peek_symbol(model), 3); // Peeking doesn't change the coder.
+    /// assert_eq!(ans.decode_symbol(model).unwrap(), 3);
+    /// assert_eq!(ans.peek_symbol(model), 7);
+    /// ```
+    ///
+    /// [`decode_symbol`]: #method.decode_symbol
+    pub fn peek_symbol<D, const PRECISION: usize>(&self, model: D) -> D::Symbol
+    where
+        D: DecoderModel<PRECISION>,
+        D::Probability: Into<Word>,
+        Word: AsPrimitive<D::Probability>,
+    {
+        model.quantile_function(self.quantile::<_, PRECISION>()).0
+    }
+
+    /// Returns the symbol that each of several candidate `models` would decode next.
+    ///
+    /// This is like calling [`peek_symbol`] for each entry of `models`, but it extracts
+    /// the quantile from the internal state only once. The coder remains unchanged. This
+    /// is useful, e.g., for debugging why two models diverge on the same compressed data,
+    /// or for comparing candidate models at decode time.
+    ///
+    /// Note that the returned symbols are only hypothetical: in an actual decoding
+    /// operation, only the model that is actually used would consume compressed data
+    /// (i.e., the coder's state after decoding with one model generally differs from its
+    /// state after decoding with another model), so only the symbols decoded *next* can be
+    /// compared in this way.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultUniformModel, stack::DefaultAnsCoder, Decode, Encode,
+    /// };
+    ///
+    /// let model10 = DefaultUniformModel::new(10);
+    /// let model20 = DefaultUniformModel::new(20);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_symbol(7, model10).unwrap();
+    ///
+    /// let probed = ans.probe_models(&[&model10, &model20]);
+    /// assert_eq!(probed[0], 7); // The model that was used for encoding.
+    /// assert_eq!(probed, [model10, model20].map(|model| ans.peek_symbol(model)));
+    /// assert_eq!(ans.decode_symbol(model10).unwrap(), 7); // The coder is unchanged.
+    /// ```
+    ///
+    /// [`peek_symbol`]: Self::peek_symbol
+    pub fn probe_models<D, const PRECISION: usize>(&self, models: &[&D]) -> Vec<D::Symbol>
+    where
+        D: DecoderModel<PRECISION>,
+        D::Probability: Into<Word>,
+        Word: AsPrimitive<D::Probability>,
+    {
+        let quantile = self.quantile::<D::Probability, PRECISION>();
+        models
+            .iter()
+            .map(|model| model.quantile_function(quantile).0)
+            .collect()
+    }
+
+    /// Returns the lowest `PRECISION` bits of `state`, i.e., the quantile that the next
+    /// call to `decode_symbol` passes to the entropy model.
+    #[inline(always)]
+    fn quantile<Probability, const PRECISION: usize>(&self) -> Probability
+    where
+        Probability: BitArray + Into<Word>,
+        Word: AsPrimitive<Probability>,
+    {
+        generic_static_asserts!(
+            (Word: BitArray, State:BitArray; const PRECISION: usize);
+            PROBABILITY_SUPPORTS_PRECISION: State::BITS >= Word::BITS + PRECISION;
+            NON_ZERO_PRECISION: PRECISION > 0;
+            STATE_SUPPORTS_AT_LEAST_TWO_WORDS: State::BITS >= 2 * Word::BITS;
+        );
+
+        (self.state % (State::one() << PRECISION)).as_().as_()
+    }
+
     /// Assembles the current compressed data into a single slice.
     ///
     /// Returns the concatenation of [`bulk`] and [`state`]. The concatenation truncates
@@ -514,8 +852,26 @@ where
     /// assert_eq!(compressed_collected, *ans.get_compressed().unwrap());
     /// ```
     ///
+    /// # Order of the Words
+    ///
+    /// The order in which this method yields words is part of the stable API, i.e., it will
+    /// not change in future versions of `constriction` without a major version bump:
+    ///
+    /// - first, all words of [`bulk`] in the order in which the backend stores them (i.e.,
+    ///   the order in which they were written, so that the word written last comes last);
+    /// - then the nonzero words of the internal [`state`], from its least significant to
+    ///   its most significant word (leading zero words of `state` are omitted, so the last
+    ///   yielded word is never zero).
+    ///
+    /// This is the same order as in [`get_compressed`] and [`into_compressed`]. A decoder
+    /// reads the words in reverse order (see also [`into_canonical_ans_bytes`], which
+    /// serializes the words in decoding order).
+    ///
     /// [`get_compressed`]: #method.get_compressed
     /// [`into_compressed`]: #method.into_compressed
+    /// [`bulk`]: #method.bulk
+    /// [`state`]: #method.state
+    /// [`into_canonical_ans_bytes`]: #method.into_canonical_ans_bytes
     pub fn iter_compressed<'a>(&'a self) -> impl Iterator<Item = Word> + 'a
     where
         &'a Backend: IntoIterator<Item = &'a Word>,
@@ -525,10 +881,49 @@ where
         bulk_iter.chain(state_iter)
     }
 
+    /// Iterates over the bytes of the compressed data currently on the ans.
+    ///
+    /// Yields the words of [`iter_compressed`] in the same (stable) order, and splits each
+    /// word into `Word::BITS / 8` bytes in little-endian byte order. Thus, the result is
+    /// independent of the platform's endianness. This is useful for byte-level tooling such
+    /// as hashing or diffing compressed data.
+    ///
+    /// Note that this is *not* the [canonical byte format](#method.into_canonical_ans_bytes),
+    /// which stores the words in decoding order, i.e., in reverse order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder};
+    /// use probability::distribution::Gaussian;
+    ///
+    /// let message = [6, 10, -4, 2, 5, 2, 1, 0, 2];
+    /// let model = DefaultLeakyQuantizer::new(-50..=50).quantize(Gaussian::new(3.2, 9.6));
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse(&message, &model).unwrap();
+    /// assert_eq!(ans.iter_compressed().collect::<Vec<_>>(), [0xb99fcf62, 0x1555b5b7]);
+    /// assert_eq!(
+    ///     ans.iter_compressed_bytes().collect::<Vec<_>>(),
+    ///     [0x62, 0xcf, 0x9f, 0xb9, 0xb7, 0xb5, 0x55, 0x15]
+    /// );
+    /// ```
+    ///
+    /// [`iter_compressed`]: #method.iter_compressed
+    pub fn iter_compressed_bytes<'a>(&'a self) -> impl Iterator<Item = u8> + 'a
+    where
+        &'a Backend: IntoIterator<Item = &'a Word>,
+        Word: AsPrimitive<u8>,
+    {
+        self.iter_compressed()
+            .flat_map(|word| (0..Word::BITS / 8).map(move |k| (word >> (8 * k)).as_()))
+    }
+
     /// Returns the number of compressed words on the ANS coder's stack.
     ///
-    /// This includes a constant overhead of between one and two words unless the
-    /// stack is completely empty.
+    /// This includes a constant overhead of up to `State::BITS / Word::BITS` words (i.e.,
+    /// between one and two words for the default `State::BITS == 2 * Word::BITS`) unless
+    /// the stack is completely empty.
     ///
     /// This method returns the length of the slice, the `Vec<Word>`, or the iterator
     /// that would be returned by [`get_compressed`], [`into_compressed`], or
@@ -547,20 +942,155 @@ where
         self.bulk.remaining() + bit_array_to_chunks_truncated::<_, Word>(self.state).len()
     }
 
+    /// Returns the size of the compressed data in bits.
+    ///
+    /// This is `Word::BITS` times [`num_words`], i.e., it includes the bits of the internal
+    /// state that would be flushed by [`into_compressed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result does not fit into a `usize`. This can only happen on platforms
+    /// where `usize` has 32 bits (e.g., wasm32) and if there are more than 512 MiB of
+    /// compressed data. Call [`num_bits_u64`] if you need to support this case.
+    ///
+    /// [`num_words`]: #method.num_words
+    /// [`into_compressed`]: #method.into_compressed
+    /// [`num_bits_u64`]: #method.num_bits_u64
     pub fn num_bits(&self) -> usize
     where
         Backend: BoundedReadWords<Word, Stack>,
     {
-        Word::BITS * self.num_words()
+        bit_count_to_usize(self.num_bits_u64())
+    }
+
+    /// Same as [`num_bits`] but returns a `u64`, which doesn't overflow even on platforms
+    /// where `usize` has only 32 bits.
+    ///
+    /// [`num_bits`]: #method.num_bits
+    pub fn num_bits_u64(&self) -> u64
+    where
+        Backend: BoundedReadWords<Word, Stack>,
+    {
+        num_bits_u64::<Word>(self.num_words())
     }
 
+    /// Returns the number of bits of compressed data that actually carry information.
+    ///
+    /// Different to [`num_bits`], this doesn't count the leading zero bits of the internal
+    /// state and its most significant one bit.
+    ///
+    /// More precisely, the result is `Word::BITS` times the number of words in the bulk plus
+    /// the number of bits in the internal state below its most significant one bit. Thus, an
+    /// empty coder (whose state is zero) and a coder whose state is exactly one (i.e., only
+    /// the most significant one bit) both have zero valid bits, a state of `0b10` or `0b11`
+    /// holds exactly one valid bit, and a state with all bits set holds `State::BITS - 1`
+    /// valid bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same (rare) conditions as [`num_bits`]. Call
+    /// [`num_valid_bits_u64`] if you need to support very large data on 32-bit platforms.
+    ///
+    /// [`num_bits`]: #method.num_bits
+    /// [`num_valid_bits_u64`]: #method.num_valid_bits_u64
     pub fn num_valid_bits(&self) -> usize
     where
         Backend: BoundedReadWords<Word, Stack>,
     {
-        Word::BITS * self.bulk.remaining()
-            + core::cmp::max(State::BITS - self.state.leading_zeros() as usize, 1)
-            - 1
+        bit_count_to_usize(self.num_valid_bits_u64())
+    }
+
+    /// Same as [`num_valid_bits`] but returns a `u64`, which doesn't overflow even on
+    /// platforms where `usize` has only 32 bits.
+    ///
+    /// [`num_valid_bits`]: #method.num_valid_bits
+    pub fn num_valid_bits_u64(&self) -> u64
+    where
+        Backend: BoundedReadWords<Word, Stack>,
+    {
+        num_valid_bits_u64::<Word, State>(self.bulk.remaining(), self.state)
+    }
+
+    /// Estimates how many words the compressed data would occupy with a different word
+    /// size.
+    ///
+    /// Returns the number of `NewWord`s that are needed to store the [`num_valid_bits`] of
+    /// the current compressed data, plus the most significant one bit of the internal
+    /// state (which an `AnsCoder` always has to store unless it's empty). This helps you
+    /// decide whether it's worth switching to a different word size (see discussion of
+    /// [presets](crate::stream#presets)) without having to re-encode your data.
+    ///
+    /// Note that this is only an approximation. Re-encoding the same symbols with a coder
+    /// that uses `NewWord`s typically results in a slightly different size because the
+    /// rounding of the internal state at word boundaries happens at different points, and
+    /// because a different word size usually comes with an entropy model of different
+    /// `PRECISION`. The estimate does not include any framing that you might add to the
+    /// compressed data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder};
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse(0..5, DefaultUniformModel::new(10)).unwrap();
+    /// assert_eq!(ans.num_valid_bits(), 36);
+    /// assert_eq!(ans.num_words(), 2);
+    /// assert_eq!(ans.estimate_words_in_width::<u32>(), 2);
+    /// assert_eq!(ans.estimate_words_in_width::<u16>(), 3); // 37 bits fit into 3 `u16`s.
+    /// assert_eq!(ans.estimate_words_in_width::<u8>(), 5);
+    /// ```
+    ///
+    /// [`num_valid_bits`]: #method.num_valid_bits
+    pub fn estimate_words_in_width<NewWord: BitArray>(&self) -> usize
+    where
+        Backend: BoundedReadWords<Word, Stack>,
+    {
+        if self.is_empty() {
+            return 0;
+        }
+
+        use core::convert::TryInto;
+        let num_bits = self.num_valid_bits_u64() + 1;
+        num_bits
+            .div_ceil(NewWord::BITS as u64)
+            .try_into()
+            .expect("Number of words overflows `usize`.")
+    }
+
+    /// Returns the number of bits per compressed word, i.e., `Word::BITS`.
+    ///
+    /// Together with [`state_bits`](Self::state_bits), this allows generic code (e.g., a
+    /// logging or serialization layer) to record the configuration of a coder without
+    /// knowing its concrete type. For the [`DefaultAnsCoder`], this returns 32.
+    pub const fn compressed_word_bits(&self) -> usize {
+        Word::BITS
+    }
+
+    /// Returns the number of bits of the internal coder state, i.e., `State::BITS`.
+    ///
+    /// For the [`DefaultAnsCoder`], this returns 64. See also
+    /// [`compressed_word_bits`](Self::compressed_word_bits).
+    pub const fn state_bits(&self) -> usize {
+        State::BITS
+    }
+
+    /// Returns the number of leading zero bits of the internal coder state, i.e.,
+    /// `State::BITS` minus the number of significant bits in the state.
+    ///
+    /// This allows you to predict whether encoding the next symbol will transfer a word
+    /// from the internal state to the compressed data (which you may want to know if you
+    /// manage flushing yourself, e.g., in a custom interleaving scheme). Encoding a symbol
+    /// whose fixed-point information content (i.e., `PRECISION - log2(probability)`, where
+    /// `probability` is the fixed-point probability returned by the entropy model) is
+    /// - at most `state_headroom_bits()` never transfers a word; and
+    /// - at least `state_headroom_bits() + 1` always transfers a word.
+    ///
+    /// In the remaining case, whether a word gets transferred depends on the exact value of
+    /// the state. Note that [`num_words`](Self::num_words) also counts the words in the
+    /// internal state, so it can increase even if no word gets transferred.
+    pub fn state_headroom_bits(&self) -> usize {
+        self.state.leading_zeros() as usize
     }
 
     pub fn into_decoder(self) -> AnsCoder<Word, State, Backend::IntoReadWords>
@@ -592,6 +1122,62 @@ where
         }
     }
 
+    /// Consumes the `AnsCoder` and returns an encoder that can overwrite compressed data in
+    /// place.
+    ///
+    /// The returned `AnsCoder` wraps the original backend in a [`Cursor`] that points to the
+    /// end of the compressed data. It implements [`Seek`] and [`Pos`], and it can both
+    /// encode and decode. After seeking back to a checkpoint `(pos, state)`, encoding writes
+    /// over the compressed words that follow position `pos` rather than appending to them.
+    /// Unlike the original backend, the returned encoder can't grow the buffer: encoding
+    /// more words than fit into the buffer returns an error.
+    ///
+    /// **Use with care:** overwriting compressed data invalidates everything that was
+    /// encoded after the checkpoint, and seeking to a position with a state that wasn't
+    /// recorded at exactly this position (e.g., via [`Pos::pos`]) silently corrupts the
+    /// stack. Also, words beyond the cursor position aren't removed after overwriting, so
+    /// truncate the buffer to the cursor position when you convert back (see example).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     stream::{model::DefaultUniformModel, stack::{AnsCoder, DefaultAnsCoder}, Decode},
+    ///     Pos, Seek,
+    /// };
+    ///
+    /// // Each symbol of this model has an information content of exactly 16 bits.
+    /// let model = DefaultUniformModel::new(1 << 16);
+    /// let mut encoder = DefaultAnsCoder::new();
+    /// encoder.encode_iid_symbols_reverse([1, 2, 3], model).unwrap();
+    /// let checkpoint = encoder.pos();
+    /// encoder.encode_iid_symbols_reverse([4, 5], model).unwrap();
+    ///
+    /// // Replace the symbols `[4, 5]`, which were encoded after `checkpoint`.
+    /// let mut encoder = encoder.into_seekable_encoder();
+    /// encoder.seek(checkpoint).unwrap();
+    /// encoder.encode_iid_symbols_reverse([6, 7], model).unwrap();
+    ///
+    /// // Convert back to an `AnsCoder` with a `Vec` backend.
+    /// let (cursor, state) = encoder.into_raw_parts();
+    /// let (mut bulk, pos) = cursor.into_buf_and_pos();
+    /// bulk.truncate(pos);
+    /// let mut decoder = AnsCoder::<u32, u64>::from_raw_parts(bulk, state);
+    /// let decoded = decoder.decode_iid_symbols(5, model).collect::<Result<Vec<_>, _>>();
+    /// assert_eq!(decoded.unwrap(), [6, 7, 1, 2, 3]);
+    /// assert!(decoder.is_empty());
+    /// ```
+    pub fn into_seekable_encoder(self) -> AnsCoder<Word, State, Cursor<Word, Backend>>
+    where
+        Backend: AsMut<[Word]>,
+    {
+        AnsCoder {
+            bulk: Cursor::new_at_write_end_mut(self.bulk),
+            state: self.state,
+            phantom: PhantomData,
+        }
+    }
+
     pub fn as_decoder<'a>(&'a self) -> AnsCoder<Word, State, Backend::AsReadWords>
     where
         Backend: AsReadWords<'a, Word, Stack>,
@@ -618,13 +1204,17 @@ where
     ///
     /// # Limitations
     ///
-    /// TODO: this text is outdated.
-    ///
     /// This method is only implemented for `AnsCoder`s whose backing store of compressed
-    /// data (`Backend`) implements `AsRef<[Word]>`. This includes the default
-    /// backing data store `Backend = Vec<Word>`.
+    /// data (`Backend`) implements [`AsSeekReadWords`]. This includes the default
+    /// backing data store `Backend = Vec<Word>`, so you can call this method on an
+    /// encoder right after encoding, without a round trip through [`into_compressed`]
+    /// and [`from_compressed`]. If you want to start decoding at a specific checkpoint,
+    /// call [`seekable_decoder_at`] instead.
     ///
     /// [`into_seekable_decoder`]: Self::into_seekable_decoder
+    /// [`into_compressed`]: #method.into_compressed
+    /// [`from_compressed`]: #method.from_compressed
+    /// [`seekable_decoder_at`]: Self::seekable_decoder_at
     pub fn as_seekable_decoder<'a>(&'a self) -> AnsCoder<Word, State, Backend::AsSeekReadWords>
     where
         Backend: AsSeekReadWords<'a, Word, Stack>,
@@ -635,763 +1225,6034 @@ where
             phantom: PhantomData,
         }
     }
-}
 
-impl<Word, State> AnsCoder<Word, State>
-where
-    Word: BitArray + Into<State>,
-    State: BitArray + AsPrimitive<Word>,
-{
-    /// Discards all compressed data and resets the coder to the same state as
-    /// [`Coder::new`](#method.new).
-    pub fn clear(&mut self) {
-        self.bulk.clear();
-        self.state = State::zero();
+    /// Returns a seekable decoder that is already positioned at `checkpoint`.
+    ///
+    /// This is equivalent to calling [`as_seekable_decoder`] followed by [`seek`], and it
+    /// is typically used to decode a chunk of compressed data right after encoding it,
+    /// where `checkpoint` was obtained by calling [`pos`] on the encoder. Returns
+    /// `Err(())` if seeking fails (e.g., because `checkpoint` is out of bounds). See
+    /// [`seekable_decoders_at`] if you need decoders for several checkpoints.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode},
+    ///     Pos,
+    /// };
+    ///
+    /// let model = DefaultUniformModel::new(100);
+    /// let mut encoder = DefaultAnsCoder::new();
+    /// encoder.encode_iid_symbols_reverse([40, 50, 60], model).unwrap();
+    /// let checkpoint = encoder.pos();
+    /// encoder.encode_iid_symbols_reverse([10, 20, 30], model).unwrap();
+    ///
+    /// // Jump directly to the second chunk without decoding the first one.
+    /// let mut decoder = encoder.seekable_decoder_at(checkpoint).unwrap();
+    /// let decoded = decoder.decode_iid_symbols(3, model).collect::<Result<Vec<_>, _>>();
+    /// assert_eq!(decoded.unwrap(), [40, 50, 60]);
+    /// assert!(decoder.is_empty());
+    ///
+    /// // The encoder is still intact.
+    /// assert_eq!(encoder.decode_symbol(model).unwrap(), 10);
+    /// ```
+    ///
+    /// [`as_seekable_decoder`]: Self::as_seekable_decoder
+    /// [`seek`]: crate::Seek::seek
+    /// [`pos`]: crate::Pos::pos
+    /// [`seekable_decoders_at`]: Self::seekable_decoders_at
+    #[allow(clippy::type_complexity, clippy::result_unit_err)]
+    pub fn seekable_decoder_at<'a>(
+        &'a self,
+        checkpoint: <AnsCoder<Word, State, Backend::AsSeekReadWords> as PosSeek>::Position,
+    ) -> Result<AnsCoder<Word, State, Backend::AsSeekReadWords>, ()>
+    where
+        Backend: AsSeekReadWords<'a, Word, Stack>,
+    {
+        let mut decoder = self.as_seekable_decoder();
+        decoder.seek(checkpoint)?;
+        Ok(decoder)
     }
-}
 
-impl<'bulk, Word, State> AnsCoder<Word, State, Cursor<Word, &'bulk [Word]>>
-where
-    Word: BitArray + Into<State>,
-    State: BitArray + AsPrimitive<Word>,
-{
-    // TODO: proper error type (also for `from_compressed`)
-    #[allow(clippy::result_unit_err)]
-    pub fn from_compressed_slice(compressed: &'bulk [Word]) -> Result<Self, ()> {
-        Self::from_compressed(backends::Cursor::new_at_write_end(compressed)).map_err(|_| ())
+    /// Returns one seekable decoder for each checkpoint in a jump table.
+    ///
+    /// Each entry of `checkpoints` is a position as returned by [`Pos::pos`] (i.e., a
+    /// tuple of a position in the compressed data and a coder state), e.g., recorded during
+    /// encoding. The returned decoders are positioned at the respective checkpoints and
+    /// share read-only access to the compressed data, just like decoders obtained from
+    /// [`as_seekable_decoder`]. They are independent of each other, so you can move them to
+    /// different threads and decode the segments between checkpoints in parallel (provided
+    /// that the entropy models are `Sync`).
+    ///
+    /// Returns `Err(())` if seeking to any of the checkpoints fails (e.g., because its
+    /// position is out of bounds).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode},
+    ///     Pos,
+    /// };
+    ///
+    /// let model = DefaultUniformModel::new(100);
+    /// let segments = [[1, 2, 3], [4, 5, 6], [7, 8, 9]];
+    /// let mut encoder = DefaultAnsCoder::new();
+    /// let mut checkpoints = Vec::new();
+    /// for segment in segments.iter().rev() {
+    ///     encoder.encode_iid_symbols_reverse(segment, model).unwrap();
+    ///     checkpoints.push(encoder.pos());
+    /// }
+    /// checkpoints.reverse();
+    ///
+    /// let decoders = encoder.seekable_decoders_at(&checkpoints).unwrap();
+    /// std::thread::scope(|scope| {
+    ///     for (mut decoder, segment) in decoders.into_iter().zip(&segments) {
+    ///         scope.spawn(move || {
+    ///             let decoded = decoder.decode_iid_symbols(3, model).collect::<Result<Vec<_>, _>>();
+    ///             assert_eq!(&decoded.unwrap(), segment);
+    ///         });
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// [`as_seekable_decoder`]: Self::as_seekable_decoder
+    #[allow(clippy::type_complexity, clippy::result_unit_err)]
+    pub fn seekable_decoders_at<'a>(
+        &'a self,
+        checkpoints: &[<AnsCoder<Word, State, Backend::AsSeekReadWords> as PosSeek>::Position],
+    ) -> Result<Vec<AnsCoder<Word, State, Backend::AsSeekReadWords>>, ()>
+    where
+        Backend: AsSeekReadWords<'a, Word, Stack>,
+    {
+        checkpoints
+            .iter()
+            .map(|checkpoint| self.seekable_decoder_at(checkpoint.clone()))
+            .collect()
     }
 
-    pub fn from_binary_slice(data: &'bulk [Word]) -> Self {
-        Self::from_binary(backends::Cursor::new_at_write_end(data)).unwrap_infallible()
+    /// Checks that a jump table is plausible for the compressed data on this coder.
+    ///
+    /// A jump table is a list of checkpoints `(position, state)` as returned by
+    /// [`pos`](crate::Pos::pos) during encoding, listed in the order in which a decoder
+    /// encounters them (i.e., in the reverse order of encoding, as in the example for
+    /// [`seekable_decoders_at`]). This method checks that
+    /// - each `position` is within the compressed data currently on the coder (not
+    ///   counting the words of the internal state);
+    /// - each `state` satisfies the invariant of the internal coder state, i.e., it is at
+    ///   least `2^(State::BITS - Word::BITS)` unless `position == 0`; and
+    /// - positions don't increase from one checkpoint to the next.
+    ///
+    /// Returns `Err(index)` with the index of the first checkpoint that violates these
+    /// conditions. This is a cheap sanity check that catches the common mistake of using a
+    /// jump table that was created for different compressed data before decoding from
+    /// arbitrary positions. It can't detect all mismatches since it doesn't decode any
+    /// data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Encode},
+    ///     Pos,
+    /// };
+    ///
+    /// let model = DefaultUniformModel::new(1000);
+    /// let mut encoder = DefaultAnsCoder::new();
+    /// let mut checkpoints = Vec::new();
+    /// for start in [200, 100, 0] {
+    ///     encoder.encode_iid_symbols_reverse(start..start + 10, model).unwrap();
+    ///     checkpoints.push(encoder.pos());
+    /// }
+    /// checkpoints.reverse();
+    /// assert_eq!(encoder.validate_jump_table(&checkpoints), Ok(()));
+    ///
+    /// checkpoints[1].1 = 3; // Corrupt the state of the second checkpoint.
+    /// assert_eq!(encoder.validate_jump_table(&checkpoints), Err(1));
+    /// ```
+    ///
+    /// [`seekable_decoders_at`]: Self::seekable_decoders_at
+    pub fn validate_jump_table(&self, table: &[(usize, State)]) -> Result<(), usize>
+    where
+        Backend: BoundedReadWords<Word, Stack>,
+    {
+        let len = self.bulk.remaining();
+        let threshold = State::one() << (State::BITS - Word::BITS);
+        let mut previous_position = len;
+        for (index, &(position, state)) in table.iter().enumerate() {
+            if position > previous_position || (position != 0 && state < threshold) {
+                return Err(index);
+            }
+            previous_position = position;
+        }
+
+        Ok(())
     }
-}
 
-impl<Word, State, Buf> AnsCoder<Word, State, Reverse<Cursor<Word, Buf>>>
-where
-    Word: BitArray + Into<State>,
-    State: BitArray + AsPrimitive<Word>,
-    Buf: AsRef<[Word]>,
-{
-    pub fn from_reversed_compressed(compressed: Buf) -> Result<Self, Buf> {
-        Self::from_compressed(Reverse(Cursor::new_at_write_beginning(compressed)))
-            .map_err(|Reverse(cursor)| cursor.into_buf_and_pos().0)
+    /// Checks that the coder upholds its internal invariant.
+    ///
+    /// An `AnsCoder` maintains the invariant that its internal state is at least
+    /// `2^(State::BITS - Word::BITS)` unless its bulk of compressed words is empty. All
+    /// safe constructors and coding operations uphold this invariant, but the low-level
+    /// constructor [`from_raw_parts`] can't check it. Violating the invariant is not a
+    /// memory safety issue, but it leads to incorrect results (e.g., from [`is_empty`]) and
+    /// to corrupted data when encoding or decoding.
+    ///
+    /// Returns `Err` with a description of the problem if the invariant is violated. This
+    /// method is cheap, so it's a convenient one-liner to assert in property tests or
+    /// fuzzers, or to call in debug builds after assembling a coder from untrusted parts.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder};
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    /// assert_eq!(ans.validate(), Ok(()));
+    /// ans.encode_iid_symbols_reverse(0..100, DefaultUniformModel::new(1000)).unwrap();
+    /// assert_eq!(ans.validate(), Ok(()));
+    ///
+    /// // A nonempty bulk requires a large enough state.
+    /// let (bulk, state) = ans.into_raw_parts();
+    /// assert!(DefaultAnsCoder::from_raw_parts(bulk.clone(), state).validate().is_ok());
+    /// assert!(DefaultAnsCoder::from_raw_parts(bulk, 3).validate().is_err());
+    /// ```
+    ///
+    /// [`from_raw_parts`]: Self::from_raw_parts
+    /// [`is_empty`]: Self::is_empty
+    pub fn validate(&self) -> Result<(), &'static str>
+    where
+        Backend: BoundedReadWords<Word, Stack>,
+    {
+        if self.bulk.remaining() != 0 && self.state < State::one() << (State::BITS - Word::BITS) {
+            Err("The state of an `AnsCoder` with nonempty bulk must be at least `2^(State::BITS - Word::BITS)`.")
+        } else {
+            Ok(())
+        }
     }
+}
 
-    pub fn from_reversed_binary(data: Buf) -> Self {
-        Self::from_binary(Reverse(Cursor::new_at_write_beginning(data))).unwrap_infallible()
+/// Error type for parsing compressed data from bytes.
+///
+/// Returned by [`AnsCoder::from_compressed_le_bytes`] and
+/// [`AnsCoder::from_compressed_be_bytes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressedBytesError {
+    /// The number of bytes is not a multiple of the number of bytes per compressed word.
+    InvalidLength,
+
+    /// The last compressed word is zero, which never happens for data obtained from
+    /// [`AnsCoder::into_compressed`] (see [`AnsCoder::from_compressed`]).
+    TrailingZeroWord,
+}
+
+impl Display for CompressedBytesError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidLength => write!(
+                f,
+                "Number of bytes is not a multiple of the number of bytes per compressed word."
+            ),
+            Self::TrailingZeroWord => write!(f, "Compressed data ends in a zero word."),
+        }
     }
 }
 
-impl<Word, State, Iter, ReadError> AnsCoder<Word, State, FallibleIteratorReadWords<Iter>>
-where
-    Word: BitArray + Into<State>,
-    State: BitArray + AsPrimitive<Word>,
-    Iter: Iterator<Item = Result<Word, ReadError>>,
-    FallibleIteratorReadWords<Iter>: ReadWords<Word, Stack, ReadError = ReadError>,
-{
-    pub fn from_reversed_compressed_iter(compressed: Iter) -> Result<Self, Fuse<Iter>> {
-        Self::from_compressed(FallibleIteratorReadWords::new(compressed))
-            .map_err(|iterator_backend| iterator_backend.into_iter())
+#[cfg(feature = "std")]
+impl std::error::Error for CompressedBytesError {}
+
+/// Error type for [`AnsCoder::decode_moe`].
+///
+/// Indicates that the gate model decoded a gate that doesn't select any of the provided
+/// expert models.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GateOutOfRangeError {
+    /// The decoded gate.
+    pub gate: usize,
+
+    /// The number of expert models, i.e., the exclusive upper bound for valid gates.
+    pub num_experts: usize,
+}
+
+impl Display for GateOutOfRangeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Decoded gate {} is out of bounds for {} expert models.",
+            self.gate, self.num_experts
+        )
     }
+}
 
-    pub fn from_reversed_binary_iter(data: Iter) -> Result<Self, ReadError> {
-        Self::from_binary(FallibleIteratorReadWords::new(data))
+#[cfg(feature = "std")]
+impl std::error::Error for GateOutOfRangeError {}
+
+/// Error type for [`AnsCoder::encode_contextual_symbols`] and
+/// [`AnsCoder::decode_contextual_symbols`].
+///
+/// Indicates that a context (i.e., the initial context or a symbol converted to a `usize`)
+/// doesn't select any of the sub-models of the [`ContextModel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextOutOfRangeError {
+    /// The invalid context.
+    pub context: usize,
+
+    /// The number of contexts of the `ContextModel`, i.e., the exclusive upper bound for
+    /// valid contexts.
+    pub num_contexts: usize,
+}
+
+impl Display for ContextOutOfRangeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Context {} is out of bounds for a context model with {} contexts.",
+            self.context, self.num_contexts
+        )
     }
 }
 
-impl<Word, State, Backend> AnsCoder<Word, State, Backend>
+#[cfg(feature = "std")]
+impl std::error::Error for ContextOutOfRangeError {}
+
+fn num_valid_bits_u64<Word: BitArray, State: BitArray>(bulk_len: usize, state: State) -> u64 {
+    let state_bits = core::cmp::max(State::BITS - state.leading_zeros() as usize, 1) - 1;
+    num_bits_u64::<Word>(bulk_len) + state_bits as u64
+}
+
+/// Builds a categorical entropy model from a row of logits (i.e., unnormalized
+/// log-probabilities) by applying a numerically stable softmax.
+///
+/// # Panics
+///
+/// Panics if any entry of `logits` is not finite, or if `logits` has fewer than two
+/// entries or at least `2^PRECISION - 1` entries (so that not every entry can be assigned a
+/// nonzero probability).
+fn categorical_from_logits<Probability, const PRECISION: usize>(
+    logits: &[f32],
+) -> ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>
+where
+    Probability: BitArray + AsPrimitive<usize>,
+    usize: AsPrimitive<Probability>,
+    f64: AsPrimitive<Probability>,
+{
+    assert!(
+        logits.iter().all(|logit| logit.is_finite()),
+        "Logits must be finite."
+    );
+
+    // Subtracting the maximum logit avoids overflow in `exp` and guarantees that at least
+    // one term is exactly one, so the normalization can't underflow to zero.
+    let max_logit = logits
+        .iter()
+        .fold(f32::NEG_INFINITY, |max, &logit| max.max(logit));
+    let probabilities = logits
+        .iter()
+        .map(|&logit| libm::exp(logit as f64 - max_logit as f64))
+        .collect::<Vec<_>>();
+
+    ContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast::<f64>(
+        &probabilities,
+        None,
+    )
+    .expect("Each row of logits must have at least two and fewer than `2^PRECISION - 1` entries.")
+}
+
+impl<Word, State> AnsCoder<Word, State>
 where
     Word: BitArray + Into<State>,
     State: BitArray + AsPrimitive<Word>,
-    Backend: WriteWords<Word>,
 {
-    pub fn encode_symbols_reverse<S, M, I, const PRECISION: usize>(
-        &mut self,
-        symbols_and_models: I,
-    ) -> Result<(), DefaultEncoderError<Backend::WriteError>>
-    where
-        S: Borrow<M::Symbol>,
-        M: EncoderModel<PRECISION>,
-        M::Probability: Into<Word>,
-        Word: AsPrimitive<M::Probability>,
-        I: IntoIterator<Item = (S, M)>,
-        I::IntoIter: DoubleEndedIterator,
-    {
-        self.encode_symbols(symbols_and_models.into_iter().rev())
+    /// Returns the number of valid bits of compressed data that were added between two
+    /// checkpoints.
+    ///
+    /// The arguments `earlier` and `later` are checkpoints obtained from
+    /// [`pos_and_state`], where `later` was taken after encoding some more symbols on top
+    /// of `earlier`. The result is the difference in [`num_valid_bits`] between the two
+    /// checkpoints, i.e., `Word::BITS` times the number of bulk words that were pushed in
+    /// between, corrected by the valid bits of the internal state at either end. This lets
+    /// you attribute the compressed size to individual regions of a message (e.g., to each
+    /// chunk when encoding chunk by chunk) without having to keep copies of the coder.
+    ///
+    /// The result depends only on the two checkpoints, so this is an associated function
+    /// rather than a method, e.g., `DefaultAnsCoder::bits_between(earlier, later)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `later` holds fewer valid bits than `earlier`, which indicates that the
+    /// checkpoints were passed in the wrong order or belong to different coders. Also
+    /// panics under the same (rare) conditions as [`num_bits`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder};
+    ///
+    /// let model = DefaultUniformModel::new(1000);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// let start = ans.pos_and_state();
+    /// ans.encode_iid_symbols_reverse([1, 2, 3], model).unwrap();
+    /// let middle = ans.pos_and_state();
+    /// ans.encode_iid_symbols_reverse([4, 5, 6, 7, 8], model).unwrap();
+    /// let end = ans.pos_and_state();
+    ///
+    /// let first = DefaultAnsCoder::bits_between(start, middle);
+    /// let second = DefaultAnsCoder::bits_between(middle, end);
+    /// assert_eq!(first + second, ans.num_valid_bits());
+    /// ```
+    ///
+    /// [`pos_and_state`]: #method.pos_and_state
+    /// [`num_valid_bits`]: #method.num_valid_bits
+    /// [`num_bits`]: #method.num_bits
+    pub fn bits_between(earlier: (usize, State), later: (usize, State)) -> usize {
+        let earlier = num_valid_bits_u64::<Word, State>(earlier.0, earlier.1);
+        let later = num_valid_bits_u64::<Word, State>(later.0, later.1);
+        let difference = later
+            .checked_sub(earlier)
+            .expect("Checkpoint `later` holds fewer valid bits than checkpoint `earlier`.");
+        bit_count_to_usize(difference)
     }
 
-    pub fn try_encode_symbols_reverse<S, M, E, I, const PRECISION: usize>(
-        &mut self,
-        symbols_and_models: I,
-    ) -> Result<(), TryCodingError<DefaultEncoderError<Backend::WriteError>, E>>
-    where
-        S: Borrow<M::Symbol>,
-        M: EncoderModel<PRECISION>,
-        M::Probability: Into<Word>,
-        Word: AsPrimitive<M::Probability>,
-        I: IntoIterator<Item = core::result::Result<(S, M), E>>,
-        I::IntoIter: DoubleEndedIterator,
-    {
-        self.try_encode_symbols(symbols_and_models.into_iter().rev())
+    /// Discards all compressed data and resets the coder to the same state as
+    /// [`Coder::new`](#method.new).
+    pub fn clear(&mut self) {
+        self.bulk.clear();
+        self.state = State::zero();
     }
 
-    pub fn encode_iid_symbols_reverse<S, M, I, const PRECISION: usize>(
-        &mut self,
-        symbols: I,
-        model: M,
-    ) -> Result<(), DefaultEncoderError<Backend::WriteError>>
-    where
-        S: Borrow<M::Symbol>,
-        M: EncoderModel<PRECISION> + Copy,
-        M::Probability: Into<Word>,
-        Word: AsPrimitive<M::Probability>,
-        I: IntoIterator<Item = S>,
-        I::IntoIter: DoubleEndedIterator,
-    {
-        self.encode_iid_symbols(symbols.into_iter().rev(), model)
+    /// Returns the number of compressed words for which the backend has allocated memory.
+    ///
+    /// This is the capacity of the `Vec<Word>` that holds the bulk of the compressed data
+    /// (see [`bulk`](Self::bulk)). Compare it to [`num_words`](Self::num_words) to detect
+    /// over-allocation, and call [`shrink_to_fit`](Self::shrink_to_fit) to release unused
+    /// memory. Note that `num_words` can exceed `capacity_words` by up to
+    /// `State::BITS / Word::BITS` since it also counts the words of the coder's internal
+    /// state, which aren't stored in the backend.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::stack::DefaultAnsCoder;
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.reserve_words(1000);
+    /// assert!(ans.capacity_words() >= 1000);
+    /// ans.shrink_to_fit();
+    /// assert_eq!(ans.capacity_words(), 0);
+    /// ```
+    pub fn capacity_words(&self) -> usize {
+        self.bulk.capacity()
     }
 
-    /// Consumes the ANS coder and returns the compressed data.
+    /// Starts a transaction for speculative encoding.
     ///
-    /// The returned data can be used to recreate an ANS coder with the same state
-    /// (e.g., for decoding) by passing it to
-    /// [`from_compressed`](#method.from_compressed).
+    /// Returns a guard that implements [`Encode`], so you can encode symbols through it as
+    /// usual. The guard remembers the length of the compressed data and the internal coder
+    /// state at the time of calling `begin`. If you call [`AnsCoderTransaction::commit`]
+    /// then all changes made through the guard are kept. If, however, the guard gets
+    /// dropped without calling `commit` (e.g., due to an early return), then the `AnsCoder`
+    /// is rolled back to the exact state it had when `begin` was called.
     ///
-    /// If you don't want to consume the ANS coder, consider calling
-    /// [`get_compressed`](#method.get_compressed),
-    /// [`iter_compressed`](#method.iter_compressed) instead.
+    /// This is useful, e.g., for beam-search style encoders that try out several candidate
+    /// continuations and keep only the cheapest one.
     ///
-    /// # Example
+    /// The guard dereferences only to a shared reference to the `AnsCoder`, and it
+    /// provides no methods that remove words from the compressed data. Encoding only ever
+    /// appends words, so truncating the compressed data to its original length and
+    /// restoring the original state undoes all changes. Decoding (or anything else that
+    /// could consume words that existed before the transaction started) through the
+    /// guard is rejected at compile time:
     ///
-    /// ```
-    /// use constriction::stream::{
-    ///     model::DefaultContiguousCategoricalEntropyModel, stack::DefaultAnsCoder, Decode
-    /// };
+    /// ```compile_fail
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode};
     ///
+    /// let model = DefaultUniformModel::new(100);
     /// let mut ans = DefaultAnsCoder::new();
+    /// let mut transaction = ans.begin();
+    /// transaction.decode_symbol(model).unwrap(); // Error: the guard doesn't implement `Decode`.
+    /// ```
     ///
-    /// // Push some data onto the ANS coder's stack:
-    /// let symbols = vec![8, 2, 0, 7];
-    /// let probabilities = vec![0.03, 0.07, 0.1, 0.1, 0.2, 0.2, 0.1, 0.15, 0.05];
-    /// let model = DefaultContiguousCategoricalEntropyModel
-    ///     ::from_floating_point_probabilities_fast(&probabilities, None).unwrap();
-    /// ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+    /// # Example
     ///
-    /// // Get the compressed data, consuming the ANS coder:
-    /// let compressed = ans.into_compressed().unwrap();
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Encode};
     ///
-    /// // ... write `compressed` to a file and then read it back later ...
+    /// let model = DefaultUniformModel::new(100);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_symbol(5, model).unwrap();
+    /// let original = ans.clone().into_compressed().unwrap();
     ///
-    /// // Create a new ANS coder with the same state and use it for decompression:
-    /// let mut ans = DefaultAnsCoder::from_compressed(compressed).expect("Corrupted compressed file.");
-    /// let reconstructed = ans
-    ///     .decode_iid_symbols(4, &model)
-    ///     .collect::<Result<Vec<_>, _>>()
-    ///     .unwrap();
-    /// assert_eq!(reconstructed, symbols);
-    /// assert!(ans.is_empty())
+    /// {
+    ///     let mut transaction = ans.begin();
+    ///     transaction.encode_iid_symbols_reverse([1, 2, 3], model).unwrap();
+    ///     // `transaction` is dropped here without `commit`, so the changes are rolled back.
+    /// }
+    /// assert_eq!(ans.clone().into_compressed().unwrap(), original);
+    ///
+    /// let mut transaction = ans.begin();
+    /// transaction.encode_iid_symbols_reverse([4, 5, 6], model).unwrap();
+    /// transaction.commit(); // Keeps the changes.
+    /// assert_ne!(ans.into_compressed().unwrap(), original);
     /// ```
-    pub fn into_compressed(mut self) -> Result<Backend, Backend::WriteError> {
-        self.bulk
-            .extend_from_iter(bit_array_to_chunks_truncated(self.state).rev())?;
-        Ok(self.bulk)
+    pub fn begin(&mut self) -> AnsCoderTransaction<'_, Word, State> {
+        AnsCoderTransaction {
+            len: self.bulk.len(),
+            state: self.state,
+            inner: self,
+        }
     }
 
-    /// Returns the binary data if it fits precisely into an integer number of
-    /// `Word`s
+    /// Serializes the compressed data into a canonical, platform independent byte string.
     ///
-    /// This method is meant for rather advanced use cases. For most common use cases,
-    /// you probably want to call [`into_compressed`] instead.
+    /// The output of [`into_compressed`] is a sequence of words in *stack* order, i.e., the
+    /// decoder reads it from the end, and its byte representation in memory depends on the
+    /// endianness of the platform. Other rANS implementations typically store compressed
+    /// data in the order in which the decoder reads it instead. This method converts the
+    /// compressed data into the following canonical format, which can be exchanged with
+    /// any implementation that follows the same specification:
     ///
-    /// This method is the inverse of [`from_binary`]. It is equivalent to calling
-    /// [`into_compressed`], verifying that the returned vector ends in a `1` word, and
-    /// popping off that trailing `1` word.
+    /// # Canonical Byte Format
     ///
-    /// Returns `Err(())` if the compressed data (excluding an obligatory trailing
-    /// `1` bit) does not fit into an integer number of `Word`s. This error
-    /// case includes the case of an empty `AnsCoder` (since an empty `AnsCoder` lacks the
-    /// obligatory trailing one-bit).
+    /// - The canonical representation is a sequence of `n * (Word::BITS / 8)` bytes for some
+    ///   `n >= 0`, encoding a sequence of `n` words `w_0, w_1, ..., w_{n-1}`.
+    /// - Each word is serialized in little-endian byte order, i.e., byte `k` of word `w_i`
+    ///   (for `k = 0, ..., Word::BITS / 8 - 1`) holds bits `8k` through `8k + 7` of `w_i`,
+    ///   and it is stored at byte offset `i * (Word::BITS / 8) + k`.
+    /// - Words are stored in *decoding order*: `w_0` is the word that a decoder reads
+    ///   first. Thus, `w_0` is the most significant word of the initial decoder state, and
+    ///   `w_0, w_1, ...` are the words of `into_compressed` in reverse order.
+    /// - The initial decoder state is obtained by reading words `w_0, w_1, ...` into the
+    ///   state (most significant word first) until the state is at least `2^(State::BITS -
+    ///   Word::BITS)` or the data is exhausted. Decoding a symbol with `PRECISION` bits of
+    ///   precision uses the lowest `PRECISION` bits of the state as the quantile and refills
+    ///   the state from the next word whenever it drops below `2^(State::BITS -
+    ///   Word::BITS)`.
+    /// - The first word `w_0` is never zero (an empty message is represented by zero
+    ///   bytes).
     ///
-    /// # Example
+    /// # Test Vector
     ///
-    /// ```
-    /// // Some binary data we want to represent on a `AnsCoder`.
-    /// let data = vec![0x89ab_cdef, 0x0123_4567];
+    /// Encoding the message `[6, 10, -4, 2, 5, 2, 1, 0, 2]` with a [`DefaultAnsCoder`]
+    /// (i.e., 32-bit words and a 64-bit state) with the entropy model
+    /// `DefaultLeakyQuantizer::new(-50..=50).quantize(Gaussian::new(3.2, 9.6))` (i.e., with
+    /// 24 bits of precision) results in the canonical bytes
+    /// `b7 b5 55 15 62 cf 9f b9`:
     ///
-    /// // Constructing a `AnsCoder` with `from_binary` indicates that all bits of `data` are
-    /// // considered part of the information-carrying payload.
-    /// let stack1 = constriction::stream::stack::DefaultAnsCoder::from_binary(data.clone()).unwrap();
-    /// assert_eq!(stack1.clone().into_binary().unwrap(), data); // <-- Retrieves the original `data`.
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode
+    /// };
+    /// use probability::distribution::Gaussian;
     ///
-    /// // By contrast, if we construct a `AnsCoder` with `from_compressed`, we indicate that
-    /// // - any leading `0` bits of the last entry of `data` are not considered part of
-    /// //   the information-carrying payload; and
-    /// // - the (obligatory) first `1` bit of the last entry of `data` defines the
-    /// //   boundary between unused bits and information-carrying bits; it is therefore
-    /// //   also not considered part of the payload.
-    /// // Therefore, `stack2` below only contains `32 * 2 - 7 - 1 = 56` bits of payload,
-    /// // which cannot be exported into an integer number of `u32` words:
-    /// let stack2 = constriction::stream::stack::DefaultAnsCoder::from_compressed(data.clone()).unwrap();
-    /// assert!(stack2.clone().into_binary().is_err()); // <-- Returns an error.
+    /// let message = [6, 10, -4, 2, 5, 2, 1, 0, 2];
+    /// let model = DefaultLeakyQuantizer::new(-50..=50).quantize(Gaussian::new(3.2, 9.6));
     ///
-    /// // Use `into_compressed` to retrieve the data in this case:
-    /// assert_eq!(stack2.into_compressed().unwrap(), data);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse(&message, &model).unwrap();
+    /// let bytes = ans.into_canonical_ans_bytes();
+    /// assert_eq!(bytes, [0xb7, 0xb5, 0x55, 0x15, 0x62, 0xcf, 0x9f, 0xb9]);
     ///
-    /// // Calling `into_compressed` on `stack1` would append an extra `1` bit to indicate
-    /// // the boundary between information-carrying bits and padding `0` bits:
-    /// assert_eq!(stack1.into_compressed().unwrap(), vec![0x89ab_cdef, 0x0123_4567, 0x0000_0001]);
+    /// let mut ans = DefaultAnsCoder::from_canonical_ans_bytes(&bytes).unwrap();
+    /// let decoded = ans
+    ///     .decode_iid_symbols(message.len(), &model)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(decoded, message);
     /// ```
     ///
-    /// [`from_binary`]: #method.from_binary
+    /// The inverse of this method is [`from_canonical_ans_bytes`].
+    ///
     /// [`into_compressed`]: #method.into_compressed
-    pub fn into_binary(mut self) -> Result<Backend, Option<Backend::WriteError>> {
-        let valid_bits = (State::BITS - 1).wrapping_sub(self.state.leading_zeros() as usize);
-
-        if valid_bits % Word::BITS != 0 || valid_bits == usize::MAX {
-            Err(None)
-        } else {
-            let truncated_state = self.state ^ (State::one() << valid_bits);
-            self.bulk
-                .extend_from_iter(bit_array_to_chunks_truncated(truncated_state).rev())?;
-            Ok(self.bulk)
+    /// [`from_canonical_ans_bytes`]: #method.from_canonical_ans_bytes
+    pub fn into_canonical_ans_bytes(self) -> Vec<u8>
+    where
+        Word: AsPrimitive<u8>,
+    {
+        let bytes_per_word = Word::BITS / 8;
+        let compressed = self.into_compressed().unwrap_infallible();
+        let mut bytes = alloc::vec![0; compressed.len() * bytes_per_word];
+        for (&word, chunk) in compressed
+            .iter()
+            .rev()
+            .zip(bytes.chunks_exact_mut(bytes_per_word))
+        {
+            word_to_bytes(word, chunk, Endianness::LittleEndian);
         }
+        bytes
     }
-}
 
-impl<Word, State, Buf> AnsCoder<Word, State, Cursor<Word, Buf>>
-where
-    Word: BitArray,
-    State: BitArray + AsPrimitive<Word> + From<Word>,
-    Buf: AsRef<[Word]> + AsMut<[Word]>,
-{
-    pub fn into_reversed(self) -> AnsCoder<Word, State, Reverse<Cursor<Word, Buf>>> {
-        let (bulk, state) = self.into_raw_parts();
-        AnsCoder {
-            bulk: bulk.into_reversed(),
-            state,
-            phantom: PhantomData,
+    /// Creates a coder from compressed data in the canonical byte format.
+    ///
+    /// This is the inverse of [`into_canonical_ans_bytes`], whose documentation specifies
+    /// the canonical byte format. Returns `Err(())` if `bytes` is not a valid canonical
+    /// representation, i.e., if its length is not a multiple of `Word::BITS / 8` or if the
+    /// first word is zero.
+    ///
+    /// [`into_canonical_ans_bytes`]: #method.into_canonical_ans_bytes
+    #[allow(clippy::result_unit_err)]
+    pub fn from_canonical_ans_bytes(bytes: &[u8]) -> Result<Self, ()>
+    where
+        u8: Into<Word>,
+    {
+        let bytes_per_word = Word::BITS / 8;
+        if bytes.len() % bytes_per_word != 0 {
+            return Err(());
         }
-    }
-}
 
-impl<Word, State, Buf> AnsCoder<Word, State, Reverse<Cursor<Word, Buf>>>
-where
-    Word: BitArray,
-    State: BitArray + AsPrimitive<Word> + From<Word>,
-    Buf: AsRef<[Word]> + AsMut<[Word]>,
-{
-    pub fn into_reversed(self) -> AnsCoder<Word, State, Cursor<Word, Buf>> {
-        let (bulk, state) = self.into_raw_parts();
-        AnsCoder {
-            bulk: bulk.into_reversed(),
-            state,
-            phantom: PhantomData,
-        }
+        let compressed = bytes
+            .chunks_exact(bytes_per_word)
+            .rev()
+            .map(|chunk| bytes_to_word(chunk, Endianness::LittleEndian))
+            .collect();
+        Self::from_compressed(compressed).map_err(|_| ())
     }
-}
-
-impl<Word, State, Backend> Code for AnsCoder<Word, State, Backend>
-where
-    Word: BitArray + Into<State>,
-    State: BitArray + AsPrimitive<Word>,
-{
-    type Word = Word;
-    type State = State;
 
-    #[inline(always)]
-    fn state(&self) -> Self::State {
-        self.state
+    /// Like [`into_canonical_ans_bytes`] but appends a footer that identifies the format.
+    ///
+    /// The footer allows tools to recognize compressed data produced by `constriction`
+    /// (see [`detect_format`]). It is appended at the *end* so that the compressed data
+    /// itself still starts at the beginning of the returned bytes, where a decoder starts
+    /// reading. The footer consists of the following 8 bytes:
+    ///
+    /// - a version byte (currently `1`);
+    /// - the number of bits per compressed word ([`compressed_word_bits`]) as a single byte;
+    /// - the number of bits of the coder's state ([`state_bits`]) as a single byte;
+    /// - a zero byte (reserved for future use); and
+    /// - the four magic bytes `b"CNST"`.
+    ///
+    /// Use [`from_canonical_ans_bytes_with_optional_footer`] to read the data back in.
+    ///
+    /// [`into_canonical_ans_bytes`]: #method.into_canonical_ans_bytes
+    /// [`compressed_word_bits`]: #method.compressed_word_bits
+    /// [`state_bits`]: #method.state_bits
+    /// [`from_canonical_ans_bytes_with_optional_footer`]:
+    ///     #method.from_canonical_ans_bytes_with_optional_footer
+    pub fn into_canonical_ans_bytes_with_footer(self) -> Vec<u8>
+    where
+        Word: AsPrimitive<u8>,
+    {
+        let word_bits = self.compressed_word_bits() as u8;
+        let state_bits = self.state_bits() as u8;
+        let mut bytes = self.into_canonical_ans_bytes();
+        bytes.extend_from_slice(&[FOOTER_VERSION, word_bits, state_bits, 0]);
+        bytes.extend_from_slice(&FOOTER_MAGIC);
+        bytes
     }
-}
-
-impl<Word, State, Backend, const PRECISION: usize> Encode<PRECISION>
-    for AnsCoder<Word, State, Backend>
-where
-    Word: BitArray + Into<State>,
-    State: BitArray + AsPrimitive<Word>,
-    Backend: WriteWords<Word>,
-{
-    type FrontendError = DefaultEncoderFrontendError;
-    type BackendError = Backend::WriteError;
 
-    /// Encodes a single symbol and appends it to the compressed data.
+    /// Creates a coder from canonical bytes that may or may not end in a footer.
     ///
-    /// This is a low level method. You probably usually want to call a batch method
-    /// like [`encode_symbols`](#method.encode_symbols) or
-    /// [`encode_iid_symbols`](#method.encode_iid_symbols) instead. See examples there.
+    /// If `bytes` ends in a footer as written by [`into_canonical_ans_bytes_with_footer`]
+    /// (see [`detect_format`]) then this method validates that the footer matches the
+    /// word and state sizes of `Self`, strips it off, and reads the remaining bytes with
+    /// [`from_canonical_ans_bytes`]. Otherwise, it treats `bytes` as raw canonical bytes.
     ///
-    /// The bound `impl Borrow<M::Symbol>` on argument `symbol` essentially means that
-    /// you can provide the symbol either by value or by reference, at your choice.
+    /// Returns `Err(())` if the footer records different word or state sizes than those of
+    /// `Self`, or if the compressed data is not in valid canonical format.
     ///
-    /// Returns [`Err(ImpossibleSymbol)`] if `symbol` has zero probability under the
-    /// entropy model `model`. This error can usually be avoided by using a
-    /// "leaky" distribution as the entropy model, i.e., a distribution that assigns a
-    /// nonzero probability to all symbols within a finite domain. Leaky distributions
-    /// can be constructed with, e.g., a
-    /// [`LeakyQuantizer`](models/struct.LeakyQuantizer.html) or with
-    /// [`LeakyCategorical::from_floating_point_probabilities`](
-    /// models/struct.LeakyCategorical.html#method.from_floating_point_probabilities).
+    /// # Example
     ///
-    /// TODO: move this and similar doc comments to the trait definition.
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultUniformModel,
+    ///     stack::{DefaultAnsCoder, SmallAnsCoder},
+    ///     Decode,
+    /// };
     ///
-    /// [`Err(ImpossibleSymbol)`]: enum.EncodingError.html#variant.ImpossibleSymbol
-    fn encode_symbol<M>(
-        &mut self,
-        symbol: impl Borrow<M::Symbol>,
-        model: M,
-    ) -> Result<(), DefaultEncoderError<Self::BackendError>>
+    /// let model = DefaultUniformModel::new(10);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse([3, 1, 4, 1, 5], model).unwrap();
+    /// let with_footer = ans.clone().into_canonical_ans_bytes_with_footer();
+    /// let raw = ans.into_canonical_ans_bytes();
+    ///
+    /// for bytes in [&with_footer, &raw] {
+    ///     let mut ans = DefaultAnsCoder::from_canonical_ans_bytes_with_optional_footer(bytes)
+    ///         .unwrap();
+    ///     let decoded = ans.decode_iid_symbols(5, model).collect::<Result<Vec<_>, _>>();
+    ///     assert_eq!(decoded.unwrap(), [3, 1, 4, 1, 5]);
+    /// }
+    ///
+    /// // The footer records that the data was produced with 32-bit words.
+    /// assert!(SmallAnsCoder::from_canonical_ans_bytes_with_optional_footer(&with_footer)
+    ///     .is_err());
+    /// ```
+    ///
+    /// [`into_canonical_ans_bytes_with_footer`]: #method.into_canonical_ans_bytes_with_footer
+    /// [`from_canonical_ans_bytes`]: #method.from_canonical_ans_bytes
+    #[allow(clippy::result_unit_err)]
+    pub fn from_canonical_ans_bytes_with_optional_footer(bytes: &[u8]) -> Result<Self, ()>
     where
-        M: EncoderModel<PRECISION>,
-        M::Probability: Into<Self::Word>,
-        Self::Word: AsPrimitive<M::Probability>,
+        u8: Into<Word>,
     {
-        generic_static_asserts!(
-            (Word: BitArray, State:BitArray; const PRECISION: usize);
-            PROBABILITY_SUPPORTS_PRECISION: State::BITS >= Word::BITS + PRECISION;
-            NON_ZERO_PRECISION: PRECISION > 0;
-            STATE_SUPPORTS_AT_LEAST_TWO_WORDS: State::BITS >= 2 * Word::BITS;
-        );
-
-        let (left_sided_cumulative, probability) = model
-            .left_cumulative_and_probability(symbol)
-            .ok_or_else(|| DefaultEncoderFrontendError::ImpossibleSymbol.into_coder_error())?;
+        match detect_format(bytes) {
+            Some(info) => {
+                if info.word_bits != Word::BITS || info.state_bits != State::BITS {
+                    return Err(());
+                }
+                Self::from_canonical_ans_bytes(&bytes[..bytes.len() - FOOTER_LEN])
+            }
+            None => Self::from_canonical_ans_bytes(bytes),
+        }
+    }
 
-        if (self.state >> (State::BITS - PRECISION)) >= probability.get().into().into() {
-            self.bulk.write(self.state.as_())?;
-            self.state = self.state >> Word::BITS;
-            // At this point, the invariant on `self.state` (see its doc comment) is
-            // temporarily violated, but it will be restored below.
+    /// Serializes the compressed data into bytes with the given byte order.
+    ///
+    /// Returns the words of [`into_compressed`] in the same order, with each word
+    /// serialized into `Word::BITS / 8` bytes in the given `endianness`. This differs from
+    /// the [canonical byte format](#method.into_canonical_ans_bytes), which always uses
+    /// little endian byte order and stores the words in reverse order.
+    ///
+    /// The inverse of this method is [`from_compressed_bytes`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode},
+    ///     Endianness,
+    /// };
+    ///
+    /// let model = DefaultUniformModel::new(10);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse([3, 1, 4, 1, 5], model).unwrap();
+    /// let compressed = ans.clone().into_compressed().unwrap();
+    ///
+    /// let bytes = ans.into_compressed_bytes(Endianness::BigEndian);
+    /// assert_eq!(bytes.len(), 4 * compressed.len());
+    /// assert_eq!(bytes[..4], compressed[0].to_be_bytes());
+    ///
+    /// let mut ans = DefaultAnsCoder::from_compressed_bytes(&bytes, Endianness::BigEndian)
+    ///     .unwrap();
+    /// let decoded = ans
+    ///     .decode_iid_symbols(5, model)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(decoded, [3, 1, 4, 1, 5]);
+    /// ```
+    ///
+    /// [`into_compressed`]: #method.into_compressed
+    /// [`from_compressed_bytes`]: #method.from_compressed_bytes
+    pub fn into_compressed_bytes(self, endianness: Endianness) -> Vec<u8>
+    where
+        Word: AsPrimitive<u8>,
+    {
+        let bytes_per_word = Word::BITS / 8;
+        let compressed = self.into_compressed().unwrap_infallible();
+        let mut bytes = alloc::vec![0; compressed.len() * bytes_per_word];
+        for (&word, chunk) in compressed
+            .iter()
+            .zip(bytes.chunks_exact_mut(bytes_per_word))
+        {
+            word_to_bytes(word, chunk, endianness);
         }
+        bytes
+    }
 
-        let remainder = (self.state % probability.get().into().into()).as_().as_();
-        let prefix = self.state / probability.get().into().into();
-        let quantile = left_sided_cumulative + remainder;
-        self.state = prefix << PRECISION | quantile.into().into();
+    /// Creates a coder from bytes written by [`into_compressed_bytes`].
+    ///
+    /// The argument `endianness` must match the byte order that was used for
+    /// serialization. Returns `Err(())` if the length of `bytes` is not a multiple of
+    /// `Word::BITS / 8` or if the resulting words are not valid compressed data (see
+    /// [`from_compressed`]).
+    ///
+    /// [`into_compressed_bytes`]: #method.into_compressed_bytes
+    /// [`from_compressed`]: #method.from_compressed
+    #[allow(clippy::result_unit_err)]
+    pub fn from_compressed_bytes(bytes: &[u8], endianness: Endianness) -> Result<Self, ()>
+    where
+        u8: Into<Word>,
+    {
+        Self::parse_compressed_bytes(bytes, endianness).map_err(|_| ())
+    }
 
-        Ok(())
+    /// Creates a coder from compressed data that was serialized in little endian byte order.
+    ///
+    /// This is useful if you receive compressed data as raw bytes, e.g., from a network
+    /// socket, and it is equivalent to calling [`from_compressed_bytes`] with
+    /// [`Endianness::LittleEndian`], except that it returns a [`CompressedBytesError`] that
+    /// describes what's wrong with malformed input.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     stream::{
+    ///         model::DefaultUniformModel,
+    ///         stack::{CompressedBytesError, DefaultAnsCoder},
+    ///         Decode,
+    ///     },
+    ///     Endianness,
+    /// };
+    ///
+    /// let model = DefaultUniformModel::new(10);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse([3, 1, 4, 1, 5], model).unwrap();
+    /// let bytes = ans.into_compressed_bytes(Endianness::LittleEndian);
+    ///
+    /// let mut ans = DefaultAnsCoder::from_compressed_le_bytes(&bytes).unwrap();
+    /// let decoded = ans.decode_iid_symbols(5, model).collect::<Result<Vec<_>, _>>();
+    /// assert_eq!(decoded.unwrap(), [3, 1, 4, 1, 5]);
+    ///
+    /// assert_eq!(
+    ///     DefaultAnsCoder::from_compressed_le_bytes(&bytes[1..]).unwrap_err(),
+    ///     CompressedBytesError::InvalidLength
+    /// );
+    /// ```
+    ///
+    /// [`from_compressed_bytes`]: #method.from_compressed_bytes
+    pub fn from_compressed_le_bytes(bytes: &[u8]) -> Result<Self, CompressedBytesError>
+    where
+        u8: Into<Word>,
+    {
+        Self::parse_compressed_bytes(bytes, Endianness::LittleEndian)
     }
 
-    fn maybe_full(&self) -> bool {
-        self.bulk.maybe_full()
+    /// Same as [`from_compressed_le_bytes`] but for big endian byte order.
+    ///
+    /// [`from_compressed_le_bytes`]: #method.from_compressed_le_bytes
+    pub fn from_compressed_be_bytes(bytes: &[u8]) -> Result<Self, CompressedBytesError>
+    where
+        u8: Into<Word>,
+    {
+        Self::parse_compressed_bytes(bytes, Endianness::BigEndian)
     }
-}
 
-impl<Word, State, Backend, const PRECISION: usize> Decode<PRECISION>
-    for AnsCoder<Word, State, Backend>
-where
-    Word: BitArray + Into<State>,
-    State: BitArray + AsPrimitive<Word>,
-    Backend: ReadWords<Word, Stack>,
-{
-    /// ANS coding is surjective, and we (deliberately) allow decoding past EOF (in a
-    /// deterministic way) for consistency. Therefore, decoding cannot fail.    
-    type FrontendError = Infallible;
+    fn parse_compressed_bytes(
+        bytes: &[u8],
+        endianness: Endianness,
+    ) -> Result<Self, CompressedBytesError>
+    where
+        u8: Into<Word>,
+    {
+        let bytes_per_word = Word::BITS / 8;
+        if bytes.len() % bytes_per_word != 0 {
+            return Err(CompressedBytesError::InvalidLength);
+        }
 
-    type BackendError = Backend::ReadError;
+        let compressed = bytes
+            .chunks_exact(bytes_per_word)
+            .map(|chunk| bytes_to_word(chunk, endianness))
+            .collect();
+        Self::from_compressed(compressed).map_err(|_| CompressedBytesError::TrailingZeroWord)
+    }
 
-    /// Decodes a single symbol and pops it off the compressed data.
+    /// Serializes the compressed data into a lowercase hexadecimal string.
     ///
-    /// This is a low level method. You usually probably want to call a batch method
-    /// like [`decode_symbols`](#method.decode_symbols) or
-    /// [`decode_iid_symbols`](#method.decode_iid_symbols) instead.
+    /// The string encodes the [canonical bytes](#method.into_canonical_ans_bytes) with two
+    /// hex digits per byte. This is convenient for embedding compressed data in text-based
+    /// formats, or for logging. See [`into_base64`](#method.into_base64) for a more
+    /// compact text representation.
     ///
-    /// This method is called `decode_symbol` rather than `decode_symbol` to stress the
-    /// fact that the `AnsCoder` is a stack: `decode_symbol` will return the *last* symbol
-    /// that was previously encoded via [`encode_symbol`](#method.encode_symbol).
+    /// The inverse of this method is [`from_hex`](#method.from_hex).
     ///
-    /// Note that this method cannot fail. It will still produce symbols in a
-    /// deterministic way even if the stack is empty, but such symbols will not
-    /// recover any previously encoded data and will generally have low entropy.
-    /// Still, being able to pop off an arbitrary number of symbols can sometimes be
-    /// useful in edge cases of, e.g., the bits-back algorithm.
-    #[inline(always)]
-    fn decode_symbol<M>(
-        &mut self,
-        model: M,
-    ) -> Result<M::Symbol, CoderError<Self::FrontendError, Self::BackendError>>
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder};
+    /// use probability::distribution::Gaussian;
+    ///
+    /// let message = [6, 10, -4, 2, 5, 2, 1, 0, 2];
+    /// let model = DefaultLeakyQuantizer::new(-50..=50).quantize(Gaussian::new(3.2, 9.6));
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse(&message, &model).unwrap();
+    /// let hex = ans.into_hex();
+    /// assert_eq!(hex, "b7b5551562cf9fb9");
+    ///
+    /// let ans = DefaultAnsCoder::from_hex(&hex).unwrap();
+    /// assert_eq!(ans.into_hex(), hex);
+    /// ```
+    pub fn into_hex(self) -> String
     where
-        M: DecoderModel<PRECISION>,
-        M::Probability: Into<Self::Word>,
-        Self::Word: AsPrimitive<M::Probability>,
+        Word: AsPrimitive<u8>,
     {
-        generic_static_asserts!(
-            (Word: BitArray, State:BitArray; const PRECISION: usize);
-            PROBABILITY_SUPPORTS_PRECISION: State::BITS >= Word::BITS + PRECISION;
-            NON_ZERO_PRECISION: PRECISION > 0;
-            STATE_SUPPORTS_AT_LEAST_TWO_WORDS: State::BITS >= 2 * Word::BITS;
-        );
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        let bytes = self.into_canonical_ans_bytes();
+        let mut hex = String::with_capacity(2 * bytes.len());
+        for byte in bytes {
+            hex.push(DIGITS[(byte >> 4) as usize] as char);
+            hex.push(DIGITS[(byte & 0x0f) as usize] as char);
+        }
+        hex
+    }
 
-        let quantile = (self.state % (State::one() << PRECISION)).as_().as_();
-        let (symbol, left_sided_cumulative, probability) = model.quantile_function(quantile);
-        let remainder = quantile - left_sided_cumulative;
-        self.state =
-            (self.state >> PRECISION) * probability.get().into().into() + remainder.into().into();
-        if self.state < State::one() << (State::BITS - Word::BITS) {
-            // Invariant on `self.state` (see its doc comment) is violated. Restore it by
-            // refilling with a compressed word from `self.bulk` if available.
-            if let Some(word) = self.bulk.read()? {
-                self.state = (self.state << Word::BITS) | word.into();
+    /// Creates a coder from a hexadecimal string as returned by [`into_hex`].
+    ///
+    /// Accepts both lowercase and uppercase hex digits. Returns `Err(())` if `hex` is not a
+    /// valid hex string or if the decoded bytes are not a valid [canonical
+    /// representation](#method.from_canonical_ans_bytes).
+    ///
+    /// [`into_hex`]: #method.into_hex
+    #[allow(clippy::result_unit_err)]
+    pub fn from_hex(hex: &str) -> Result<Self, ()>
+    where
+        u8: Into<Word>,
+    {
+        fn digit(c: u8) -> Result<u8, ()> {
+            match c {
+                b'0'..=b'9' => Ok(c - b'0'),
+                b'a'..=b'f' => Ok(c - b'a' + 10),
+                b'A'..=b'F' => Ok(c - b'A' + 10),
+                _ => Err(()),
             }
         }
 
-        Ok(symbol)
-    }
-
-    fn maybe_exhausted(&self) -> bool {
-        self.is_empty()
+        let hex = hex.as_bytes();
+        if hex.len() % 2 != 0 {
+            return Err(());
+        }
+        let bytes = hex
+            .chunks_exact(2)
+            .map(|pair| Ok(digit(pair[0])? << 4 | digit(pair[1])?))
+            .collect::<Result<Vec<_>, ()>>()?;
+        Self::from_canonical_ans_bytes(&bytes)
     }
-}
 
-impl<Word, State, Backend> PosSeek for AnsCoder<Word, State, Backend>
+    /// Serializes the compressed data into a base64 string.
+    ///
+    /// The string encodes the [canonical bytes](#method.into_canonical_ans_bytes) with the
+    /// standard base64 alphabet (RFC 4648) and with padding. Thus, the result is
+    /// independent of the platform's endianness, and it can be embedded in JSON or other
+    /// text-based formats. (Use a percent-encoding if you want to embed it in a URL.)
+    ///
+    /// This method is only available if the crate feature `base64` is enabled. The inverse
+    /// of this method is [`from_base64`](#method.from_base64).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder};
+    /// use probability::distribution::Gaussian;
+    ///
+    /// let message = [6, 10, -4, 2, 5, 2, 1, 0, 2];
+    /// let model = DefaultLeakyQuantizer::new(-50..=50).quantize(Gaussian::new(3.2, 9.6));
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse(&message, &model).unwrap();
+    /// let base64 = ans.into_base64();
+    /// assert_eq!(base64, "t7VVFWLPn7k=");
+    ///
+    /// let ans = DefaultAnsCoder::from_base64(&base64).unwrap();
+    /// assert_eq!(ans.into_base64(), base64);
+    /// ```
+    #[cfg(feature = "base64")]
+    pub fn into_base64(self) -> String
+    where
+        Word: AsPrimitive<u8>,
+    {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD.encode(self.into_canonical_ans_bytes())
+    }
+
+    /// Creates a coder from a base64 string as returned by [`into_base64`].
+    ///
+    /// Expects the standard base64 alphabet with padding. Returns `Err(())` if `base64` is
+    /// not a valid base64 string or if the decoded bytes are not a valid [canonical
+    /// representation](#method.from_canonical_ans_bytes).
+    ///
+    /// This method is only available if the crate feature `base64` is enabled.
+    ///
+    /// [`into_base64`]: #method.into_base64
+    #[cfg(feature = "base64")]
+    #[allow(clippy::result_unit_err)]
+    pub fn from_base64(base64: &str) -> Result<Self, ()>
+    where
+        u8: Into<Word>,
+    {
+        use base64::Engine;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(base64)
+            .map_err(|_| ())?;
+        Self::from_canonical_ans_bytes(&bytes)
+    }
+
+    /// Like [`into_compressed`] but also computes a digest of the compressed data.
+    ///
+    /// Feeds the compressed data into `hasher` while materializing it, and returns the
+    /// compressed data together with `hasher.finish()`. This avoids a second pass over the
+    /// compressed data.
+    ///
+    /// The digest is defined as the result of calling [`Hash::hash`] on each word of the
+    /// returned `Vec` in order (with no length prefix), followed by `hasher.finish()`. Note
+    /// that this is *not* guaranteed to be the same as hashing the returned `Vec` (or slice)
+    /// as a whole, since the `Hash` implementation for slices additionally hashes the length
+    /// in an unspecified way.
+    ///
+    /// Note that the digest is meant for detecting accidental data corruption. Use a
+    /// cryptographic hash if you need to protect against deliberate tampering.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder};
+    /// use std::{collections::hash_map::DefaultHasher, hash::{Hash, Hasher}};
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse([3, 1, 4, 1, 5], DefaultUniformModel::new(10)).unwrap();
+    /// let (compressed, digest) = ans.into_compressed_with_digest(DefaultHasher::new());
+    ///
+    /// // Verify the digest, e.g., after sending `compressed` over a network.
+    /// let mut hasher = DefaultHasher::new();
+    /// for word in &compressed {
+    ///     word.hash(&mut hasher);
+    /// }
+    /// assert_eq!(digest, hasher.finish());
+    /// ```
+    ///
+    /// [`into_compressed`]: #method.into_compressed
+    pub fn into_compressed_with_digest<H: Hasher>(mut self, mut hasher: H) -> (Vec<Word>, u64) {
+        let state_words = bit_array_to_chunks_truncated::<State, Word>(self.state).rev();
+
+        for word in &self.bulk {
+            word.hash(&mut hasher);
+        }
+        self.bulk.reserve(state_words.len());
+        for word in state_words {
+            word.hash(&mut hasher);
+            self.bulk.push(word);
+        }
+
+        (self.bulk, hasher.finish())
+    }
+
+    /// Injects fresh randomness into a coder that is used as a sampler.
+    ///
+    /// When an `AnsCoder` is used for sampling (e.g., in bits-back coding, where decoding
+    /// from a stream of random bits draws samples from the entropy model), the available
+    /// randomness eventually runs out. Decoding still works after that point (ANS decoding
+    /// is surjective), but the decoded symbols are then no longer distributed according to
+    /// the entropy model. This method pushes `num_words` words obtained from calling
+    /// `random_word` onto the compressed data and refills the internal state from them
+    /// (restoring the invariant that the state has at least `State::BITS - Word::BITS`
+    /// significant bits), so that subsequent decoding continues to sample from the entropy
+    /// models. You'll typically call it as `ans.refresh_entropy(n, || rng.next_u32())`
+    /// with some pseudo-random number generator `rng`.
+    ///
+    /// # Warning
+    ///
+    /// This method modifies the compressed data in a way that cannot be undone by
+    /// decoding. It is only meaningful in pure sampling mode, i.e., if the coder contains
+    /// no more encoded symbols that you still want to decode. Otherwise, the injected words
+    /// would be decoded before (and mixed into) any remaining encoded data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode};
+    ///
+    /// // Start from an empty coder, which contains no randomness at all.
+    /// let mut sampler = DefaultAnsCoder::new();
+    /// let model = DefaultUniformModel::new(10);
+    /// assert_eq!(sampler.decode_symbol(model).unwrap(), 0);
+    ///
+    /// // Use a simple linear congruential generator for the sake of the example.
+    /// let mut seed = 1234u32;
+    /// sampler.refresh_entropy(10, || {
+    ///     seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+    ///     seed
+    /// });
+    /// assert!(!sampler.is_empty());
+    /// let samples = sampler.decode_iid_symbols(10, model).collect::<Result<Vec<_>, _>>();
+    /// assert!(samples.unwrap().iter().any(|&sample| sample != 0));
+    /// ```
+    pub fn refresh_entropy(&mut self, num_words: usize, mut random_word: impl FnMut() -> Word) {
+        self.bulk.extend((0..num_words).map(|_| random_word()));
+        while self.state < State::one() << (State::BITS - Word::BITS) {
+            if let Some(word) = self.bulk.pop() {
+                self.state = (self.state << Word::BITS) | word.into();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Decodes a symbol with one entropy model and immediately re-encodes it with another.
+    ///
+    /// This is the typical inner step of bits-back coding: a latent variable is decoded
+    /// with its approximate posterior `decode_model` (thus "getting bits back") and then
+    /// encoded with its prior (or any other model) `encode_model`. Returns the decoded
+    /// symbol.
+    ///
+    /// If the symbol turns out to have zero probability under `encode_model` then this
+    /// method returns `Err(ImpossibleSymbol)` and restores the coder to exactly the state
+    /// it had before the method call, i.e., it undoes the decoding step. Thus, the coder is
+    /// never left in a half-finished intermediate state.
+    ///
+    /// # Net Effect on the Compressed Size
+    ///
+    /// Decoding the symbol `x` reduces the size of the compressed data by approximately
+    /// `-log2(P(x))` bits and encoding it increases the size by approximately
+    /// `-log2(Q(x))` bits, where `P` and `Q` are the probability distributions of
+    /// `decode_model` and `encode_model`, respectively. Thus, [`num_bits`] changes by
+    /// approximately `log2(P(x) / Q(x))` bits (up to rounding to whole words). If both
+    /// models are identical then the compressed data doesn't change at all.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode};
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse([3, 1, 4, 1, 5], DefaultUniformModel::new(10))
+    ///     .unwrap();
+    /// let original = ans.clone();
+    ///
+    /// // Decode with a uniform model over 100 symbols, then encode with one over 1000.
+    /// let symbol = ans
+    ///     .recode_symbol(DefaultUniformModel::new(100), DefaultUniformModel::new(1000))
+    ///     .unwrap();
+    /// assert!(symbol < 100);
+    /// assert!(ans.num_valid_bits() > original.num_valid_bits());
+    ///
+    /// // Recoding in the opposite direction restores the original compressed data.
+    /// let restored = ans
+    ///     .recode_symbol(DefaultUniformModel::new(1000), DefaultUniformModel::new(100))
+    ///     .unwrap();
+    /// assert_eq!(restored, symbol);
+    /// assert_eq!(ans, original);
+    /// ```
+    ///
+    /// [`num_bits`]: Self::num_bits
+    pub fn recode_symbol<Dd, De, const PRECISION: usize>(
+        &mut self,
+        decode_model: Dd,
+        encode_model: De,
+    ) -> Result<Dd::Symbol, DefaultEncoderError<Infallible>>
+    where
+        Dd: DecoderModel<PRECISION>,
+        De: EncoderModel<PRECISION, Symbol = Dd::Symbol>,
+        Dd::Probability: Into<Word>,
+        Word: AsPrimitive<Dd::Probability>,
+        De::Probability: Into<Word>,
+        Word: AsPrimitive<De::Probability>,
+    {
+        let (symbol, left_sided_cumulative, probability) = self
+            .decode_interval::<_, _, PRECISION>(|quantile| {
+                let (symbol, left_sided_cumulative, probability) =
+                    decode_model.quantile_function(quantile);
+                (
+                    (symbol, left_sided_cumulative, probability),
+                    left_sided_cumulative,
+                    probability,
+                )
+            })
+            .unwrap_infallible();
+
+        match self.encode_symbol(&symbol, encode_model) {
+            Ok(()) => Ok(symbol),
+            Err(err) => {
+                // Undo the decoding step. Since encoding is the exact inverse of decoding,
+                // this restores the original compressed data.
+                self.encode_interval::<_, PRECISION>(left_sided_cumulative, probability, None)
+                    .unwrap_infallible();
+                Err(err)
+            }
+        }
+    }
+
+    /// Splits the coder at a checkpoint into a "bottom" and a "top" coder.
+    ///
+    /// The argument `at` has to be a checkpoint that was obtained from
+    /// [`pos_and_state`](Self::pos_and_state) on this coder at some earlier time, and the
+    /// compressed data below the checkpoint must not have been decoded since then. After
+    /// the method call, `self` holds only the data that had been encoded before the
+    /// checkpoint, i.e., `self` is reset to exactly the same state it had when the
+    /// checkpoint was taken. The returned coder holds the data that was encoded after the
+    /// checkpoint, so that decoding from it yields the same symbols that decoding from the
+    /// original coder would have yielded until reaching the checkpoint. The two coders can
+    /// then be decoded independently (e.g., on different threads or after storing them
+    /// separately).
+    ///
+    /// The symbols above the checkpoint were encoded on top of the state at the checkpoint
+    /// (ANS coding is not prefix free), so this state is also kept at the bottom of the
+    /// returned coder. As a consequence, the returned coder is not [empty](Self::is_empty)
+    /// after decoding all symbols above the checkpoint (it is then left in the state
+    /// `at.1`), and the total size of both coders exceeds the size of the original coder
+    /// by up to one `State`.
+    ///
+    /// Returns `Err(())` and leaves `self` unchanged if `at` is not a valid checkpoint on
+    /// this coder, i.e., if its position lies beyond the end of the compressed data or if
+    /// its state violates the invariant of an `AnsCoder` at this position.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultUniformModel, stack::DefaultAnsCoder, Code, Decode,
+    /// };
+    ///
+    /// let model = DefaultUniformModel::new(1000);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse([1, 2, 3, 4, 5], model).unwrap();
+    /// let checkpoint = ans.pos_and_state();
+    /// ans.encode_iid_symbols_reverse([10, 20, 30, 40, 50, 60], model).unwrap();
+    ///
+    /// let mut top = ans.split_off(checkpoint).unwrap();
+    /// assert_eq!(ans.pos_and_state(), checkpoint);
+    ///
+    /// let decoded = ans.decode_iid_symbols(5, model).collect::<Result<Vec<_>, _>>();
+    /// assert_eq!(decoded.unwrap(), [1, 2, 3, 4, 5]);
+    /// assert!(ans.is_empty());
+    ///
+    /// let decoded = top.decode_iid_symbols(6, model).collect::<Result<Vec<_>, _>>();
+    /// assert_eq!(decoded.unwrap(), [10, 20, 30, 40, 50, 60]);
+    /// assert_eq!(top.state(), checkpoint.1);
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn split_off(&mut self, at: (usize, State)) -> Result<AnsCoder<Word, State>, ()> {
+        let (pos, state) = at;
+        if pos > self.bulk.len() || (pos != 0 && state < State::one() << (State::BITS - Word::BITS))
+        {
+            return Err(());
+        }
+
+        // The top coder doesn't need its own copy of the words below `pos`: decoding the
+        // symbols above the checkpoint reads only words above `pos` (it ends in the state
+        // `state` with exactly `pos` words left on the stack).
+        let top = AnsCoder {
+            bulk: self.bulk.split_off(pos),
+            state: self.state,
+            phantom: PhantomData,
+        };
+        self.state = state;
+
+        Ok(top)
+    }
+}
+
+/// Guard for speculative encoding on an [`AnsCoder`].
+///
+/// Returned by [`AnsCoder::begin`], see documentation there.
+#[derive(Debug)]
+pub struct AnsCoderTransaction<'a, Word, State>
 where
     Word: BitArray + Into<State>,
     State: BitArray + AsPrimitive<Word>,
-    Backend: PosSeek,
-    Self: Code,
 {
-    type Position = (Backend::Position, <Self as Code>::State);
+    inner: &'a mut AnsCoder<Word, State>,
+    len: usize,
+    state: State,
 }
 
-impl<Word, State, Backend> Seek for AnsCoder<Word, State, Backend>
+impl<Word, State> AnsCoderTransaction<'_, Word, State>
 where
     Word: BitArray + Into<State>,
     State: BitArray + AsPrimitive<Word>,
-    Backend: Seek,
 {
-    fn seek(&mut self, (pos, state): Self::Position) -> Result<(), ()> {
-        self.bulk.seek(pos)?;
-        self.state = state;
-        Ok(())
+    /// Keeps all changes that were made to the `AnsCoder` through this guard.
+    pub fn commit(self) {
+        core::mem::forget(self);
+    }
+
+    /// Explicitly rolls back all changes that were made to the `AnsCoder` through this
+    /// guard.
+    ///
+    /// This is equivalent to just dropping the guard.
+    pub fn rollback(self) {}
+
+    /// Same as [`AnsCoder::encode_symbols_reverse`].
+    pub fn encode_symbols_reverse<S, M, I, const PRECISION: usize>(
+        &mut self,
+        symbols_and_models: I,
+    ) -> Result<(), DefaultEncoderError<Infallible>>
+    where
+        S: Borrow<M::Symbol>,
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+        I: IntoIterator<Item = (S, M)>,
+        I::IntoIter: DoubleEndedIterator,
+    {
+        self.inner.encode_symbols_reverse(symbols_and_models)
+    }
+
+    /// Same as [`AnsCoder::try_encode_symbols_reverse`].
+    pub fn try_encode_symbols_reverse<S, M, E, I, const PRECISION: usize>(
+        &mut self,
+        symbols_and_models: I,
+    ) -> Result<(), TryCodingError<DefaultEncoderError<Infallible>, E>>
+    where
+        S: Borrow<M::Symbol>,
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+        I: IntoIterator<Item = core::result::Result<(S, M), E>>,
+        I::IntoIter: DoubleEndedIterator,
+    {
+        self.inner.try_encode_symbols_reverse(symbols_and_models)
+    }
+
+    /// Same as [`AnsCoder::encode_iid_symbols_reverse`].
+    pub fn encode_iid_symbols_reverse<S, M, I, const PRECISION: usize>(
+        &mut self,
+        symbols: I,
+        model: M,
+    ) -> Result<(), DefaultEncoderError<Infallible>>
+    where
+        S: Borrow<M::Symbol>,
+        M: EncoderModel<PRECISION> + Copy,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+        I: IntoIterator<Item = S>,
+        I::IntoIter: DoubleEndedIterator,
+    {
+        self.inner.encode_iid_symbols_reverse(symbols, model)
     }
 }
 
-impl<Word, State, Backend> Pos for AnsCoder<Word, State, Backend>
+impl<Word, State> Deref for AnsCoderTransaction<'_, Word, State>
 where
     Word: BitArray + Into<State>,
     State: BitArray + AsPrimitive<Word>,
-    Backend: Pos,
 {
-    fn pos(&self) -> Self::Position {
-        (self.bulk.pos(), self.state())
+    type Target = AnsCoder<Word, State>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner
     }
 }
 
-/// Provides temporary read-only access to the compressed data wrapped in a
-/// [`AnsCoder`].
-///
-/// Dereferences to `&[Word]`. See [`Coder::get_compressed`] for an example.
-///
-/// [`AnsCoder`]: struct.Coder.html
-/// [`Coder::get_compressed`]: struct.Coder.html#method.get_compressed
-struct CoderGuard<'a, Word, State, Backend, const SEALED: bool>
+impl<Word, State> Code for AnsCoderTransaction<'_, Word, State>
 where
     Word: BitArray + Into<State>,
     State: BitArray + AsPrimitive<Word>,
-    Backend: WriteWords<Word> + ReadWords<Word, Stack>,
 {
-    inner: &'a mut AnsCoder<Word, State, Backend>,
+    type Word = Word;
+    type State = State;
+
+    #[inline(always)]
+    fn state(&self) -> Self::State {
+        self.inner.state
+    }
 }
 
-impl<'a, Word, State, Backend, const SEALED: bool> CoderGuard<'a, Word, State, Backend, SEALED>
+impl<Word, State, const PRECISION: usize> Encode<PRECISION> for AnsCoderTransaction<'_, Word, State>
 where
     Word: BitArray + Into<State>,
     State: BitArray + AsPrimitive<Word>,
-    Backend: WriteWords<Word> + ReadWords<Word, Stack>,
 {
+    type FrontendError = DefaultEncoderFrontendError;
+    type BackendError = Infallible;
+
     #[inline(always)]
-    fn new(
-        ans: &'a mut AnsCoder<Word, State, Backend>,
-    ) -> Result<Self, CoderError<(), Backend::WriteError>> {
-        // Append state. Will be undone in `<Self as Drop>::drop`.
-        let mut chunks_rev = bit_array_to_chunks_truncated(ans.state);
-        if SEALED && chunks_rev.next() != Some(Word::one()) {
-            return Err(CoderError::Frontend(()));
-        }
-        for chunk in chunks_rev.rev() {
-            ans.bulk.write(chunk)?
-        }
+    fn encode_symbol<M>(
+        &mut self,
+        symbol: impl Borrow<M::Symbol>,
+        model: M,
+    ) -> Result<(), DefaultEncoderError<Self::BackendError>>
+    where
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        self.inner.encode_symbol(symbol, model)
+    }
+
+    fn maybe_full(&self) -> bool {
+        Encode::<PRECISION>::maybe_full(&*self.inner)
+    }
+}
+
+impl<Word, State> Drop for AnsCoderTransaction<'_, Word, State>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    fn drop(&mut self) {
+        self.inner.bulk.truncate(self.len);
+        self.inner.state = self.state;
+    }
+}
+
+impl<State> AnsCoder<u32, State>
+where
+    State: BitArray + AsPrimitive<u32>,
+    u32: Into<State>,
+{
+    /// Consumes the coder and returns the compressed data as little-endian `u32` words.
+    ///
+    /// Each yielded word has the same *in-memory representation* as the corresponding word
+    /// of [`into_compressed`] would have on a little-endian platform, i.e., the yielded
+    /// words are `word.to_le()` for each `word` of the compressed data. Writing the raw
+    /// memory of the yielded words to a file (e.g., with [`u32::to_ne_bytes`]) thus produces
+    /// exactly the same bytes as the Python API does when you call
+    /// `compressed = coder.get_compressed()` followed by `compressed.tofile(filename)` on a
+    /// little-endian machine (or after `compressed.byteswap(inplace=True)` on a big-endian
+    /// machine, as recommended in the Python API documentation). On little-endian
+    /// platforms, this method is a no-op compared to [`into_compressed`].
+    ///
+    /// The inverse of this method is [`from_le_words`].
+    ///
+    /// [`into_compressed`]: #method.into_compressed
+    /// [`from_le_words`]: #method.from_le_words
+    pub fn into_compressed_le_words(self) -> impl ExactSizeIterator<Item = u32> {
+        self.into_compressed()
+            .unwrap_infallible()
+            .into_iter()
+            .map(u32::to_le)
+    }
+
+    /// Creates a coder from compressed data given as little-endian `u32` words.
+    ///
+    /// This is the inverse of [`into_compressed_le_words`]. Each provided word is converted
+    /// with [`u32::from_le`], so you can pass in words whose raw memory was read directly
+    /// from a file written by the Python API (see [`into_compressed_le_words`]), i.e., the
+    /// equivalent of `np.fromfile(filename, dtype=np.uint32)` followed by
+    /// `constriction.stream.stack.AnsCoder(compressed)` on a little-endian machine.
+    ///
+    /// Returns `Err(compressed)` with the converted (native endian) words under the same
+    /// conditions as [`from_compressed`], i.e., if the data ends in a zero word.
+    ///
+    /// [`into_compressed_le_words`]: #method.into_compressed_le_words
+    /// [`from_compressed`]: #method.from_compressed
+    pub fn from_le_words(words: impl IntoIterator<Item = u32>) -> Result<Self, Vec<u32>> {
+        Self::from_compressed(words.into_iter().map(u32::from_le).collect())
+    }
+}
+
+impl<'bulk, Word, State> AnsCoder<Word, State, Cursor<Word, &'bulk [Word]>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    // TODO: proper error type (also for `from_compressed`)
+    #[allow(clippy::result_unit_err)]
+    pub fn from_compressed_slice(compressed: &'bulk [Word]) -> Result<Self, ()> {
+        Self::from_compressed(backends::Cursor::new_at_write_end(compressed)).map_err(|_| ())
+    }
+
+    /// Creates a decoder for compressed data that is embedded at a known position within
+    /// a larger buffer.
+    ///
+    /// This is equivalent to `from_compressed_slice(&buffer[offset..offset + len])` except
+    /// that it doesn't panic for out-of-bounds ranges. Returns `Err(())` if the range
+    /// `offset..offset + len` does not fit into `buffer`, or under the same conditions as
+    /// [`from_compressed_slice`], i.e., if the selected words end in a zero word. The
+    /// compressed data is not copied.
+    ///
+    /// This is useful for container formats that store ANS-compressed data at a known
+    /// word offset among other data:
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode};
+    ///
+    /// let model = DefaultUniformModel::new(10);
+    /// let mut encoder = DefaultAnsCoder::new();
+    /// encoder.encode_iid_symbols_reverse([3, 1, 4, 1, 5], model).unwrap();
+    /// let compressed = encoder.into_compressed().unwrap();
+    ///
+    /// // Embed the compressed data between a header and a trailer.
+    /// let mut container = vec![0xdead_beef, compressed.len() as u32];
+    /// container.extend_from_slice(&compressed);
+    /// container.push(0);
+    ///
+    /// let mut decoder =
+    ///     DefaultAnsCoder::from_compressed_slice_at(&container, 2, container[1] as usize)
+    ///         .unwrap();
+    /// let decoded = decoder
+    ///     .decode_iid_symbols(5, model)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(decoded, [3, 1, 4, 1, 5]);
+    /// assert!(decoder.is_empty());
+    /// ```
+    ///
+    /// [`from_compressed_slice`]: #method.from_compressed_slice
+    #[allow(clippy::result_unit_err)]
+    pub fn from_compressed_slice_at(
+        buffer: &'bulk [Word],
+        offset: usize,
+        len: usize,
+    ) -> Result<Self, ()> {
+        let end = offset.checked_add(len).ok_or(())?;
+        Self::from_compressed_slice(buffer.get(offset..end).ok_or(())?)
+    }
+
+    pub fn from_binary_slice(data: &'bulk [Word]) -> Self {
+        Self::from_binary(backends::Cursor::new_at_write_end(data)).unwrap_infallible()
+    }
+}
+
+impl<Word, State, Buf> AnsCoder<Word, State, Cursor<Word, Buf>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Buf: SafeBuf<Word> + AsMut<[Word]>,
+{
+    /// Creates a decoder that reads backward from an owned buffer without copying it.
+    ///
+    /// This is similar to [`from_compressed_slice`] except that the returned `AnsCoder`
+    /// takes ownership of `compressed` (e.g., a `Box<[Word]>` or a `&mut [Word]`) rather
+    /// than borrowing it immutably. Decoding doesn't modify the buffer, so you can recover
+    /// it unchanged via [`into_raw_parts`] once you're done decoding, and then reuse it.
+    /// Since the buffer is mutable, the returned `AnsCoder` can also encode symbols back
+    /// into the words that were already decoded (but it can't grow the buffer).
+    ///
+    /// Returns the argument `compressed` back to the caller, wrapped in an `Err` variant,
+    /// under the same conditions as [`from_compressed`], i.e., if `compressed` ends in a
+    /// zero word.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode};
+    ///
+    /// let model = DefaultUniformModel::new(10);
+    /// let mut encoder = DefaultAnsCoder::new();
+    /// encoder.encode_iid_symbols_reverse([3, 1, 4, 1, 5], model).unwrap();
+    /// let buffer = encoder.into_compressed().unwrap().into_boxed_slice();
+    /// let original = buffer.clone();
+    ///
+    /// let mut decoder = DefaultAnsCoder::from_compressed_buf(buffer).unwrap();
+    /// let decoded = decoder
+    ///     .decode_iid_symbols(5, model)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(decoded, [3, 1, 4, 1, 5]);
+    /// assert!(decoder.is_empty());
+    ///
+    /// let (cursor, _state) = decoder.into_raw_parts();
+    /// let (buffer, _pos) = cursor.into_buf_and_pos();
+    /// assert_eq!(buffer, original);
+    /// ```
+    ///
+    /// [`from_compressed_slice`]: #method.from_compressed_slice
+    /// [`from_compressed`]: #method.from_compressed
+    /// [`into_raw_parts`]: #method.into_raw_parts
+    pub fn from_compressed_buf(compressed: Buf) -> Result<Self, Buf> {
+        Self::from_compressed(Cursor::new_at_write_end_mut(compressed))
+            .map_err(|cursor| cursor.into_buf_and_pos().0)
+    }
+}
+
+impl<Word, State, Buf> AnsCoder<Word, State, Reverse<Cursor<Word, Buf>>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Buf: AsRef<[Word]>,
+{
+    pub fn from_reversed_compressed(compressed: Buf) -> Result<Self, Buf> {
+        Self::from_compressed(Reverse(Cursor::new_at_write_beginning(compressed)))
+            .map_err(|Reverse(cursor)| cursor.into_buf_and_pos().0)
+    }
+
+    pub fn from_reversed_binary(data: Buf) -> Self {
+        Self::from_binary(Reverse(Cursor::new_at_write_beginning(data))).unwrap_infallible()
+    }
+}
+
+impl<Word, State, Iter, ReadError> AnsCoder<Word, State, FallibleIteratorReadWords<Iter>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Iter: Iterator<Item = Result<Word, ReadError>>,
+    FallibleIteratorReadWords<Iter>: ReadWords<Word, Stack, ReadError = ReadError>,
+{
+    pub fn from_reversed_compressed_iter(compressed: Iter) -> Result<Self, Fuse<Iter>> {
+        Self::from_compressed(FallibleIteratorReadWords::new(compressed))
+            .map_err(|iterator_backend| iterator_backend.into_iter())
+    }
+
+    pub fn from_reversed_binary_iter(data: Iter) -> Result<Self, ReadError> {
+        Self::from_binary(FallibleIteratorReadWords::new(data))
+    }
+}
+
+impl<Word, State, Backend> AnsCoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: WriteWords<Word>,
+{
+    /// Preallocates capacity in the backend for at least `additional` more compressed words.
+    ///
+    /// Call this method before encoding a large number of symbols if you can estimate the
+    /// size of the compressed data (e.g., from the entropy of your entropy model) to avoid
+    /// repeated reallocations of the backend. The argument is in units of compressed words
+    /// (i.e., of type `Word`), not in bits or in symbols. This method only has an effect on
+    /// backends that implement [`WriteWords::reserve`] (such as the default backend
+    /// `Vec<Word>`), and it doesn't change the compressed data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder};
+    ///
+    /// let model = DefaultUniformModel::new(1000);
+    /// let mut ans = DefaultAnsCoder::new();
+    ///
+    /// // Each symbol contains about 10 bits of information, i.e., less than a `u32`.
+    /// ans.reserve_words(100_000);
+    /// let capacity = ans.bulk().capacity();
+    /// ans.encode_iid_symbols_reverse((0..100_000).map(|i| i % 1000), model).unwrap();
+    /// assert_eq!(ans.bulk().capacity(), capacity); // No reallocations happened.
+    /// ```
+    pub fn reserve_words(&mut self, additional: usize) {
+        self.bulk.reserve(additional);
+    }
+
+    /// Releases unused capacity of the backend.
+    ///
+    /// This is useful for long-lived coders that once held a lot of compressed data, e.g.,
+    /// after decoding most of a large message. This method only has an effect on backends
+    /// that implement [`WriteWords::shrink_to_fit`] (such as the default backend
+    /// `Vec<Word>`), and it doesn't change the compressed data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode};
+    ///
+    /// let model = DefaultUniformModel::new(1000);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse((0..100_000).map(|i| i % 1000), model).unwrap();
+    ///
+    /// // Decode most of the message. This doesn't release any memory.
+    /// for symbol in ans.decode_iid_symbols(99_990, model) {
+    ///     let _ = symbol.unwrap();
+    /// }
+    /// let capacity = ans.bulk().capacity();
+    /// assert!(capacity > 1000);
+    ///
+    /// ans.shrink_to_fit();
+    /// // The exact capacity after shrinking is up to the allocator, but it decreases.
+    /// assert!(ans.bulk().capacity() < capacity);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.bulk.shrink_to_fit();
+    }
+
+    pub fn encode_symbols_reverse<S, M, I, const PRECISION: usize>(
+        &mut self,
+        symbols_and_models: I,
+    ) -> Result<(), DefaultEncoderError<Backend::WriteError>>
+    where
+        S: Borrow<M::Symbol>,
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+        I: IntoIterator<Item = (S, M)>,
+        I::IntoIter: DoubleEndedIterator,
+    {
+        self.encode_symbols(symbols_and_models.into_iter().rev())
+    }
+
+    pub fn try_encode_symbols_reverse<S, M, E, I, const PRECISION: usize>(
+        &mut self,
+        symbols_and_models: I,
+    ) -> Result<(), TryCodingError<DefaultEncoderError<Backend::WriteError>, E>>
+    where
+        S: Borrow<M::Symbol>,
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+        I: IntoIterator<Item = core::result::Result<(S, M), E>>,
+        I::IntoIter: DoubleEndedIterator,
+    {
+        self.try_encode_symbols(symbols_and_models.into_iter().rev())
+    }
+
+    pub fn encode_iid_symbols_reverse<S, M, I, const PRECISION: usize>(
+        &mut self,
+        symbols: I,
+        model: M,
+    ) -> Result<(), DefaultEncoderError<Backend::WriteError>>
+    where
+        S: Borrow<M::Symbol>,
+        M: EncoderModel<PRECISION> + Copy,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+        I: IntoIterator<Item = S>,
+        I::IntoIter: DoubleEndedIterator,
+    {
+        self.encode_iid_symbols(symbols.into_iter().rev(), model)
+    }
+
+    /// Encodes several independent chunks of i.i.d. symbols and returns a jump table.
+    ///
+    /// Encodes the chunks in reverse order (so that they can be decoded in their original
+    /// order) and records a checkpoint after encoding each chunk. The returned jump table has
+    /// one entry per chunk, in the original order of `chunks`: entry `i` is the position (as
+    /// returned by [`pos_and_state`]) from which the `i`'th chunk can be decoded. You can pass
+    /// the jump table to [`seekable_decoders_at`] to decode the chunks in parallel, or seek to
+    /// one of its entries to decode only a single chunk.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     stream::{model::DefaultUniformModel, stack::DefaultAnsCoder, Decode},
+    ///     Seek,
+    /// };
+    ///
+    /// let model = DefaultUniformModel::new(100);
+    /// let chunks: [&[usize]; 3] = [&[1, 2, 3], &[4, 5], &[6, 7, 8, 9]];
+    /// let mut encoder = DefaultAnsCoder::new();
+    /// let jump_table = encoder.encode_chunks_reverse(&chunks, model).unwrap();
+    /// assert_eq!(jump_table.len(), 3);
+    ///
+    /// // Decode only the second chunk.
+    /// let mut decoder = encoder.as_seekable_decoder();
+    /// decoder.seek(jump_table[1]).unwrap();
+    /// let decoded = decoder.decode_iid_symbols(2, model).collect::<Result<Vec<_>, _>>();
+    /// assert_eq!(decoded.unwrap(), [4, 5]);
+    /// ```
+    ///
+    /// [`pos_and_state`]: Self::pos_and_state
+    /// [`seekable_decoders_at`]: Self::seekable_decoders_at
+    #[allow(clippy::type_complexity)]
+    pub fn encode_chunks_reverse<C, M, const PRECISION: usize>(
+        &mut self,
+        chunks: &[C],
+        model: M,
+    ) -> Result<Vec<(Backend::Position, State)>, DefaultEncoderError<Backend::WriteError>>
+    where
+        C: AsRef<[M::Symbol]>,
+        M: EncoderModel<PRECISION> + Copy,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+        Backend: Pos,
+    {
+        let mut jump_table = Vec::with_capacity(chunks.len());
+        for chunk in chunks.iter().rev() {
+            self.encode_iid_symbols_reverse(chunk.as_ref(), model)?;
+            jump_table.push(self.pos_and_state());
+        }
+        jump_table.reverse();
+        Ok(jump_table)
+    }
+
+    /// Encodes a sequence of symbols under a "mixture of experts" model.
+    ///
+    /// For each index `i`, the gate `gates[i]` selects the expert model
+    /// `experts[gates[i]]`, which is used to encode `symbols[i]`. The gate itself is also
+    /// encoded, using the entropy model `gate_model`. Since the `AnsCoder` is a stack, this
+    /// method processes the symbols in reverse order and, for each `i`, encodes the symbol
+    /// *before* the gate so that [`decode_moe`] can decode the gate first and then use it to
+    /// select the expert for decoding the symbol.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `symbols` and `gates` have different lengths or if any gate is out of
+    /// bounds for `experts`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::{DefaultContiguousCategoricalEntropyModel, DefaultUniformModel},
+    ///     stack::DefaultAnsCoder,
+    /// };
+    ///
+    /// let gate_model = DefaultContiguousCategoricalEntropyModel
+    ///     ::from_floating_point_probabilities_fast(&[0.8, 0.2], None).unwrap();
+    /// let experts = [DefaultUniformModel::new(4), DefaultUniformModel::new(100)];
+    ///
+    /// let symbols = [2, 3, 70, 0, 42];
+    /// let gates = [0, 0, 1, 0, 1];
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_moe(&symbols, &gates, &gate_model, &experts).unwrap();
+    ///
+    /// let (decoded_gates, decoded_symbols) = ans.decode_moe(5, &gate_model, &experts).unwrap();
+    /// assert_eq!(decoded_gates, gates);
+    /// assert_eq!(decoded_symbols, symbols);
+    /// assert!(ans.is_empty());
+    /// ```
+    ///
+    /// [`decode_moe`]: Self::decode_moe
+    pub fn encode_moe<G, M, const PRECISION: usize>(
+        &mut self,
+        symbols: &[M::Symbol],
+        gates: &[usize],
+        gate_model: G,
+        experts: &[M],
+    ) -> Result<(), DefaultEncoderError<Backend::WriteError>>
+    where
+        G: EncoderModel<PRECISION, Symbol = usize> + Copy,
+        G::Probability: Into<Word>,
+        Word: AsPrimitive<G::Probability>,
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+    {
+        assert_eq!(symbols.len(), gates.len());
+
+        for (symbol, &gate) in symbols.iter().zip(gates).rev() {
+            self.encode_symbol(symbol, &experts[gate])?;
+            self.encode_symbol(gate, gate_model)?;
+        }
+
+        Ok(())
+    }
+
+    /// Encodes several consecutive fields of symbols, each under its own entropy model.
+    ///
+    /// Each entry `(symbols, model)` of `fields` is a field whose symbols are all encoded
+    /// with the same `model`. Fields and the symbols within each field are encoded in
+    /// reverse order so that [`decode_fields`] can decode them in their normal order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder};
+    ///
+    /// let header = [3, 1];
+    /// let payload = [41, 59, 26, 53];
+    /// let checksum = [7];
+    /// let fields = [
+    ///     (&header[..], DefaultUniformModel::new(4)),
+    ///     (&payload[..], DefaultUniformModel::new(100)),
+    ///     (&checksum[..], DefaultUniformModel::new(16)),
+    /// ];
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_fields(&fields).unwrap();
+    ///
+    /// let decoded = ans
+    ///     .decode_fields(&[
+    ///         (2, DefaultUniformModel::new(4)),
+    ///         (4, DefaultUniformModel::new(100)),
+    ///         (1, DefaultUniformModel::new(16)),
+    ///     ])
+    ///     .unwrap();
+    /// assert_eq!(decoded, [&header[..], &payload[..], &checksum[..]]);
+    /// assert!(ans.is_empty());
+    /// ```
+    ///
+    /// [`decode_fields`]: Self::decode_fields
+    pub fn encode_fields<M, const PRECISION: usize>(
+        &mut self,
+        fields: &[(&[M::Symbol], M)],
+    ) -> Result<(), DefaultEncoderError<Backend::WriteError>>
+    where
+        M: EncoderModel<PRECISION> + Copy,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+    {
+        for &(symbols, model) in fields.iter().rev() {
+            self.encode_iid_symbols_reverse(symbols, model)?;
+        }
+
+        Ok(())
+    }
+
+    /// Encodes a sequence of optional (nullable) symbols.
+    ///
+    /// For each entry of `values`, this method encodes a presence flag under the entropy
+    /// model `presence_model`, followed by the symbol itself under `value_model` if the
+    /// entry is `Some(symbol)`. The presence flag is the symbol `1` for `Some(_)` and `0`
+    /// for `None`, so `presence_model` is typically a categorical distribution over two
+    /// symbols that expresses how sparse the data is. Since the `AnsCoder` is a stack, this
+    /// method processes `values` in reverse order and, for each entry, encodes the symbol
+    /// *before* the presence flag so that [`decode_optional`] can decode the flag first and
+    /// then decide whether to decode a symbol.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::{DefaultContiguousCategoricalEntropyModel, DefaultUniformModel},
+    ///     stack::DefaultAnsCoder,
+    /// };
+    ///
+    /// // Only about 20% of the values are present.
+    /// let presence_model = DefaultContiguousCategoricalEntropyModel
+    ///     ::from_floating_point_probabilities_fast(&[0.8, 0.2], None).unwrap();
+    /// let value_model = DefaultUniformModel::new(100);
+    ///
+    /// let values = [None, Some(42), None, None, None, Some(7), None];
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_optional(&values, &presence_model, value_model).unwrap();
+    ///
+    /// let decoded = ans.decode_optional(7, &presence_model, value_model).unwrap();
+    /// assert_eq!(decoded, values);
+    /// assert!(ans.is_empty());
+    /// ```
+    ///
+    /// [`decode_optional`]: Self::decode_optional
+    pub fn encode_optional<P, M, const PRECISION: usize>(
+        &mut self,
+        values: &[Option<M::Symbol>],
+        presence_model: P,
+        value_model: M,
+    ) -> Result<(), DefaultEncoderError<Backend::WriteError>>
+    where
+        P: EncoderModel<PRECISION, Symbol = usize> + Copy,
+        P::Probability: Into<Word>,
+        Word: AsPrimitive<P::Probability>,
+        M: EncoderModel<PRECISION> + Copy,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+    {
+        for value in values.iter().rev() {
+            if let Some(symbol) = value {
+                self.encode_symbol(symbol, value_model)?;
+            }
+            self.encode_symbol(value.is_some() as usize, presence_model)?;
+        }
+
+        Ok(())
+    }
+
+    /// Encodes symbols under categorical entropy models given by rows of logits.
+    ///
+    /// This is a convenience method for integrating with machine learning models (e.g.,
+    /// neural networks) that output unnormalized log-probabilities ("logits"). For each
+    /// position `i`, this method applies a numerically stable softmax to the row
+    /// `logits[i]`, quantizes the result to a categorical distribution with `PRECISION`
+    /// bits of precision (in the same way as
+    /// [`ContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast`]), and
+    /// uses it to encode `symbols[i]`. Thus, `symbols[i]` must be an index into
+    /// `logits[i]`. As with all encoding methods of an `AnsCoder`, the symbols are encoded
+    /// in reverse order so that [`decode_from_logits`] decodes them in their normal order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `symbols` and `logits` have different lengths, if any logit is not finite,
+    /// or if any row of `logits` has fewer than two entries or at least `2^PRECISION - 1`
+    /// entries.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::stack::DefaultAnsCoder;
+    ///
+    /// // E.g., the output of a neural network for three positions and four classes.
+    /// let logits = [
+    ///     [2.0f32, -1.0, 0.5, 0.0],
+    ///     [-3.0, 4.0, 1.0, 1.0],
+    ///     [0.0, 0.0, 0.0, 7.5],
+    /// ];
+    /// let rows = logits.iter().map(|row| &row[..]).collect::<Vec<_>>();
+    /// let symbols = [0, 1, 3];
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_from_logits::<u32, 24>(&symbols, &rows).unwrap();
+    ///
+    /// let decoded = ans.decode_from_logits::<u32, 24>(&rows).unwrap();
+    /// assert_eq!(decoded, symbols);
+    /// assert!(ans.is_empty());
+    /// ```
+    ///
+    /// [`decode_from_logits`]: Self::decode_from_logits
+    /// [`ContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast`]:
+    ///     crate::stream::model::ContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast
+    pub fn encode_iid_from_logits<Probability, const PRECISION: usize>(
+        &mut self,
+        symbols: &[usize],
+        logits: &[&[f32]],
+    ) -> Result<(), DefaultEncoderError<Backend::WriteError>>
+    where
+        Probability: BitArray + Into<Word> + AsPrimitive<usize>,
+        Word: AsPrimitive<Probability>,
+        usize: AsPrimitive<Probability>,
+        f64: AsPrimitive<Probability>,
+    {
+        assert_eq!(symbols.len(), logits.len());
+
+        for (&symbol, &row) in symbols.iter().zip(logits).rev() {
+            let model = categorical_from_logits::<Probability, PRECISION>(row);
+            self.encode_symbol(symbol, model.as_view())?;
+        }
+
+        Ok(())
+    }
+
+    /// Encodes a sequence of integers by their second differences ("delta-of-delta").
+    ///
+    /// This is a common technique for compressing smooth or locally linear signals, such as
+    /// timestamps sampled at an (approximately) regular interval. The first two values
+    /// `values[0]` and `values[1]` are encoded directly with the entropy models
+    /// `first_two_models[0]` and `first_two_models[1]`, respectively. Each subsequent value
+    /// `values[i]` for `i >= 2` is represented by its second difference
+    /// `(values[i] - values[i - 1]) - (values[i - 1] - values[i - 2])`, which is encoded
+    /// with `dod_model`. For a locally linear signal, second differences concentrate around
+    /// zero regardless of the slope of the signal, so `dod_model` can be a narrow
+    /// distribution centered at zero.
+    ///
+    /// Differences are calculated in wrapping arithmetic, so the reconstruction in
+    /// [`decode_delta_of_delta`] is lossless even if intermediate differences overflow (but
+    /// all second differences still have to lie within the support of `dod_model`). Since
+    /// the `AnsCoder` is a stack, this method encodes the second differences in reverse
+    /// order and the first two values last, so that a decoder can decode them first and
+    /// then reconstruct the remaining values by a double cumulative sum. `values` may have
+    /// fewer than two entries, in which case only the first entry (if any) is encoded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultLeakyQuantizer, stack::DefaultAnsCoder,
+    /// };
+    /// use probability::distribution::Gaussian;
+    ///
+    /// // Timestamps with a period of about 1000 and a little bit of jitter.
+    /// let timestamps = [1_000_000, 1_001_000, 1_002_001, 1_003_000, 1_004_000, 1_004_999];
+    ///
+    /// let quantizer = DefaultLeakyQuantizer::new(0..=2_000_000);
+    /// let first_model = quantizer.quantize(Gaussian::new(1e6, 1e5));
+    /// let second_model = quantizer.quantize(Gaussian::new(1e6 + 1e3, 1e5));
+    /// let dod_model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 1.0));
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_delta_of_delta(&timestamps, &dod_model, [&first_model, &second_model])
+    ///     .unwrap();
+    ///
+    /// let decoded = ans
+    ///     .decode_delta_of_delta(6, &dod_model, [&first_model, &second_model])
+    ///     .unwrap();
+    /// assert_eq!(decoded, timestamps);
+    /// assert!(ans.is_empty());
+    /// ```
+    ///
+    /// [`decode_delta_of_delta`]: Self::decode_delta_of_delta
+    pub fn encode_delta_of_delta<M, F, const PRECISION: usize>(
+        &mut self,
+        values: &[M::Symbol],
+        dod_model: M,
+        first_two_models: [F; 2],
+    ) -> Result<(), DefaultEncoderError<Backend::WriteError>>
+    where
+        M: EncoderModel<PRECISION> + Copy,
+        M::Symbol: WrappingAdd + WrappingSub,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+        F: EncoderModel<PRECISION, Symbol = M::Symbol>,
+        F::Probability: Into<Word>,
+        Word: AsPrimitive<F::Probability>,
+    {
+        for window in values.windows(3).rev() {
+            let delta = window[2].wrapping_sub(&window[1]);
+            let previous_delta = window[1].wrapping_sub(&window[0]);
+            self.encode_symbol(delta.wrapping_sub(&previous_delta), dod_model)?;
+        }
+
+        for (value, model) in values.iter().zip(&first_two_models).rev() {
+            self.encode_symbol(value, model)?;
+        }
+
+        Ok(())
+    }
+
+    /// Encodes `symbols` with an order-1 context model, i.e., a Markov chain.
+    ///
+    /// Encodes each symbol `symbols[i]` with the sub-model `model.model_for(context)`, where
+    /// the context is the previous symbol `symbols[i - 1]` (converted to a `usize`), or
+    /// `initial_context` for the first symbol. As with all encoding methods of an
+    /// `AnsCoder`, the symbols are encoded in reverse order so that
+    /// [`decode_contextual_symbols`] decodes them in their normal order. See
+    /// [`ContextModel`] for an example.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(TryCodingError::InvalidEntropyModel(ContextOutOfRangeError { .. }))` if
+    /// `initial_context` or any symbol except the last one (converted to a `usize`) is not a
+    /// valid context of `model` (see [`ContextModel::num_contexts`]). All contexts are
+    /// checked before anything is encoded, so the coder is left unchanged in this case.
+    ///
+    /// [`decode_contextual_symbols`]: Self::decode_contextual_symbols
+    pub fn encode_contextual_symbols<M, const PRECISION: usize>(
+        &mut self,
+        symbols: &[M::Symbol],
+        model: &ContextModel<M, PRECISION>,
+        initial_context: usize,
+    ) -> Result<(), TryCodingError<DefaultEncoderError<Backend::WriteError>, ContextOutOfRangeError>>
+    where
+        M: EncoderModel<PRECISION>,
+        M::Symbol: Clone + Into<usize>,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+    {
+        let Some(first) = symbols.first() else {
+            return Ok(());
+        };
+        let num_contexts = model.num_contexts();
+        let contexts = core::iter::once(initial_context).chain(
+            symbols[..symbols.len() - 1]
+                .iter()
+                .map(|s| s.clone().into()),
+        );
+        for context in contexts {
+            if context >= num_contexts {
+                return Err(TryCodingError::InvalidEntropyModel(
+                    ContextOutOfRangeError {
+                        context,
+                        num_contexts,
+                    },
+                ));
+            }
+        }
+
+        for window in symbols.windows(2).rev() {
+            self.encode_symbol(&window[1], model.model_for(window[0].clone().into()))?;
+        }
+        self.encode_symbol(first, model.model_for(initial_context))?;
+
+        Ok(())
+    }
+
+    /// Encodes `symbols` in blocks, choosing the cheapest entropy model for each block.
+    ///
+    /// Splits `symbols` into consecutive blocks of `block_size` symbols (the last block may
+    /// be shorter) and, for each block, selects the model from `candidates` under which the
+    /// block has the lowest information content. Each block is then encoded with its
+    /// selected model, preceded by a short header that records the index of the selected
+    /// candidate. The header is encoded with a uniform distribution over the candidates,
+    /// i.e., it costs `log2(candidates.len())` bits per block (and nothing if there's only a
+    /// single candidate). Candidates under which some symbol of a block has zero
+    /// probability are never selected for this block.
+    ///
+    /// Returns the indices of the selected candidates (one per block). Use
+    /// [`decode_blocks_best_of`] with the same `block_size` and `candidates` to decode.
+    ///
+    /// Returns an error with [`DefaultEncoderFrontendError::ImpossibleSymbol`] if none of
+    /// the candidates can encode some block. In this case, nothing is encoded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is zero or if `candidates` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultContiguousCategoricalEntropyModel, stack::DefaultAnsCoder
+    /// };
+    ///
+    /// let candidates = [
+    ///     DefaultContiguousCategoricalEntropyModel
+    ///         ::from_floating_point_probabilities_fast(&[0.9, 0.05, 0.05], None).unwrap(),
+    ///     DefaultContiguousCategoricalEntropyModel
+    ///         ::from_floating_point_probabilities_fast(&[0.05, 0.05, 0.9], None).unwrap(),
+    /// ];
+    ///
+    /// let symbols = [0, 0, 1, 0, 2, 2, 2, 1];
+    /// let mut ans = DefaultAnsCoder::new();
+    /// let choices = ans.encode_blocks_best_of(&symbols, 4, &candidates).unwrap();
+    /// assert_eq!(choices, [0, 1]);
+    ///
+    /// let decoded = ans.decode_blocks_best_of(symbols.len(), 4, &candidates).unwrap();
+    /// assert_eq!(decoded, symbols);
+    /// assert!(ans.is_empty());
+    /// ```
+    ///
+    /// [`decode_blocks_best_of`]: Self::decode_blocks_best_of
+    pub fn encode_blocks_best_of<M, const PRECISION: usize>(
+        &mut self,
+        symbols: &[M::Symbol],
+        block_size: usize,
+        candidates: &[M],
+    ) -> Result<Vec<usize>, DefaultEncoderError<Backend::WriteError>>
+    where
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Word> + Into<f64> + AsPrimitive<usize>,
+        Word: AsPrimitive<M::Probability>,
+        usize: AsPrimitive<M::Probability>,
+    {
+        assert!(!candidates.is_empty());
+
+        let choices = symbols
+            .chunks(block_size)
+            .map(|block| {
+                // Maximize the log-likelihood of the block, which is equivalent to
+                // minimizing its information content.
+                let mut best = None;
+                for (index, candidate) in candidates.iter().enumerate() {
+                    let log_likelihood = block
+                        .iter()
+                        .map(|symbol| {
+                            let (_, probability) =
+                                candidate.left_cumulative_and_probability(symbol)?;
+                            Some(libm::log2(probability.get().into()))
+                        })
+                        .sum::<Option<f64>>();
+                    if let Some(log_likelihood) = log_likelihood {
+                        if best.map_or(true, |(_, best)| log_likelihood > best) {
+                            best = Some((index, log_likelihood));
+                        }
+                    }
+                }
+                best.map(|(index, _)| index)
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| DefaultEncoderFrontendError::ImpossibleSymbol.into_coder_error())?;
+
+        let header_model = (candidates.len() > 1)
+            .then(|| UniformModel::<M::Probability, PRECISION>::new(candidates.len()));
+        for (block, &choice) in symbols.chunks(block_size).zip(&choices).rev() {
+            for symbol in block.iter().rev() {
+                self.encode_symbol(symbol, &candidates[choice])?;
+            }
+            if let Some(header_model) = header_model {
+                self.encode_symbol(choice, header_model)?;
+            }
+        }
+
+        Ok(choices)
+    }
+
+    /// Encodes `amt` symbols whose coding intervals are provided lazily by a callback.
+    ///
+    /// This is a low-level alternative to [`encode_symbols_reverse`] for use cases where
+    /// constructing a full entropy model for each symbol would be too expensive, e.g.,
+    /// because each model would have to store a large probability table. Instead, the
+    /// callback `interval_fn` only has to provide the coding interval of the symbol that is
+    /// actually being encoded: `interval_fn(index)` must return the tuple
+    /// `(left_cumulative, probability)` that [`EncoderModel::left_cumulative_and_probability`]
+    /// would return for the symbol at position `index` of the message. This is all that
+    /// ANS needs for encoding.
+    ///
+    /// Since ANS operates as a stack, the callback is called in *reverse* order, i.e., with
+    /// `index = amt - 1, amt - 2, ..., 0`, so that the symbols can be decoded in their
+    /// normal order with [`decode_with_quantile_fn`].
+    ///
+    /// The caller is responsible for providing valid intervals, i.e., for satisfying
+    /// `left_cumulative + probability <= 2^PRECISION`. Intervals are not validated. An
+    /// invalid interval doesn't lead to undefined behavior, but it will corrupt the
+    /// compressed data, and it may cause an arithmetic overflow, which panics in debug
+    /// builds.
+    ///
+    /// # Panics
+    ///
+    /// May panic in debug builds if `interval_fn` returns an invalid interval (see above).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{stream::stack::DefaultAnsCoder, BitArray};
+    ///
+    /// // Symbol `i` of the message is either `0` with probability `(i + 1) / 2^24` or
+    /// // `1` with probability `1 - (i + 1) / 2^24`.
+    /// let message = [1, 0, 1, 1, 0];
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_with_interval_fn::<u32, _, 24>(message.len(), |index| {
+    ///     let boundary = index as u32 + 1;
+    ///     if message[index] == 0 {
+    ///         (0, boundary.into_nonzero().unwrap())
+    ///     } else {
+    ///         (boundary, ((1 << 24) - boundary).into_nonzero().unwrap())
+    ///     }
+    /// })
+    /// .unwrap();
+    ///
+    /// let decoded = ans
+    ///     .decode_with_quantile_fn::<_, u32, _, 24>(message.len(), |index, quantile| {
+    ///         let boundary = index as u32 + 1;
+    ///         if quantile < boundary {
+    ///             (0, 0, boundary.into_nonzero().unwrap())
+    ///         } else {
+    ///             (1, boundary, ((1 << 24) - boundary).into_nonzero().unwrap())
+    ///         }
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(decoded, message);
+    /// assert!(ans.is_empty());
+    /// ```
+    ///
+    /// [`encode_symbols_reverse`]: Self::encode_symbols_reverse
+    /// [`decode_with_quantile_fn`]: Self::decode_with_quantile_fn
+    pub fn encode_with_interval_fn<Probability, F, const PRECISION: usize>(
+        &mut self,
+        amt: usize,
+        mut interval_fn: F,
+    ) -> Result<(), Backend::WriteError>
+    where
+        Probability: BitArray + Into<Word>,
+        Word: AsPrimitive<Probability>,
+        F: FnMut(usize) -> (Probability, Probability::NonZero),
+    {
+        for index in (0..amt).rev() {
+            let (left_sided_cumulative, probability) = interval_fn(index);
+            self.encode_interval::<_, PRECISION>(left_sided_cumulative, probability, None)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn encode_interval<Probability, const PRECISION: usize>(
+        &mut self,
+        left_sided_cumulative: Probability,
+        probability: Probability::NonZero,
+        division_hint: Option<DivisionHint>,
+    ) -> Result<(), Backend::WriteError>
+    where
+        Probability: BitArray + Into<Word>,
+        Word: AsPrimitive<Probability>,
+    {
+        generic_static_asserts!(
+            (Word: BitArray, State:BitArray; const PRECISION: usize);
+            PROBABILITY_SUPPORTS_PRECISION: State::BITS >= Word::BITS + PRECISION;
+            NON_ZERO_PRECISION: PRECISION > 0;
+            STATE_SUPPORTS_AT_LEAST_TWO_WORDS: State::BITS >= 2 * Word::BITS;
+        );
+
+        let probability: State = probability.get().into().into();
+        if (self.state >> (State::BITS - PRECISION)) >= probability {
+            self.bulk.write(self.state.as_())?;
+            self.state = self.state >> Word::BITS;
+            // At this point, the invariant on `self.state` (see its doc comment) is
+            // temporarily violated, but it will be restored below.
+        }
+
+        // Division hints only support dividends that fit into a `u64` (which is always the
+        // case unless `State` is `u128`); fall back to a regular division otherwise.
+        let prefix = match division_hint.zip(self.state.to_u64()) {
+            Some((hint, state)) => {
+                debug_assert_eq!(<State as NumCast>::from(hint.divisor()), Some(probability));
+                <State as NumCast>::from(hint.quotient(state)).expect("quotient <= dividend")
+            }
+            None => self.state / probability,
+        };
+        let remainder = (self.state - prefix * probability).as_().as_();
+        // No overflow even for `PRECISION == Probability::BITS`: `remainder < probability`,
+        // so `quantile` is smaller than the right-sided cumulative, which is at most
+        // `2^PRECISION`. Further, `prefix < 2^(State::BITS - PRECISION)` due to the check
+        // above, so the left shift doesn't lose any bits.
+        let quantile = left_sided_cumulative + remainder;
+        self.state = prefix << PRECISION | quantile.into().into();
+
+        Ok(())
+    }
+
+    /// Consumes the ANS coder and returns the compressed data.
+    ///
+    /// The returned data can be used to recreate an ANS coder with the same state
+    /// (e.g., for decoding) by passing it to
+    /// [`from_compressed`](#method.from_compressed).
+    ///
+    /// If you don't want to consume the ANS coder, consider calling
+    /// [`get_compressed`](#method.get_compressed),
+    /// [`iter_compressed`](#method.iter_compressed) instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultContiguousCategoricalEntropyModel, stack::DefaultAnsCoder, Decode
+    /// };
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    ///
+    /// // Push some data onto the ANS coder's stack:
+    /// let symbols = vec![8, 2, 0, 7];
+    /// let probabilities = vec![0.03, 0.07, 0.1, 0.1, 0.2, 0.2, 0.1, 0.15, 0.05];
+    /// let model = DefaultContiguousCategoricalEntropyModel
+    ///     ::from_floating_point_probabilities_fast(&probabilities, None).unwrap();
+    /// ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+    ///
+    /// // Get the compressed data, consuming the ANS coder:
+    /// let compressed = ans.into_compressed().unwrap();
+    ///
+    /// // ... write `compressed` to a file and then read it back later ...
+    ///
+    /// // Create a new ANS coder with the same state and use it for decompression:
+    /// let mut ans = DefaultAnsCoder::from_compressed(compressed).expect("Corrupted compressed file.");
+    /// let reconstructed = ans
+    ///     .decode_iid_symbols(4, &model)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(reconstructed, symbols);
+    /// assert!(ans.is_empty())
+    /// ```
+    pub fn into_compressed(mut self) -> Result<Backend, Backend::WriteError> {
+        self.bulk
+            .extend_from_iter(bit_array_to_chunks_truncated(self.state).rev())?;
+        Ok(self.bulk)
+    }
+
+    /// Returns the binary data if it fits precisely into an integer number of
+    /// `Word`s
+    ///
+    /// This method is meant for rather advanced use cases. For most common use cases,
+    /// you probably want to call [`into_compressed`] instead.
+    ///
+    /// This method is the inverse of [`from_binary`]. It is equivalent to calling
+    /// [`into_compressed`], verifying that the returned vector ends in a `1` word, and
+    /// popping off that trailing `1` word.
+    ///
+    /// Returns `Err(())` if the compressed data (excluding an obligatory trailing
+    /// `1` bit) does not fit into an integer number of `Word`s. This error
+    /// case includes the case of an empty `AnsCoder` (since an empty `AnsCoder` lacks the
+    /// obligatory trailing one-bit).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // Some binary data we want to represent on a `AnsCoder`.
+    /// let data = vec![0x89ab_cdef, 0x0123_4567];
+    ///
+    /// // Constructing a `AnsCoder` with `from_binary` indicates that all bits of `data` are
+    /// // considered part of the information-carrying payload.
+    /// let stack1 = constriction::stream::stack::DefaultAnsCoder::from_binary(data.clone()).unwrap();
+    /// assert_eq!(stack1.clone().into_binary().unwrap(), data); // <-- Retrieves the original `data`.
+    ///
+    /// // By contrast, if we construct a `AnsCoder` with `from_compressed`, we indicate that
+    /// // - any leading `0` bits of the last entry of `data` are not considered part of
+    /// //   the information-carrying payload; and
+    /// // - the (obligatory) first `1` bit of the last entry of `data` defines the
+    /// //   boundary between unused bits and information-carrying bits; it is therefore
+    /// //   also not considered part of the payload.
+    /// // Therefore, `stack2` below only contains `32 * 2 - 7 - 1 = 56` bits of payload,
+    /// // which cannot be exported into an integer number of `u32` words:
+    /// let stack2 = constriction::stream::stack::DefaultAnsCoder::from_compressed(data.clone()).unwrap();
+    /// assert!(stack2.clone().into_binary().is_err()); // <-- Returns an error.
+    ///
+    /// // Use `into_compressed` to retrieve the data in this case:
+    /// assert_eq!(stack2.into_compressed().unwrap(), data);
+    ///
+    /// // Calling `into_compressed` on `stack1` would append an extra `1` bit to indicate
+    /// // the boundary between information-carrying bits and padding `0` bits:
+    /// assert_eq!(stack1.into_compressed().unwrap(), vec![0x89ab_cdef, 0x0123_4567, 0x0000_0001]);
+    /// ```
+    ///
+    /// [`from_binary`]: #method.from_binary
+    /// [`into_compressed`]: #method.into_compressed
+    pub fn into_binary(mut self) -> Result<Backend, Option<Backend::WriteError>> {
+        let valid_bits = (State::BITS - 1).wrapping_sub(self.state.leading_zeros() as usize);
+
+        if valid_bits % Word::BITS != 0 || valid_bits == usize::MAX {
+            Err(None)
+        } else {
+            let truncated_state = self.state ^ (State::one() << valid_bits);
+            self.bulk
+                .extend_from_iter(bit_array_to_chunks_truncated(truncated_state).rev())?;
+            Ok(self.bulk)
+        }
+    }
+
+    /// Seals the current segment of compressed data and resets the internal state to zero
+    /// while keeping all data in `bulk`.
+    ///
+    /// In contrast to [`clear`], this method does not discard any compressed data. It
+    /// appends the words held in the internal `state` to `bulk` (just like
+    /// [`into_compressed`] does) and then sets `state` to zero. Subsequently encoded symbols
+    /// therefore start a new *independent segment* that is appended after the existing
+    /// data, reusing the already allocated buffer.
+    ///
+    /// # Invariant Implications
+    ///
+    /// After calling this method, `state` is zero even though `bulk` may be nonempty,
+    /// which the `AnsCoder` normally only allows for an empty `bulk`. As a consequence,
+    /// [`is_empty`] returns `true` right after this method returns, and segments must not
+    /// be decoded across their boundaries: decoding past the beginning of a segment would
+    /// read words from the preceding segment into the state. Instead, record the segment
+    /// boundaries (e.g., as `ans.bulk().len()` right after calling `reset_state`), and
+    /// decode each segment with its own decoder over the corresponding subslice of the
+    /// final compressed data, e.g., using [`from_compressed_slice`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode
+    /// };
+    ///
+    /// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+    /// let model = quantizer.quantize(probability::distribution::Gaussian::new(0.0, 10.0));
+    /// let mut ans = DefaultAnsCoder::new();
+    ///
+    /// ans.encode_iid_symbols_reverse(&[3, -12, 7], &model).unwrap();
+    /// ans.reset_state().unwrap();
+    /// let boundary = ans.bulk().len();
+    /// ans.encode_iid_symbols_reverse(&[-1, 20], &model).unwrap();
+    /// let compressed = ans.into_compressed().unwrap();
+    ///
+    /// let mut first = DefaultAnsCoder::from_compressed_slice(&compressed[..boundary]).unwrap();
+    /// let mut second = DefaultAnsCoder::from_compressed_slice(&compressed[boundary..]).unwrap();
+    /// let first = first.decode_iid_symbols(3, &model).collect::<Result<Vec<_>, _>>().unwrap();
+    /// let second = second.decode_iid_symbols(2, &model).collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert_eq!(first, [3, -12, 7]);
+    /// assert_eq!(second, [-1, 20]);
+    /// ```
+    ///
+    /// [`clear`]: #method.clear
+    /// [`into_compressed`]: #method.into_compressed
+    /// [`is_empty`]: #method.is_empty
+    /// [`from_compressed_slice`]: #method.from_compressed_slice
+    pub fn reset_state(&mut self) -> Result<(), Backend::WriteError> {
+        self.bulk
+            .extend_from_iter(bit_array_to_chunks_truncated(self.state).rev())?;
+        self.state = State::zero();
+        Ok(())
+    }
+}
+
+impl<Word, State, Backend> AnsCoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: ReadWords<Word, Stack>,
+{
+    /// Decodes `amt` symbols that were encoded with [`encode_moe`].
+    ///
+    /// For each symbol, this method first decodes the gate `gate` using the entropy model
+    /// `gate_model`, and then decodes the symbol using the expert model `experts[gate]`.
+    /// Returns the tuple `(gates, symbols)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(CoderError::Frontend(GateOutOfRangeError { .. }))` if `gate_model`
+    /// decodes a gate that is out of bounds for `experts`, which can happen for corrupted
+    /// compressed data unless the support of `gate_model` is a subset of
+    /// `0..experts.len()`. The coder is left in an unspecified state in this case.
+    ///
+    /// [`encode_moe`]: Self::encode_moe
+    pub fn decode_moe<G, M, const PRECISION: usize>(
+        &mut self,
+        amt: usize,
+        gate_model: G,
+        experts: &[M],
+    ) -> Result<(Vec<usize>, Vec<M::Symbol>), CoderError<GateOutOfRangeError, Backend::ReadError>>
+    where
+        G: DecoderModel<PRECISION, Symbol = usize> + Copy,
+        G::Probability: Into<Word>,
+        Word: AsPrimitive<G::Probability>,
+        M: DecoderModel<PRECISION>,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+    {
+        let mut gates = Vec::with_capacity(amt);
+        let mut symbols = Vec::with_capacity(amt);
+        for _ in 0..amt {
+            let gate = self
+                .decode_symbol(gate_model)
+                .map_err(|err| err.map_frontend(|infallible| match infallible {}))?;
+            let expert = experts
+                .get(gate)
+                .ok_or(CoderError::Frontend(GateOutOfRangeError {
+                    gate,
+                    num_experts: experts.len(),
+                }))?;
+            symbols.push(
+                self.decode_symbol(expert)
+                    .map_err(|err| err.map_frontend(|infallible| match infallible {}))?,
+            );
+            gates.push(gate);
+        }
+
+        Ok((gates, symbols))
+    }
+
+    /// Decodes several consecutive fields of symbols that were encoded with
+    /// [`encode_fields`].
+    ///
+    /// Each entry `(amt, model)` of `fields` decodes `amt` symbols with `model`. Returns one
+    /// vector of decoded symbols per field, in the same order as `fields`.
+    ///
+    /// [`encode_fields`]: Self::encode_fields
+    pub fn decode_fields<M, const PRECISION: usize>(
+        &mut self,
+        fields: &[(usize, M)],
+    ) -> Result<Vec<Vec<M::Symbol>>, CoderError<Infallible, Backend::ReadError>>
+    where
+        M: DecoderModel<PRECISION> + Copy,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+    {
+        fields
+            .iter()
+            .map(|&(amt, model)| {
+                (0..amt)
+                    .map(|_| self.decode_symbol(model))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect()
+    }
+
+    /// Decodes `amt` optional symbols that were encoded with [`encode_optional`].
+    ///
+    /// For each entry, this method first decodes the presence flag using `presence_model`.
+    /// If the flag is nonzero, it then decodes a symbol using `value_model` and returns
+    /// `Some(symbol)`. Otherwise, it returns `None` for this entry without decoding a
+    /// symbol.
+    ///
+    /// [`encode_optional`]: Self::encode_optional
+    pub fn decode_optional<P, M, const PRECISION: usize>(
+        &mut self,
+        amt: usize,
+        presence_model: P,
+        value_model: M,
+    ) -> Result<Vec<Option<M::Symbol>>, CoderError<Infallible, Backend::ReadError>>
+    where
+        P: DecoderModel<PRECISION, Symbol = usize> + Copy,
+        P::Probability: Into<Word>,
+        Word: AsPrimitive<P::Probability>,
+        M: DecoderModel<PRECISION> + Copy,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+    {
+        (0..amt)
+            .map(|_| {
+                if self.decode_symbol(presence_model)? != 0 {
+                    Ok(Some(self.decode_symbol(value_model)?))
+                } else {
+                    Ok(None)
+                }
+            })
+            .collect()
+    }
+
+    /// Decodes one symbol per row of logits, inverting [`encode_iid_from_logits`].
+    ///
+    /// The argument `logits` and the type parameters `Probability` and `PRECISION` must be
+    /// the same as for encoding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any logit is not finite, or if any row of `logits` has fewer than two
+    /// entries or at least `2^PRECISION - 1` entries.
+    ///
+    /// [`encode_iid_from_logits`]: Self::encode_iid_from_logits
+    pub fn decode_from_logits<Probability, const PRECISION: usize>(
+        &mut self,
+        logits: &[&[f32]],
+    ) -> Result<Vec<usize>, CoderError<Infallible, Backend::ReadError>>
+    where
+        Probability: BitArray + Into<Word> + AsPrimitive<usize>,
+        Word: AsPrimitive<Probability>,
+        usize: AsPrimitive<Probability>,
+        f64: AsPrimitive<Probability>,
+    {
+        logits
+            .iter()
+            .map(|&row| {
+                let model = categorical_from_logits::<Probability, PRECISION>(row);
+                self.decode_symbol(model.as_view())
+            })
+            .collect()
+    }
+
+    /// Decodes `amt` integers that were encoded with [`encode_delta_of_delta`].
+    ///
+    /// The arguments `dod_model` and `first_two_models` must be the same as for encoding.
+    ///
+    /// [`encode_delta_of_delta`]: Self::encode_delta_of_delta
+    pub fn decode_delta_of_delta<M, F, const PRECISION: usize>(
+        &mut self,
+        amt: usize,
+        dod_model: M,
+        first_two_models: [F; 2],
+    ) -> Result<Vec<M::Symbol>, CoderError<Infallible, Backend::ReadError>>
+    where
+        M: DecoderModel<PRECISION> + Copy,
+        M::Symbol: WrappingAdd + WrappingSub + Clone,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+        F: DecoderModel<PRECISION, Symbol = M::Symbol>,
+        F::Probability: Into<Word>,
+        Word: AsPrimitive<F::Probability>,
+    {
+        let mut values = Vec::with_capacity(amt);
+        for model in first_two_models.iter().take(amt) {
+            values.push(self.decode_symbol(model)?);
+        }
+
+        if amt > 2 {
+            let mut previous = values[1].clone();
+            let mut delta = previous.wrapping_sub(&values[0]);
+            for _ in 2..amt {
+                delta = delta.wrapping_add(&self.decode_symbol(dod_model)?);
+                previous = previous.wrapping_add(&delta);
+                values.push(previous.clone());
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Decodes `amt` symbols that were encoded with [`encode_contextual_symbols`].
+    ///
+    /// The arguments `model` and `initial_context` must be the same as for encoding.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(CoderError::Frontend(ContextOutOfRangeError { .. }))` if
+    /// `initial_context` or any decoded symbol except the last one (converted to a `usize`)
+    /// is not a valid context of `model` (see [`ContextModel::num_contexts`]). This can
+    /// happen for corrupted compressed data unless the support of each sub-model is a
+    /// subset of `0..model.num_contexts()`. The coder is left in an unspecified state in
+    /// this case.
+    ///
+    /// [`encode_contextual_symbols`]: Self::encode_contextual_symbols
+    pub fn decode_contextual_symbols<M, const PRECISION: usize>(
+        &mut self,
+        amt: usize,
+        model: &ContextModel<M, PRECISION>,
+        initial_context: usize,
+    ) -> Result<Vec<M::Symbol>, CoderError<ContextOutOfRangeError, Backend::ReadError>>
+    where
+        M: DecoderModel<PRECISION>,
+        M::Symbol: Clone + Into<usize>,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+    {
+        let mut symbols = Vec::with_capacity(amt);
+        let mut context = initial_context;
+        for _ in 0..amt {
+            let sub_model = model.models().get(context).ok_or(CoderError::Frontend(
+                ContextOutOfRangeError {
+                    context,
+                    num_contexts: model.num_contexts(),
+                },
+            ))?;
+            let symbol = self
+                .decode_symbol(sub_model)
+                .map_err(|err| err.map_frontend(|infallible| match infallible {}))?;
+            context = symbol.clone().into();
+            symbols.push(symbol);
+        }
+
+        Ok(symbols)
+    }
+
+    /// Decodes symbols until a budget of compressed words or symbols is used up or the coder
+    /// is empty.
+    ///
+    /// This is useful for cooperative scheduling, e.g., in an event loop that should only
+    /// spend a bounded amount of work per tick. Each call decodes symbols with `model` for as
+    /// long as fewer than `max_words` compressed words have been read from the backend
+    /// during the call, fewer than `max_symbols` symbols have been decoded during the call,
+    /// and the coder is not [empty](Self::is_empty). Resume decoding by calling the method
+    /// again on a later tick. The concatenation of the symbols returned by consecutive calls
+    /// is the same as if all symbols had been decoded at once.
+    ///
+    /// # Interaction With the Refill Policy
+    ///
+    /// The `AnsCoder` reads compressed words lazily: decoding a symbol refills the internal
+    /// state with (at most) one word from the backend, and only if the state would
+    /// otherwise drop below its lower bound. Thus, the number of consumed words (measured
+    /// as the decrease of [`remaining`](BoundedReadWords::remaining)) never exceeds
+    /// `max_words`, but a call may decode many symbols without consuming any words (e.g.,
+    /// for symbols with high probability, or once the backend is exhausted and the
+    /// remaining information is held in the internal state). The symbol budget
+    /// `max_symbols` bounds the work per call in this case. A budget of `max_words == 0` or
+    /// `max_symbols == 0` decodes no symbols at all.
+    ///
+    /// Decoding stops early when the coder becomes empty, i.e., once all symbols that were
+    /// encoded on the stack have been decoded. If the coder holds other data than symbols
+    /// encoded with `model` (e.g., random bits for bits-back coding) then decoding past
+    /// the end of the encoded symbols may never empty the coder, so repeatedly calling this
+    /// method until the coder is empty may not terminate. Each individual call always
+    /// terminates after at most `max_symbols` symbols.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder};
+    ///
+    /// let model = DefaultUniformModel::new(1000);
+    /// let symbols = (0..100).collect::<Vec<_>>();
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+    ///
+    /// let mut decoded = Vec::new();
+    /// while !ans.is_empty() {
+    ///     let chunk = ans.decode_while_budget(model, 4, 50).unwrap();
+    ///     decoded.extend(chunk);
+    ///     // ... yield control to other tasks here ...
+    /// }
+    /// assert_eq!(decoded, symbols);
+    /// ```
+    pub fn decode_while_budget<D, const PRECISION: usize>(
+        &mut self,
+        model: D,
+        max_words: usize,
+        max_symbols: usize,
+    ) -> Result<Vec<D::Symbol>, CoderError<Infallible, Backend::ReadError>>
+    where
+        Backend: BoundedReadWords<Word, Stack>,
+        D: DecoderModel<PRECISION> + Copy,
+        D::Probability: Into<Word>,
+        Word: AsPrimitive<D::Probability>,
+    {
+        let initial_remaining = self.bulk.remaining();
+        let mut symbols = Vec::new();
+        while symbols.len() < max_symbols
+            && !self.is_empty()
+            && initial_remaining - self.bulk.remaining() < max_words
+        {
+            symbols.push(self.decode_symbol(model)?);
+        }
+
+        Ok(symbols)
+    }
+
+    /// Decodes a single symbol unless the coder is empty.
+    ///
+    /// Returns `Ok(None)` if the coder is [empty](Self::is_empty) before decoding, and
+    /// otherwise decodes a symbol exactly like [`decode_symbol`] does and returns
+    /// `Ok(Some(symbol))`. This is useful if you want to stop decoding at the end of the
+    /// compressed data, rather than relying on `decode_symbol`, which deliberately allows
+    /// decoding past the end of the data (in a deterministic but meaningless way).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultUniformModel, stack::DefaultAnsCoder};
+    ///
+    /// let model = DefaultUniformModel::new(10);
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_iid_symbols_reverse([3, 7], model).unwrap();
+    ///
+    /// assert_eq!(ans.try_decode_symbol(model).unwrap(), Some(3));
+    /// assert_eq!(ans.try_decode_symbol(model).unwrap(), Some(7));
+    /// assert_eq!(ans.try_decode_symbol(model).unwrap(), None);
+    /// ```
+    ///
+    /// [`decode_symbol`]: #method.decode_symbol
+    pub fn try_decode_symbol<D, const PRECISION: usize>(
+        &mut self,
+        model: D,
+    ) -> Result<Option<D::Symbol>, CoderError<Infallible, Backend::ReadError>>
+    where
+        D: DecoderModel<PRECISION>,
+        D::Probability: Into<Word>,
+        Word: AsPrimitive<D::Probability>,
+    {
+        if self.is_empty() {
+            Ok(None)
+        } else {
+            self.decode_symbol(model).map(Some)
+        }
+    }
+
+    /// Decodes i.i.d. symbols until the coder is empty, but at most `max_amt` symbols.
+    ///
+    /// Returns an iterator that calls [`try_decode_symbol`] with `model` and terminates as
+    /// soon as the coder is empty, after the first error, or after yielding `max_amt`
+    /// symbols, whichever comes first. Thus, if the compressed data consists of exactly `n`
+    /// symbols that were encoded with `model`, where `n <= max_amt`, then the iterator
+    /// yields exactly these `n` symbols.
+    ///
+    /// The limit `max_amt` guarantees termination even if the compressed data holds other
+    /// information than symbols encoded with `model`, in which case decoding may never
+    /// empty the coder. Set it to an upper bound on the number of symbols you expect (or to
+    /// `usize::MAX` if you trust the compressed data), and check [`is_empty`] after
+    /// decoding to find out whether all data was consumed.
+    ///
+    /// [`try_decode_symbol`]: Self::try_decode_symbol
+    /// [`is_empty`]: Self::is_empty
+    pub fn try_decode_iid_symbols<'a, D, const PRECISION: usize>(
+        &'a mut self,
+        model: D,
+        max_amt: usize,
+    ) -> impl Iterator<Item = Result<D::Symbol, CoderError<Infallible, Backend::ReadError>>> + 'a
+    where
+        D: DecoderModel<PRECISION> + Copy + 'a,
+        D::Probability: Into<Word>,
+        Word: AsPrimitive<D::Probability>,
+    {
+        let mut failed = false;
+        core::iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+            let result = self.try_decode_symbol(model).transpose()?;
+            failed = result.is_err();
+            Some(result)
+        })
+        .take(max_amt)
+    }
+
+    /// Decodes `amt` symbols that were encoded with [`encode_blocks_best_of`].
+    ///
+    /// The arguments `block_size` and `candidates` must be the same as for encoding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is zero or if `candidates` is empty.
+    ///
+    /// [`encode_blocks_best_of`]: Self::encode_blocks_best_of
+    pub fn decode_blocks_best_of<M, const PRECISION: usize>(
+        &mut self,
+        amt: usize,
+        block_size: usize,
+        candidates: &[M],
+    ) -> Result<Vec<M::Symbol>, CoderError<Infallible, Backend::ReadError>>
+    where
+        M: DecoderModel<PRECISION>,
+        M::Probability: Into<Word> + AsPrimitive<usize>,
+        Word: AsPrimitive<M::Probability>,
+        usize: AsPrimitive<M::Probability>,
+    {
+        assert!(block_size != 0);
+        assert!(!candidates.is_empty());
+
+        let header_model = (candidates.len() > 1)
+            .then(|| UniformModel::<M::Probability, PRECISION>::new(candidates.len()));
+        let mut symbols = Vec::with_capacity(amt);
+        while symbols.len() < amt {
+            let choice = match header_model {
+                Some(header_model) => self.decode_symbol(header_model)?,
+                None => 0,
+            };
+            let model = &candidates[choice];
+            for _ in 0..block_size.min(amt - symbols.len()) {
+                symbols.push(self.decode_symbol(model)?);
+            }
+        }
+
+        Ok(symbols)
+    }
+
+    /// Decodes `amt` symbols whose entropy models are provided lazily by a callback.
+    ///
+    /// This is the decoding counterpart of [`encode_with_interval_fn`]. For each position
+    /// `index = 0, 1, ..., amt - 1` (in this order), the callback is called as
+    /// `quantile_fn(index, quantile)` and must return the tuple `(symbol, left_cumulative,
+    /// probability)` that [`DecoderModel::quantile_function`] would return for `quantile`
+    /// under the entropy model of the symbol at position `index`.
+    ///
+    /// See [`encode_with_interval_fn`] for an example.
+    ///
+    /// [`encode_with_interval_fn`]: Self::encode_with_interval_fn
+    pub fn decode_with_quantile_fn<Symbol, Probability, F, const PRECISION: usize>(
+        &mut self,
+        amt: usize,
+        mut quantile_fn: F,
+    ) -> Result<Vec<Symbol>, Backend::ReadError>
+    where
+        Probability: BitArray + Into<Word>,
+        Word: AsPrimitive<Probability>,
+        F: FnMut(usize, Probability) -> (Symbol, Probability, Probability::NonZero),
+    {
+        (0..amt)
+            .map(|index| {
+                self.decode_interval::<_, _, PRECISION>(|quantile| quantile_fn(index, quantile))
+            })
+            .collect()
+    }
+
+    #[inline(always)]
+    fn decode_interval<Symbol, Probability, const PRECISION: usize>(
+        &mut self,
+        quantile_function: impl FnOnce(Probability) -> (Symbol, Probability, Probability::NonZero),
+    ) -> Result<Symbol, Backend::ReadError>
+    where
+        Probability: BitArray + Into<Word>,
+        Word: AsPrimitive<Probability>,
+    {
+        let quantile = self.quantile::<_, PRECISION>();
+        let (symbol, left_sided_cumulative, probability) = quantile_function(quantile);
+        let remainder = quantile - left_sided_cumulative;
+        // No overflow: `(self.state >> PRECISION) < 2^(State::BITS - PRECISION)` and
+        // `remainder < probability <= 2^PRECISION`, so the result is below `2^State::BITS`.
+        self.state =
+            (self.state >> PRECISION) * probability.get().into().into() + remainder.into().into();
+        if self.state < State::one() << (State::BITS - Word::BITS) {
+            // Invariant on `self.state` (see its doc comment) is violated. Restore it by
+            // refilling with a compressed word from `self.bulk` if available.
+            if let Some(word) = self.bulk.read()? {
+                self.state = (self.state << Word::BITS) | word.into();
+            }
+        }
+
+        Ok(symbol)
+    }
+}
+
+impl<Word, State, Buf> AnsCoder<Word, State, Cursor<Word, Buf>>
+where
+    Word: BitArray,
+    State: BitArray + AsPrimitive<Word> + From<Word>,
+    Buf: AsRef<[Word]> + AsMut<[Word]>,
+{
+    pub fn into_reversed(self) -> AnsCoder<Word, State, Reverse<Cursor<Word, Buf>>> {
+        let (bulk, state) = self.into_raw_parts();
+        AnsCoder {
+            bulk: bulk.into_reversed(),
+            state,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Word, State, Buf> AnsCoder<Word, State, Reverse<Cursor<Word, Buf>>>
+where
+    Word: BitArray,
+    State: BitArray + AsPrimitive<Word> + From<Word>,
+    Buf: AsRef<[Word]> + AsMut<[Word]>,
+{
+    pub fn into_reversed(self) -> AnsCoder<Word, State, Cursor<Word, Buf>> {
+        let (bulk, state) = self.into_raw_parts();
+        AnsCoder {
+            bulk: bulk.into_reversed(),
+            state,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Word, State, Backend> Code for AnsCoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    type Word = Word;
+    type State = State;
+
+    #[inline(always)]
+    fn state(&self) -> Self::State {
+        self.state
+    }
+}
+
+impl<Word, State, Backend, const PRECISION: usize> Encode<PRECISION>
+    for AnsCoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: WriteWords<Word>,
+{
+    type FrontendError = DefaultEncoderFrontendError;
+    type BackendError = Backend::WriteError;
+
+    /// Encodes a single symbol and appends it to the compressed data.
+    ///
+    /// This is a low level method. You probably usually want to call a batch method
+    /// like [`encode_symbols`](#method.encode_symbols) or
+    /// [`encode_iid_symbols`](#method.encode_iid_symbols) instead. See examples there.
+    ///
+    /// The bound `impl Borrow<M::Symbol>` on argument `symbol` essentially means that
+    /// you can provide the symbol either by value or by reference, at your choice.
+    ///
+    /// Returns [`Err(ImpossibleSymbol)`] if `symbol` has zero probability under the
+    /// entropy model `model`. This error can usually be avoided by using a
+    /// "leaky" distribution as the entropy model, i.e., a distribution that assigns a
+    /// nonzero probability to all symbols within a finite domain. Leaky distributions
+    /// can be constructed with, e.g., a
+    /// [`LeakyQuantizer`](models/struct.LeakyQuantizer.html) or with
+    /// [`LeakyCategorical::from_floating_point_probabilities`](
+    /// models/struct.LeakyCategorical.html#method.from_floating_point_probabilities).
+    ///
+    /// TODO: move this and similar doc comments to the trait definition.
+    ///
+    /// [`Err(ImpossibleSymbol)`]: enum.EncodingError.html#variant.ImpossibleSymbol
+    fn encode_symbol<M>(
+        &mut self,
+        symbol: impl Borrow<M::Symbol>,
+        model: M,
+    ) -> Result<(), DefaultEncoderError<Self::BackendError>>
+    where
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        generic_static_asserts!(
+            (Word: BitArray, State:BitArray; const PRECISION: usize);
+            PROBABILITY_SUPPORTS_PRECISION: State::BITS >= Word::BITS + PRECISION;
+            NON_ZERO_PRECISION: PRECISION > 0;
+            STATE_SUPPORTS_AT_LEAST_TWO_WORDS: State::BITS >= 2 * Word::BITS;
+        );
+
+        let symbol = symbol.borrow();
+        let (left_sided_cumulative, probability) = model
+            .left_cumulative_and_probability(symbol)
+            .ok_or_else(|| DefaultEncoderFrontendError::ImpossibleSymbol.into_coder_error())?;
+        let division_hint = model.division_hint(symbol);
+
+        self.encode_interval::<_, PRECISION>(left_sided_cumulative, probability, division_hint)?;
+        Ok(())
+    }
+
+    fn maybe_full(&self) -> bool {
+        self.bulk.maybe_full()
+    }
+}
+
+impl<Word, State, Backend, const PRECISION: usize> Decode<PRECISION>
+    for AnsCoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: ReadWords<Word, Stack>,
+{
+    /// ANS coding is surjective, and we (deliberately) allow decoding past EOF (in a
+    /// deterministic way) for consistency. Therefore, decoding cannot fail.    
+    type FrontendError = Infallible;
+
+    type BackendError = Backend::ReadError;
+
+    /// Decodes a single symbol and pops it off the compressed data.
+    ///
+    /// This is a low level method. You usually probably want to call a batch method
+    /// like [`decode_symbols`](#method.decode_symbols) or
+    /// [`decode_iid_symbols`](#method.decode_iid_symbols) instead.
+    ///
+    /// This method is called `decode_symbol` rather than `decode_symbol` to stress the
+    /// fact that the `AnsCoder` is a stack: `decode_symbol` will return the *last* symbol
+    /// that was previously encoded via [`encode_symbol`](#method.encode_symbol).
+    ///
+    /// Note that this method cannot fail. It will still produce symbols in a
+    /// deterministic way even if the stack is empty, but such symbols will not
+    /// recover any previously encoded data and will generally have low entropy.
+    /// Still, being able to pop off an arbitrary number of symbols can sometimes be
+    /// useful in edge cases of, e.g., the bits-back algorithm.
+    #[inline(always)]
+    fn decode_symbol<M>(
+        &mut self,
+        model: M,
+    ) -> Result<M::Symbol, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        M: DecoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        generic_static_asserts!(
+            (Word: BitArray, State:BitArray; const PRECISION: usize);
+            PROBABILITY_SUPPORTS_PRECISION: State::BITS >= Word::BITS + PRECISION;
+            NON_ZERO_PRECISION: PRECISION > 0;
+            STATE_SUPPORTS_AT_LEAST_TWO_WORDS: State::BITS >= 2 * Word::BITS;
+        );
+
+        Ok(self.decode_interval::<_, _, PRECISION>(|quantile| model.quantile_function(quantile))?)
+    }
+
+    fn maybe_exhausted(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<Word, State, Backend> PosSeek for AnsCoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: PosSeek,
+    Self: Code,
+{
+    type Position = (Backend::Position, <Self as Code>::State);
+}
+
+impl<Word, State, Backend> Seek for AnsCoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: Seek,
+{
+    /// Jumps to a checkpoint `(pos, state)` that was recorded with [`Pos::pos`], either
+    /// during encoding or during decoding.
+    ///
+    /// Whether you can seek *backward*, i.e., to a checkpoint whose compressed data has
+    /// already been consumed by decoding, depends on the `Backend`. Decoding from an
+    /// `AnsCoder` consumes compressed data, so seeking backward requires a backend that
+    /// retains the entire underlying buffer, such as the [`Cursor`] used by the decoders
+    /// returned from [`as_seekable_decoder`] and [`into_seekable_decoder`]. By contrast, a
+    /// `Vec<Word>` backend discards consumed words, so the `Seek` implementation for `Vec`
+    /// only allows seeking forward and returns `Err(())` otherwise.
+    ///
+    /// [`Cursor`]: crate::backends::Cursor
+    /// [`as_seekable_decoder`]: AnsCoder::as_seekable_decoder
+    /// [`into_seekable_decoder`]: AnsCoder::into_seekable_decoder
+    fn seek(&mut self, (pos, state): Self::Position) -> Result<(), ()> {
+        self.bulk.seek(pos)?;
+        self.state = state;
+        Ok(())
+    }
+}
+
+impl<Word, State, Backend> Pos for AnsCoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: Pos,
+{
+    fn pos(&self) -> Self::Position {
+        self.pos_and_state()
+    }
+}
+
+impl<Word, State> IntoIterator for AnsCoder<Word, State, Vec<Word>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    type Item = Word;
+    type IntoIter = IntoCompressedIter<Word, State>;
+
+    /// Consumes the coder and iterates over its compressed data.
+    ///
+    /// Yields the same words in the same order as [`AnsCoder::into_compressed`] but doesn't
+    /// require materializing the internal state into the `Vec` first.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoCompressedIter {
+            bulk: self.bulk.into_iter(),
+            state: self.state,
+        }
+    }
+}
+
+/// Owning iterator over the compressed data of an [`AnsCoder`].
+///
+/// Returned by the implementation of [`IntoIterator`] for an `AnsCoder` with the default
+/// backend `Vec<Word>`. Yields the words of the backend followed by the significant words
+/// of the internal state, i.e., the same words as [`AnsCoder::into_compressed`].
+#[derive(Debug, Clone)]
+pub struct IntoCompressedIter<Word, State> {
+    bulk: alloc::vec::IntoIter<Word>,
+
+    /// Remaining part of the coder state; its least significant word comes next.
+    state: State,
+}
+
+impl<Word, State> Iterator for IntoCompressedIter<Word, State>
+where
+    Word: BitArray,
+    State: BitArray + AsPrimitive<Word>,
+{
+    type Item = Word;
+
+    fn next(&mut self) -> Option<Word> {
+        if let Some(word) = self.bulk.next() {
+            Some(word)
+        } else if self.state != State::zero() {
+            // `AnsCoder` guarantees that `State::BITS >= 2 * Word::BITS`.
+            let word = self.state.as_();
+            self.state = self.state >> Word::BITS;
+            Some(word)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let num_state_bits = State::BITS - self.state.leading_zeros() as usize;
+        let len = self.bulk.len() + num_state_bits.div_ceil(Word::BITS);
+        (len, Some(len))
+    }
+}
+
+impl<Word, State> ExactSizeIterator for IntoCompressedIter<Word, State>
+where
+    Word: BitArray,
+    State: BitArray + AsPrimitive<Word>,
+{
+}
+
+impl<Word, State> core::iter::FusedIterator for IntoCompressedIter<Word, State>
+where
+    Word: BitArray,
+    State: BitArray + AsPrimitive<Word>,
+{
+}
+
+/// Empirically measures how the fixed-point `PRECISION` of an entropy model affects the
+/// bit rate.
+///
+/// For each entry `precision` of `precisions`, this function constructs a
+/// [`ContiguousCategoricalEntropyModel`] with the requested `precision` from the
+/// floating-point `probabilities` (using [`from_floating_point_probabilities_fast`]),
+/// encodes all `symbols` with this model on a [`DefaultAnsCoder`], and reports the achieved
+/// bit rate. Returns a vector of pairs `(precision, bits_per_symbol)` in the same order as
+/// `precisions`, where `bits_per_symbol` is the number of valid bits (see
+/// [`AnsCoder::num_valid_bits`]) divided by `symbols.len()`.
+///
+/// This allows you to choose `PRECISION` empirically for your data rather than by rule of
+/// thumb (see [discussion of presets](super#presets)). Since `PRECISION` is a const
+/// generic parameter, each supported precision is monomorphized separately; the supported
+/// precisions are `1..=32`, which are all precisions that a `DefaultAnsCoder` supports.
+///
+/// # Errors
+///
+/// Returns an error if `symbols` is empty (since the bit rate per symbol is undefined in
+/// this case) or if measuring the bit rate fails for some entry of `precisions`, see
+/// [`BitrateVsPrecisionError`].
+///
+/// # Example
+///
+/// ```
+/// let probabilities = [0.02, 0.5, 0.3, 0.15, 0.03];
+/// let symbols = [1, 2, 1, 3, 1, 0, 2, 1, 4, 1, 2, 3];
+/// let curve = constriction::stream::stack::bitrate_vs_precision(
+///     &symbols, &probabilities, &[4, 8, 12, 24],
+/// ).unwrap();
+///
+/// for (precision, bits_per_symbol) in curve {
+///     println!("PRECISION = {precision}: {bits_per_symbol} bits per symbol");
+/// }
+/// ```
+///
+/// [`ContiguousCategoricalEntropyModel`]: super::model::ContiguousCategoricalEntropyModel
+/// [`from_floating_point_probabilities_fast`]:
+///     super::model::ContiguousCategoricalEntropyModel::from_floating_point_probabilities_fast
+pub fn bitrate_vs_precision(
+    symbols: &[usize],
+    probabilities: &[f64],
+    precisions: &[usize],
+) -> Result<Vec<(usize, f64)>, BitrateVsPrecisionError> {
+    fn num_valid_bits<const PRECISION: usize>(
+        symbols: &[usize],
+        probabilities: &[f64],
+    ) -> Result<usize, BitrateVsPrecisionError> {
+        let model = super::model::ContiguousCategoricalEntropyModel::<u32, _, PRECISION>
+            ::from_floating_point_probabilities_fast(probabilities, None)
+            .map_err(|()| BitrateVsPrecisionError::InvalidProbabilities { precision: PRECISION })?;
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(symbols, model.as_view())
+            .map_err(|_| BitrateVsPrecisionError::ImpossibleSymbol {
+                precision: PRECISION,
+            })?;
+        Ok(ans.num_valid_bits())
+    }
+
+    macro_rules! dispatch {
+        ($precision:expr; $($p:literal),*) => {
+            match $precision {
+                $($p => num_valid_bits::<$p>(symbols, probabilities),)*
+                precision => Err(BitrateVsPrecisionError::UnsupportedPrecision(precision)),
+            }
+        };
+    }
+
+    if symbols.is_empty() {
+        return Err(BitrateVsPrecisionError::NoSymbols);
+    }
+
+    precisions
+        .iter()
+        .map(|&precision| {
+            let bits = dispatch!(
+                precision;
+                1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+                17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32
+            )?;
+            Ok((precision, bits as f64 / symbols.len() as f64))
+        })
+        .collect()
+}
+
+/// Error type for [`bitrate_vs_precision`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitrateVsPrecisionError {
+    /// The provided sequence of symbols is empty.
+    NoSymbols,
+
+    /// The wrapped precision is not in the supported range `1..=32`.
+    UnsupportedPrecision(usize),
+
+    /// No entropy model with the given `precision` could be constructed from the provided
+    /// probabilities (e.g., because there are more than `2^precision` of them).
+    InvalidProbabilities { precision: usize },
+
+    /// A symbol is out of bounds for the provided probabilities.
+    ImpossibleSymbol { precision: usize },
+}
+
+impl Display for BitrateVsPrecisionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NoSymbols => write!(f, "Can't measure the bit rate of an empty message."),
+            Self::UnsupportedPrecision(precision) => {
+                write!(f, "Unsupported precision {precision} (must be in 1..=32).")
+            }
+            Self::InvalidProbabilities { precision } => write!(
+                f,
+                "Can't construct an entropy model with precision {precision} from the provided probabilities."
+            ),
+            Self::ImpossibleSymbol { precision } => write!(
+                f,
+                "Tried to encode a symbol that is out of bounds for the provided probabilities (at precision {precision})."
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BitrateVsPrecisionError {}
+
+/// The magic bytes at the very end of a [footer](AnsCoder::into_canonical_ans_bytes_with_footer).
+const FOOTER_MAGIC: [u8; 4] = *b"CNST";
+
+/// The version of the footer format, see [`AnsCoder::into_canonical_ans_bytes_with_footer`].
+const FOOTER_VERSION: u8 = 1;
+
+/// The length of a footer in bytes.
+const FOOTER_LEN: usize = 8;
+
+/// Information about compressed data, as recorded in a footer.
+///
+/// See [`detect_format`] and [`AnsCoder::into_canonical_ans_bytes_with_footer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FooterInfo {
+    /// The number of bits per compressed word of the `AnsCoder` that produced the data, see
+    /// [`AnsCoder::compressed_word_bits`].
+    pub word_bits: usize,
+
+    /// The number of bits of the internal state of the `AnsCoder` that produced the data,
+    /// see [`AnsCoder::state_bits`].
+    pub state_bits: usize,
+}
+
+/// Checks whether `bytes` ends in a footer as written by
+/// [`AnsCoder::into_canonical_ans_bytes_with_footer`].
+///
+/// Returns the information recorded in the footer, or `None` if `bytes` doesn't end in a
+/// valid footer (in which case `bytes` should be treated as raw compressed data, e.g., as
+/// returned by [`AnsCoder::into_canonical_ans_bytes`]). Note that detection is heuristic:
+/// raw compressed data could, in principle, end in the same bytes as a footer. This is
+/// very unlikely for real data, but you should not rely on `detect_format` if you have
+/// control over both the writer and the reader.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::DefaultUniformModel,
+///     stack::{detect_format, DefaultAnsCoder, FooterInfo},
+/// };
+///
+/// let mut ans = DefaultAnsCoder::new();
+/// ans.encode_iid_symbols_reverse([3, 1, 4, 1, 5], DefaultUniformModel::new(10)).unwrap();
+/// let raw = ans.clone().into_canonical_ans_bytes();
+/// let with_footer = ans.into_canonical_ans_bytes_with_footer();
+///
+/// assert_eq!(detect_format(&raw), None);
+/// assert_eq!(
+///     detect_format(&with_footer),
+///     Some(FooterInfo { word_bits: 32, state_bits: 64 })
+/// );
+/// ```
+pub fn detect_format(bytes: &[u8]) -> Option<FooterInfo> {
+    let footer = bytes
+        .len()
+        .checked_sub(FOOTER_LEN)
+        .map(|start| &bytes[start..])?;
+    if footer[4..] != FOOTER_MAGIC || footer[0] != FOOTER_VERSION || footer[3] != 0 {
+        return None;
+    }
+
+    let info = FooterInfo {
+        word_bits: footer[1] as usize,
+        state_bits: footer[2] as usize,
+    };
+    let bytes_per_word = info.word_bits / 8;
+    let valid = info.word_bits % 8 == 0
+        && bytes_per_word != 0
+        && info.state_bits >= 2 * info.word_bits
+        && (bytes.len() - FOOTER_LEN) % bytes_per_word == 0;
+    valid.then_some(info)
+}
+
+/// Self-describing container for compressed data and the metadata needed to decode it.
+///
+/// A `CompressedMessage` records, in addition to the compressed words, the fixed-point
+/// `PRECISION` of the entropy model, the sizes of the `Word` and `State` types of the
+/// [`AnsCoder`] that produced the data, and (optionally) the number of encoded symbols.
+/// Thus, you can store or transmit a `CompressedMessage` without any out-of-band
+/// metadata, and [`decode_from_message`] verifies that the decoder is set up consistently
+/// with the encoder before decoding anything.
+///
+/// Create a `CompressedMessage` with [`encode_to_message`]. If the crate feature `serde`
+/// is enabled, then `CompressedMessage` implements `serde::Serialize` and
+/// `serde::Deserialize`.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::{DefaultUniformModel, UniformModel},
+///     stack::{decode_from_message, encode_to_message},
+/// };
+///
+/// let model = DefaultUniformModel::new(10);
+/// let message = encode_to_message::<u32, u64, _, 24>(&[3, 1, 4, 1, 5], model).unwrap();
+/// assert_eq!(message.precision, 24);
+/// assert_eq!(message.num_symbols, Some(5));
+///
+/// let decoded = decode_from_message::<u32, u64, _, 24>(&message, model).unwrap();
+/// assert_eq!(decoded, [3, 1, 4, 1, 5]);
+///
+/// // Trying to decode with a different precision fails instead of returning garbage.
+/// let other_model = UniformModel::<u32, 20>::new(10);
+/// assert!(decode_from_message::<u32, u64, _, 20>(&message, other_model).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompressedMessage<Word> {
+    /// The `PRECISION` of the entropy model that was used for encoding.
+    pub precision: usize,
+
+    /// The number of bits per compressed word, see [`AnsCoder::compressed_word_bits`].
+    pub word_bits: usize,
+
+    /// The number of bits of the coder's state, see [`AnsCoder::state_bits`].
+    pub state_bits: usize,
+
+    /// The number of encoded symbols, if known.
+    pub num_symbols: Option<u64>,
+
+    /// The compressed data, as returned by [`AnsCoder::into_compressed`].
+    pub compressed: Vec<Word>,
+}
+
+/// Encodes i.i.d. `symbols` with `model` into a self-describing [`CompressedMessage`].
+///
+/// The returned message records `PRECISION`, `Word::BITS`, `State::BITS`, and the number
+/// of symbols, so that it can be decoded with [`decode_from_message`] without any
+/// additional metadata. See [`CompressedMessage`] for an example.
+pub fn encode_to_message<Word, State, M, const PRECISION: usize>(
+    symbols: &[M::Symbol],
+    model: M,
+) -> Result<CompressedMessage<Word>, DefaultEncoderError<Infallible>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    M: EncoderModel<PRECISION> + Copy,
+    M::Probability: Into<Word>,
+    Word: AsPrimitive<M::Probability>,
+{
+    let mut ans = AnsCoder::<Word, State>::new();
+    ans.encode_iid_symbols_reverse(symbols, model)?;
+    Ok(CompressedMessage {
+        precision: PRECISION,
+        word_bits: Word::BITS,
+        state_bits: State::BITS,
+        num_symbols: Some(symbols.len() as u64),
+        compressed: ans.into_compressed().unwrap_infallible(),
+    })
+}
+
+/// Decodes a [`CompressedMessage`] that was created with [`encode_to_message`].
+///
+/// Returns `Err(())` if the metadata in `message` is inconsistent with the decoder, i.e.,
+/// if `PRECISION`, `Word::BITS`, or `State::BITS` differ from the values recorded in
+/// `message`, if `message` doesn't record the number of symbols, if the compressed data
+/// is invalid, or if the compressed data doesn't hold exactly the recorded number of
+/// symbols, i.e., if it runs out before all recorded symbols are decoded or if not all
+/// compressed data is consumed afterwards (which indicates that `model` differs from the
+/// model that was used for encoding). See [`CompressedMessage`] for an example.
+///
+/// The recorded number of symbols is not trusted for memory allocation. Symbols are
+/// decoded lazily, and decoding stops with an error as soon as the compressed data runs
+/// out, so that a corrupted or malicious message can't make this function allocate
+/// memory for more symbols than the compressed data actually holds.
+#[allow(clippy::result_unit_err)]
+pub fn decode_from_message<Word, State, D, const PRECISION: usize>(
+    message: &CompressedMessage<Word>,
+    model: D,
+) -> Result<Vec<D::Symbol>, ()>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    D: DecoderModel<PRECISION> + Copy,
+    D::Probability: Into<Word>,
+    Word: AsPrimitive<D::Probability>,
+{
+    if message.precision != PRECISION
+        || message.word_bits != Word::BITS
+        || message.state_bits != State::BITS
+    {
+        return Err(());
+    }
+    let num_symbols = message
+        .num_symbols
+        .and_then(|num_symbols| usize::try_from(num_symbols).ok())
+        .ok_or(())?;
+
+    let mut ans =
+        AnsCoder::<Word, State>::from_compressed(message.compressed.clone()).map_err(|_| ())?;
+    let mut symbols = Vec::new();
+    for _ in 0..num_symbols {
+        match ans.try_decode_symbol(model) {
+            Ok(Some(symbol)) => symbols.push(symbol),
+            _ => return Err(()),
+        }
+    }
+    if !ans.is_empty() {
+        return Err(());
+    }
+
+    Ok(symbols)
+}
+
+/// Provides temporary read-only access to the compressed data wrapped in a
+/// [`AnsCoder`].
+///
+/// Dereferences to `&[Word]`. See [`Coder::get_compressed`] for an example.
+///
+/// [`AnsCoder`]: struct.Coder.html
+/// [`Coder::get_compressed`]: struct.Coder.html#method.get_compressed
+struct CoderGuard<'a, Word, State, Backend, const SEALED: bool>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: WriteWords<Word> + ReadWords<Word, Stack>,
+{
+    inner: &'a mut AnsCoder<Word, State, Backend>,
+}
+
+impl<'a, Word, State, Backend, const SEALED: bool> CoderGuard<'a, Word, State, Backend, SEALED>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: WriteWords<Word> + ReadWords<Word, Stack>,
+{
+    #[inline(always)]
+    fn new(
+        ans: &'a mut AnsCoder<Word, State, Backend>,
+    ) -> Result<Self, CoderError<(), Backend::WriteError>> {
+        // Append state. Will be undone in `<Self as Drop>::drop`.
+        let mut chunks_rev = bit_array_to_chunks_truncated(ans.state);
+        if SEALED && chunks_rev.next() != Some(Word::one()) {
+            return Err(CoderError::Frontend(()));
+        }
+        for chunk in chunks_rev.rev() {
+            ans.bulk.write(chunk)?
+        }
+
+        Ok(Self { inner: ans })
+    }
+}
+
+impl<'a, Word, State, Backend, const SEALED: bool> Drop
+    for CoderGuard<'a, Word, State, Backend, SEALED>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: WriteWords<Word> + ReadWords<Word, Stack>,
+{
+    fn drop(&mut self) {
+        // Revert what we did in `Self::new`.
+        let mut chunks_rev = bit_array_to_chunks_truncated(self.inner.state);
+        if SEALED {
+            chunks_rev.next();
+        }
+        for _ in chunks_rev {
+            core::mem::drop(self.inner.bulk.read());
+        }
+    }
+}
+
+impl<'a, Word, State, Backend, const SEALED: bool> Deref
+    for CoderGuard<'a, Word, State, Backend, SEALED>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: WriteWords<Word> + ReadWords<Word, Stack>,
+{
+    type Target = Backend;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner.bulk
+    }
+}
+
+impl<Word, State, Backend, const SEALED: bool> Debug
+    for CoderGuard<'_, Word, State, Backend, SEALED>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: WriteWords<Word> + ReadWords<Word, Stack> + Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::model::{
+        ContiguousCategoricalEntropyModel, DefaultLeakyQuantizer, DefaultUniformModel,
+        EntropyModel, IterableEntropyModel, LeakyQuantizer, SmallLeakyQuantizer,
+    };
+    use super::*;
+    use crate::stream::ShortChunk;
+    extern crate std;
+    use std::dbg;
+
+    use probability::distribution::{Gaussian, Inverse};
+    use rand_xoshiro::{
+        rand_core::{RngCore, SeedableRng},
+        Xoshiro256StarStar,
+    };
+
+    #[test]
+    fn compress_none() {
+        let coder1 = DefaultAnsCoder::new();
+        assert!(coder1.is_empty());
+        let compressed = coder1.into_compressed().unwrap();
+        assert!(compressed.is_empty());
+
+        let coder2 = DefaultAnsCoder::from_compressed(compressed).unwrap();
+        assert!(coder2.is_empty());
+    }
+
+    #[test]
+    fn compress_one() {
+        generic_compress_few(core::iter::once(5), 1)
+    }
+
+    #[test]
+    fn compress_two() {
+        generic_compress_few([2, 8].iter().cloned(), 1)
+    }
+
+    #[test]
+    fn compress_ten() {
+        generic_compress_few(0..10, 2)
+    }
+
+    #[test]
+    fn compress_twenty() {
+        generic_compress_few(-10..10, 4)
+    }
+
+    fn generic_compress_few<I>(symbols: I, expected_size: usize)
+    where
+        I: IntoIterator<Item = i32>,
+        I::IntoIter: Clone + DoubleEndedIterator,
+    {
+        let symbols = symbols.into_iter();
+
+        let mut encoder = DefaultAnsCoder::new();
+        let quantizer = DefaultLeakyQuantizer::new(-127..=127);
+        let model = quantizer.quantize(Gaussian::new(3.2, 5.1));
+
+        // We don't reuse the same encoder for decoding because we want to test
+        // if exporting and re-importing of compressed data works.
+        encoder.encode_iid_symbols(symbols.clone(), model).unwrap();
+        let compressed = encoder.into_compressed().unwrap();
+        assert_eq!(compressed.len(), expected_size);
+
+        let mut decoder = DefaultAnsCoder::from_compressed(compressed).unwrap();
+        for symbol in symbols.rev() {
+            assert_eq!(decoder.decode_symbol(model).unwrap(), symbol);
+        }
+        assert!(decoder.is_empty());
+    }
+
+    #[test]
+    fn compress_many_u32_u64_32() {
+        generic_compress_many::<u32, u64, u32, 32>();
+    }
+
+    #[test]
+    fn compress_many_u32_u64_24() {
+        generic_compress_many::<u32, u64, u32, 24>();
+    }
+
+    #[test]
+    fn compress_many_u32_u64_16() {
+        generic_compress_many::<u32, u64, u16, 16>();
+    }
+
+    #[test]
+    fn compress_many_u32_u64_8() {
+        generic_compress_many::<u32, u64, u8, 8>();
+    }
+
+    #[test]
+    fn compress_many_u16_u64_16() {
+        generic_compress_many::<u16, u64, u16, 16>();
+    }
+
+    #[test]
+    fn compress_many_u16_u64_12() {
+        generic_compress_many::<u16, u64, u16, 12>();
+    }
+
+    #[test]
+    fn compress_many_u16_u64_8() {
+        generic_compress_many::<u16, u64, u8, 8>();
+    }
+
+    #[test]
+    fn compress_many_u8_u64_8() {
+        generic_compress_many::<u8, u64, u8, 8>();
+    }
+
+    #[test]
+    fn compress_many_u16_u32_16() {
+        generic_compress_many::<u16, u32, u16, 16>();
+    }
+
+    #[test]
+    fn compress_many_u16_u32_12() {
+        generic_compress_many::<u16, u32, u16, 12>();
+    }
+
+    #[test]
+    fn compress_many_u16_u32_8() {
+        generic_compress_many::<u16, u32, u8, 8>();
+    }
+
+    #[test]
+    fn compress_many_u8_u32_8() {
+        generic_compress_many::<u8, u32, u8, 8>();
+    }
+
+    #[test]
+    fn compress_many_u8_u16_8() {
+        generic_compress_many::<u8, u16, u8, 8>();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn compress_many_u32_u128_24() {
+        generic_compress_many::<u32, u128, u32, 24>();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn compress_many_u16_u128_16() {
+        generic_compress_many::<u16, u128, u16, 16>();
+    }
+
+    #[test]
+    fn wide_state() {
+        generic_wide_state::<u16, u64, u16, 16>();
+        generic_wide_state::<u16, u64, u16, 12>();
+        generic_wide_state::<u8, u64, u8, 8>();
+
+        #[cfg(feature = "std")]
+        {
+            generic_wide_state::<u32, u128, u32, 32>();
+            generic_wide_state::<u32, u128, u32, 24>();
+        }
+    }
+
+    #[test]
+    fn max_precision() {
+        generic_max_precision::<u8, u16, 8>();
+        generic_max_precision::<u16, u32, 16>();
+        generic_max_precision::<u16, u64, 16>();
+        generic_max_precision::<u32, u64, 32>();
+    }
+
+    /// Tests `PRECISION == Word::BITS` (the largest precision that a `State` with twice as
+    /// many bits as a `Word` supports) with extreme probabilities and extreme states.
+    fn generic_max_precision<Word, State, const PRECISION: usize>()
+    where
+        State: BitArray + AsPrimitive<Word>,
+        Word: BitArray + Into<State> + AsPrimitive<Word>,
+    {
+        assert_eq!(PRECISION, Word::BITS);
+
+        // Probabilities `[1, 2^PRECISION - 3, 1, 1]`, i.e., the smallest and the largest
+        // possible probabilities for a model with four symbols.
+        let two = Word::one() + Word::one();
+        let model =
+            ContiguousCategoricalEntropyModel::<Word, Vec<Word>, PRECISION>::from_nonzero_fixed_point_probabilities(
+                [Word::one(), Word::max_value() - two, Word::one(), Word::one()],
+                false,
+            )
+            .unwrap();
+        assert_eq!(model.quantile_function(Word::zero()).0, 0);
+        assert_eq!(model.quantile_function(Word::one()).0, 1);
+        assert_eq!(model.quantile_function(Word::max_value() - two).0, 1);
+        assert_eq!(
+            model.quantile_function(Word::max_value() - Word::one()).0,
+            2
+        );
+        assert_eq!(model.quantile_function(Word::max_value()).0, 3);
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2307);
+        let symbols = (0..20)
+            .flat_map(|i| {
+                // Alternate between runs of rare and runs of common symbols.
+                let len = if i % 2 == 0 { 30 } else { 100 };
+                (0..len)
+                    .map(|_| {
+                        if i % 2 == 0 {
+                            [0, 2, 3][rng.next_u32() as usize % 3]
+                        } else {
+                            1
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let threshold = State::one() << (State::BITS - Word::BITS);
+        let bulk = alloc::vec![Word::max_value(), Word::zero(), Word::one()];
+        let initial_coders = [
+            AnsCoder::<Word, State>::new(),
+            AnsCoder::from_raw_parts(Vec::new(), State::one()),
+            AnsCoder::from_raw_parts(Vec::new(), State::max_value()),
+            AnsCoder::from_raw_parts(bulk.clone(), threshold),
+            AnsCoder::from_raw_parts(bulk, State::max_value()),
+        ];
+
+        for original in initial_coders {
+            let mut ans = original.clone();
+            for &symbol in symbols.iter().rev() {
+                ans.encode_symbol(symbol, &model).unwrap();
+                assert_eq!(ans.validate(), Ok(()));
+            }
+            for &symbol in &symbols {
+                assert_eq!(ans.decode_symbol(&model).unwrap(), symbol);
+            }
+            assert_eq!(ans.into_raw_parts(), original.into_raw_parts());
+        }
+    }
+
+    fn generic_wide_state<Word, State, Probability, const PRECISION: usize>()
+    where
+        State: BitArray + AsPrimitive<Word>,
+        Word: BitArray + Into<State> + AsPrimitive<Probability>,
+        Probability: BitArray + Into<Word> + AsPrimitive<usize>,
+        usize: AsPrimitive<Probability>,
+    {
+        let model = crate::stream::model::UniformModel::<Probability, PRECISION>::new(100);
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2224);
+        let symbols = (0..500)
+            .map(|_| rng.next_u32() as usize % 100)
+            .collect::<Vec<_>>();
+
+        let mut ans = AnsCoder::<Word, State>::new();
+        assert!(ans.is_empty());
+        assert_eq!(ans.num_valid_bits(), 0);
+        assert_eq!(ans.num_words(), 0);
+
+        for (i, &symbol) in symbols.iter().enumerate().rev() {
+            ans.encode_symbol(symbol, model).unwrap();
+            assert!(!ans.is_empty());
+            assert!(ans.num_words() <= ans.bulk().len() + State::BITS / Word::BITS);
+            let overhead = ans.num_bits() - ans.num_valid_bits();
+            assert!((1..=Word::BITS).contains(&overhead));
+            if i < symbols.len() - 50 {
+                // By now, the state must have reached its steady-state regime.
+                assert!(ans.state() >= State::one() << (State::BITS - Word::BITS));
+            }
+        }
+
+        let compressed = ans.into_compressed().unwrap();
+        let mut ans = AnsCoder::<Word, State>::from_compressed(compressed).unwrap();
+        for &symbol in &symbols {
+            assert_eq!(ans.decode_symbol(model).unwrap(), symbol);
+        }
+        assert!(ans.is_empty());
+        assert_eq!(ans.num_valid_bits(), 0);
+        assert_eq!(ans.num_words(), 0);
+    }
+
+    fn generic_compress_many<Word, State, Probability, const PRECISION: usize>()
+    where
+        State: BitArray + AsPrimitive<Word>,
+        Word: BitArray + Into<State> + AsPrimitive<Probability>,
+        Probability: BitArray + Into<Word> + AsPrimitive<usize> + Into<f64>,
+        u32: AsPrimitive<Probability>,
+        usize: AsPrimitive<Probability>,
+        f64: AsPrimitive<Probability>,
+        i32: AsPrimitive<Probability>,
+    {
+        #[cfg(not(miri))]
+        const AMT: usize = 1000;
+
+        #[cfg(miri)]
+        const AMT: usize = 100;
+
+        let mut symbols_gaussian = Vec::with_capacity(AMT);
+        let mut means = Vec::with_capacity(AMT);
+        let mut stds = Vec::with_capacity(AMT);
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(
+            (Word::BITS as u64).rotate_left(3 * 16)
+                ^ (State::BITS as u64).rotate_left(2 * 16)
+                ^ (Probability::BITS as u64).rotate_left(16)
+                ^ PRECISION as u64,
+        );
+
+        for _ in 0..AMT {
+            let mean = (200.0 / u32::MAX as f64) * rng.next_u32() as f64 - 100.0;
+            let std_dev = (10.0 / u32::MAX as f64) * rng.next_u32() as f64 + 0.001;
+            let quantile = (rng.next_u32() as f64 + 0.5) / (1u64 << 32) as f64;
+            let dist = Gaussian::new(mean, std_dev);
+            let symbol = (dist.inverse(quantile).round() as i32).clamp(-127, 127);
+
+            symbols_gaussian.push(symbol);
+            means.push(mean);
+            stds.push(std_dev);
+        }
+
+        let hist = [
+            1u32, 186545, 237403, 295700, 361445, 433686, 509456, 586943, 663946, 737772, 1657269,
+            896675, 922197, 930672, 916665, 0, 0, 0, 0, 0, 723031, 650522, 572300, 494702, 418703,
+            347600, 1, 283500, 226158, 178194, 136301, 103158, 76823, 55540, 39258, 27988, 54269,
+        ];
+        let categorical_probabilities = hist.iter().map(|&x| x as f64).collect::<Vec<_>>();
+        let categorical =
+            ContiguousCategoricalEntropyModel::<Probability, _, PRECISION>::from_floating_point_probabilities_fast::<f64>(
+                &categorical_probabilities,None
+            )
+            .unwrap();
+        let mut symbols_categorical = Vec::with_capacity(AMT);
+        let max_probability = Probability::max_value() >> (Probability::BITS - PRECISION);
+        for _ in 0..AMT {
+            let quantile = rng.next_u32().as_() & max_probability;
+            let symbol = categorical.quantile_function(quantile).0;
+            symbols_categorical.push(symbol);
+        }
+
+        let mut ans = AnsCoder::<Word, State>::new();
+
+        ans.encode_iid_symbols_reverse(&symbols_categorical, &categorical)
+            .unwrap();
+        dbg!(
+            ans.num_valid_bits(),
+            AMT as f64 * categorical.entropy_base2::<f64>()
+        );
+
+        let quantizer = LeakyQuantizer::<_, _, Probability, PRECISION>::new(-127..=127);
+        ans.encode_symbols_reverse(symbols_gaussian.iter().zip(&means).zip(&stds).map(
+            |((&symbol, &mean), &core)| (symbol, quantizer.quantize(Gaussian::new(mean, core))),
+        ))
+        .unwrap();
+        dbg!(ans.num_valid_bits());
+
+        // Test if import/export of compressed data works.
+        let compressed = ans.into_compressed().unwrap();
+        let mut ans = AnsCoder::from_compressed(compressed).unwrap();
+
+        let reconstructed_gaussian = ans
+            .decode_symbols(
+                means
+                    .iter()
+                    .zip(&stds)
+                    .map(|(&mean, &core)| quantizer.quantize(Gaussian::new(mean, core))),
+            )
+            .collect::<Result<Vec<_>, CoderError<Infallible, Infallible>>>()
+            .unwrap();
+        let reconstructed_categorical = ans
+            .decode_iid_symbols(AMT, &categorical)
+            .collect::<Result<Vec<_>, CoderError<Infallible, Infallible>>>()
+            .unwrap();
+
+        assert!(ans.is_empty());
+
+        assert_eq!(symbols_gaussian, reconstructed_gaussian);
+        assert_eq!(symbols_categorical, reconstructed_categorical);
+    }
+
+    #[test]
+    fn reset_state() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2222);
+        let segments = [50, 1, 200]
+            .iter()
+            .map(|&len| {
+                (0..len)
+                    .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        let mut boundaries = Vec::from([0]);
+        for segment in &segments {
+            ans.encode_iid_symbols_reverse(segment, &model).unwrap();
+            ans.reset_state().unwrap();
+            assert!(ans.is_empty());
+            boundaries.push(ans.bulk().len());
+        }
+        let capacity = ans.bulk().capacity();
+        let compressed = ans.into_compressed().unwrap();
+        assert_eq!(compressed.len(), *boundaries.last().unwrap());
+        assert_eq!(compressed.capacity(), capacity);
+
+        for (segment, range) in segments.iter().zip(boundaries.windows(2)) {
+            let mut decoder =
+                DefaultAnsCoder::from_compressed_slice(&compressed[range[0]..range[1]]).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(segment.len(), &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded, segment);
+            assert!(decoder.is_empty());
+        }
+    }
+
+    #[test]
+    fn decode_iid_symbols_with_histogram() {
+        let quantizer = DefaultLeakyQuantizer::new(-20..=20);
+        let model = quantizer.quantize(Gaussian::new(0.0, 3.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2223);
+        let symbols = (0..1000)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let (decoded, histogram) = ans
+            .decode_iid_symbols_with_histogram(symbols.len(), &model)
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(ans.is_empty());
+
+        assert_eq!(histogram.values().sum::<u64>(), symbols.len() as u64);
+        for (symbol, &count) in &histogram {
+            let expected = symbols.iter().filter(|&s| s == symbol).count() as u64;
+            assert_eq!(count, expected);
+        }
+    }
+
+    #[test]
+    fn decode_iid_chunks() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2225);
+        let symbols = (0..100)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+
+        for (amt, chunk_size) in [(100, 10), (100, 7), (100, 1), (100, 200), (37, 8), (0, 4)] {
+            let expected = ans
+                .as_decoder()
+                .decode_iid_symbols(amt, &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let chunks = ans
+                .as_decoder()
+                .decode_iid_chunks(amt, chunk_size, ShortChunk::Keep, &model)
+                .unwrap();
+
+            assert_eq!(chunks.len(), amt.div_ceil(chunk_size));
+            for chunk in chunks.iter().rev().skip(1) {
+                assert_eq!(chunk.len(), chunk_size);
+            }
+            assert_eq!(chunks.concat(), expected);
+
+            let mut decoder = ans.as_decoder();
+            let chunks = decoder
+                .decode_iid_chunks(amt, chunk_size, ShortChunk::Omit, &model)
+                .unwrap();
+            assert_eq!(chunks.len(), amt / chunk_size);
+            assert!(chunks.iter().all(|chunk| chunk.len() == chunk_size));
+            let full_len = amt - amt % chunk_size;
+            assert_eq!(chunks.concat(), expected[..full_len]);
+
+            // The symbols of the omitted short chunk are left on the coder.
+            let remaining = decoder
+                .decode_iid_symbols(amt % chunk_size, &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(remaining, expected[full_len..]);
+        }
+    }
+
+    #[test]
+    fn decode_iid_fold() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2255);
+        let symbols = (0..100)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+
+        let sum = ans
+            .as_decoder()
+            .decode_iid_fold(symbols.len(), &model, 0, |sum, symbol| sum + symbol)
+            .unwrap();
+        assert_eq!(sum, symbols.iter().sum::<i32>());
+
+        let max = ans
+            .as_decoder()
+            .decode_iid_fold(50, &model, i32::MIN, core::cmp::max)
+            .unwrap();
+        assert_eq!(max, *symbols[..50].iter().max().unwrap());
+
+        let decoded = ans
+            .decode_iid_fold(symbols.len(), model, Vec::new(), |mut decoded, symbol| {
+                decoded.push(symbol);
+                decoded
+            })
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(ans.is_empty());
+    }
+
+    #[test]
+    fn decode_iid_symbols_into() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2299);
+        let symbols = (0..100)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+        let expected = ans
+            .as_decoder()
+            .decode_iid_symbols(symbols.len(), model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(expected, symbols);
+
+        // Decode in chunks of different sizes, reusing a single buffer.
+        let mut buf = [0; 30];
+        let mut decoded = Vec::new();
+        for len in [0, 30, 1, 29, 30, 10] {
+            let out = &mut buf[..len];
+            ans.decode_iid_symbols_into(out, model).unwrap();
+            decoded.extend_from_slice(out);
+        }
+        assert_eq!(decoded, expected);
+        assert!(ans.is_empty());
+    }
+
+    #[test]
+    fn decode_symbols_while() {
+        let model = DefaultUniformModel::new(100);
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2309);
+        let mut random_message = |len: usize| {
+            (0..len)
+                .map(|_| 1 + rng.next_u32() as usize % 99)
+                .collect::<Vec<_>>()
+        };
+
+        // Two sentinel-terminated messages, followed by some trailing symbols.
+        let first = random_message(50);
+        let second = random_message(0);
+        let trailing = random_message(20);
+        let mut symbols = first.clone();
+        symbols.push(0);
+        symbols.extend_from_slice(&second);
+        symbols.push(0);
+        symbols.extend_from_slice(&trailing);
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+
+        let (decoded, terminated) = ans
+            .decode_symbols_while(model, 1000, false, |&symbol| symbol != 0)
+            .unwrap();
+        assert_eq!(decoded, first);
+        assert!(terminated);
+
+        // The sentinel was consumed, so the coder is now positioned at the second message.
+        let (decoded, terminated) = ans
+            .decode_symbols_while(model, 1, true, |&symbol| symbol != 0)
+            .unwrap();
+        assert_eq!(decoded, [0]);
+        assert!(terminated);
+
+        // The remaining symbols are unaffected.
+        let decoded = ans
+            .decode_iid_symbols(trailing.len(), model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, trailing);
+        assert!(ans.is_empty());
+
+        // Terminate on a predicate that depends on more than the current symbol.
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&trailing, model).unwrap();
+        let mut count = 0;
+        let (decoded, terminated) = ans
+            .decode_symbols_while(model, 1000, true, |_| {
+                count += 1;
+                count < 5
+            })
+            .unwrap();
+        assert_eq!(decoded, trailing[..5]);
+        assert!(terminated);
+        let decoded = ans
+            .decode_iid_symbols(15, model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, trailing[5..]);
+        assert!(ans.is_empty());
+
+        // Without a terminating symbol, decoding stops after `max_symbols` symbols, even
+        // though the coder could keep decoding past the end of the data.
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&trailing, model).unwrap();
+        let (decoded, terminated) = ans
+            .decode_symbols_while(model, 100, false, |_| true)
+            .unwrap();
+        assert_eq!(decoded.len(), 100);
+        assert_eq!(decoded[..trailing.len()], trailing);
+        assert!(!terminated);
+
+        let (decoded, terminated) = ans.decode_symbols_while(model, 0, true, |_| false).unwrap();
+        assert!(decoded.is_empty());
+        assert!(!terminated);
+    }
+
+    #[test]
+    fn moe() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let experts = [
+            quantizer.quantize(Gaussian::new(-20.0, 5.0)),
+            quantizer.quantize(Gaussian::new(30.0, 15.0)),
+        ];
+        let gate_model =
+            ContiguousCategoricalEntropyModel::<u32, _, 24>::from_floating_point_probabilities_fast(
+                &[0.3, 0.7],
+                None,
+            )
+            .unwrap();
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2226);
+        let gates = (0..500)
+            .map(|_| gate_model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+        let symbols = gates
+            .iter()
+            .map(|&gate| {
+                experts[gate]
+                    .quantile_function(rng.next_u32() % (1 << 24))
+                    .0
+            })
+            .collect::<Vec<_>>();
+        assert!(gates.contains(&0) && gates.contains(&1));
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_moe(&symbols, &gates, &gate_model, &experts)
+            .unwrap();
+        let compressed = ans.into_compressed().unwrap();
+
+        let mut ans = DefaultAnsCoder::from_compressed(compressed).unwrap();
+        let (decoded_gates, decoded_symbols) = ans
+            .decode_moe(symbols.len(), &gate_model, &experts)
+            .unwrap();
+        assert_eq!(decoded_gates, gates);
+        assert_eq!(decoded_symbols, symbols);
+        assert!(ans.is_empty());
+
+        // A gate model whose support exceeds the number of experts.
+        let wide_gate_model =
+            ContiguousCategoricalEntropyModel::<u32, _, 24>::from_floating_point_probabilities_fast(
+                &[0.3, 0.3, 0.4],
+                None,
+            )
+            .unwrap();
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_symbol(2, &wide_gate_model).unwrap();
+        assert_eq!(
+            ans.decode_moe(1, &wide_gate_model, &experts),
+            Err(CoderError::Frontend(GateOutOfRangeError {
+                gate: 2,
+                num_experts: 2
+            }))
+        );
+    }
+
+    #[test]
+    fn bitrate_vs_precision() {
+        let probabilities = [0.001, 0.3, 0.2, 0.4, 0.099];
+        let model =
+            ContiguousCategoricalEntropyModel::<u32, _, 24>::from_floating_point_probabilities_fast(
+                &probabilities,
+                None,
+            )
+            .unwrap();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2227);
+        let symbols = (0..2000)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let precisions = [3, 4, 8, 12, 16, 24, 32];
+        let curve = super::bitrate_vs_precision(&symbols, &probabilities, &precisions).unwrap();
+        assert_eq!(
+            curve
+                .iter()
+                .map(|&(precision, _)| precision)
+                .collect::<Vec<_>>(),
+            precisions
+        );
+
+        let entropy = model.entropy_base2::<f64>();
+        for &(_, bits_per_symbol) in &curve {
+            assert!(bits_per_symbol > 0.9 * entropy);
+        }
+        // Low precision can't represent the small probabilities well, which costs bits.
+        assert!(curve[0].1 > curve[5].1 + 0.1);
+        assert!((curve[5].1 - entropy).abs() < 0.1);
+
+        use super::BitrateVsPrecisionError::*;
+        assert_eq!(
+            super::bitrate_vs_precision(&symbols, &probabilities, &[2]),
+            Err(InvalidProbabilities { precision: 2 })
+        );
+        assert_eq!(
+            super::bitrate_vs_precision(&symbols, &probabilities, &[33]),
+            Err(UnsupportedPrecision(33))
+        );
+        assert_eq!(
+            super::bitrate_vs_precision(&symbols, &probabilities, &[0]),
+            Err(UnsupportedPrecision(0))
+        );
+        assert_eq!(
+            super::bitrate_vs_precision(&[5], &probabilities, &[24]),
+            Err(ImpossibleSymbol { precision: 24 })
+        );
+        assert_eq!(
+            super::bitrate_vs_precision(&[], &probabilities, &[24]),
+            Err(NoSymbols)
+        );
+        assert_eq!(
+            super::bitrate_vs_precision(&symbols, &probabilities, &[]),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn transaction() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2229);
+        let mut sample = |len: usize| {
+            (0..len)
+                .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                .collect::<Vec<_>>()
+        };
+
+        let prefix = sample(100);
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&prefix, &model).unwrap();
+        let checkpoint = ans.clone();
+
+        // Explore several candidate continuations, keeping track of the cheapest one.
+        let candidates = (0..5).map(|i| sample(10 + 20 * i)).collect::<Vec<_>>();
+        let mut best = None;
+        for (i, candidate) in candidates.iter().enumerate() {
+            let mut transaction = ans.begin();
+            transaction
+                .encode_iid_symbols_reverse(candidate, &model)
+                .unwrap();
+            assert_ne!(transaction.state(), checkpoint.state());
+            let num_bits = transaction.num_valid_bits();
+            if best.map_or(true, |(_, best_bits)| num_bits < best_bits) {
+                best = Some((i, num_bits));
+            }
+            if i % 2 == 0 {
+                transaction.rollback();
+            } else {
+                core::mem::drop(transaction);
+            }
+            assert_eq!(ans.bulk(), checkpoint.bulk());
+            assert_eq!(ans.state(), checkpoint.state());
+        }
+
+        let (best_index, best_bits) = best.unwrap();
+        let mut transaction = ans.begin();
+        transaction
+            .encode_iid_symbols_reverse(&candidates[best_index], &model)
+            .unwrap();
+        transaction.commit();
+        assert_eq!(ans.num_valid_bits(), best_bits);
+
+        let decoded = ans
+            .decode_iid_symbols(candidates[best_index].len() + prefix.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            &decoded[..candidates[best_index].len()],
+            &candidates[best_index]
+        );
+        assert_eq!(&decoded[candidates[best_index].len()..], &prefix);
+        assert!(ans.is_empty());
+    }
+
+    #[test]
+    fn transaction_on_partially_decoded_coder() {
+        let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+        let message = (-50..50).collect::<Vec<i32>>();
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&message, &model).unwrap();
+
+        // Decode part of the message so that the transaction starts below the original top.
+        let decoded = ans
+            .decode_iid_symbols(30, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, &message[..30]);
+        let checkpoint = ans.clone();
+
+        let mut transaction = ans.begin();
+        transaction
+            .encode_iid_symbols_reverse((0..20).map(|i| 3 * i - 30), model)
+            .unwrap();
+        Encode::<24>::encode_symbol(&mut transaction, 7, &model).unwrap();
+        assert!(transaction.num_words() > checkpoint.num_words());
+        transaction.rollback();
+
+        assert_eq!(ans.bulk(), checkpoint.bulk());
+        assert_eq!(ans.state(), checkpoint.state());
+        let decoded = ans
+            .decode_iid_symbols(70, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, &message[30..]);
+        assert!(ans.is_empty());
+    }
+
+    #[test]
+    fn le_words() {
+        // Bytes of the file that `compressed.tofile(...)` writes in the "Hello, World" example
+        // of the Python API (see `README-python.md`).
+        let file = [0x62, 0xcf, 0x9f, 0xb9, 0xb7, 0xb5, 0x55, 0x15];
+        let message = [6, 10, -4, 2, 5, 2, 1, 0, 2];
+        let model = DefaultLeakyQuantizer::new(-50..=50).quantize(Gaussian::new(3.2, 9.6));
+
+        // Reinterpret the raw bytes as words, like `np.fromfile(..., dtype=np.uint32)` does.
+        let raw_words = file
+            .chunks_exact(4)
+            .map(|chunk| u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect::<Vec<_>>();
+        let mut decoder = DefaultAnsCoder::from_le_words(raw_words.iter().copied()).unwrap();
+        assert_eq!(
+            decoder.clone().into_compressed().unwrap(),
+            [0xb99f_cf62, 0x1555_b5b7]
+        );
+        let decoded = decoder
+            .decode_iid_symbols(message.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, message);
+        assert!(decoder.is_empty());
+
+        let mut encoder = DefaultAnsCoder::new();
+        encoder
+            .encode_iid_symbols_reverse(&message, &model)
+            .unwrap();
+        let le_words = encoder.into_compressed_le_words().collect::<Vec<_>>();
+        assert_eq!(le_words, raw_words);
+        let bytes = le_words
+            .iter()
+            .flat_map(|word| word.to_ne_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(bytes, file);
+
+        assert!(DefaultAnsCoder::from_le_words([1, 0]).is_err());
+    }
+
+    #[test]
+    fn canonical_ans_bytes() {
+        // Test vector from the documentation of `into_canonical_ans_bytes`.
+        let message = [6, 10, -4, 2, 5, 2, 1, 0, 2];
+        let model = DefaultLeakyQuantizer::new(-50..=50).quantize(Gaussian::new(3.2, 9.6));
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&message, &model).unwrap();
+        let bytes = ans.into_canonical_ans_bytes();
+        assert_eq!(bytes, [0xb7, 0xb5, 0x55, 0x15, 0x62, 0xcf, 0x9f, 0xb9]);
+
+        // Check the specified decoding procedure by hand for the first symbol.
+        let initial_state = (0x1555_b5b7u64 << 32) | 0xb99f_cf62;
+        assert_eq!(
+            model
+                .quantile_function((initial_state % (1 << 24)) as u32)
+                .0,
+            message[0]
+        );
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let quantizer = SmallLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(3.5, 20.0));
+        let symbols = (0..100)
+            .map(|_| {
+                model
+                    .quantile_function((rng.next_u32() % (1 << 12)) as u16)
+                    .0
+            })
+            .collect::<Vec<_>>();
+        let mut ans = SmallAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let compressed = ans.clone().into_compressed().unwrap();
+        let bytes = ans.into_canonical_ans_bytes();
+        assert_eq!(bytes.len(), 2 * compressed.len());
+        assert_eq!(
+            u16::from_le_bytes([bytes[0], bytes[1]]),
+            *compressed.last().unwrap()
+        );
+
+        let mut ans = SmallAnsCoder::from_canonical_ans_bytes(&bytes).unwrap();
+        let decoded = ans
+            .decode_iid_symbols(symbols.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(ans.is_empty());
+
+        assert!(DefaultAnsCoder::from_canonical_ans_bytes(&[])
+            .unwrap()
+            .is_empty());
+        assert!(DefaultAnsCoder::from_canonical_ans_bytes(&bytes[..3]).is_err());
+        assert!(DefaultAnsCoder::from_canonical_ans_bytes(&[0, 0, 0, 0, 1, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn text_encodings() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(3.5, 20.0));
+        let symbols = (0..100)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let bytes = ans.clone().into_canonical_ans_bytes();
+
+        let hex = ans.clone().into_hex();
+        assert_eq!(hex.len(), 2 * bytes.len());
+        let mut decoder = DefaultAnsCoder::from_hex(&hex).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(symbols.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(decoder.is_empty());
+        assert_eq!(
+            DefaultAnsCoder::from_hex(&hex.to_uppercase())
+                .unwrap()
+                .into_canonical_ans_bytes(),
+            bytes
+        );
+
+        assert!(DefaultAnsCoder::from_hex("").unwrap().is_empty());
+        assert!(DefaultAnsCoder::from_hex(&hex[1..]).is_err());
+        assert!(DefaultAnsCoder::from_hex(&hex[..6]).is_err());
+        assert!(DefaultAnsCoder::from_hex("0000000001000000").is_err());
+        assert!(DefaultAnsCoder::from_hex("b7b5551562cf9fbg").is_err());
+
+        #[cfg(feature = "base64")]
+        {
+            let base64 = ans.into_base64();
+            let mut decoder = DefaultAnsCoder::from_base64(&base64).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(decoder.is_empty());
+
+            assert!(DefaultAnsCoder::from_base64("").unwrap().is_empty());
+            assert!(DefaultAnsCoder::from_base64("t7VVFWLPn7k").is_err());
+            assert!(DefaultAnsCoder::from_base64("t7VVFW=").is_err());
+            assert!(DefaultAnsCoder::from_base64("AAAAAAEAAAA=").is_err());
+        }
+    }
+
+    #[test]
+    fn delta_of_delta() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let values = (0..1000)
+            .map(|i| 50_000 + 37 * i + (rng.next_u32() % 3) as i32 - 1)
+            .collect::<Vec<_>>();
+
+        let first_model = DefaultLeakyQuantizer::new(0..=100_000).quantize(Gaussian::new(5e4, 1e4));
+        let diff_model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 2.0));
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_delta_of_delta(&values, &diff_model, [&first_model, &first_model])
+            .unwrap();
+        let dod_bits = ans.num_valid_bits();
+
+        // Compare to first-difference coding with the same model for the differences.
+        let mut first_difference = DefaultAnsCoder::new();
+        first_difference
+            .encode_iid_symbols_reverse(values.windows(2).map(|w| w[1] - w[0]), &diff_model)
+            .unwrap();
+        first_difference
+            .encode_symbol(values[0], first_model)
+            .unwrap();
+        assert!(dod_bits < first_difference.num_valid_bits() / 2);
+
+        let decoded = ans
+            .decode_delta_of_delta(values.len(), &diff_model, [&first_model, &first_model])
+            .unwrap();
+        assert_eq!(decoded, values);
+        assert!(ans.is_empty());
+
+        for len in 0..4 {
+            let mut ans = DefaultAnsCoder::new();
+            ans.encode_delta_of_delta(&values[..len], &diff_model, [&first_model, &first_model])
+                .unwrap();
+            let decoded = ans
+                .decode_delta_of_delta(len, &diff_model, [&first_model, &first_model])
+                .unwrap();
+            assert_eq!(decoded, &values[..len]);
+            assert!(ans.is_empty());
+        }
+    }
+
+    #[test]
+    fn decode_while_budget() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(3.5, 20.0));
+        let symbols = (0..1000)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+
+        let unbounded = ans
+            .clone()
+            .decode_iid_symbols(symbols.len(), model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(unbounded, symbols);
+
+        for max_words in [1, 3, 10, 1000] {
+            for max_symbols in [1, 7, usize::MAX] {
+                let mut ans = ans.clone();
+                let mut decoded = Vec::new();
+                while !ans.is_empty() {
+                    let remaining = ans.bulk().len();
+                    let chunk = ans
+                        .decode_while_budget(model, max_words, max_symbols)
+                        .unwrap();
+                    assert!(!chunk.is_empty());
+                    assert!(chunk.len() <= max_symbols);
+                    assert!(remaining - ans.bulk().len() <= max_words);
+                    decoded.extend(chunk);
+                }
+                assert_eq!(decoded, unbounded);
+            }
+        }
+
+        assert!(ans.decode_while_budget(model, 0, 10).unwrap().is_empty());
+        assert!(ans.decode_while_budget(model, 10, 0).unwrap().is_empty());
+        assert!(DefaultAnsCoder::new()
+            .decode_while_budget(model, 10, 10)
+            .unwrap()
+            .is_empty());
+
+        // Decoding data that doesn't consist of symbols encoded with `model` may never empty
+        // the coder, but each call still terminates.
+        let mut ans = DefaultAnsCoder::from_compressed([0x1234_5678].to_vec()).unwrap();
+        for _ in 0..10 {
+            let chunk = ans.decode_while_budget(model, 1000, 50).unwrap();
+            assert!(chunk.len() <= 50);
+        }
+    }
+
+    #[test]
+    fn peek_symbol() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(3.5, 20.0));
+        let symbols = (0..100)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+
+        for &expected in &symbols {
+            let bulk = ans.bulk().clone();
+            let state = ans.state();
+            let peeked = ans.peek_symbol(model);
+            assert_eq!(ans.bulk(), &bulk);
+            assert_eq!(ans.state(), state);
+
+            assert_eq!(peeked, expected);
+            assert_eq!(ans.decode_symbol(model).unwrap(), peeked);
+        }
+        assert!(ans.is_empty());
+
+        // On an empty coder, peeking agrees with the deterministic "garbage" of decoding.
+        for _ in 0..3 {
+            let peeked = ans.peek_symbol(model);
+            assert_eq!(ans.decode_symbol(model).unwrap(), peeked);
+        }
+    }
+
+    #[test]
+    fn iter_compressed_order() {
+        // Golden test vector that pins the (stable) order of `iter_compressed`.
+        let message = [6, 10, -4, 2, 5, 2, 1, 0, 2, -7, 13, 3, 3, 0, -20, 8];
+        let model = DefaultLeakyQuantizer::new(-50..=50).quantize(Gaussian::new(3.2, 9.6));
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(message, model).unwrap();
+        assert_eq!(ans.bulk(), &[0xe977_f565, 0x93c3_d1c4]);
+        assert_eq!(ans.state(), 0x0000_0008_bba2_4c75);
+
+        // Bulk in storage order, then state from least to most significant nonzero word.
+        let words = ans.iter_compressed().collect::<Vec<_>>();
+        assert_eq!(words, [0xe977_f565, 0x93c3_d1c4, 0xbba2_4c75, 0x0000_0008]);
+        assert_eq!(words, ans.clone().into_compressed().unwrap());
+
+        let bytes = ans.iter_compressed_bytes().collect::<Vec<_>>();
+        assert_eq!(
+            bytes,
+            [
+                0x65, 0xf5, 0x77, 0xe9, 0xc4, 0xd1, 0xc3, 0x93, 0x75, 0x4c, 0xa2, 0xbb, 0x08, 0x00,
+                0x00, 0x00
+            ]
+        );
+        // Same bytes as the canonical byte format, but with the words in reverse order.
+        let canonical = ans.into_canonical_ans_bytes();
+        assert_eq!(
+            canonical
+                .chunks(4)
+                .rev()
+                .flatten()
+                .copied()
+                .collect::<Vec<_>>(),
+            bytes
+        );
+    }
+
+    #[test]
+    fn estimate_words_in_width() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let symbols = (0..1000)
+            .map(|_| (rng.next_u32() % 64) as usize)
+            .collect::<Vec<_>>();
+
+        // There's no way to repack compressed data into a different word size, so we
+        // compare to re-encoding with a uniform model of the same support size. We use a
+        // support size that is a power of two so that the models are identical for all
+        // precisions.
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, UniformModel::<u32, 24>::new(64))
+            .unwrap();
+        assert_eq!(ans.estimate_words_in_width::<u32>(), ans.num_words());
+
+        let mut small = SmallAnsCoder::new();
+        small
+            .encode_iid_symbols_reverse(&symbols, UniformModel::<u16, 12>::new(64))
+            .unwrap();
+        let estimate = ans.estimate_words_in_width::<u16>();
+        assert!(estimate.abs_diff(small.num_words()) <= 2);
+
+        let mut tiny = AnsCoder::<u8, u32>::new();
+        tiny.encode_iid_symbols_reverse(&symbols, UniformModel::<u8, 8>::new(64))
+            .unwrap();
+        let estimate = ans.estimate_words_in_width::<u8>();
+        assert!(estimate.abs_diff(tiny.num_words()) <= 2);
+
+        assert_eq!(DefaultAnsCoder::new().estimate_words_in_width::<u16>(), 0);
+    }
+
+    #[test]
+    fn try_decode() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(3.5, 20.0));
+        let symbols = (0..100)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+
+        let mut decoder = ans.clone();
+        for &expected in &symbols {
+            assert_eq!(decoder.try_decode_symbol(model).unwrap(), Some(expected));
+        }
+        assert!(decoder.is_empty());
+        assert_eq!(decoder.try_decode_symbol(model).unwrap(), None);
+        assert_eq!(decoder.try_decode_symbol(model).unwrap(), None);
+
+        let mut decoder = ans.clone();
+        let decoded = decoder
+            .try_decode_iid_symbols(&model, usize::MAX)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(decoder.is_empty());
+        assert_eq!(decoder.try_decode_iid_symbols(model, usize::MAX).count(), 0);
+
+        // Stops after `max_amt` symbols.
+        let mut decoder = ans.clone();
+        let decoded = decoder
+            .try_decode_iid_symbols(&model, 30)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols[..30]);
+        assert!(!decoder.is_empty());
+        assert_eq!(
+            decoder.try_decode_iid_symbols(model, 1000).count(),
+            symbols.len() - 30
+        );
+
+        // Terminates even if the data never empties the coder.
+        let mut decoder = DefaultAnsCoder::from_compressed([0x1234_5678].to_vec()).unwrap();
+        assert!(decoder.try_decode_iid_symbols(model, 500).count() <= 500);
+
+        // Works with backends other than `Vec` too.
+        let compressed = ans.into_compressed().unwrap();
+        let mut decoder = DefaultAnsCoder::from_compressed_slice(&compressed).unwrap();
+        assert_eq!(
+            decoder.try_decode_iid_symbols(model, usize::MAX).count(),
+            symbols.len()
+        );
+    }
+
+    #[test]
+    fn reserve_words() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(3.5, 20.0));
+        let symbols = (0..100_000)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+
+        let mut reserved = DefaultAnsCoder::new();
+        reserved.reserve_words(ans.num_words());
+        let capacity = reserved.bulk().capacity();
+        assert!(capacity >= ans.num_words());
+        reserved
+            .encode_iid_symbols_reverse(&symbols, model)
+            .unwrap();
+        assert_eq!(reserved.bulk().capacity(), capacity);
+
+        assert_eq!(
+            reserved.into_compressed().unwrap(),
+            ans.into_compressed().unwrap()
+        );
+
+        // Backends without a real implementation of `reserve` are unaffected.
+        let mut buf = [0u32; 8];
+        let mut cursor_coder = AnsCoder::<u32, u64, _>::from_raw_parts(
+            Cursor::new_at_write_beginning(&mut buf[..]),
+            0,
+        );
+        cursor_coder.reserve_words(1000);
+        cursor_coder.encode_symbol(5, model).unwrap();
+        assert!(!cursor_coder.is_empty());
+    }
+
+    #[test]
+    fn shrink_to_fit() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2270);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(3.5, 20.0));
+        let symbols = (0..100_000)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+        let reference = ans.clone();
+
+        let num_decoded = symbols.len() - 100;
+        let decoded = ans
+            .decode_iid_symbols(num_decoded, model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols[..num_decoded]);
+        let capacity = ans.bulk().capacity();
+        let num_words = ans.num_words();
+        assert!(capacity > 10 * num_words);
+
+        ans.shrink_to_fit();
+        assert!(ans.bulk().capacity() < capacity);
+        assert_eq!(ans.num_words(), num_words);
+
+        let decoded = ans
+            .decode_iid_symbols(100, model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols[num_decoded..]);
+        assert!(ans.is_empty());
+
+        // Shrinking doesn't change the compressed data, and it can be called on any backend.
+        let mut shrunk = reference.clone();
+        shrunk.shrink_to_fit();
+        assert_eq!(shrunk, reference);
+        let mut buf = [0u32; 8];
+        let mut cursor_coder = AnsCoder::<u32, u64, _>::from_raw_parts(
+            Cursor::new_at_write_beginning(&mut buf[..]),
+            0,
+        );
+        cursor_coder.encode_symbol(5, model).unwrap();
+        cursor_coder.shrink_to_fit();
+        assert_eq!(cursor_coder.decode_symbol(model).unwrap(), 5);
+    }
 
-        Ok(Self { inner: ans })
+    #[test]
+    fn capacity_words() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(3.5, 20.0));
+        let symbols = (0..1000).map(|i| (i % 201) - 100).collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        assert_eq!(ans.capacity_words(), 0);
+        ans.reserve_words(10_000);
+        let reserved = ans.capacity_words();
+        assert!(reserved >= 10_000);
+        assert_eq!(reserved, ans.bulk().capacity());
+
+        ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+        assert_eq!(ans.capacity_words(), reserved);
+        assert!(ans.num_words() < reserved / 2);
+
+        ans.shrink_to_fit();
+        let shrunk = ans.capacity_words();
+        assert!(shrunk < reserved);
+        assert!(shrunk >= ans.bulk().len());
+        assert!(ans.num_words() <= shrunk + 2);
+
+        ans.clear();
+        assert_eq!(ans.capacity_words(), shrunk);
+        ans.shrink_to_fit();
+        assert_eq!(ans.capacity_words(), 0);
     }
-}
 
-impl<'a, Word, State, Backend, const SEALED: bool> Drop
-    for CoderGuard<'a, Word, State, Backend, SEALED>
-where
-    Word: BitArray + Into<State>,
-    State: BitArray + AsPrimitive<Word>,
-    Backend: WriteWords<Word> + ReadWords<Word, Stack>,
-{
-    fn drop(&mut self) {
-        // Revert what we did in `Self::new`.
-        let mut chunks_rev = bit_array_to_chunks_truncated(self.inner.state);
-        if SEALED {
-            chunks_rev.next();
-        }
-        for _ in chunks_rev {
-            core::mem::drop(self.inner.bulk.read());
+    #[test]
+    fn logits() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let mut random_logit = || (rng.next_u32() as f64 / u32::MAX as f64 * 40.0 - 20.0) as f32;
+        let rows = (0..200)
+            .map(|i| (0..2 + i % 30).map(|_| random_logit()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let logits = rows.iter().map(|row| &row[..]).collect::<Vec<_>>();
+        let symbols = rows
+            .iter()
+            .map(|row| rng.next_u32() as usize % row.len())
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_from_logits::<u32, 24>(&symbols, &logits)
+            .unwrap();
+
+        // Equivalent to encoding with explicitly constructed categorical models.
+        let mut expected = DefaultAnsCoder::new();
+        for (&symbol, row) in symbols.iter().zip(&rows).rev() {
+            let max = row.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+            let probabilities = row
+                .iter()
+                .map(|&logit| (logit as f64 - max as f64).exp())
+                .collect::<Vec<_>>();
+            let model =
+                ContiguousCategoricalEntropyModel::<u32, _, 24>::from_floating_point_probabilities_fast(
+                    &probabilities,
+                    None,
+                )
+                .unwrap();
+            expected.encode_symbol(symbol, model).unwrap();
         }
-    }
-}
+        assert_eq!(
+            ans.clone().into_compressed().unwrap(),
+            expected.into_compressed().unwrap()
+        );
 
-impl<'a, Word, State, Backend, const SEALED: bool> Deref
-    for CoderGuard<'a, Word, State, Backend, SEALED>
-where
-    Word: BitArray + Into<State>,
-    State: BitArray + AsPrimitive<Word>,
-    Backend: WriteWords<Word> + ReadWords<Word, Stack>,
-{
-    type Target = Backend;
+        let decoded = ans.decode_from_logits::<u32, 24>(&logits).unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(ans.is_empty());
 
-    fn deref(&self) -> &Self::Target {
-        &self.inner.bulk
+        // Large logits don't overflow thanks to the numerically stable softmax.
+        let row = [1000.0f32, 999.0, -1000.0];
+        let mut ans = SmallAnsCoder::new();
+        ans.encode_iid_from_logits::<u16, 12>(&[2, 0, 1], &[&row, &row, &row])
+            .unwrap();
+        assert_eq!(
+            ans.decode_from_logits::<u16, 12>(&[&row, &row, &row])
+                .unwrap(),
+            [2, 0, 1]
+        );
+        assert!(ans.is_empty());
+
+        // Symbols that are out of bounds for their row can't be encoded.
+        let mut ans = DefaultAnsCoder::new();
+        assert!(ans
+            .encode_iid_from_logits::<u32, 24>(&[3], &[&[0.0, 1.0, 2.0]])
+            .is_err());
     }
-}
 
-impl<Word, State, Backend, const SEALED: bool> Debug
-    for CoderGuard<'_, Word, State, Backend, SEALED>
-where
-    Word: BitArray + Into<State>,
-    State: BitArray + AsPrimitive<Word>,
-    Backend: WriteWords<Word> + ReadWords<Word, Stack> + Debug,
-{
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        Debug::fmt(&**self, f)
+    #[test]
+    #[should_panic(expected = "fewer than `2^PRECISION - 1` entries")]
+    fn logits_row_too_long() {
+        // At `PRECISION == 3`, rows must have fewer than `2^3 - 1 == 7` entries.
+        let row = [0.0f32; 7];
+        let mut ans = DefaultAnsCoder::new();
+        let _ = ans.encode_iid_from_logits::<u32, 3>(&[0], &[&row]);
     }
-}
+    #[test]
+    fn footer() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let quantizer = SmallLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(3.5, 20.0));
+        let symbols = (0..100)
+            .map(|_| {
+                model
+                    .quantile_function((rng.next_u32() % (1 << 12)) as u16)
+                    .0
+            })
+            .collect::<Vec<_>>();
+        let mut ans = SmallAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+
+        let raw = ans.clone().into_canonical_ans_bytes();
+        let with_footer = ans.into_canonical_ans_bytes_with_footer();
+        assert_eq!(&with_footer[..raw.len()], &raw[..]);
+        assert_eq!(
+            &with_footer[raw.len()..],
+            &[1, 16, 32, 0, b'C', b'N', b'S', b'T']
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::super::model::{
-        ContiguousCategoricalEntropyModel, DefaultLeakyQuantizer, IterableEntropyModel,
-        LeakyQuantizer,
-    };
-    use super::*;
-    extern crate std;
-    use std::dbg;
+        assert_eq!(super::detect_format(&raw), None);
+        assert_eq!(
+            super::detect_format(&with_footer),
+            Some(FooterInfo {
+                word_bits: 16,
+                state_bits: 32
+            })
+        );
 
-    use probability::distribution::{Gaussian, Inverse};
-    use rand_xoshiro::{
-        rand_core::{RngCore, SeedableRng},
-        Xoshiro256StarStar,
-    };
+        for bytes in [&raw, &with_footer] {
+            let mut decoder =
+                SmallAnsCoder::from_canonical_ans_bytes_with_optional_footer(bytes).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(decoder.is_empty());
+        }
 
-    #[test]
-    fn compress_none() {
-        let coder1 = DefaultAnsCoder::new();
-        assert!(coder1.is_empty());
-        let compressed = coder1.into_compressed().unwrap();
-        assert!(compressed.is_empty());
+        // A footer for a different coder configuration is rejected.
+        assert!(
+            DefaultAnsCoder::from_canonical_ans_bytes_with_optional_footer(&with_footer).is_err()
+        );
 
-        let coder2 = DefaultAnsCoder::from_compressed(compressed).unwrap();
-        assert!(coder2.is_empty());
+        // Empty data with a footer.
+        let empty = DefaultAnsCoder::new().into_canonical_ans_bytes_with_footer();
+        assert_eq!(empty.len(), 8);
+        assert!(
+            DefaultAnsCoder::from_canonical_ans_bytes_with_optional_footer(&empty)
+                .unwrap()
+                .is_empty()
+        );
+
+        // Corrupted footers are not detected.
+        let mut corrupted = with_footer.clone();
+        *corrupted.last_mut().unwrap() = b'X';
+        assert_eq!(super::detect_format(&corrupted), None);
+        let mut corrupted = with_footer.clone();
+        corrupted.pop();
+        assert_eq!(super::detect_format(&corrupted), None);
+        assert_eq!(super::detect_format(&with_footer[raw.len() + 1..]), None);
     }
 
     #[test]
-    fn compress_one() {
-        generic_compress_few(core::iter::once(5), 1)
+    fn compressed_bytes() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2256);
+        let symbols = (0..100)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let compressed = ans.clone().into_compressed().unwrap();
+
+        let expected_le = compressed
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+        let expected_be = compressed
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Vec<_>>();
+        let little = ans.clone().into_compressed_bytes(Endianness::LittleEndian);
+        let big = ans.clone().into_compressed_bytes(Endianness::BigEndian);
+        assert_eq!(little, expected_le);
+        assert_eq!(big, expected_be);
+
+        for (bytes, endianness) in [
+            (&little, Endianness::LittleEndian),
+            (&big, Endianness::BigEndian),
+        ] {
+            let mut decoder = DefaultAnsCoder::from_compressed_bytes(bytes, endianness).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(decoder.is_empty());
+
+            assert!(
+                DefaultAnsCoder::from_compressed_bytes(&bytes[..bytes.len() - 1], endianness)
+                    .is_err()
+            );
+        }
+
+        // Other word sizes.
+        let model = UniformModel::<u16, 16>::new(1000);
+        let symbols = (0..100).map(|i| (i * 7) % 1000).collect::<Vec<_>>();
+        let mut ans = AnsCoder::<u16, u32>::new();
+        ans.encode_iid_symbols_reverse(&symbols, model).unwrap();
+        let compressed = ans.clone().into_compressed().unwrap();
+        let bytes = ans.into_compressed_bytes(Endianness::LittleEndian);
+        assert_eq!(
+            bytes,
+            compressed
+                .iter()
+                .flat_map(|word| word.to_le_bytes())
+                .collect::<Vec<_>>()
+        );
+        let mut decoder =
+            AnsCoder::<u16, u32>::from_compressed_bytes(&bytes, Endianness::LittleEndian).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(symbols.len(), model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+
+        assert_eq!(
+            DefaultAnsCoder::new().into_compressed_bytes(Endianness::BigEndian),
+            Vec::<u8>::new()
+        );
+        assert!(
+            DefaultAnsCoder::from_compressed_bytes(&[], Endianness::NATIVE)
+                .unwrap()
+                .is_empty()
+        );
     }
 
     #[test]
-    fn compress_two() {
-        generic_compress_few([2, 8].iter().cloned(), 1)
+    fn validate() {
+        let ans = DefaultAnsCoder::new();
+        assert_eq!(ans.validate(), Ok(()));
+
+        let mut ans = DefaultAnsCoder::from_compressed(alloc::vec![0x1234_5678, 9]).unwrap();
+        assert_eq!(ans.validate(), Ok(()));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2303);
+        let symbols = (0..100)
+            .map(|_| rng.next_u32() as usize % 1000)
+            .collect::<Vec<_>>();
+        ans.encode_iid_symbols_reverse(&symbols, DefaultUniformModel::new(1000))
+            .unwrap();
+        assert_eq!(ans.validate(), Ok(()));
+
+        // An empty bulk is valid with any state.
+        for state in [0, 1, u64::MAX] {
+            assert!(DefaultAnsCoder::from_raw_parts(Vec::new(), state)
+                .validate()
+                .is_ok());
+        }
+
+        // A nonempty bulk requires a state of at least `2^32`.
+        let (bulk, _) = ans.into_raw_parts();
+        assert!(DefaultAnsCoder::from_raw_parts(bulk.clone(), 1 << 32)
+            .validate()
+            .is_ok());
+        for state in [0, 1, (1 << 32) - 1] {
+            assert!(DefaultAnsCoder::from_raw_parts(bulk.clone(), state)
+                .validate()
+                .is_err());
+        }
     }
 
     #[test]
-    fn compress_ten() {
-        generic_compress_few(0..10, 2)
+    fn recode_symbol() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let prior = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let posterior = quantizer.quantize(Gaussian::new(3.2, 2.5));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2305);
+        let symbols = (0..100)
+            .map(|_| prior.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, prior).unwrap();
+        let original = ans.clone();
+
+        // Recoding with identical models changes nothing.
+        for _ in 0..10 {
+            let symbol = ans.recode_symbol(prior, prior).unwrap();
+            assert_eq!(symbol, symbols[0]);
+            assert_eq!(ans.num_words(), original.num_words());
+            assert_eq!(ans, original);
+        }
+
+        // Recoding with different models is undone by recoding with swapped models.
+        let mut recoded = Vec::new();
+        for _ in 0..10 {
+            recoded.push(ans.recode_symbol(posterior, prior).unwrap());
+        }
+        for &symbol in recoded.iter().rev() {
+            assert_eq!(ans.recode_symbol(prior, posterior).unwrap(), symbol);
+        }
+        assert_eq!(ans, original);
+
+        // An impossible symbol leaves the coder unchanged.
+        let narrow = DefaultUniformModel::new(2);
+        let wide = DefaultUniformModel::new(1000);
+        let mut num_errors = 0;
+        for _ in 0..100 {
+            let before = ans.clone();
+            match ans.recode_symbol(wide, narrow) {
+                Ok(symbol) => assert!(symbol < 2),
+                Err(err) => {
+                    assert_eq!(
+                        err,
+                        CoderError::Frontend(DefaultEncoderFrontendError::ImpossibleSymbol)
+                    );
+                    assert_eq!(ans, before);
+                    assert_eq!(ans.state(), before.state());
+                    num_errors += 1;
+                }
+            }
+        }
+        assert!(num_errors > 0);
     }
 
     #[test]
-    fn compress_twenty() {
-        generic_compress_few(-10..10, 4)
+    fn split_off() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2311);
+        let chunks = [0, 1, 17, 100, 3, 250]
+            .iter()
+            .map(|&len| {
+                (0..len)
+                    .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // Encode the chunks one after the other, taking a checkpoint before each chunk.
+        let mut ans = DefaultAnsCoder::new();
+        let mut checkpoints = Vec::new();
+        for chunk in &chunks {
+            checkpoints.push(ans.pos_and_state());
+            ans.encode_iid_symbols_reverse(chunk, model).unwrap();
+        }
+        let original = ans.clone();
+
+        // Invalid checkpoints are rejected and leave the coder unchanged.
+        assert!(ans.split_off((ans.num_words() + 1, 0)).is_err());
+        assert!(ans.split_off((1, 1)).is_err());
+        assert_eq!(ans, original);
+
+        // Split off the chunks from the top down.
+        let mut tops = Vec::new();
+        for &checkpoint in checkpoints.iter().rev() {
+            tops.push((ans.split_off(checkpoint).unwrap(), checkpoint.1));
+            assert_eq!(ans.pos_and_state(), checkpoint);
+        }
+        assert!(ans.is_empty());
+
+        // Each piece decodes to its chunk, independently of all other pieces.
+        for (chunk, (mut top, checkpoint_state)) in chunks.iter().zip(tops.into_iter().rev()) {
+            let decoded = top
+                .decode_iid_symbols(chunk.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded, chunk);
+            assert_eq!(top.state(), checkpoint_state);
+            assert!(top.bulk().is_empty());
+        }
+
+        // Splitting at a checkpoint in the middle leaves a bottom coder that can still be
+        // used for encoding.
+        let mut ans = original.clone();
+        ans.split_off(checkpoints[3]).unwrap();
+        for chunk in &chunks[3..] {
+            ans.encode_iid_symbols_reverse(chunk, model).unwrap();
+        }
+        assert_eq!(ans, original);
+
+        // Splitting at the current position yields a top coder without any symbols.
+        let mut ans = original;
+        let top = ans.split_off(ans.pos_and_state()).unwrap();
+        assert!(top.bulk().is_empty());
+        assert_eq!(top.state(), ans.state());
     }
 
-    fn generic_compress_few<I>(symbols: I, expected_size: usize)
-    where
-        I: IntoIterator<Item = i32>,
-        I::IntoIter: Clone + DoubleEndedIterator,
-    {
-        let symbols = symbols.into_iter();
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_round_trip() {
+        use super::super::model::DefaultContiguousCategoricalEntropyModel;
+        use arbitrary::{Arbitrary, Unstructured};
+
+        fn round_trip(bytes: &[u8]) -> arbitrary::Result<()> {
+            let mut u = Unstructured::new(bytes);
+            let original = DefaultAnsCoder::arbitrary(&mut u)?;
+            assert_eq!(original.validate(), Ok(()));
+
+            let num_symbols = u.int_in_range(0..=20)?;
+            let mut models = Vec::with_capacity(num_symbols);
+            let mut symbols = Vec::with_capacity(num_symbols);
+            for _ in 0..num_symbols {
+                let model = DefaultContiguousCategoricalEntropyModel::arbitrary(&mut u)?;
+                let total = model
+                    .symbol_table()
+                    .map(|(_, _, probability)| probability.get() as u64)
+                    .sum::<u64>();
+                assert_eq!(total, 1 << 24);
+                symbols.push(u.int_in_range(0..=model.support_size() - 1)?);
+                models.push(model);
+            }
 
-        let mut encoder = DefaultAnsCoder::new();
-        let quantizer = DefaultLeakyQuantizer::new(-127..=127);
-        let model = quantizer.quantize(Gaussian::new(3.2, 5.1));
+            let mut ans = original.clone();
+            for (&symbol, model) in symbols.iter().zip(&models).rev() {
+                ans.encode_symbol(symbol, model).unwrap();
+                assert_eq!(ans.validate(), Ok(()));
+            }
+            for (&symbol, model) in symbols.iter().zip(&models) {
+                assert_eq!(ans.decode_symbol(model).unwrap(), symbol);
+            }
+            assert_eq!(ans, original);
+            Ok(())
+        }
 
-        // We don't reuse the same encoder for decoding because we want to test
-        // if exporting and re-importing of compressed data works.
-        encoder.encode_iid_symbols(symbols.clone(), model).unwrap();
-        let compressed = encoder.into_compressed().unwrap();
-        assert_eq!(compressed.len(), expected_size);
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2304);
+        for len in [0, 1, 7, 8, 100, 1000, 10_000] {
+            for _ in 0..10 {
+                let bytes = (0..len).map(|_| rng.next_u32() as u8).collect::<Vec<_>>();
+                round_trip(&bytes).unwrap();
+            }
+        }
+    }
 
-        let mut decoder = DefaultAnsCoder::from_compressed(compressed).unwrap();
-        for symbol in symbols.rev() {
-            assert_eq!(decoder.decode_symbol(model).unwrap(), symbol);
+    #[test]
+    fn validate_jump_table() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2257);
+
+        let mut ans = DefaultAnsCoder::new();
+        let mut table = alloc::vec![ans.pos()];
+        for _ in 0..10 {
+            let symbols = (0..50)
+                .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                .collect::<Vec<_>>();
+            ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+            table.push(ans.pos());
         }
-        assert!(decoder.is_empty());
+        table.reverse();
+        assert_eq!(ans.validate_jump_table(&table), Ok(()));
+        assert_eq!(ans.validate_jump_table(&[]), Ok(()));
+
+        // A state that violates the invariant.
+        let mut corrupted = table.clone();
+        corrupted[3].1 >>= 40;
+        assert_eq!(ans.validate_jump_table(&corrupted), Err(3));
+
+        // A position that is out of bounds.
+        let mut corrupted = table.clone();
+        corrupted[0].0 += 1;
+        assert_eq!(ans.validate_jump_table(&corrupted), Err(0));
+
+        // Positions that are not monotonic.
+        let mut corrupted = table.clone();
+        corrupted.swap(5, 6);
+        assert_eq!(ans.validate_jump_table(&corrupted), Err(6));
+
+        // A jump table for different (shorter) data.
+        let mut other = DefaultAnsCoder::new();
+        other
+            .encode_iid_symbols_reverse([1, 2, 3, 4, 5], model)
+            .unwrap();
+        assert_eq!(other.validate_jump_table(&table), Err(0));
+
+        // The valid table still works for random-access decoding.
+        let decoders = ans.seekable_decoders_at(&table).unwrap();
+        assert_eq!(decoders.len(), table.len());
     }
 
     #[test]
-    fn compress_many_u32_u64_32() {
-        generic_compress_many::<u32, u64, u32, 32>();
+    fn partial_eq() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2258);
+        let symbols = (0..100)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let compressed = ans.clone().into_compressed().unwrap();
+
+        let from_compressed = DefaultAnsCoder::from_compressed(compressed.clone()).unwrap();
+        assert_eq!(from_compressed, ans);
+
+        // `from_binary` implicitly appends a `1` word.
+        let from_binary = DefaultAnsCoder::from_binary(compressed.clone()).unwrap();
+        let mut with_one = compressed.clone();
+        with_one.push(1);
+        assert_eq!(
+            from_binary,
+            DefaultAnsCoder::from_compressed(with_one.clone()).unwrap()
+        );
+        assert_ne!(from_binary, ans);
+
+        // Different backends.
+        let small_vec = AnsCoder::<u32, u64, smallvec::SmallVec<[u32; 8]>>::from_compressed(
+            compressed.iter().copied().collect(),
+        )
+        .unwrap();
+        assert_eq!(ans, small_vec);
+        assert_eq!(small_vec, ans);
+        let small_vec = AnsCoder::<u32, u64, smallvec::SmallVec<[u32; 8]>>::from_binary(
+            compressed.iter().copied().collect(),
+        )
+        .unwrap();
+        assert_eq!(small_vec, from_binary);
+
+        // Decoding a symbol changes the content.
+        let mut decoder = ans.clone();
+        assert_eq!(decoder, ans);
+        decoder.decode_symbol(&model).unwrap();
+        assert_ne!(decoder, ans);
+
+        assert_eq!(DefaultAnsCoder::new(), DefaultAnsCoder::new());
+        assert_ne!(DefaultAnsCoder::new(), ans);
     }
 
     #[test]
-    fn compress_many_u32_u64_24() {
-        generic_compress_many::<u32, u64, u32, 24>();
+    fn seekable_decoder_at() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2259);
+
+        let chunks = (0..5)
+            .map(|_| {
+                (0..40)
+                    .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut encoder = DefaultAnsCoder::new();
+        let mut checkpoints = Vec::new();
+        for chunk in chunks.iter().rev() {
+            encoder.encode_iid_symbols_reverse(chunk, &model).unwrap();
+            checkpoints.push(encoder.pos());
+        }
+        checkpoints.reverse();
+
+        for (chunk, &checkpoint) in chunks.iter().zip(&checkpoints) {
+            let mut decoder = encoder.seekable_decoder_at(checkpoint).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(chunk.len(), &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded, chunk);
+        }
+
+        let (pos, state) = checkpoints[0];
+        assert!(encoder.seekable_decoder_at((pos + 1, state)).is_err());
     }
 
     #[test]
-    fn compress_many_u32_u64_16() {
-        generic_compress_many::<u32, u64, u16, 16>();
+    fn probe_models() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let models = [
+            quantizer.quantize(Gaussian::new(0.0, 10.0)),
+            quantizer.quantize(Gaussian::new(0.5, 10.0)),
+            quantizer.quantize(Gaussian::new(20.0, 3.0)),
+        ];
+        let model_refs = models.iter().collect::<Vec<_>>();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2260);
+        let symbols = (0..100)
+            .map(|_| models[0].quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &models[0])
+            .unwrap();
+
+        let mut num_diverged = 0;
+        for &symbol in &symbols {
+            let probed = ans.probe_models(&model_refs);
+            assert_eq!(probed.len(), models.len());
+            assert_eq!(probed[0], symbol);
+            for (model, &probed) in models.iter().zip(&probed) {
+                assert_eq!(ans.peek_symbol(model), probed);
+            }
+            if probed[1] != symbol {
+                num_diverged += 1;
+            }
+            assert_eq!(ans.decode_symbol(&models[0]).unwrap(), symbol);
+        }
+        assert!(ans.is_empty());
+
+        // Similar models mostly agree, but not always.
+        assert!(num_diverged > 0 && num_diverged < symbols.len() / 2);
+        assert!(ans.probe_models::<_, 24>(&[&models[0]; 0]).is_empty());
     }
 
     #[test]
-    fn compress_many_u32_u64_8() {
-        generic_compress_many::<u32, u64, u8, 8>();
+    fn compressed_message() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2261);
+        let symbols = (0..100)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let message = encode_to_message::<u32, u64, _, 24>(&symbols, &model).unwrap();
+        assert_eq!(message.word_bits, 32);
+        assert_eq!(message.state_bits, 64);
+        assert_eq!(message.num_symbols, Some(100));
+        assert_eq!(
+            decode_from_message::<u32, u64, _, 24>(&message, &model).unwrap(),
+            symbols
+        );
+
+        // Mismatched metadata or model.
+        let small_model = SmallLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+        assert!(decode_from_message::<u32, u64, _, 12>(&message, &small_model).is_err());
+        let mut wrong = message.clone();
+        wrong.state_bits = 32;
+        assert!(decode_from_message::<u32, u64, _, 24>(&wrong, &model).is_err());
+        let mut wrong = message.clone();
+        wrong.num_symbols = None;
+        assert!(decode_from_message::<u32, u64, _, 24>(&wrong, &model).is_err());
+        let mut wrong = message.clone();
+        wrong.num_symbols = Some(99);
+        assert!(decode_from_message::<u32, u64, _, 24>(&wrong, &model).is_err());
+        let mut wrong = message.clone();
+        wrong.num_symbols = Some(101);
+        assert!(decode_from_message::<u32, u64, _, 24>(&wrong, &model).is_err());
+        let mut wrong = message.clone();
+        wrong.num_symbols = Some(u64::MAX);
+        assert!(decode_from_message::<u32, u64, _, 24>(&wrong, &model).is_err());
+        let mut wrong = message.clone();
+        wrong.compressed.drain(..2);
+        assert!(decode_from_message::<u32, u64, _, 24>(&wrong, &model).is_err());
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&message).unwrap();
+            let deserialized: CompressedMessage<u32> = serde_json::from_str(&json).unwrap();
+            assert_eq!(deserialized, message);
+            assert_eq!(
+                decode_from_message::<u32, u64, _, 24>(&deserialized, &model).unwrap(),
+                symbols
+            );
+        }
     }
 
     #[test]
-    fn compress_many_u16_u64_16() {
-        generic_compress_many::<u16, u64, u16, 16>();
+    fn blocks_best_of() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let candidates = [
+            quantizer.quantize(Gaussian::new(-50.0, 5.0)),
+            quantizer.quantize(Gaussian::new(0.0, 30.0)),
+            quantizer.quantize(Gaussian::new(50.0, 5.0)),
+        ];
+
+        // Draw each block from a different candidate.
+        let block_size = 20;
+        let sources = [2, 0, 0, 1, 2, 1, 0];
+        let mut symbols = sources
+            .iter()
+            .flat_map(|&source| {
+                let model = candidates[source];
+                (0..block_size)
+                    .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        symbols.truncate(symbols.len() - 5); // Make the last block shorter.
+
+        let mut ans = DefaultAnsCoder::new();
+        let choices = ans
+            .encode_blocks_best_of(&symbols, block_size, &candidates)
+            .unwrap();
+        assert_eq!(choices, sources);
+
+        let mut single = DefaultAnsCoder::new();
+        single
+            .encode_iid_symbols_reverse(&symbols, candidates[1])
+            .unwrap();
+        assert!(ans.num_bits() < single.num_bits());
+
+        let decoded = ans
+            .decode_blocks_best_of(symbols.len(), block_size, &candidates)
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(ans.is_empty());
+
+        // A single candidate doesn't need any headers.
+        let mut ans = DefaultAnsCoder::new();
+        let choices = ans
+            .encode_blocks_best_of(&symbols, block_size, &candidates[1..2])
+            .unwrap();
+        assert_eq!(choices, [0; 7]);
+        assert_eq!(ans.into_compressed(), single.into_compressed());
+
+        // Blocks that no candidate can encode.
+        let categorical =
+            ContiguousCategoricalEntropyModel::<u32, _, 24>::from_floating_point_probabilities_fast(
+                &[0.5, 0.5],
+                None,
+            )
+            .unwrap();
+        let mut ans = DefaultAnsCoder::new();
+        assert!(ans
+            .encode_blocks_best_of(&[0, 1, 2, 0], 2, &[categorical.clone(), categorical])
+            .is_err());
+        assert!(ans.is_empty());
     }
 
     #[test]
-    fn compress_many_u16_u64_12() {
-        generic_compress_many::<u16, u64, u16, 12>();
+    fn collect_compressed() {
+        let model = DefaultLeakyQuantizer::new(-50..=50).quantize(Gaussian::new(3.2, 9.6));
+        let symbols = [6, 10, -4, 2, 5, 2, 1, 0, 2];
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let compressed = ans.into_compressed().unwrap();
+
+        let mut ans: DefaultAnsCoder = compressed.iter().copied().collect();
+        let decoded = ans
+            .decode_iid_symbols(symbols.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(ans.is_empty());
+
+        let ans: DefaultAnsCoder = core::iter::empty().collect();
+        assert!(ans.is_empty());
+
+        let ans = DefaultAnsCoder::try_collect_compressed(compressed.iter().copied()).unwrap();
+        assert_eq!(ans.into_compressed().unwrap(), compressed);
+        assert_eq!(
+            DefaultAnsCoder::try_collect_compressed([1, 2, 0].iter().copied()).unwrap_err(),
+            [1, 2, 0]
+        );
     }
 
     #[test]
-    fn compress_many_u16_u64_8() {
-        generic_compress_many::<u16, u64, u8, 8>();
+    #[should_panic]
+    fn collect_compressed_with_trailing_zero() {
+        let _: DefaultAnsCoder = [1, 2, 0].iter().copied().collect();
     }
 
     #[test]
-    fn compress_many_u8_u64_8() {
-        generic_compress_many::<u8, u64, u8, 8>();
+    fn fields() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let models = [
+            quantizer.quantize(Gaussian::new(-20.0, 3.0)),
+            quantizer.quantize(Gaussian::new(0.0, 50.0)),
+            quantizer.quantize(Gaussian::new(70.0, 10.0)),
+        ];
+        let fields = models
+            .iter()
+            .zip([5, 100, 17])
+            .map(|(model, len)| {
+                (0..len)
+                    .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_fields(
+            &fields
+                .iter()
+                .zip(&models)
+                .map(|(field, model)| (&field[..], model))
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+
+        // Equivalent to encoding all fields in reverse with `encode_symbols_reverse`.
+        let mut expected = DefaultAnsCoder::new();
+        expected
+            .encode_symbols_reverse(
+                fields
+                    .iter()
+                    .zip(&models)
+                    .flat_map(|(field, model)| field.iter().map(move |symbol| (symbol, model))),
+            )
+            .unwrap();
+        assert_eq!(ans.clone().into_compressed(), expected.into_compressed());
+
+        let decoded = ans
+            .decode_fields(
+                &fields
+                    .iter()
+                    .zip(&models)
+                    .map(|(field, model)| (field.len(), model))
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap();
+        assert_eq!(decoded, fields);
+        assert!(ans.is_empty());
     }
 
     #[test]
-    fn compress_many_u16_u32_16() {
-        generic_compress_many::<u16, u32, u16, 16>();
+    fn optional() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let value_model = quantizer.quantize(Gaussian::new(3.5, 20.0));
+        let presence_model =
+            ContiguousCategoricalEntropyModel::<u32, Vec<u32>, 24>::from_floating_point_probabilities_fast(
+                &[0.7, 0.3],
+                None,
+            )
+            .unwrap();
+
+        let values = (0..1000)
+            .map(|_| {
+                if rng.next_u32() % 10 < 3 {
+                    Some(value_model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        assert!(values.iter().any(Option::is_some));
+        assert!(values.iter().any(Option::is_none));
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_optional(&values, &presence_model, value_model)
+            .unwrap();
+
+        // Absent values only cost their presence flag.
+        let mut flags_only = DefaultAnsCoder::new();
+        flags_only
+            .encode_iid_symbols_reverse(
+                values.iter().map(|v| v.is_some() as usize),
+                &presence_model,
+            )
+            .unwrap();
+        assert!(ans.num_valid_bits() > flags_only.num_valid_bits());
+
+        let decoded = ans
+            .decode_optional(values.len(), &presence_model, value_model)
+            .unwrap();
+        assert_eq!(decoded, values);
+        assert!(ans.is_empty());
     }
 
     #[test]
-    fn compress_many_u16_u32_12() {
-        generic_compress_many::<u16, u32, u16, 12>();
-    }
+    fn compressed_with_digest() {
+        use core::hash::{Hash, Hasher};
+        use std::collections::hash_map::DefaultHasher;
 
-    #[test]
-    fn compress_many_u16_u32_8() {
-        generic_compress_many::<u16, u32, u8, 8>();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(3.5, 20.0));
+        for amt in [0, 1, 2, 10, 1000] {
+            let symbols = (0..amt)
+                .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                .collect::<Vec<_>>();
+            let mut ans = DefaultAnsCoder::new();
+            ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+
+            let expected = ans.clone().into_compressed().unwrap();
+            let (compressed, digest) = ans.into_compressed_with_digest(DefaultHasher::new());
+            assert_eq!(compressed, expected);
+
+            let mut hasher = DefaultHasher::new();
+            for word in &compressed {
+                word.hash(&mut hasher);
+            }
+            assert_eq!(digest, hasher.finish());
+        }
     }
 
     #[test]
-    fn compress_many_u8_u32_8() {
-        generic_compress_many::<u8, u32, u8, 8>();
+    fn refresh_entropy() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let probabilities = [0.1, 0.4, 0.2, 0.3];
+        let model =
+            ContiguousCategoricalEntropyModel::<u32, _, 24>::from_floating_point_probabilities_fast(
+                &probabilities,
+                None,
+            )
+            .unwrap();
+
+        // Exhaust the initial randomness.
+        let mut sampler = DefaultAnsCoder::from_binary(Vec::from([rng.next_u32()])).unwrap();
+        for _ in 0..100 {
+            sampler.decode_symbol(&model).unwrap();
+        }
+        assert!(sampler.bulk().is_empty());
+        assert!(sampler.state() < 1 << 32);
+
+        let amt = 100_000;
+        sampler.refresh_entropy(amt, || rng.next_u32());
+        assert!(sampler.state() >= 1 << 32);
+        let mut counts = [0usize; 4];
+        for _ in 0..amt {
+            counts[sampler.decode_symbol(&model).unwrap()] += 1;
+        }
+        assert!(!sampler.is_empty());
+        for (&count, &probability) in counts.iter().zip(&probabilities) {
+            let frequency = count as f64 / amt as f64;
+            assert!((frequency - probability).abs() < 0.01);
+        }
+
+        // Words get moved into the state until the invariant is satisfied.
+        let mut sampler = DefaultAnsCoder::new();
+        sampler.refresh_entropy(1, || 0x1234_5678);
+        assert_eq!(sampler.state(), 0x1234_5678);
+        sampler.refresh_entropy(1, || 0x9abc_def0);
+        assert_eq!(sampler.state(), 0x1234_5678_9abc_def0);
+        assert!(sampler.bulk().is_empty());
     }
 
     #[test]
-    fn compress_many_u8_u16_8() {
-        generic_compress_many::<u8, u16, u8, 8>();
-    }
+    fn seekable_decoders_at() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let segments = (0..8)
+            .map(|_| {
+                (0..500)
+                    .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
 
-    fn generic_compress_many<Word, State, Probability, const PRECISION: usize>()
-    where
-        State: BitArray + AsPrimitive<Word>,
-        Word: BitArray + Into<State> + AsPrimitive<Probability>,
-        Probability: BitArray + Into<Word> + AsPrimitive<usize> + Into<f64>,
-        u32: AsPrimitive<Probability>,
-        usize: AsPrimitive<Probability>,
-        f64: AsPrimitive<Probability>,
-        i32: AsPrimitive<Probability>,
-    {
-        #[cfg(not(miri))]
-        const AMT: usize = 1000;
+        let mut encoder = DefaultAnsCoder::new();
+        let mut checkpoints = Vec::new();
+        for segment in segments.iter().rev() {
+            encoder.encode_iid_symbols_reverse(segment, &model).unwrap();
+            checkpoints.push(encoder.pos());
+        }
+        checkpoints.reverse();
 
-        #[cfg(miri)]
-        const AMT: usize = 100;
+        let serial = encoder
+            .as_decoder()
+            .decode_iid_symbols(8 * 500, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
 
-        let mut symbols_gaussian = Vec::with_capacity(AMT);
-        let mut means = Vec::with_capacity(AMT);
-        let mut stds = Vec::with_capacity(AMT);
+        let decoders = encoder.seekable_decoders_at(&checkpoints).unwrap();
+        let parallel = std::thread::scope(|scope| {
+            let handles = decoders
+                .into_iter()
+                .map(|mut decoder| {
+                    let model = &model;
+                    scope.spawn(move || {
+                        decoder
+                            .decode_iid_symbols(500, model)
+                            .collect::<Result<Vec<_>, _>>()
+                            .unwrap()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(parallel, segments);
+        assert_eq!(parallel.concat(), serial);
+
+        let (pos, state) = checkpoints[0];
+        assert!(encoder
+            .seekable_decoders_at(&[(pos, state), (encoder.bulk().len() + 1, state)])
+            .is_err());
+    }
 
-        let mut rng = Xoshiro256StarStar::seed_from_u64(
-            (Word::BITS as u64).rotate_left(3 * 16)
-                ^ (State::BITS as u64).rotate_left(2 * 16)
-                ^ (Probability::BITS as u64).rotate_left(16)
-                ^ PRECISION as u64,
+    #[test]
+    fn num_bits_u64() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(-50..50, model).unwrap();
+        assert_eq!(ans.num_bits_u64(), ans.num_bits() as u64);
+        assert_eq!(ans.num_valid_bits_u64(), ans.num_valid_bits() as u64);
+
+        // Simulate more compressed data than a 32-bit `usize` could count bits of.
+        let num_words = 1usize << 28;
+        let state = 0x0000_0123_4567_89abu64;
+        assert_eq!(super::num_bits_u64::<u32>(num_words), 1 << 33);
+        assert!(super::num_bits_u64::<u32>(num_words) > u32::MAX as u64);
+        assert_eq!(
+            super::num_valid_bits_u64::<u32, u64>(num_words, state),
+            (1 << 33) + 40
         );
+        assert_eq!(super::num_valid_bits_u64::<u32, u64>(num_words, 0), 1 << 33);
+    }
 
-        for _ in 0..AMT {
-            let mean = (200.0 / u32::MAX as f64) * rng.next_u32() as f64 - 100.0;
-            let std_dev = (10.0 / u32::MAX as f64) * rng.next_u32() as f64 + 0.001;
-            let quantile = (rng.next_u32() as f64 + 0.5) / (1u64 << 32) as f64;
-            let dist = Gaussian::new(mean, std_dev);
-            let symbol = (dist.inverse(quantile).round() as i32).clamp(-127, 127);
-
-            symbols_gaussian.push(symbol);
-            means.push(mean);
-            stds.push(std_dev);
-        }
+    #[test]
+    fn type_parameters() {
+        let ans = DefaultAnsCoder::new();
+        assert_eq!((ans.compressed_word_bits(), ans.state_bits()), (32, 64));
+        let ans = SmallAnsCoder::new();
+        assert_eq!((ans.compressed_word_bits(), ans.state_bits()), (16, 32));
+        let ans = AnsCoder::<u8, u64>::new();
+        assert_eq!((ans.compressed_word_bits(), ans.state_bits()), (8, 64));
+
+        assert_eq!(UniformModel::<u32, 24>::new(10).precision(), 24);
+        assert_eq!(UniformModel::<u16, 12>::new(10).precision(), 12);
+    }
 
-        let hist = [
-            1u32, 186545, 237403, 295700, 361445, 433686, 509456, 586943, 663946, 737772, 1657269,
-            896675, 922197, 930672, 916665, 0, 0, 0, 0, 0, 723031, 650522, 572300, 494702, 418703,
-            347600, 1, 283500, 226158, 178194, 136301, 103158, 76823, 55540, 39258, 27988, 54269,
-        ];
-        let categorical_probabilities = hist.iter().map(|&x| x as f64).collect::<Vec<_>>();
-        let categorical =
-            ContiguousCategoricalEntropyModel::<Probability, _, PRECISION>::from_floating_point_probabilities_fast::<f64>(
-                &categorical_probabilities,None
-            )
+    #[test]
+    fn interval_fn() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let models = (0..100)
+            .map(|i| quantizer.quantize(Gaussian::new(i as f64 - 50.0, 1.0 + i as f64 / 5.0)))
+            .collect::<Vec<_>>();
+        let symbols = models
+            .iter()
+            .map(|model| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut expected = DefaultAnsCoder::new();
+        expected
+            .encode_symbols_reverse(symbols.iter().zip(&models))
             .unwrap();
-        let mut symbols_categorical = Vec::with_capacity(AMT);
-        let max_probability = Probability::max_value() >> (Probability::BITS - PRECISION);
-        for _ in 0..AMT {
-            let quantile = rng.next_u32().as_() & max_probability;
-            let symbol = categorical.quantile_function(quantile).0;
-            symbols_categorical.push(symbol);
-        }
 
-        let mut ans = AnsCoder::<Word, State>::new();
+        let mut calls = Vec::new();
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_with_interval_fn::<_, _, 24>(symbols.len(), |index| {
+            calls.push(index);
+            models[index]
+                .left_cumulative_and_probability(symbols[index])
+                .unwrap()
+        })
+        .unwrap();
+        assert!(calls.iter().copied().eq((0..symbols.len()).rev()));
+        assert_eq!(ans.clone().into_compressed(), expected.into_compressed());
 
-        ans.encode_iid_symbols_reverse(&symbols_categorical, &categorical)
+        let decoded = ans
+            .decode_with_quantile_fn::<_, _, _, 24>(symbols.len(), |index, quantile| {
+                models[index].quantile_function(quantile)
+            })
             .unwrap();
-        dbg!(
-            ans.num_valid_bits(),
-            AMT as f64 * categorical.entropy_base2::<f64>()
-        );
-
-        let quantizer = LeakyQuantizer::<_, _, Probability, PRECISION>::new(-127..=127);
-        ans.encode_symbols_reverse(symbols_gaussian.iter().zip(&means).zip(&stds).map(
-            |((&symbol, &mean), &core)| (symbol, quantizer.quantize(Gaussian::new(mean, core))),
-        ))
-        .unwrap();
-        dbg!(ans.num_valid_bits());
+        assert_eq!(decoded, symbols);
+        assert!(ans.is_empty());
+    }
 
-        // Test if import/export of compressed data works.
-        let compressed = ans.into_compressed().unwrap();
-        let mut ans = AnsCoder::from_compressed(compressed).unwrap();
+    #[test]
+    fn compressed_slice_at() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(3.5, 20.0));
+        let symbols = (0..100)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
 
-        let reconstructed_gaussian = ans
-            .decode_symbols(
-                means
-                    .iter()
-                    .zip(&stds)
-                    .map(|(&mean, &core)| quantizer.quantize(Gaussian::new(mean, core))),
-            )
-            .collect::<Result<Vec<_>, CoderError<Infallible, Infallible>>>()
-            .unwrap();
-        let reconstructed_categorical = ans
-            .decode_iid_symbols(AMT, &categorical)
-            .collect::<Result<Vec<_>, CoderError<Infallible, Infallible>>>()
+        let mut encoder = DefaultAnsCoder::new();
+        encoder
+            .encode_iid_symbols_reverse(&symbols, &model)
             .unwrap();
+        let compressed = encoder.into_compressed().unwrap();
 
-        assert!(ans.is_empty());
+        // Surround the compressed data with other data, including zero words right after it.
+        let mut buffer = Vec::from([0x1234_5678, 0, 0xffff_ffff]);
+        let offset = buffer.len();
+        buffer.extend_from_slice(&compressed);
+        buffer.extend_from_slice(&[0, 0, 42]);
+
+        let mut decoder =
+            DefaultAnsCoder::from_compressed_slice_at(&buffer, offset, compressed.len()).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(symbols.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(decoder.is_empty());
 
-        assert_eq!(symbols_gaussian, reconstructed_gaussian);
-        assert_eq!(symbols_categorical, reconstructed_categorical);
+        // Invalid ranges.
+        let len = compressed.len();
+        assert!(DefaultAnsCoder::from_compressed_slice_at(&buffer, offset, len + 1).is_err());
+        assert!(DefaultAnsCoder::from_compressed_slice_at(&buffer, buffer.len(), 1).is_err());
+        assert!(DefaultAnsCoder::from_compressed_slice_at(&buffer, 1, usize::MAX).is_err());
+        assert!(
+            DefaultAnsCoder::from_compressed_slice_at(&buffer, buffer.len(), 0)
+                .unwrap()
+                .is_empty()
+        );
     }
 
     #[test]
@@ -1487,4 +7348,490 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn state_headroom_bits() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2279);
+        let mut ans = DefaultAnsCoder::new();
+        assert_eq!(ans.state_headroom_bits(), 64);
+
+        let (mut num_certain_transfers, mut num_certain_non_transfers) = (0, 0);
+        for _ in 0..10_000 {
+            // Encode the symbol `1` with a random (nonzero) fixed-point probability.
+            let probability = (1 + rng.next_u32() % ((1 << 24) - 1)) >> (rng.next_u32() % 24);
+            let probability = probability.max(1);
+            let model =
+                crate::stream::model::DefaultBernoulli::from_fixed_point_probability_of_one(
+                    probability,
+                )
+                .unwrap();
+            let information_content = 24.0 - (probability as f64).log2();
+
+            let headroom = ans.state_headroom_bits();
+            // Note that `num_words` also counts the words of the internal state.
+            let num_bulk_words = ans.bulk().len();
+            ans.encode_symbol(1, model).unwrap();
+            let transferred = ans.bulk().len() != num_bulk_words;
+
+            if information_content <= headroom as f64 {
+                assert!(!transferred);
+                num_certain_non_transfers += 1;
+            } else if information_content >= headroom as f64 + 1.0 {
+                assert!(transferred);
+                num_certain_transfers += 1;
+            }
+        }
+
+        assert!(num_certain_transfers > 100);
+        assert!(num_certain_non_transfers > 100);
+    }
+
+    #[test]
+    fn into_iter() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2280);
+
+        let mut ans = DefaultAnsCoder::new();
+        assert_eq!(ans.clone().into_iter().count(), 0);
+        for _ in 0..100 {
+            let symbol = model.quantile_function(rng.next_u32() % (1 << 24)).0;
+            ans.encode_symbol(symbol, model).unwrap();
+
+            let iter = ans.clone().into_iter();
+            assert_eq!(iter.len(), ans.num_words());
+            let words = iter.collect::<Vec<_>>();
+            assert_eq!(words, ans.clone().into_compressed().unwrap());
+        }
+
+        let mut ans = SmallAnsCoder::new();
+        let model = SmallLeakyQuantizer::new(-10..=10).quantize(Gaussian::new(0.0, 3.0));
+        for _ in 0..10 {
+            ans.encode_symbol(rng.next_u32() as i32 % 10, model)
+                .unwrap();
+            let words = ans.clone().into_iter().collect::<Vec<_>>();
+            assert_eq!(words, ans.clone().into_compressed().unwrap());
+        }
+    }
+
+    #[test]
+    fn display_hex() {
+        let ans = DefaultAnsCoder::new();
+        assert_eq!(alloc::format!("{}", ans), "");
+
+        let ans =
+            DefaultAnsCoder::from_compressed(alloc::vec![0xab, 0x1234_5678, 0x9abc_def0]).unwrap();
+        assert_eq!(alloc::format!("{}", ans), "000000ab123456789abcdef0");
+        assert_eq!(alloc::format!("{:#}", ans), "000000ab 12345678 9abcdef0");
+
+        let ans = SmallAnsCoder::from_compressed(alloc::vec![0x0f, 0xa000]).unwrap();
+        assert_eq!(alloc::format!("{:#}", ans), "000f a000");
+
+        // The state words are rendered just like in `iter_compressed`.
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse([3, -7, 12, 0, 5], model)
+            .unwrap();
+        let expected = ans
+            .iter_compressed()
+            .map(|word| alloc::format!("{:08x}", word))
+            .collect::<Vec<_>>();
+        assert_eq!(alloc::format!("{}", ans), expected.concat());
+        assert_eq!(alloc::format!("{:#}", ans), expected.join(" "));
+    }
+
+    #[test]
+    fn from_compressed_buf() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2285);
+        let symbols = (0..1000)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut encoder = DefaultAnsCoder::new();
+        encoder.encode_iid_symbols_reverse(&symbols, model).unwrap();
+        let compressed = encoder.into_compressed().unwrap();
+
+        // Owned boxed slice.
+        let mut decoder =
+            DefaultAnsCoder::from_compressed_buf(compressed.clone().into_boxed_slice()).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(symbols.len(), model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(decoder.is_empty());
+        let (buf, pos) = decoder.into_raw_parts().0.into_buf_and_pos();
+        assert_eq!(pos, 0);
+        assert_eq!(&*buf, &compressed[..]);
+
+        // Mutably borrowed buffer, reused for a second round of decoding.
+        let mut buf = compressed.clone();
+        for _ in 0..2 {
+            let mut decoder = DefaultAnsCoder::from_compressed_buf(&mut buf[..]).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(decoder.is_empty());
+        }
+        assert_eq!(buf, compressed);
+
+        // Trailing zero words are rejected, and the buffer is returned unchanged.
+        let mut invalid = compressed;
+        invalid.push(0);
+        let invalid = invalid.into_boxed_slice();
+        match DefaultAnsCoder::from_compressed_buf(invalid.clone()) {
+            Ok(_) => panic!("should reject trailing zero word"),
+            Err(buf) => assert_eq!(buf, invalid),
+        }
+    }
+
+    #[test]
+    fn decode_symbols_lazily() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let models = |amt| {
+            (0..amt).map(move |i| quantizer.quantize(Gaussian::new((i % 20) as f64 - 10.0, 5.0)))
+        };
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2286);
+        let symbols = models(100)
+            .map(|model| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut encoder = DefaultAnsCoder::new();
+        encoder
+            .encode_symbols_reverse(symbols[50..].iter().zip(models(100).skip(50)))
+            .unwrap();
+        let num_words_at_half = encoder.num_words();
+        encoder
+            .encode_symbols_reverse(symbols[..50].iter().zip(models(50)))
+            .unwrap();
+
+        // Only pull as many models as we decode symbols.
+        let num_models_pulled = core::cell::Cell::new(0);
+        let mut remaining_models = models(100).inspect(|_| {
+            num_models_pulled.set(num_models_pulled.get() + 1);
+        });
+
+        let first_half = encoder
+            .decode_symbols(remaining_models.by_ref())
+            .take(50)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(first_half, symbols[..50]);
+        assert_eq!(num_models_pulled.get(), 50);
+        assert_eq!(encoder.num_words(), num_words_at_half);
+
+        let second_half = encoder
+            .decode_symbols(remaining_models)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(second_half, symbols[50..]);
+        assert_eq!(num_models_pulled.get(), 100);
+        assert!(encoder.is_empty());
+    }
+
+    #[test]
+    fn seek_backward() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2273);
+        let chunks = (0..3)
+            .map(|_| {
+                (0..100)
+                    .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut encoder = DefaultAnsCoder::new();
+        let mut checkpoints = Vec::new();
+        for chunk in chunks.iter().rev() {
+            encoder.encode_iid_symbols_reverse(chunk, &model).unwrap();
+            checkpoints.push(encoder.pos());
+        }
+        checkpoints.reverse();
+
+        // Decode everything forward, then seek back to the middle chunk and decode it again.
+        let mut decoder = encoder.as_seekable_decoder();
+        for chunk in &chunks {
+            let decoded = decoder
+                .decode_iid_symbols(100, &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded, chunk);
+        }
+        assert!(decoder.is_empty());
+
+        decoder.seek(checkpoints[1]).unwrap();
+        assert_eq!(decoder.pos(), checkpoints[1]);
+        let decoded = decoder
+            .decode_iid_symbols(100, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, chunks[1]);
+        assert_eq!(decoder.pos(), checkpoints[2]);
+
+        // A `Vec` backend discards consumed words, so it only supports seeking forward.
+        let mut decoder = encoder;
+        let decoded = decoder
+            .decode_iid_symbols(200, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded[100..], chunks[1][..]);
+        assert!(decoder.seek(checkpoints[1]).is_err());
+        decoder.seek(checkpoints[2]).unwrap();
+    }
+
+    #[test]
+    fn num_valid_bits_edge_cases() {
+        // Empty coder: zero state and empty bulk.
+        let ans = DefaultAnsCoder::new();
+        assert_eq!(ans.num_valid_bits(), 0);
+        assert_eq!(ans.num_bits(), 0);
+
+        // The most significant one bit of the state doesn't count as valid.
+        let ans = DefaultAnsCoder::from_raw_parts(Vec::new(), 1);
+        assert_eq!(ans.num_valid_bits(), 0);
+        assert_eq!(ans.num_bits(), 32);
+
+        // A state of `0b10` or `0b11` holds exactly one valid bit.
+        for state in [0b10, 0b11] {
+            let ans = DefaultAnsCoder::from_raw_parts(Vec::new(), state);
+            assert_eq!(ans.num_valid_bits(), 1);
+            assert_eq!(ans.num_bits(), 32);
+        }
+
+        // A full state word holds `State::BITS - 1` valid bits, and each word in the bulk
+        // adds `Word::BITS` valid bits.
+        let ans = DefaultAnsCoder::from_raw_parts(Vec::new(), u64::MAX);
+        assert_eq!(ans.num_valid_bits(), 63);
+        assert_eq!(ans.num_bits(), 64);
+        let ans = DefaultAnsCoder::from_raw_parts(alloc::vec![3, 0], u64::MAX);
+        assert_eq!(ans.num_valid_bits(), 2 * 32 + 63);
+        assert_eq!(ans.num_bits(), 4 * 32);
+
+        // The smallest state that's allowed for a nonempty bulk.
+        let ans = DefaultAnsCoder::from_raw_parts(alloc::vec![7], 1 << 32);
+        assert_eq!(ans.num_valid_bits(), 32 + 32);
+        assert_eq!(ans.num_bits(), 3 * 32);
+
+        // `from_binary` prepends a one bit to the data, which doesn't count as valid.
+        let ans = DefaultAnsCoder::from_binary(alloc::vec![0b101]).unwrap();
+        assert_eq!(ans.num_valid_bits(), 32);
+    }
+
+    #[test]
+    fn bits_between() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2301);
+
+        let mut ans = DefaultAnsCoder::new();
+        let mut checkpoint = ans.pos_and_state();
+        let mut total = 0;
+        for &len in &[0, 1, 2, 17, 100, 3] {
+            let bits_before = ans.num_valid_bits();
+            let chunk = (0..len)
+                .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                .collect::<Vec<_>>();
+            ans.encode_iid_symbols_reverse(&chunk, model).unwrap();
+
+            let next_checkpoint = ans.pos_and_state();
+            let bits = DefaultAnsCoder::bits_between(checkpoint, next_checkpoint);
+            assert_eq!(bits, ans.num_valid_bits() - bits_before);
+            assert_eq!(DefaultAnsCoder::bits_between(checkpoint, checkpoint), 0);
+            total += bits;
+            checkpoint = next_checkpoint;
+        }
+
+        assert_eq!(
+            DefaultAnsCoder::bits_between((0, 0), ans.pos_and_state()),
+            ans.num_valid_bits()
+        );
+        assert_eq!(total, ans.num_valid_bits());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bits_between_wrong_order() {
+        let mut ans = DefaultAnsCoder::new();
+        let earlier = ans.pos_and_state();
+        ans.encode_iid_symbols_reverse(0..10, DefaultUniformModel::new(10))
+            .unwrap();
+        let later = ans.pos_and_state();
+        DefaultAnsCoder::bits_between(later, earlier);
+    }
+
+    #[test]
+    fn encode_chunks_reverse() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2294);
+        let chunks = [0, 1, 17, 100, 3]
+            .iter()
+            .map(|&len| {
+                (0..len)
+                    .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut encoder = DefaultAnsCoder::new();
+        let jump_table = encoder.encode_chunks_reverse(&chunks, model).unwrap();
+        assert_eq!(jump_table.len(), chunks.len());
+        assert_eq!(encoder.validate_jump_table(&jump_table), Ok(()));
+        assert_eq!(jump_table[0], encoder.pos());
+
+        // Seek to each checkpoint and decode the corresponding chunk.
+        for (&checkpoint, chunk) in jump_table.iter().zip(&chunks).rev() {
+            let mut decoder = encoder.seekable_decoder_at(checkpoint).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(chunk.len(), &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded, chunk);
+        }
+
+        // The jump table also works for decoding the chunks in parallel.
+        let decoders = encoder.seekable_decoders_at(&jump_table).unwrap();
+        for (mut decoder, chunk) in decoders.into_iter().zip(&chunks) {
+            let decoded = decoder
+                .decode_iid_symbols(chunk.len(), &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded, chunk);
+        }
+
+        // Decoding sequentially reads the chunks back in their original order.
+        let decoded = encoder
+            .decode_iid_symbols(chunks.iter().map(Vec::len).sum(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, chunks.concat());
+        assert!(encoder.is_empty());
+    }
+
+    #[test]
+    fn into_seekable_encoder() {
+        // Each symbol has an information content of exactly 16 bits, so replacing symbols
+        // with different ones doesn't change the amount of compressed data.
+        let model = DefaultUniformModel::new(1 << 16);
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2295);
+        let mut random_symbols = |amt: usize| {
+            (0..amt)
+                .map(|_| rng.next_u32() as usize % (1 << 16))
+                .collect::<Vec<_>>()
+        };
+        let bottom = random_symbols(100);
+        let top = random_symbols(10);
+        let replacement = random_symbols(10);
+        assert_ne!(top, replacement);
+
+        let mut encoder = DefaultAnsCoder::new();
+        encoder.encode_iid_symbols_reverse(&bottom, model).unwrap();
+        let checkpoint = encoder.pos();
+        encoder.encode_iid_symbols_reverse(&top, model).unwrap();
+        let end = encoder.pos();
+        let original = encoder.clone();
+
+        let mut encoder = encoder.into_seekable_encoder();
+        assert_eq!(encoder.pos(), end);
+        encoder.seek(checkpoint).unwrap();
+        encoder
+            .encode_iid_symbols_reverse(&replacement, model)
+            .unwrap();
+        assert_eq!(encoder.pos().0, end.0);
+
+        // Decoding reflects the overwritten symbols.
+        let mut decoder = encoder.clone();
+        let decoded = decoder
+            .decode_iid_symbols(110, model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded[..10], replacement[..]);
+        assert_eq!(decoded[10..], bottom[..]);
+        assert!(decoder.is_empty());
+
+        // Converting back to a `Vec` backend yields the same compressed data as encoding
+        // the replacement symbols right away.
+        let (cursor, state) = encoder.into_raw_parts();
+        let (mut bulk, pos) = cursor.into_buf_and_pos();
+        bulk.truncate(pos);
+        let rewritten = DefaultAnsCoder::from_raw_parts(bulk, state);
+        let mut expected = DefaultAnsCoder::new();
+        expected.encode_iid_symbols_reverse(&bottom, model).unwrap();
+        expected
+            .encode_iid_symbols_reverse(&replacement, model)
+            .unwrap();
+        assert_eq!(rewritten, expected);
+
+        // The seekable encoder can't grow the buffer.
+        let mut encoder = original.into_seekable_encoder();
+        encoder.seek(checkpoint).unwrap();
+        let too_long = random_symbols(20);
+        assert!(encoder
+            .encode_iid_symbols_reverse(&too_long, model)
+            .is_err());
+    }
+
+    #[test]
+    fn compressed_le_and_be_bytes() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2296);
+        let symbols = (0..100)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+            .collect::<Vec<_>>();
+
+        let mut encoder = DefaultAnsCoder::new();
+        encoder.encode_iid_symbols_reverse(&symbols, model).unwrap();
+        let le_bytes = encoder
+            .clone()
+            .into_compressed_bytes(Endianness::LittleEndian);
+        let be_bytes = encoder.clone().into_compressed_bytes(Endianness::BigEndian);
+        assert_ne!(le_bytes, be_bytes);
+
+        for mut decoder in [
+            DefaultAnsCoder::from_compressed_le_bytes(&le_bytes).unwrap(),
+            DefaultAnsCoder::from_compressed_be_bytes(&be_bytes).unwrap(),
+        ] {
+            assert_eq!(decoder, encoder);
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(decoder.is_empty());
+        }
+
+        assert!(DefaultAnsCoder::from_compressed_le_bytes(&[])
+            .unwrap()
+            .is_empty());
+
+        for len in [1, 2, 3, 5, le_bytes.len() - 1] {
+            assert_eq!(
+                DefaultAnsCoder::from_compressed_le_bytes(&le_bytes[..len]),
+                Err(CompressedBytesError::InvalidLength)
+            );
+            assert_eq!(
+                DefaultAnsCoder::from_compressed_be_bytes(&be_bytes[..len]),
+                Err(CompressedBytesError::InvalidLength)
+            );
+        }
+
+        let mut trailing_zero = le_bytes.clone();
+        trailing_zero.extend_from_slice(&[0, 0, 0, 0]);
+        assert_eq!(
+            DefaultAnsCoder::from_compressed_le_bytes(&trailing_zero),
+            Err(CompressedBytesError::TrailingZeroWord)
+        );
+        assert_eq!(
+            DefaultAnsCoder::from_compressed_be_bytes(&[1, 2, 3, 4, 0, 0, 0, 0]),
+            Err(CompressedBytesError::TrailingZeroWord)
+        );
+    }
 }
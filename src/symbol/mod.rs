@@ -247,7 +247,7 @@ impl<Word: BitArray> StackCoder<Word, Vec<Word>> {
 impl<Word: BitArray> QueueEncoder<Word, Vec<Word>> {
     pub fn with_bit_capacity(bit_capacity: usize) -> Self {
         Self {
-            backend: Vec::with_capacity((bit_capacity + Word::BITS - 1) / Word::BITS),
+            backend: Vec::with_capacity(bit_capacity.div_ceil(Word::BITS)),
             ..Default::default()
         }
     }
@@ -1,3 +1,5 @@
+import pickle
+
 import constriction
 import numpy as np
 import scipy.stats
@@ -29,6 +31,33 @@ def test_queue_gaussian():
     assert np.all(reconstructed2 == symbols)
 
 
+def test_queue_encode_decode_order():
+    probabilities = np.array([0.2, 0.4, 0.1, 0.3], dtype=np.float32)
+    categorical = constriction.stream.model.Categorical(probabilities, perfect=False)
+    gaussian_family = constriction.stream.model.QuantizedGaussian(-100, 100)
+    message_part1 = np.array([1, 2, 0, 3, 2, 3, 0], dtype=np.int64)
+    message_part2 = np.array([6, 10, -4, 2], dtype=np.int16)
+    means = np.array([2.5, 13.1, -1.1, -3.0], dtype=np.float64)
+    stds = np.array([4.1, 8.7, 6.2, 5.4], dtype=np.float64)
+
+    # Unlike an `AnsCoder`, a range coder decodes symbols in the same order
+    # in which they were encoded.
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(3, categorical)
+    encoder.encode(message_part1, categorical)
+    encoder.encode(message_part2, gaussian_family, means, stds)
+
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert decoder.decode(categorical) == 3
+    decoded_part1 = decoder.decode(categorical, len(message_part1), dtype=np.int64)
+    decoded_part2 = decoder.decode(gaussian_family, means, stds, dtype=np.int16)
+    assert decoded_part1.dtype == np.int64
+    assert decoded_part2.dtype == np.int16
+    assert np.all(decoded_part1 == message_part1)
+    assert np.all(decoded_part2 == message_part2)
+    assert decoder.maybe_exhausted()
+
+
 def test_stack_gaussian():
     encoder = constriction.stream.stack.AnsCoder()
 
@@ -55,6 +84,157 @@ def test_stack_gaussian():
     assert np.all(reconstructed2 == symbols)
 
 
+def test_stack_symbol_dtypes():
+    model = constriction.stream.model.QuantizedGaussian(-100, 100)
+    symbols = np.array([23, -15, 78, 43, -69], dtype=np.int32)
+    means = np.array([35.2, -1.7, 30.1, 71.2, -75.1], dtype=np.float64)
+    stds = np.array([10.1, 25.3, 23.8, 35.4, 3.9], dtype=np.float64)
+    iid_model = constriction.stream.model.QuantizedGaussian(-100, 100, 10.0, 30.0)
+
+    reference = constriction.stream.stack.AnsCoder()
+    reference.encode_reverse(symbols, model, means, stds)
+    reference.encode_reverse(symbols, iid_model)
+    reference_compressed = reference.get_compressed()
+
+    for dtype in [np.int16, np.int32, np.int64]:
+        encoder = constriction.stream.stack.AnsCoder()
+        encoder.encode_reverse(symbols.astype(dtype), model, means, stds)
+        encoder.encode_reverse(symbols.astype(dtype), iid_model)
+        compressed = encoder.get_compressed()
+        assert np.all(compressed == reference_compressed)
+
+        decoder = constriction.stream.stack.AnsCoder(compressed)
+        reconstructed_iid = decoder.decode(iid_model, len(symbols), dtype=dtype)
+        reconstructed_params = decoder.decode(model, means, stds, dtype=dtype)
+        assert decoder.is_empty()
+        for reconstructed in [reconstructed_iid, reconstructed_params]:
+            assert reconstructed.dtype == dtype
+            assert np.all(reconstructed == symbols)
+
+    # The default `dtype` is `np.int32`.
+    decoder = constriction.stream.stack.AnsCoder(reference_compressed)
+    assert decoder.decode(iid_model, len(symbols)).dtype == np.int32
+
+    encoder = constriction.stream.stack.AnsCoder()
+    try:
+        encoder.encode_reverse(symbols.astype(np.uint8), iid_model)
+        assert False
+    except TypeError:
+        pass
+    try:
+        encoder.encode_reverse(np.array([2**40], dtype=np.int64), iid_model)
+        assert False
+    except OverflowError:
+        pass
+    try:
+        decoder.decode(iid_model, 5, dtype=np.float32)
+        assert False
+    except TypeError:
+        pass
+
+    # Decoding into `np.int16` fails without losing data if a symbol doesn't fit.
+    wide_model = constriction.stream.model.QuantizedGaussian(-40000, 40000, 0.0, 10.0)
+    wide_symbols = np.array([-35000, 12, 34000], dtype=np.int32)
+    encoder = constriction.stream.stack.AnsCoder()
+    encoder.encode_reverse(wide_symbols, wide_model)
+    decoder = constriction.stream.stack.AnsCoder(encoder.get_compressed())
+    try:
+        decoder.decode(wide_model, len(wide_symbols), dtype=np.int16)
+        assert False
+    except OverflowError:
+        pass
+    reconstructed = decoder.decode(wide_model, len(wide_symbols), dtype=np.int64)
+    assert np.all(reconstructed == wide_symbols)
+    assert decoder.is_empty()
+
+
+def test_stack_compressed_chunks():
+    model = constriction.stream.model.QuantizedGaussian(-100, 100, 10.0, 30.0)
+    symbols = np.random.RandomState(2289).randint(-100, 101, size=1000).astype(np.int32)
+    encoder = constriction.stream.stack.AnsCoder()
+    encoder.encode_reverse(symbols, model)
+    compressed = encoder.get_compressed()
+
+    for chunk_size in [1, 7, 100, len(compressed), len(compressed) + 10]:
+        chunks = list(encoder.iter_compressed_chunks(chunk_size))
+        assert all(chunk.dtype == np.uint32 for chunk in chunks)
+        assert all(0 < len(chunk) <= chunk_size for chunk in chunks)
+        assert np.all(np.concatenate(chunks) == compressed)
+
+    assert list(constriction.stream.stack.AnsCoder().iter_compressed_chunks(10)) == []
+
+    chunks = encoder.iter_compressed_chunks(10)
+    next(chunks)
+    encoder.decode(model)
+    try:
+        next(chunks)
+        assert False
+    except RuntimeError:
+        pass
+
+    # Modifications that leave the size of the compressed data unchanged are detected too.
+    chunks = encoder.iter_compressed_chunks(10)
+    next(chunks)
+    symbol = encoder.decode(model)
+    encoder.encode_reverse(symbol, model)
+    try:
+        next(chunks)
+        assert False
+    except RuntimeError:
+        pass
+
+
+def test_stack_context_manager(tmp_path):
+    model = constriction.stream.model.QuantizedGaussian(-100, 100, 10.0, 30.0)
+    symbols = np.random.RandomState(2290).randint(-100, 101, size=100).astype(np.int32)
+    reference = constriction.stream.stack.AnsCoder()
+    reference.encode_reverse(symbols, model)
+    compressed = reference.get_compressed()
+
+    for endianness, dtype in [("little", "<u4"), ("big", ">u4")]:
+        path = tmp_path / ("compressed-%s.bin" % endianness)
+        with constriction.stream.stack.AnsCoder(path=str(path), endianness=endianness) as coder:
+            coder.encode_reverse(symbols, model)
+        assert np.all(np.fromfile(path, dtype=dtype) == compressed)
+
+    # An exception inside the `with` block leaves the file untouched.
+    path = tmp_path / "untouched.bin"
+    path.write_bytes(b"original")
+    try:
+        with constriction.stream.stack.AnsCoder(path=path) as coder:
+            coder.encode_reverse(symbols, model)
+            raise KeyError("test")
+        assert False
+    except KeyError:
+        pass
+    assert path.read_bytes() == b"original"
+
+    try:
+        constriction.stream.stack.AnsCoder(path=path, endianness="middle")
+        assert False
+    except ValueError:
+        pass
+
+
+def test_stack_pickle():
+    model = constriction.stream.model.QuantizedGaussian(-100, 100, 10.0, 30.0)
+    symbols = np.random.RandomState(2291).randint(-100, 101, size=100).astype(np.int32)
+    for amt in [0, 1, len(symbols)]:
+        coder = constriction.stream.stack.AnsCoder()
+        coder.encode_reverse(symbols[:amt], model)
+        restored = pickle.loads(pickle.dumps(coder))
+        assert np.all(restored.get_compressed() == coder.get_compressed())
+        assert np.all(restored.decode(model, amt) == coder.decode(model, amt))
+        assert np.all(coder.decode(model, 5) == restored.decode(model, 5))
+
+    coder = constriction.stream.stack.AnsCoder()
+    try:
+        coder.__setstate__((np.array([1, 2], dtype=np.uint32), 5, None, True))
+        assert False
+    except ValueError:
+        pass
+
+
 def test_chain_gaussian():
     rng = np.random.RandomState(123)
     original_data = rng.randint(2**32, size=100, dtype=np.uint32)